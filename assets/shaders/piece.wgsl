@@ -0,0 +1,108 @@
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+// draws a piece of the puzzle image through the mask of the piece, see `src/atlas.rs`
+
+const MODE_COLOR: u32 = 0u;
+const MODE_WHITE: u32 = 1u;
+const MODE_SHADOW: u32 = 3u;
+
+// directions the mask is spread into for the outline of the silhouette and the shadow
+const OUTLINE_SAMPLES: u32 = 16u;
+const TAU: f32 = 6.2831853;
+
+struct PieceMaterial {
+    color: vec4<f32>,
+    image_rect: vec4<f32>,
+    mask_rect: vec4<f32>,
+    stroke_color: vec4<f32>,
+    stroke_image: f32,
+    outline: f32,
+    blur: f32,
+    mode: u32,
+};
+
+@group(2) @binding(0) var<uniform> material: PieceMaterial;
+@group(2) @binding(1) var image_texture: texture_2d<f32>;
+@group(2) @binding(2) var image_sampler: sampler;
+@group(2) @binding(3) var mask_texture: texture_2d<f32>;
+@group(2) @binding(4) var mask_sampler: sampler;
+
+// the body of the piece at `uv` of the piece, nothing outside of its slot in the atlas
+fn body(uv: vec2<f32>) -> f32 {
+    if any(uv < vec2(0.0)) || any(uv > vec2(1.0)) {
+        return 0.0;
+    }
+    return textureSampleLevel(
+        mask_texture,
+        mask_sampler,
+        material.mask_rect.xy + uv * material.mask_rect.zw,
+        0.0,
+    ).a;
+}
+
+// the puzzle image at `uv` of the piece
+fn image_at(uv: vec2<f32>) -> vec4<f32> {
+    return textureSampleLevel(
+        image_texture,
+        image_sampler,
+        material.image_rect.xy + uv * material.image_rect.zw,
+        0.0,
+    );
+}
+
+@fragment
+fn fragment(mesh: VertexOutput) -> @location(0) vec4<f32> {
+    // the mesh of a silhouette with an outline is grown by the outline on every side
+    let size = material.mask_rect.zw * vec2<f32>(textureDimensions(mask_texture));
+    let uv = (mesh.uv * (size + 2.0 * material.outline) - material.outline) / size;
+
+    var image = textureSample(
+        image_texture,
+        image_sampler,
+        material.image_rect.xy + uv * material.image_rect.zw,
+    );
+    // red is the cut line, green the outline and alpha the body of the piece
+    let mask = textureSample(
+        mask_texture,
+        mask_sampler,
+        material.mask_rect.xy + uv * material.mask_rect.zw,
+    );
+
+    var color: vec4<f32>;
+    if material.mode == MODE_COLOR {
+        if material.blur > 0.0 {
+            // the image averaged around the pixel, the mask stays sharp
+            var sum = image;
+            for (var i = 0u; i < OUTLINE_SAMPLES; i++) {
+                let angle = f32(i) * TAU / f32(OUTLINE_SAMPLES);
+                let offset = vec2(cos(angle), sin(angle)) * material.blur / size;
+                sum += image_at(uv + offset) + image_at(uv + offset * 0.5);
+            }
+            image = sum / f32(2u * OUTLINE_SAMPLES + 1u);
+        }
+        let line = mix(material.stroke_color.rgb, image.rgb * 0.5, material.stroke_image);
+        color = vec4(mix(image.rgb, line, mask.r * material.stroke_color.a), image.a * mask.a);
+    } else if material.mode == MODE_WHITE {
+        var alpha = body(uv);
+        if material.outline > 0.0 {
+            for (var i = 0u; i < OUTLINE_SAMPLES; i++) {
+                let angle = f32(i) * TAU / f32(OUTLINE_SAMPLES);
+                let offset = vec2(cos(angle), sin(angle)) * material.outline / size;
+                alpha = max(alpha, max(body(uv + offset), body(uv + offset * 0.5)));
+            }
+        }
+        color = vec4(1.0, 1.0, 1.0, alpha);
+    } else if material.mode == MODE_SHADOW {
+        // the body blurred by the outline
+        var alpha = body(uv);
+        for (var i = 0u; i < OUTLINE_SAMPLES; i++) {
+            let angle = f32(i) * TAU / f32(OUTLINE_SAMPLES);
+            let offset = vec2(cos(angle), sin(angle)) * material.outline / size;
+            alpha += body(uv + offset) + body(uv + offset * 0.5);
+        }
+        color = vec4(1.0, 1.0, 1.0, alpha / f32(2u * OUTLINE_SAMPLES + 1u));
+    } else {
+        color = vec4(1.0, 1.0, 1.0, mask.g);
+    }
+    return color * material.color;
+}
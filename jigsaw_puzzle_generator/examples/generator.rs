@@ -22,6 +22,7 @@ fn main() {
         .origin_image
         .save("images/origin_image.png")
         .expect("Failed to save image");
+    std::fs::write("images/cut_lines.svg", template.to_svg()).expect("Failed to save svg");
 
     for piece in template.pieces.iter() {
         piece
@@ -0,0 +1,65 @@
+//! The errors of cutting a puzzle, e.g. for settings out of their range or an image too small
+//! for the number of pieces.
+
+use crate::{JITTERS, TAB_SIZES};
+use std::fmt::{Display, Formatter};
+
+/// Why a puzzle could not be cut
+#[derive(Debug, Clone, PartialEq)]
+pub enum JigsawError {
+    /// The tab size is not in [`TAB_SIZES`]
+    TabSizeOutOfRange(f32),
+    /// The jitter is not in [`JITTERS`]
+    JitterOutOfRange(f32),
+    /// There are no pieces in a row or a column
+    NoPieces,
+    /// The pieces would be smaller than a few pixels
+    ImageTooSmall {
+        width: u32,
+        height: u32,
+        pieces_in_column: usize,
+        pieces_in_row: usize,
+    },
+    /// The cut needs more pieces in a row, e.g. two triangles
+    TooFewPiecesInRow { needed: usize },
+    /// The path around the piece of this index is broken
+    BrokenPiece(usize),
+}
+
+impl Display for JigsawError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JigsawError::TabSizeOutOfRange(tab_size) => write!(
+                f,
+                "The tab size {} is not between {} and {}",
+                tab_size,
+                TAB_SIZES.start(),
+                TAB_SIZES.end()
+            ),
+            JigsawError::JitterOutOfRange(jitter) => write!(
+                f,
+                "The jitter {} is not between {} and {}",
+                jitter,
+                JITTERS.start(),
+                JITTERS.end()
+            ),
+            JigsawError::NoPieces => write!(f, "A puzzle needs at least one piece"),
+            JigsawError::ImageTooSmall {
+                width,
+                height,
+                pieces_in_column,
+                pieces_in_row,
+            } => write!(
+                f,
+                "An image of {}x{} pixels is too small for {}x{} pieces",
+                width, height, pieces_in_column, pieces_in_row
+            ),
+            JigsawError::TooFewPiecesInRow { needed } => {
+                write!(f, "The puzzle needs at least {} pieces in a row", needed)
+            }
+            JigsawError::BrokenPiece(index) => write!(f, "The path of piece {} is broken", index),
+        }
+    }
+}
+
+impl std::error::Error for JigsawError {}
@@ -0,0 +1,154 @@
+//! Filters which change the image before it is cut, to make a puzzle harder. All of them change
+//! the colors of the pieces, except [`ImageFilter::Blur`], which only blurs the preview of the
+//! solved puzzle.
+
+use image::{imageops, DynamicImage, Rgba, RgbaImage};
+
+/// The levels of every channel left by [`ImageFilter::Posterize`]
+const POSTERIZE_LEVELS: u8 = 4;
+/// The blur of [`ImageFilter::Blur`], of the larger side of the image
+const BLUR_SIGMA: f32 = 1.0 / 60.0;
+
+/// A filter applied to the image before it is cut
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ImageFilter {
+    Grayscale,
+    Sepia,
+    /// Only a few levels of every color remain
+    Posterize,
+    /// A heavy blur of the preview, the pieces stay sharp
+    Blur,
+}
+
+impl ImageFilter {
+    pub const ALL: [ImageFilter; 4] = [
+        ImageFilter::Grayscale,
+        ImageFilter::Sepia,
+        ImageFilter::Posterize,
+        ImageFilter::Blur,
+    ];
+
+    /// The name of the filter in a [`crate::manifest::PuzzleManifest`]
+    pub fn name(&self) -> &'static str {
+        match self {
+            ImageFilter::Grayscale => "grayscale",
+            ImageFilter::Sepia => "sepia",
+            ImageFilter::Posterize => "posterize",
+            ImageFilter::Blur => "blur",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        ImageFilter::ALL
+            .into_iter()
+            .find(|filter| filter.name() == name)
+    }
+
+    /// Whether the filter changes the pieces, otherwise only the preview
+    pub fn changes_pieces(&self) -> bool {
+        !matches!(self, ImageFilter::Blur)
+    }
+
+    /// How much harder the filter makes a puzzle, the difficulty score is multiplied by it
+    pub fn difficulty(&self) -> f32 {
+        match self {
+            ImageFilter::Grayscale => 1.4,
+            ImageFilter::Sepia => 1.3,
+            ImageFilter::Posterize => 1.2,
+            ImageFilter::Blur => 1.5,
+        }
+    }
+
+    fn apply(&self, image: &mut RgbaImage) {
+        match self {
+            ImageFilter::Grayscale => map_colors(image, |[r, g, b]| {
+                let luma = 0.299 * r + 0.587 * g + 0.114 * b;
+                [luma, luma, luma]
+            }),
+            ImageFilter::Sepia => map_colors(image, |[r, g, b]| {
+                [
+                    0.393 * r + 0.769 * g + 0.189 * b,
+                    0.349 * r + 0.686 * g + 0.168 * b,
+                    0.272 * r + 0.534 * g + 0.131 * b,
+                ]
+            }),
+            ImageFilter::Posterize => {
+                let step = 255.0 / (POSTERIZE_LEVELS - 1) as f32;
+                map_colors(image, |color| color.map(|c| (c / step).round() * step));
+            }
+            ImageFilter::Blur => {
+                let sigma = image.width().max(image.height()) as f32 * BLUR_SIGMA;
+                *image = imageops::fast_blur(image, sigma);
+            }
+        }
+    }
+}
+
+/// Changes the colors of every pixel, keeping its alpha
+fn map_colors(image: &mut RgbaImage, f: impl Fn([f32; 3]) -> [f32; 3]) {
+    for Rgba([r, g, b, _]) in image.pixels_mut() {
+        let [new_r, new_g, new_b] = f([*r as f32, *g as f32, *b as f32]);
+        (*r, *g, *b) = (
+            new_r.clamp(0.0, 255.0) as u8,
+            new_g.clamp(0.0, 255.0) as u8,
+            new_b.clamp(0.0, 255.0) as u8,
+        );
+    }
+}
+
+/// Applies the `filters` in order, the ones which only change the preview if `preview` is set
+pub fn apply_filters(image: &DynamicImage, filters: &[ImageFilter], preview: bool) -> DynamicImage {
+    let mut filtered = image.to_rgba8();
+    for filter in filters {
+        if preview || filter.changes_pieces() {
+            filter.apply(&mut filtered);
+        }
+    }
+    DynamicImage::ImageRgba8(filtered)
+}
+
+/// How hard a puzzle of `pieces` pieces is with the `filters`, every filter makes it harder
+pub fn difficulty_score(pieces: usize, filters: &[ImageFilter]) -> u32 {
+    let factor: f32 = filters.iter().map(ImageFilter::difficulty).product();
+    (pieces as f32 * factor).round() as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> DynamicImage {
+        RgbaImage::from_fn(32, 32, |x, y| {
+            Rgba([(x * 8) as u8, (y * 8) as u8, 200, 255])
+        })
+        .into()
+    }
+
+    #[test]
+    fn test_grayscale_and_posterize() {
+        let gray = apply_filters(&sample(), &[ImageFilter::Grayscale], false).to_rgba8();
+        assert!(gray
+            .pixels()
+            .all(|Rgba([r, g, b, a])| r == g && g == b && *a == 255));
+
+        let posterized = apply_filters(&sample(), &[ImageFilter::Posterize], false).to_rgba8();
+        assert!(posterized
+            .pixels()
+            .all(|pixel| pixel.0[..3].iter().all(|c| [0, 85, 170, 255].contains(c))));
+    }
+
+    #[test]
+    fn test_blur_only_changes_the_preview() {
+        let image = sample();
+        let filters = [ImageFilter::Blur];
+        assert_eq!(apply_filters(&image, &filters, false), image);
+        assert_ne!(apply_filters(&image, &filters, true), image);
+    }
+
+    #[test]
+    fn test_difficulty_score() {
+        assert_eq!(difficulty_score(100, &[]), 100);
+        let filtered = difficulty_score(100, &[ImageFilter::Grayscale, ImageFilter::Blur]);
+        assert!(filtered > difficulty_score(100, &[ImageFilter::Grayscale]));
+    }
+}
@@ -1,27 +1,48 @@
 //! The Jigsaw Puzzle library creates SVG paths which can be used to cut out puzzle pieces from a
-//! given rectangular image. It provides three public functions:
+//! given rectangular image. It provides these public functions:
 //!
 //! - [`JigsawGenerator::generate`] returns the paths from a given number of pieces in a column and a
 //!     row. This is the function you normally want to use
+//! - [`build_jigsaw_pieces`] returns the paths for a rectangle of a given size, without an image
 //! - [`generate_columns_rows_numbers`] returns an ideal distribution of pieces on the x- and y-axes
 //!     for a given total number of pieces
+//! - [`JigsawGenerator::outline`] cuts the puzzle to a closed path instead of the rectangle of the
+//!   image
+//! - [`JigsawTemplate::to_svg`] returns the paths of all pieces as an SVG document
 //! - [`round`] is a util function which approximately rounds a f32 value to two decimal places
 
 use anyhow::{anyhow, Result};
 use bezier_rs::{Bezier, BezierHandles, Identifier, Subpath};
-use glam::DVec2;
+use glam::{DAffine2, DVec2};
 use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
 
 use log::{debug, info, trace};
 use rayon::iter::ParallelIterator;
-use std::{sync::Arc, vec};
+use std::{collections::HashMap, sync::Arc, vec};
 
+pub use bezier_rs;
+pub use glam;
 pub use image;
 pub use imageproc;
+
+pub mod error;
+pub mod filter;
+pub mod manifest;
+pub mod pattern;
+pub mod voronoi;
+use error::JigsawError;
+use filter::{apply_filters, ImageFilter};
 use rand::random;
 
-const DEFAULT_TAB_SIZE: f32 = 20.0;
-const DEFAULT_JITTER: f32 = 5.0;
+pub const DEFAULT_TAB_SIZE: f32 = 20.0;
+pub const DEFAULT_JITTER: f32 = 5.0;
+/// The tab sizes [`JigsawGenerator::tab_size`] accepts
+pub const TAB_SIZES: core::ops::RangeInclusive<f32> = 10.0..=30.0;
+/// The jitters [`JigsawGenerator::jitter`] accepts
+pub const JITTERS: core::ops::RangeInclusive<f32> = 0.0..=13.0;
+
+/// The smallest width and height of a piece in pixels
+const MIN_PIECE_SIZE: u32 = 4;
 
 const MAX_WIDTH: u32 = 1920;
 const MAX_HEIGHT: u32 = 1200;
@@ -31,6 +52,10 @@ pub enum GameMode {
     #[default]
     Classic,
     Square,
+    /// Rows of triangles pointing up and down in turns
+    Triangular,
+    /// Irregular pieces from a Voronoi diagram
+    Crazy,
 }
 
 /// A segment of an indented puzzle piece edge. A segment is described by a cubic Bézier curve,
@@ -149,22 +174,28 @@ pub struct EdgeContourGenerator {
 
 impl EdgeContourGenerator {
     /// Creates a new [`EdgeContourGenerator`] instance after making sure that the optionally
-    /// provided `tab_size`, `jitter` and `seed` values are in the allowed ranges
+    /// provided `tab_size` and `jitter` values are in [`TAB_SIZES`] and [`JITTERS`]
     pub fn new(
         piece_width: f32,
         piece_height: f32,
         tab_size: Option<f32>,
         jitter: Option<f32>,
         seed: Option<usize>,
-    ) -> EdgeContourGenerator {
-        let tab_size = tab_size.unwrap_or(DEFAULT_TAB_SIZE) / 200.0;
-        assert!((0.05..=0.15).contains(&tab_size));
-        let jitter = jitter.unwrap_or(DEFAULT_JITTER) / 100.0;
-        assert!((0.0..=0.13).contains(&jitter));
+    ) -> Result<EdgeContourGenerator, JigsawError> {
+        let tab_size = tab_size.unwrap_or(DEFAULT_TAB_SIZE);
+        if !TAB_SIZES.contains(&tab_size) {
+            return Err(JigsawError::TabSizeOutOfRange(tab_size));
+        }
+        let tab_size = tab_size / 200.0;
+        let jitter = jitter.unwrap_or(DEFAULT_JITTER);
+        if !JITTERS.contains(&jitter) {
+            return Err(JigsawError::JitterOutOfRange(jitter));
+        }
+        let jitter = jitter / 100.0;
         let seed = seed.unwrap_or(0);
         let e = Self::uniform(-jitter, jitter, seed + 1);
         let (seed, flipped, a, b, c, d, e) = Self::dice(e, false, seed + 2, jitter);
-        EdgeContourGenerator {
+        Ok(EdgeContourGenerator {
             piece_width,
             piece_height,
             tab_size,
@@ -176,7 +207,7 @@ impl EdgeContourGenerator {
             c,
             d,
             e,
-        }
+        })
     }
 
     /// Normalises the seed value on a scale between 0 and 1
@@ -375,6 +406,47 @@ impl EdgeContourGenerator {
         ) = Self::dice(self.e, false, self.seed + 2, self.jitter);
         indented_edge
     }
+
+    /// Returns a new [`IndentedEdge`] along a line in any direction, e.g. the slanted side of a
+    /// triangle. The edge is cut along the x-axis at the length of the line and turned onto it
+    pub fn create_along(
+        &mut self,
+        starting_point: (f32, f32),
+        end_point: (f32, f32),
+    ) -> IndentedEdge {
+        let (dx, dy) = (
+            end_point.0 - starting_point.0,
+            end_point.1 - starting_point.1,
+        );
+        let length = dx.hypot(dy);
+        let piece_width = self.piece_width;
+        self.piece_width = length;
+        let edge = self.create((0.0, 0.0), (length, 0.0));
+        self.piece_width = piece_width;
+
+        let (cos, sin) = (dx / length, dy / length);
+        let turn = |(x, y): (f32, f32)| {
+            (
+                round(starting_point.0 + x * cos - y * sin),
+                round(starting_point.1 + x * sin + y * cos),
+            )
+        };
+        let turn_segment = |segment: IndentationSegment| IndentationSegment {
+            starting_point: turn(segment.starting_point),
+            end_point: turn(segment.end_point),
+            control_point_1: turn(segment.control_point_1),
+            control_point_2: turn(segment.control_point_2),
+        };
+        let mut edge = IndentedEdge {
+            first_segment: turn_segment(edge.first_segment),
+            middle_segment: turn_segment(edge.middle_segment),
+            last_segment: turn_segment(edge.last_segment),
+        };
+        // the ends are the corners shared with the other edges, without rounding errors
+        edge.first_segment.starting_point = starting_point;
+        edge.last_segment.end_point = end_point;
+        edge
+    }
 }
 
 #[derive(Clone, PartialEq, Debug)]
@@ -420,6 +492,20 @@ impl Edge {
             Edge::StraightEdge(oe) => oe.to_beziers(reverse),
         }
     }
+
+    pub fn starting_point(&self) -> (f32, f32) {
+        match self {
+            Edge::IndentedEdge(ie) => ie.first_segment.starting_point,
+            Edge::StraightEdge(oe) => oe.starting_point,
+        }
+    }
+
+    pub fn end_point(&self) -> (f32, f32) {
+        match self {
+            Edge::IndentedEdge(ie) => ie.last_segment.end_point,
+            Edge::StraightEdge(oe) => oe.end_point,
+        }
+    }
 }
 
 /// Divides the axis into `pieces` of equal length. Returns the starting point of each piece,
@@ -490,11 +576,9 @@ fn optimal_aspect_ratio(
     possible_dimensions: Vec<(usize, usize)>,
     image_width: f32,
     image_height: f32,
-) -> Result<(usize, usize)> {
+) -> Result<(usize, usize), JigsawError> {
     let mut width_height_diff = f32::MAX;
-    let mut number_of_pieces = *possible_dimensions
-        .first()
-        .ok_or_else(|| anyhow!("No possible dimensions found"))?;
+    let mut number_of_pieces = *possible_dimensions.first().ok_or(JigsawError::NoPieces)?;
     for (x, y) in possible_dimensions {
         let width = image_width / x as f32;
         let height = image_height / y as f32;
@@ -518,7 +602,7 @@ pub fn generate_columns_rows_numbers(
     image_width: f32,
     image_height: f32,
     number_of_pieces: usize,
-) -> Result<(usize, usize)> {
+) -> Result<(usize, usize), JigsawError> {
     let divisor_pairs = find_divisors(number_of_pieces);
     optimal_aspect_ratio(divisor_pairs, image_width, image_height)
 }
@@ -550,6 +634,10 @@ pub struct JigsawGenerator {
     jitter: Option<f32>,
     /// Optional seed value for randomness in generating the puzzle pieces.
     seed: Option<usize>,
+    /// Filters applied to the image before the pieces are cut.
+    filters: Vec<ImageFilter>,
+    /// Optional outline of the whole puzzle instead of the rectangle of the image.
+    outline: Option<Subpath<PuzzleId>>,
 }
 
 impl JigsawGenerator {
@@ -561,6 +649,8 @@ impl JigsawGenerator {
             tab_size: None,
             jitter: None,
             seed: Some(random()),
+            filters: vec![],
+            outline: None,
         }
     }
 
@@ -603,6 +693,8 @@ impl JigsawGenerator {
             tab_size: None,
             jitter: None,
             seed: None,
+            filters: vec![],
+            outline: None,
         })
     }
 
@@ -621,6 +713,29 @@ impl JigsawGenerator {
         self
     }
 
+    /// Cuts the puzzle to a closed `outline` in pixels of the image, e.g. a circle or a heart,
+    /// instead of the rectangle of the image. Pieces outside of it are left out, the ones it
+    /// crosses are cut along it and count as border pieces
+    pub fn outline(mut self, outline: Subpath<PuzzleId>) -> Self {
+        self.outline = Some(outline);
+        self
+    }
+
+    /// Filters the image before the pieces are cut, in the given order
+    pub fn filters(mut self, filters: impl IntoIterator<Item = ImageFilter>) -> Self {
+        self.filters = filters.into_iter().collect();
+        self
+    }
+
+    pub fn filter_list(&self) -> &[ImageFilter] {
+        &self.filters
+    }
+
+    /// The seed the pieces are cut with, random unless [`JigsawGenerator::seed`] was set
+    pub fn seed_value(&self) -> Option<usize> {
+        self.seed
+    }
+
     pub fn origin_image(&self) -> &DynamicImage {
         &self.origin_image
     }
@@ -633,23 +748,101 @@ impl JigsawGenerator {
         self.pieces_in_row
     }
 
+    /// The pieces of the grid, an [`JigsawGenerator::outline`] leaves out some of them, see
+    /// [`JigsawTemplate::pieces_count`] for the pieces actually cut
     pub fn pieces_count(&self) -> usize {
         self.pieces_in_column * self.pieces_in_row
     }
 
-    pub fn generate(&self, game_mode: GameMode, resize: bool) -> Result<JigsawTemplate> {
-        let target_image = if resize {
+    /// Cuts the image into the pieces, or returns a [`JigsawError`] if the settings can't cut it
+    pub fn generate(
+        &self,
+        game_mode: GameMode,
+        resize: bool,
+    ) -> Result<JigsawTemplate, JigsawError> {
+        self.generate_with_progress(game_mode, resize, |_, _| {})
+    }
+
+    /// Same as [`JigsawGenerator::generate`], `progress` is called with the number of pieces
+    /// done and the total after every piece
+    pub fn generate_with_progress(
+        &self,
+        game_mode: GameMode,
+        resize: bool,
+        progress: impl Fn(usize, usize),
+    ) -> Result<JigsawTemplate, JigsawError> {
+        let mut target_image = if resize {
             Arc::new(scale_image(&self.origin_image))
         } else {
             self.origin_image.clone()
         };
+        if self.filters.iter().any(ImageFilter::changes_pieces) {
+            target_image = Arc::new(apply_filters(&target_image, &self.filters, false));
+        }
         let (target_image_width, target_image_height) = target_image.dimensions();
         info!(
             "start processing image with {}x{}",
             target_image_width, target_image_height
         );
-        let image_width = target_image_width as f32;
-        let image_height = target_image_height as f32;
+        let (mut pieces, piece_dimensions) =
+            self.cut_pieces(game_mode, target_image.dimensions(), progress)?;
+        if let Some(outline) = &self.outline {
+            // the outline is in pixels of the image before it is resized
+            let (origin_width, origin_height) = self.origin_image.dimensions();
+            let mut outline = outline.clone();
+            outline.apply_transform(DAffine2::from_scale(DVec2::new(
+                target_image_width as f64 / origin_width.max(1) as f64,
+                target_image_height as f64 / origin_height.max(1) as f64,
+            )));
+            pieces = clip_to_outline(pieces, Arc::new(outline));
+        }
+
+        Ok(JigsawTemplate {
+            pieces,
+            origin_image: target_image,
+            piece_dimensions,
+            number_of_pieces: (self.pieces_in_column, self.pieces_in_row),
+        })
+    }
+
+    /// Makes sure the tab size and the jitter are in their ranges and an image of `image_size`
+    /// is large enough for the pieces
+    fn check(&self, image_size: (u32, u32)) -> Result<(), JigsawError> {
+        if let Some(tab_size) = self
+            .tab_size
+            .filter(|tab_size| !TAB_SIZES.contains(tab_size))
+        {
+            return Err(JigsawError::TabSizeOutOfRange(tab_size));
+        }
+        if let Some(jitter) = self.jitter.filter(|jitter| !JITTERS.contains(jitter)) {
+            return Err(JigsawError::JitterOutOfRange(jitter));
+        }
+        if self.pieces_in_column == 0 || self.pieces_in_row == 0 {
+            return Err(JigsawError::NoPieces);
+        }
+        if (image_size.0 as usize) < self.pieces_in_column * MIN_PIECE_SIZE as usize
+            || (image_size.1 as usize) < self.pieces_in_row * MIN_PIECE_SIZE as usize
+        {
+            return Err(JigsawError::ImageTooSmall {
+                width: image_size.0,
+                height: image_size.1,
+                pieces_in_column: self.pieces_in_column,
+                pieces_in_row: self.pieces_in_row,
+            });
+        }
+        Ok(())
+    }
+
+    /// Cuts an image of `image_size` into the pieces, returns them with the size of a piece
+    fn cut_pieces(
+        &self,
+        game_mode: GameMode,
+        image_size: (u32, u32),
+        progress: impl Fn(usize, usize),
+    ) -> Result<(Vec<JigsawPiece>, (f32, f32)), JigsawError> {
+        self.check(image_size)?;
+        let image_width = image_size.0 as f32;
+        let image_height = image_size.1 as f32;
         let pieces_in_column = self.pieces_in_column;
         let pieces_in_row = self.pieces_in_row;
         let (starting_points_x, piece_width) = divide_axis(image_width, pieces_in_column);
@@ -663,7 +856,7 @@ impl JigsawGenerator {
                 piece_width,
                 &starting_points_y,
                 piece_height,
-            ),
+            )?,
             GameMode::Square => self.square_generator(
                 image_width,
                 image_height,
@@ -672,6 +865,8 @@ impl JigsawGenerator {
                 &starting_points_y,
                 piece_height,
             ),
+            GameMode::Triangular => return self.triangular_cut(image_size, progress),
+            GameMode::Crazy => return self.crazy_cut(image_size, progress),
         };
 
         let mut pieces = vec![];
@@ -692,12 +887,14 @@ impl JigsawGenerator {
                 let piece = JigsawPiece::new(
                     i,
                     (*x, *y),
-                    target_image.dimensions(),
+                    image_size,
                     (piece_width, piece_height),
-                    horizontal_edges[top_index].clone(),
-                    vertical_edges[right_index].clone(),
-                    horizontal_edges[bottom_index].clone(),
-                    vertical_edges[left_index].clone(),
+                    vec![
+                        horizontal_edges[top_index].clone(),
+                        vertical_edges[right_index].clone(),
+                        horizontal_edges[bottom_index].clone(),
+                        vertical_edges[left_index].clone(),
+                    ],
                     is_boarder,
                 )?;
 
@@ -706,15 +903,202 @@ impl JigsawGenerator {
 
                 pieces.push(piece);
                 i += 1;
+                progress(i, self.pieces_count());
             }
         }
 
-        Ok(JigsawTemplate {
-            pieces,
-            origin_image: target_image,
-            piece_dimensions: (piece_width, piece_height),
-            number_of_pieces: (pieces_in_column, pieces_in_row),
-        })
+        Ok((pieces, (piece_width, piece_height)))
+    }
+
+    /// Cuts rows of triangles pointing up and down in turns for [`GameMode::Triangular`], the
+    /// first and the last triangle of a row are right-angled to fill the rectangle. Every other
+    /// row is mirrored, so the triangle sharing the base of a triangle is at the same column of
+    /// the row above or below.
+    fn triangular_cut(
+        &self,
+        image_size: (u32, u32),
+        progress: impl Fn(usize, usize),
+    ) -> Result<(Vec<JigsawPiece>, (f32, f32)), JigsawError> {
+        let columns = self.pieces_in_column;
+        let rows = self.pieces_in_row;
+        if columns < 2 {
+            return Err(JigsawError::TooFewPiecesInRow { needed: 2 });
+        }
+        let (image_width, image_height) = (image_size.0 as f32, image_size.1 as f32);
+        let (starting_points_y, piece_height) = divide_axis(image_height, rows);
+        // the corners along a row lie half a triangle apart
+        let step = round(image_width / (columns - 1) as f32);
+        let corners: Vec<f32> = (0..columns)
+            .map(|k| {
+                if k + 1 == columns {
+                    image_width
+                } else {
+                    round(k as f32 * step)
+                }
+            })
+            .collect();
+        let mut contour_gen = EdgeContourGenerator::new(
+            2.0 * step,
+            piece_height,
+            self.tab_size,
+            self.jitter,
+            self.seed,
+        )?;
+
+        let mut pieces = vec![];
+        // the bases shared with the next row, by column
+        let mut bases: Vec<Option<Edge>> = vec![None; columns];
+        for (row, top) in starting_points_y.iter().copied().enumerate() {
+            let bottom = end_point_pos(row, &starting_points_y, image_height);
+            // corner `k` lies on the top of the row for even `k + row`
+            let corner = |k: usize| {
+                let y = if (k + row).is_multiple_of(2) {
+                    top
+                } else {
+                    bottom
+                };
+                (corners[k], y)
+            };
+            // the sides between the triangles, with the sides of the image at both ends
+            let mut sides = vec![Edge::StraightEdge(StraightEdge {
+                starting_point: (0.0, top),
+                end_point: (0.0, bottom),
+            })];
+            for k in 1..columns {
+                sides.push(Edge::IndentedEdge(
+                    contour_gen.create_along(corner(k - 1), corner(k)),
+                ));
+            }
+            sides.push(Edge::StraightEdge(StraightEdge {
+                starting_point: (image_width, top),
+                end_point: (image_width, bottom),
+            }));
+
+            for column in 0..columns {
+                let index = row * columns + column;
+                let base_at_bottom = (column + row).is_multiple_of(2);
+                let base_y = if base_at_bottom { bottom } else { top };
+                let base_start = (corners[column.max(1) - 1], base_y);
+                let base_end = (corners[(column + 1).min(columns - 1)], base_y);
+                let outer = if base_at_bottom {
+                    row + 1 == rows
+                } else {
+                    row == 0
+                };
+                let base = if outer {
+                    Edge::StraightEdge(StraightEdge {
+                        starting_point: base_start,
+                        end_point: base_end,
+                    })
+                } else if base_at_bottom {
+                    let base = Edge::IndentedEdge(contour_gen.create_along(base_start, base_end));
+                    bases[column] = Some(base.clone());
+                    base
+                } else {
+                    bases[column]
+                        .take()
+                        .ok_or(JigsawError::BrokenPiece(index))?
+                };
+
+                debug!("starting process piece {index}");
+                let mut piece = JigsawPiece::new(
+                    index,
+                    (base_start.0, top),
+                    image_size,
+                    (base_end.0 - base_start.0, piece_height),
+                    vec![base, sides[column + 1].clone(), sides[column].clone()],
+                    outer || column == 0 || column + 1 == columns,
+                )?;
+                piece.triangles_in_row = Some(columns);
+                pieces.push(piece);
+                progress(pieces.len(), self.pieces_count());
+            }
+        }
+
+        Ok((pieces, (2.0 * step, piece_height)))
+    }
+
+    /// Cuts irregular pieces along the cells of a [`voronoi`] diagram for [`GameMode::Crazy`],
+    /// with a tab on every side long enough for one. The pieces keep the order of the grid the
+    /// cells are spread over.
+    fn crazy_cut(
+        &self,
+        image_size: (u32, u32),
+        progress: impl Fn(usize, usize),
+    ) -> Result<(Vec<JigsawPiece>, (f32, f32)), JigsawError> {
+        let (image_width, image_height) = (image_size.0 as f32, image_size.1 as f32);
+        let cell_size = (
+            image_width / self.pieces_in_column as f32,
+            image_height / self.pieces_in_row as f32,
+        );
+        let cells = voronoi::cells(
+            image_width,
+            image_height,
+            self.pieces_in_column,
+            self.pieces_in_row,
+            self.seed.unwrap_or(0) as u64,
+        );
+        let mut contour_gen = EdgeContourGenerator::new(
+            cell_size.0,
+            cell_size.0.min(cell_size.1),
+            self.tab_size,
+            self.jitter,
+            self.seed,
+        )?;
+        // shorter sides stay straight, a tab wouldn't fit
+        let min_tab_side = cell_size.0.min(cell_size.1) * MIN_TAB_SIDE;
+
+        let mut shared_edges: HashMap<(usize, usize), Edge> = HashMap::new();
+        let mut pieces = vec![];
+        for (index, cell) in cells.iter().enumerate() {
+            let mut edges = vec![];
+            let mut is_boarder = false;
+            for (a, b, neighbor) in cell.sides() {
+                let (a, b) = ((round(a.x), round(a.y)), (round(b.x), round(b.y)));
+                let edge = match neighbor {
+                    Some(neighbor) => shared_edges
+                        .entry((index.min(neighbor), index.max(neighbor)))
+                        .or_insert_with(|| {
+                            if (a.0 - b.0).hypot(a.1 - b.1) < min_tab_side {
+                                Edge::StraightEdge(StraightEdge {
+                                    starting_point: a,
+                                    end_point: b,
+                                })
+                            } else {
+                                Edge::IndentedEdge(contour_gen.create_along(a, b))
+                            }
+                        })
+                        .clone(),
+                    None => {
+                        is_boarder = true;
+                        Edge::StraightEdge(StraightEdge {
+                            starting_point: a,
+                            end_point: b,
+                        })
+                    }
+                };
+                edges.push(edge);
+            }
+
+            let (min, max) = cell.corners.iter().fold(
+                (glam::Vec2::splat(f32::MAX), glam::Vec2::splat(f32::MIN)),
+                |(min, max), corner| (min.min(*corner), max.max(*corner)),
+            );
+            debug!("starting process piece {index}");
+            let mut piece = JigsawPiece::new(
+                index,
+                (round(min.x), round(min.y)),
+                image_size,
+                (round(max.x - min.x), round(max.y - min.y)),
+                edges,
+                is_boarder,
+            )?;
+            piece.neighbors = Some(cell.neighbors.iter().flatten().copied().collect());
+            pieces.push(piece);
+            progress(pieces.len(), self.pieces_count());
+        }
+
+        Ok((pieces, cell_size))
     }
 
     fn square_generator(
@@ -799,14 +1183,14 @@ impl JigsawGenerator {
         piece_width: f32,
         starting_points_y: &[f32],
         piece_height: f32,
-    ) -> (Vec<Edge>, Vec<Edge>) {
+    ) -> Result<(Vec<Edge>, Vec<Edge>), JigsawError> {
         let mut contour_gen = EdgeContourGenerator::new(
             piece_width,
             piece_height,
             self.tab_size,
             self.jitter,
             self.seed,
-        );
+        )?;
         let mut vertical_edges = vec![];
         let mut horizontal_edges = vec![];
         let mut top_border = true;
@@ -866,8 +1250,113 @@ impl JigsawGenerator {
                 ),
             }))
         }
-        (vertical_edges, horizontal_edges)
+        Ok((vertical_edges, horizontal_edges))
+    }
+}
+
+/// Leaves out the pieces outside of the `outline` and cuts the ones it crosses along it. The
+/// pieces left are numbered again from zero, their neighbors with them
+fn clip_to_outline(
+    mut pieces: Vec<JigsawPiece>,
+    outline: Arc<Subpath<PuzzleId>>,
+) -> Vec<JigsawPiece> {
+    // triangles find their neighbors by their index, which changes, so they are listed instead
+    let triangle_neighbors: Vec<Option<Vec<usize>>> = pieces
+        .iter()
+        .map(|piece| {
+            let columns = piece.triangles_in_row?;
+            let candidates = [
+                piece.index.checked_sub(1),
+                Some(piece.index + 1),
+                piece.index.checked_sub(columns),
+                Some(piece.index + columns),
+            ];
+            Some(
+                candidates
+                    .into_iter()
+                    .flatten()
+                    .filter(|&other| pieces.get(other).is_some_and(|other| piece.beside(other)))
+                    .collect(),
+            )
+        })
+        .collect();
+    for (piece, neighbors) in pieces.iter_mut().zip(triangle_neighbors) {
+        if neighbors.is_some() {
+            piece.neighbors = neighbors;
+        }
     }
+
+    let outline_points: Vec<DVec2> = outline
+        .iter()
+        .flat_map(|bezier| (0..8).map(move |i| bezier_point(&bezier, i as f64 / 8.0)))
+        .collect();
+    let mut pieces: Vec<JigsawPiece> = pieces
+        .into_iter()
+        .filter_map(|mut piece| {
+            let piece_points = piece.outline(8);
+            let inside = piece_points
+                .iter()
+                .filter(|(x, y)| outline_contains(&outline, DVec2::new(*x as f64, *y as f64)))
+                .count();
+            let crossed = outline_points
+                .iter()
+                .any(|point| subpath_contains(&piece.subpath, *point));
+            if inside == 0 && !crossed {
+                return None;
+            }
+            if inside < piece_points.len() || crossed {
+                piece.is_boarder = true;
+                piece.puzzle_outline = Some(outline.clone());
+            }
+            Some(piece)
+        })
+        .collect();
+
+    let new_index: HashMap<usize, usize> = pieces
+        .iter()
+        .enumerate()
+        .map(|(index, piece)| (piece.index, index))
+        .collect();
+    for (index, piece) in pieces.iter_mut().enumerate() {
+        piece.index = index;
+        if let Some(neighbors) = piece.neighbors.as_mut() {
+            neighbors.retain_mut(|neighbor| match new_index.get(neighbor) {
+                Some(index) => {
+                    *neighbor = *index;
+                    true
+                }
+                None => false,
+            });
+        }
+    }
+    pieces
+}
+
+/// Cuts a `width` x `height` rectangle into classic pieces without an image, for when only the
+/// paths of the pieces are needed. `tab_size`, `jitter` and `seed` are the ones of
+/// [`JigsawGenerator`], the defaults are used for `None`
+pub fn build_jigsaw_pieces(
+    width: u32,
+    height: u32,
+    pieces_in_column: usize,
+    pieces_in_row: usize,
+    tab_size: Option<f32>,
+    jitter: Option<f32>,
+    seed: Option<usize>,
+) -> Result<Vec<JigsawPiece>, JigsawError> {
+    let generator = JigsawGenerator {
+        // the paths only need the size of the image
+        origin_image: Arc::new(DynamicImage::default()),
+        pieces_in_column,
+        pieces_in_row,
+        tab_size,
+        jitter,
+        seed,
+        filters: vec![],
+        outline: None,
+    };
+    let (pieces, _) = generator.cut_pieces(GameMode::Classic, (width, height), |_, _| {})?;
+    Ok(pieces)
 }
 
 #[derive(Debug, Clone)]
@@ -882,6 +1371,133 @@ pub struct JigsawTemplate {
     pub number_of_pieces: (usize, usize),
 }
 
+impl JigsawTemplate {
+    /// The pieces the puzzle is cut into, fewer than in the grid if it has an outline
+    pub fn pieces_count(&self) -> usize {
+        self.pieces.len()
+    }
+
+    /// The puzzle image at half, a quarter and so on of its resolution, one image per level, for
+    /// drawing the pieces when they are zoomed out
+    pub fn image_levels(&self, levels: usize) -> Vec<DynamicImage> {
+        let (width, height) = self.origin_image.dimensions();
+        (1..=levels)
+            .map(|level| {
+                self.origin_image.resize_exact(
+                    (width >> level).max(1),
+                    (height >> level).max(1),
+                    image::imageops::FilterType::Triangle,
+                )
+            })
+            .collect()
+    }
+
+    /// A complete SVG document the size of the puzzle image with the path of every piece, for
+    /// opening the cut lines in a vector editor or printing them
+    pub fn to_svg(&self) -> String {
+        let (width, height) = self.origin_image.dimensions();
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+             viewBox=\"0 0 {width} {height}\">\n"
+        );
+        // the pieces are cut along the outline of the puzzle, if there is one
+        let outline = self
+            .pieces
+            .iter()
+            .find_map(|piece| piece.puzzle_outline.as_ref())
+            .map(|outline| {
+                let mut path = String::new();
+                let _ = outline.subpath_to_svg(&mut path, DAffine2::IDENTITY);
+                path.trim_end().to_string()
+            });
+        match &outline {
+            Some(outline) => svg.push_str(&format!(
+                "<clipPath id=\"outline\"><path d=\"{outline}\"/></clipPath>\n\
+                 <g fill=\"none\" stroke=\"black\" stroke-width=\"1\" \
+                 clip-path=\"url(#outline)\">\n\
+                 <path id=\"outline-path\" d=\"{outline}\"/>\n"
+            )),
+            None => svg.push_str("<g fill=\"none\" stroke=\"black\" stroke-width=\"1\">\n"),
+        }
+        for piece in self.pieces.iter() {
+            let mut path = String::new();
+            // writing into a string never fails
+            let _ = piece.subpath.subpath_to_svg(&mut path, DAffine2::IDENTITY);
+            svg.push_str(&format!(
+                "<path id=\"piece-{}\" d=\"{}\"/>\n",
+                piece.index,
+                path.trim_end()
+            ));
+        }
+        svg.push_str("</g>\n</svg>\n");
+        svg
+    }
+}
+
+/// The place of a piece image in a texture atlas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtlasSlot {
+    /// The atlas page the image is on
+    pub page: usize,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Packs images of the given sizes row by row into pages of at most `max_size` pixels per side,
+/// keeping `padding` pixels between them. Returns the slot of every image and the size of every
+/// page.
+///
+/// The tallest images are packed first, so the images of a row are of about the same height.
+pub fn pack_atlas(
+    sizes: &[(u32, u32)],
+    max_size: u32,
+    padding: u32,
+) -> (Vec<AtlasSlot>, Vec<(u32, u32)>) {
+    let mut order: Vec<usize> = (0..sizes.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(sizes[i].1));
+
+    let mut slots = vec![
+        AtlasSlot {
+            page: 0,
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0
+        };
+        sizes.len()
+    ];
+    let mut pages = vec![];
+    let (mut x, mut y, mut row_height) = (0, 0, 0);
+    for i in order {
+        let (width, height) = sizes[i];
+        if x > 0 && x + width > max_size {
+            x = 0;
+            y += row_height + padding;
+            row_height = 0;
+        }
+        if pages.is_empty() || (y > 0 && y + height > max_size) {
+            pages.push((0, 0));
+            (x, y, row_height) = (0, 0, 0);
+        }
+        let page = pages.len() - 1;
+        slots[i] = AtlasSlot {
+            page,
+            x,
+            y,
+            width,
+            height,
+        };
+        let size: &mut (u32, u32) = &mut pages[page];
+        size.0 = size.0.max(x + width);
+        size.1 = size.1.max(y + height);
+        x += width + padding;
+        row_height = row_height.max(height);
+    }
+    (slots, pages)
+}
+
 /// Scales the given image to fit within the maximum width and height constraints.
 /// If the image dimensions exceed the maximum allowed dimensions, it scales the image down
 /// while maintaining the aspect ratio. Otherwise, it returns the original image.
@@ -924,11 +1540,17 @@ pub struct JigsawPiece {
     pub top_left_y: u32,
     pub crop_width: u32,
     pub crop_height: u32,
-    pub top_edge: Edge,
-    pub right_edge: Edge,
-    pub bottom_edge: Edge,
-    pub left_edge: Edge,
+    /// The edges around the piece in order, four for a rectangle and three for a triangle
+    pub edges: Vec<Edge>,
     pub is_boarder: bool,
+    /// The triangles in a row of a [`GameMode::Triangular`] puzzle, whose neighbors are found
+    /// by their index
+    pub triangles_in_row: Option<usize>,
+    /// The pieces sharing a side with this one in a [`GameMode::Crazy`] puzzle
+    pub neighbors: Option<Vec<usize>>,
+    /// The outline of the whole puzzle if it crosses this piece, see
+    /// [`JigsawGenerator::outline`]. The piece is cut along it
+    pub puzzle_outline: Option<Arc<Subpath<PuzzleId>>>,
 }
 
 impl JigsawPiece {
@@ -938,24 +1560,41 @@ impl JigsawPiece {
         start_point: (f32, f32),
         origin_image_size: (u32, u32),
         piece_size: (f32, f32),
-        top_edge: Edge,
-        right_edge: Edge,
-        bottom_edge: Edge,
-        left_edge: Edge,
+        edges: Vec<Edge>,
         is_boarder: bool,
-    ) -> Result<Self> {
-        let top_beziers = top_edge.to_beziers(false);
-        let right_beziers = right_edge.to_beziers(false);
-        let bottom_beziers = bottom_edge.to_beziers(true);
-        let left_beziers = left_edge.to_beziers(true);
-        let beziers: Vec<_> = vec![top_beziers, right_beziers, bottom_beziers, left_beziers]
-            .into_iter()
-            .flatten()
-            .collect();
+    ) -> Result<Self, JigsawError> {
+        // every edge is drawn on from where the one before ends, turned around if it starts at
+        // the other end
+        let distance = |a: (f32, f32), b: (f32, f32)| (a.0 - b.0).hypot(a.1 - b.1);
+        let mut beziers = vec![];
+        // the first edge is drawn from the corner it shares with the last one
+        let closing = |point: (f32, f32)| {
+            edges.last().map_or(0.0, |last| {
+                distance(point, last.starting_point()).min(distance(point, last.end_point()))
+            })
+        };
+        let mut at = edges.first().map(|first| {
+            if closing(first.end_point()) < closing(first.starting_point()) {
+                first.end_point()
+            } else {
+                first.starting_point()
+            }
+        });
+        for edge in edges.iter() {
+            let reverse = at.is_some_and(|at| {
+                distance(edge.end_point(), at) < distance(edge.starting_point(), at)
+            });
+            beziers.extend(edge.to_beziers(reverse));
+            at = Some(if reverse {
+                edge.starting_point()
+            } else {
+                edge.end_point()
+            });
+        }
         let subpath: Subpath<PuzzleId> = Subpath::from_beziers(&beziers, true);
         let [box_min, box_max] = subpath
             .bounding_box()
-            .ok_or(anyhow!("No bounding box found"))?;
+            .ok_or(JigsawError::BrokenPiece(index))?;
 
         let (image_width, image_height) = (origin_image_size.0, origin_image_size.1);
         let (piece_width, piece_height) = (piece_size.0, piece_size.1);
@@ -980,11 +1619,11 @@ impl JigsawPiece {
             top_left_y,
             crop_width,
             crop_height,
-            top_edge,
-            right_edge,
-            bottom_edge,
-            left_edge,
+            edges,
             is_boarder,
+            triangles_in_row: None,
+            neighbors: None,
+            puzzle_outline: None,
         })
     }
 
@@ -1017,11 +1656,54 @@ impl JigsawPiece {
                 }
             });
 
-        self.draw_bezier(&mut piece_image, WHITE_COLOR);
+        self.draw_bezier(&mut piece_image, WHITE_COLOR, DVec2::ZERO);
 
         piece_image.into()
     }
 
+    /// Draws only the outline of the piece with the given `color` onto a transparent image of the
+    /// cropped size, the line is `2 * radius + 1` pixels thick
+    pub fn outline_image(&self, color: Rgba<u8>, radius: i32) -> DynamicImage {
+        let mut image = RgbaImage::new(self.crop_width, self.crop_height);
+        for dx in -radius..=radius {
+            for dy in -radius..=radius {
+                if dx * dx + dy * dy <= radius * radius {
+                    self.draw_bezier(&mut image, color, DVec2::new(dx as f64, dy as f64));
+                }
+            }
+        }
+
+        image.into()
+    }
+
+    /// A mask of the piece at the cropped size for drawing it straight from the puzzle image: the
+    /// alpha channel covers the piece, red marks its white cut line and green the outline which
+    /// is `2 * outline_radius + 1` pixels thick
+    pub fn mask(&self, outline_radius: i32) -> RgbaImage {
+        let mut cut_line = RgbaImage::new(self.crop_width, self.crop_height);
+        self.draw_bezier(&mut cut_line, WHITE_COLOR, DVec2::ZERO);
+        let outline = self.outline_image(WHITE_COLOR, outline_radius).to_rgba8();
+
+        let mut mask = RgbaImage::new(self.crop_width, self.crop_height);
+        mask.par_enumerate_pixels_mut().for_each(|(x, y, pixel)| {
+            let point = DVec2::new(
+                self.top_left_x as f64 + x as f64,
+                self.top_left_y as f64 + y as f64,
+            );
+            let on_cut_line = cut_line.get_pixel(x, y).0[3] != 0;
+            let on_outline = outline.get_pixel(x, y).0[3] != 0;
+            let inside = on_cut_line || self.contains(point);
+            *pixel = Rgba([
+                if on_cut_line { 255 } else { 0 },
+                if on_outline { 255 } else { 0 },
+                0,
+                if inside { 255 } else { 0 },
+            ]);
+        });
+
+        mask
+    }
+
     /// Fills the not transparent parts of the image with white color
     pub fn fill_white(&self, image: &DynamicImage) -> DynamicImage {
         let mut white_image = image.to_rgba8();
@@ -1036,42 +1718,80 @@ impl JigsawPiece {
         white_image.into()
     }
 
-    fn draw_bezier(&self, image: &mut RgbaImage, color: Rgba<u8>) {
-        let top_left_x = self.top_left_x as f64;
-        let top_left_y = self.top_left_y as f64;
-        let top_left = DVec2::new(top_left_x, top_left_y);
-        for path in self.subpath.iter() {
-            match path.handles {
-                BezierHandles::Linear => {
-                    let start = path.start - top_left - 1.0;
-                    let end = path.end - top_left - 1.0;
-
-                    imageproc::drawing::draw_line_segment_mut(
-                        image,
-                        (start.x.max(0.0) as f32, start.y.max(0.0) as f32),
-                        (end.x.max(0.0) as f32, end.y.max(0.0) as f32),
-                        color,
-                    );
+    /// The average color of the body of the piece in the puzzle `image`, every `step`th pixel
+    /// in both directions is sampled
+    pub fn average_color(&self, image: &DynamicImage, step: u32) -> Rgba<u8> {
+        let (image_width, image_height) = image.dimensions();
+        let left = (self.start_point.0.max(0.0) as u32).min(image_width);
+        let top = (self.start_point.1.max(0.0) as u32).min(image_height);
+        let right = ((self.start_point.0 + self.width) as u32).min(image_width);
+        let bottom = ((self.start_point.1 + self.height) as u32).min(image_height);
+
+        let mut sum = [0u64; 4];
+        let mut count = 0u64;
+        for y in (top..bottom).step_by(step.max(1) as usize) {
+            for x in (left..right).step_by(step.max(1) as usize) {
+                let pixel = image.get_pixel(x, y);
+                for (channel, value) in sum.iter_mut().zip(pixel.0) {
+                    *channel += value as u64;
                 }
-                BezierHandles::Quadratic { .. } => {}
-                BezierHandles::Cubic {
-                    handle_start,
-                    handle_end,
-                } => {
-                    let start = (path.start.x - top_left_x, path.start.y - top_left_y);
-                    let end = (path.end.x - top_left_x, path.end.y - top_left_y);
-                    let handle_start = (handle_start.x - top_left_x, handle_start.y - top_left_y);
-                    let handle_end = (handle_end.x - top_left_x, handle_end.y - top_left_y);
+                count += 1;
+            }
+        }
+        if count == 0 {
+            return Rgba([0, 0, 0, 0]);
+        }
+        Rgba(sum.map(|channel| (channel / count) as u8))
+    }
 
-                    imageproc::drawing::draw_cubic_bezier_curve_mut(
-                        image,
-                        (start.0 as f32, start.1 as f32),
-                        (end.0 as f32, end.1 as f32),
-                        (handle_start.0 as f32, handle_start.1 as f32),
-                        (handle_end.0 as f32, handle_end.1 as f32),
-                        color,
-                    );
-                }
+    /// The variance of the brightness of the image under the piece, without its tabs, sampling
+    /// every `step`th pixel. Low for plain areas like a clear sky.
+    pub fn luma_variance(&self, image: &DynamicImage, step: u32) -> f32 {
+        let (image_width, image_height) = image.dimensions();
+        let left = (self.start_point.0.max(0.0) as u32).min(image_width);
+        let top = (self.start_point.1.max(0.0) as u32).min(image_height);
+        let right = ((self.start_point.0 + self.width) as u32).min(image_width);
+        let bottom = ((self.start_point.1 + self.height) as u32).min(image_height);
+
+        let mut sum = 0.0;
+        let mut squares = 0.0;
+        let mut count = 0.0;
+        for y in (top..bottom).step_by(step.max(1) as usize) {
+            for x in (left..right).step_by(step.max(1) as usize) {
+                let [r, g, b, _] = image.get_pixel(x, y).0;
+                let luma = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+                sum += luma;
+                squares += luma * luma;
+                count += 1.0;
+            }
+        }
+        if count == 0.0 {
+            return 0.0;
+        }
+        let mean = sum / count;
+        (squares / count - mean * mean).max(0.0)
+    }
+
+    /// Draws the outline of the piece onto an image of the whole puzzle
+    pub fn draw_cut_lines(&self, image: &mut RgbaImage, color: Rgba<u8>) {
+        let shift = DVec2::new(self.top_left_x as f64, self.top_left_y as f64);
+        self.draw_bezier(image, color, shift);
+    }
+
+    /// Draws the outline of the piece onto the cropped `image`, moved by `shift` pixels
+    fn draw_bezier(&self, image: &mut RgbaImage, color: Rgba<u8>, shift: DVec2) {
+        let top_left = DVec2::new(self.top_left_x as f64, self.top_left_y as f64) - shift;
+        let Some(puzzle_outline) = &self.puzzle_outline else {
+            draw_subpath(&self.subpath, image, color, top_left);
+            return;
+        };
+        // the cut line follows the piece inside the outline and the outline along the piece
+        let mut lines = RgbaImage::new(image.width(), image.height());
+        draw_subpath(&self.subpath, &mut lines, color, top_left);
+        draw_subpath(puzzle_outline, &mut lines, color, top_left);
+        for (x, y, pixel) in lines.enumerate_pixels() {
+            if pixel.0[3] != 0 && self.touches(top_left + DVec2::new(x as f64, y as f64)) {
+                image.put_pixel(x, y, *pixel);
             }
         }
     }
@@ -1082,17 +1802,68 @@ impl JigsawPiece {
         self_loc: (f32, f32),
         other_loc: (f32, f32),
     ) -> bool {
-        if (self_loc.0 + self.width - other_loc.0).abs() < COMPARE_THRESHOLD
-            && (self_loc.1 - other_loc.1).abs() < COMPARE_THRESHOLD
-        {
-            self.on_the_left_side(other)
-        } else {
-            false
-        }
+        self.is_on_the_left_side_within(other, self_loc, other_loc, COMPARE_THRESHOLD)
+    }
+
+    /// Like [`JigsawPiece::is_on_the_left_side`], but with a custom snapping tolerance in pixels
+    pub fn is_on_the_left_side_within(
+        &self,
+        other: &JigsawPiece,
+        self_loc: (f32, f32),
+        other_loc: (f32, f32),
+        threshold: f32,
+    ) -> bool {
+        self.lies_as_solved(other, self_loc, other_loc, threshold) && self.on_the_left_side(other)
+    }
+
+    /// Whether `other` lies within `threshold` pixels of where it lies next to the piece in the
+    /// solved puzzle. The locations point up, unlike the start points
+    fn lies_as_solved(
+        &self,
+        other: &JigsawPiece,
+        self_loc: (f32, f32),
+        other_loc: (f32, f32),
+        threshold: f32,
+    ) -> bool {
+        let offset = (
+            other.start_point.0 - self.start_point.0,
+            self.start_point.1 - other.start_point.1,
+        );
+        (other_loc.0 - self_loc.0 - offset.0).abs() < threshold
+            && (other_loc.1 - self_loc.1 - offset.1).abs() < threshold
+    }
+
+    /// Whether the piece starts at `x`, `y` in the solved puzzle. Neighbors are found by their
+    /// positions, the straight edges of square pieces don't tell them apart by their shape
+    fn starts_at(&self, x: f32, y: f32) -> bool {
+        (self.start_point.0 - x).abs() < NEIGHBOR_THRESHOLD
+            && (self.start_point.1 - y).abs() < NEIGHBOR_THRESHOLD
+    }
+
+    /// The offset from the center of this piece to the center of `other` if they share a side
+    /// in a [`GameMode::Crazy`] puzzle
+    fn neighbor_offset(&self, other: &JigsawPiece) -> Option<(f32, f32)> {
+        let neighbors = self.neighbors.as_ref()?;
+        neighbors.contains(&other.index).then(|| {
+            (
+                other.start_point.0 + other.width / 2.0 - self.start_point.0 - self.width / 2.0,
+                other.start_point.1 + other.height / 2.0 - self.start_point.1 - self.height / 2.0,
+            )
+        })
     }
 
     pub fn on_the_left_side(&self, other: &JigsawPiece) -> bool {
-        self.right_edge == other.left_edge
+        if self.neighbors.is_some() {
+            // irregular neighbors count as left and right when they lie more beside than below
+            return self
+                .neighbor_offset(other)
+                .is_some_and(|(x, y)| x > 0.0 && x >= y.abs());
+        }
+        if let Some(columns) = self.triangles_in_row {
+            // a triangle shares its slanted side with the next one in the row
+            return self.index % columns + 1 < columns && other.index == self.index + 1;
+        }
+        other.starts_at(self.start_point.0 + self.width, self.start_point.1)
     }
 
     pub fn is_on_the_right_side(
@@ -1101,17 +1872,22 @@ impl JigsawPiece {
         self_loc: (f32, f32),
         other_loc: (f32, f32),
     ) -> bool {
-        if (other_loc.0 + other.width - self_loc.0).abs() < COMPARE_THRESHOLD
-            && (self_loc.1 - other_loc.1).abs() < COMPARE_THRESHOLD
-        {
-            self.on_the_right_side(other)
-        } else {
-            false
-        }
+        self.is_on_the_right_side_within(other, self_loc, other_loc, COMPARE_THRESHOLD)
+    }
+
+    /// Like [`JigsawPiece::is_on_the_right_side`], but with a custom snapping tolerance in pixels
+    pub fn is_on_the_right_side_within(
+        &self,
+        other: &JigsawPiece,
+        self_loc: (f32, f32),
+        other_loc: (f32, f32),
+        threshold: f32,
+    ) -> bool {
+        self.lies_as_solved(other, self_loc, other_loc, threshold) && self.on_the_right_side(other)
     }
 
     pub fn on_the_right_side(&self, other: &JigsawPiece) -> bool {
-        self.left_edge == other.right_edge
+        other.on_the_left_side(self)
     }
 
     pub fn is_on_the_top_side(
@@ -1120,17 +1896,32 @@ impl JigsawPiece {
         self_loc: (f32, f32),
         other_loc: (f32, f32),
     ) -> bool {
-        if (other_loc.1 + other.height - self_loc.1).abs() < COMPARE_THRESHOLD
-            && (self_loc.0 - other_loc.0).abs() < COMPARE_THRESHOLD
-        {
-            self.on_the_top_side(other)
-        } else {
-            false
-        }
+        self.is_on_the_top_side_within(other, self_loc, other_loc, COMPARE_THRESHOLD)
+    }
+
+    /// Like [`JigsawPiece::is_on_the_top_side`], but with a custom snapping tolerance in pixels
+    pub fn is_on_the_top_side_within(
+        &self,
+        other: &JigsawPiece,
+        self_loc: (f32, f32),
+        other_loc: (f32, f32),
+        threshold: f32,
+    ) -> bool {
+        self.lies_as_solved(other, self_loc, other_loc, threshold) && self.on_the_top_side(other)
     }
 
     pub fn on_the_top_side(&self, other: &JigsawPiece) -> bool {
-        self.bottom_edge == other.top_edge
+        if self.neighbors.is_some() {
+            return self
+                .neighbor_offset(other)
+                .is_some_and(|(x, y)| y > 0.0 && y > x.abs());
+        }
+        if let Some(columns) = self.triangles_in_row {
+            // a triangle pointing up shares its base with the one at its column in the next row
+            let base_at_bottom = (self.index % columns + self.index / columns).is_multiple_of(2);
+            return base_at_bottom && other.index == self.index + columns;
+        }
+        other.starts_at(self.start_point.0, self.start_point.1 + self.height)
     }
 
     pub fn is_on_the_bottom_side(
@@ -1139,17 +1930,22 @@ impl JigsawPiece {
         self_loc: (f32, f32),
         other_loc: (f32, f32),
     ) -> bool {
-        if (other_loc.1 - other.height - self_loc.1).abs() < COMPARE_THRESHOLD
-            && (self_loc.0 - other_loc.0).abs() < COMPARE_THRESHOLD
-        {
-            self.on_the_bottom_side(other)
-        } else {
-            false
-        }
+        self.is_on_the_bottom_side_within(other, self_loc, other_loc, COMPARE_THRESHOLD)
+    }
+
+    /// Like [`JigsawPiece::is_on_the_bottom_side`], but with a custom snapping tolerance in pixels
+    pub fn is_on_the_bottom_side_within(
+        &self,
+        other: &JigsawPiece,
+        self_loc: (f32, f32),
+        other_loc: (f32, f32),
+        threshold: f32,
+    ) -> bool {
+        self.lies_as_solved(other, self_loc, other_loc, threshold) && self.on_the_bottom_side(other)
     }
 
     pub fn on_the_bottom_side(&self, other: &JigsawPiece) -> bool {
-        self.top_edge == other.bottom_edge
+        other.on_the_top_side(self)
     }
 
     pub fn beside(&self, other: &JigsawPiece) -> bool {
@@ -1159,20 +1955,74 @@ impl JigsawPiece {
             || self.on_the_right_side(other)
     }
 
+    /// The edge on the side of a four sided piece of the classic and square modes, `side` counts
+    /// clockwise from the top
+    fn rectangular_edge(&self, side: usize) -> Option<&Edge> {
+        (self.edges.len() == 4 && self.neighbors.is_none()).then(|| &self.edges[side])
+    }
+
+    /// The top edge of a classic or square piece, `None` for the pieces of the other modes
+    pub fn top_edge(&self) -> Option<&Edge> {
+        self.rectangular_edge(0)
+    }
+
+    /// The right edge of a classic or square piece, `None` for the pieces of the other modes
+    pub fn right_edge(&self) -> Option<&Edge> {
+        self.rectangular_edge(1)
+    }
+
+    /// The bottom edge of a classic or square piece, `None` for the pieces of the other modes
+    pub fn bottom_edge(&self) -> Option<&Edge> {
+        self.rectangular_edge(2)
+    }
+
+    /// The left edge of a classic or square piece, `None` for the pieces of the other modes
+    pub fn left_edge(&self) -> Option<&Edge> {
+        self.rectangular_edge(3)
+    }
+
     pub fn is_boarder(&self) -> bool {
         self.is_boarder
     }
 
-    /// Checks if a given point is inside the puzzle piece
-    /// Trick: Check if the point is inside the rotated subpath. If not, check if it is inside the original subpath
+    /// Points along the outline of the piece in image coordinates, each edge curve is sampled
+    /// `samples` times
+    pub fn outline(&self, samples: usize) -> Vec<(f32, f32)> {
+        let samples = samples.max(1);
+        self.subpath
+            .iter()
+            .flat_map(|bezier| {
+                (0..samples).map(move |i| {
+                    let point = bezier_point(&bezier, i as f64 / samples as f64);
+                    (point.x as f32, point.y as f32)
+                })
+            })
+            .collect()
+    }
+
+    /// Checks if a given point is inside the puzzle piece, cut along the outline of the puzzle
     fn contains(&self, point: DVec2) -> bool {
-        self.subpath.point_inside(
-            point,
-            // self.rotation_matrix1,
-            // self.rotation_matrix2,
-            // &self.rotated_subpath1,
-            // &self.rotated_subpath2,
-        ) || self.subpath.contains_point(point)
+        subpath_contains(&self.subpath, point)
+            && self
+                .puzzle_outline
+                .as_ref()
+                .is_none_or(|outline| outline_contains(outline, point))
+    }
+
+    /// Whether the point is at most a pixel away from the piece, for the cut lines on its edge
+    fn touches(&self, point: DVec2) -> bool {
+        let near = |contains: &dyn Fn(DVec2) -> bool| {
+            [-1.5, 0.0, 1.5].iter().any(|dx| {
+                [-1.5, 0.0, 1.5]
+                    .iter()
+                    .any(|dy| contains(point + DVec2::new(*dx, *dy)))
+            })
+        };
+        near(&|point| subpath_contains(&self.subpath, point))
+            && self
+                .puzzle_outline
+                .as_ref()
+                .is_none_or(|outline| near(&|point| outline_contains(outline, point)))
     }
 
     #[allow(dead_code)]
@@ -1206,7 +2056,91 @@ impl JigsawPiece {
     }
 }
 
-const COMPARE_THRESHOLD: f32 = 10.0;
+/// Draws a subpath onto an `image` whose top left corner is at `top_left`
+fn draw_subpath(
+    subpath: &Subpath<PuzzleId>,
+    image: &mut RgbaImage,
+    color: Rgba<u8>,
+    top_left: DVec2,
+) {
+    let (top_left_x, top_left_y) = (top_left.x, top_left.y);
+    for path in subpath.iter() {
+        match path.handles {
+            BezierHandles::Linear => {
+                let start = path.start - top_left - 1.0;
+                let end = path.end - top_left - 1.0;
+
+                imageproc::drawing::draw_line_segment_mut(
+                    image,
+                    (start.x.max(0.0) as f32, start.y.max(0.0) as f32),
+                    (end.x.max(0.0) as f32, end.y.max(0.0) as f32),
+                    color,
+                );
+            }
+            BezierHandles::Quadratic { .. } => {}
+            BezierHandles::Cubic {
+                handle_start,
+                handle_end,
+            } => {
+                let start = (path.start.x - top_left_x, path.start.y - top_left_y);
+                let end = (path.end.x - top_left_x, path.end.y - top_left_y);
+                let handle_start = (handle_start.x - top_left_x, handle_start.y - top_left_y);
+                let handle_end = (handle_end.x - top_left_x, handle_end.y - top_left_y);
+
+                imageproc::drawing::draw_cubic_bezier_curve_mut(
+                    image,
+                    (start.0 as f32, start.1 as f32),
+                    (end.0 as f32, end.1 as f32),
+                    (handle_start.0 as f32, handle_start.1 as f32),
+                    (handle_end.0 as f32, handle_end.1 as f32),
+                    color,
+                );
+            }
+        }
+    }
+}
+
+/// Whether the point is inside the closed path of a piece
+fn subpath_contains(subpath: &Subpath<PuzzleId>, point: DVec2) -> bool {
+    subpath.point_inside(point) || subpath.contains_point(point)
+}
+
+/// Whether the point is inside the outline of the puzzle. Only rays are cast, the winding number
+/// is off for points in line with an anchor of a curved outline like an ellipse
+fn outline_contains(outline: &Subpath<PuzzleId>, point: DVec2) -> bool {
+    outline.point_inside(point)
+}
+
+/// Evaluates a Bézier curve at `t` in `0.0..=1.0`
+fn bezier_point(bezier: &Bezier, t: f64) -> DVec2 {
+    let (start, end) = (bezier.start, bezier.end);
+    match bezier.handles {
+        BezierHandles::Linear => start.lerp(end, t),
+        BezierHandles::Quadratic { handle } => {
+            let u = 1.0 - t;
+            start * u * u + handle * 2.0 * u * t + end * t * t
+        }
+        BezierHandles::Cubic {
+            handle_start,
+            handle_end,
+        } => {
+            let u = 1.0 - t;
+            start * u * u * u
+                + handle_start * 3.0 * u * u * t
+                + handle_end * 3.0 * u * t * t
+                + end * t * t * t
+        }
+    }
+}
+
+/// The shortest side of a [`GameMode::Crazy`] piece with a tab, in sizes of a grid cell
+const MIN_TAB_SIDE: f32 = 0.4;
+
+/// The default distance in pixels within which two matching pieces snap together
+pub const COMPARE_THRESHOLD: f32 = 10.0;
+
+/// How far the start of a neighbor may be off, as the starting points are rounded
+const NEIGHBOR_THRESHOLD: f32 = 0.5;
 
 #[derive(Clone, PartialEq, Hash, Eq, Debug)]
 pub struct PuzzleId(u64);
@@ -1229,6 +2163,334 @@ pub enum Side {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_bezier_point() {
+        let linear = Bezier {
+            start: DVec2::new(0.0, 0.0),
+            end: DVec2::new(10.0, 20.0),
+            handles: BezierHandles::Linear,
+        };
+        assert_eq!(bezier_point(&linear, 0.5), DVec2::new(5.0, 10.0));
+
+        let cubic = Bezier {
+            start: DVec2::new(0.0, 0.0),
+            end: DVec2::new(30.0, 0.0),
+            handles: BezierHandles::Cubic {
+                handle_start: DVec2::new(10.0, 10.0),
+                handle_end: DVec2::new(20.0, 10.0),
+            },
+        };
+        assert_eq!(bezier_point(&cubic, 0.0), cubic.start);
+        assert_eq!(bezier_point(&cubic, 1.0), cubic.end);
+        assert_eq!(bezier_point(&cubic, 0.5), DVec2::new(15.0, 7.5));
+    }
+
+    #[test]
+    fn test_pack_atlas() {
+        let sizes = vec![(40, 30); 10];
+        let (slots, pages) = pack_atlas(&sizes, 100, 2);
+        // two images in a row and three rows on a page, the last page only as large as needed
+        assert_eq!(pages, vec![(82, 94), (82, 62)]);
+        assert_eq!(
+            slots[1],
+            AtlasSlot {
+                page: 0,
+                x: 42,
+                y: 0,
+                width: 40,
+                height: 30
+            }
+        );
+        assert_eq!(slots[2].y, 32);
+        assert_eq!(slots[6].page, 1);
+        assert_eq!((slots[6].x, slots[6].y), (0, 0));
+
+        for (i, a) in slots.iter().enumerate() {
+            let (width, height) = pages[a.page];
+            assert!(a.x + a.width <= width && a.y + a.height <= height);
+            for b in slots[i + 1..].iter().filter(|b| b.page == a.page) {
+                let apart = a.x + a.width <= b.x
+                    || b.x + b.width <= a.x
+                    || a.y + a.height <= b.y
+                    || b.y + b.height <= a.y;
+                assert!(apart, "{:?} overlaps {:?}", a, b);
+            }
+        }
+
+        // the tallest images share the first row
+        let (slots, pages) = pack_atlas(&[(40, 20), (40, 30), (40, 30)], 100, 2);
+        assert_eq!(pages, vec![(82, 52)]);
+        assert_eq!((slots[1].x, slots[1].y), (0, 0));
+        assert_eq!((slots[2].x, slots[2].y), (42, 0));
+        assert_eq!((slots[0].x, slots[0].y), (0, 32));
+    }
+
+    #[test]
+    fn test_image_levels() {
+        let template = JigsawTemplate {
+            pieces: vec![],
+            origin_image: Arc::new(RgbaImage::new(200, 101).into()),
+            piece_dimensions: (100.0, 101.0),
+            number_of_pieces: (2, 1),
+        };
+        let sizes: Vec<_> = template
+            .image_levels(2)
+            .iter()
+            .map(GenericImageView::dimensions)
+            .collect();
+        assert_eq!(sizes, vec![(100, 50), (50, 25)]);
+    }
+
+    #[test]
+    fn test_triangular_neighbors() {
+        let image = RgbaImage::from_pixel(300, 200, Rgba([0, 0, 0, 255]));
+        let template = JigsawGenerator::new(image.into(), 4, 3)
+            .generate(GameMode::Triangular, false)
+            .unwrap();
+        let pieces = &template.pieces;
+        assert_eq!(pieces.len(), 12);
+        assert!(pieces.iter().all(|piece| piece.edges.len() == 3));
+        // the sides within a row
+        assert!(pieces[0].on_the_left_side(&pieces[1]));
+        assert!(pieces[2].on_the_left_side(&pieces[3]));
+        assert!(!pieces[3].on_the_left_side(&pieces[4]));
+        // the bases between the rows, the first triangle of a row points up in turns
+        assert!(pieces[0].on_the_top_side(&pieces[4]));
+        assert!(!pieces[1].on_the_top_side(&pieces[5]));
+        assert!(pieces[5].on_the_top_side(&pieces[9]));
+        assert!(pieces[9].on_the_bottom_side(&pieces[5]));
+        assert!(!pieces[4].on_the_top_side(&pieces[8]));
+        // triangles pointing up only touch the top of the image with their tip
+        assert!(!pieces[2].is_boarder());
+        assert!(pieces[3].is_boarder());
+    }
+
+    #[test]
+    fn test_triangular_cover_the_image() {
+        let image = RgbaImage::from_pixel(300, 200, Rgba([0, 0, 0, 255]));
+        let template = JigsawGenerator::new(image.into(), 5, 2)
+            .seed(3)
+            .generate(GameMode::Triangular, false)
+            .unwrap();
+        let covered: usize = template
+            .pieces
+            .iter()
+            .map(|piece| {
+                piece
+                    .mask(0)
+                    .pixels()
+                    .filter(|pixel| pixel.0[3] != 0)
+                    .count()
+            })
+            .sum();
+        // the cut lines belong to both pieces beside them
+        let area = 300.0 * 200.0;
+        assert!((covered as f32 - area).abs() < area * 0.05, "{covered}");
+    }
+
+    #[test]
+    fn test_crazy_neighbors() {
+        let image = RgbaImage::from_pixel(600, 400, Rgba([0, 0, 0, 255]));
+        let template = JigsawGenerator::new(image.into(), 6, 4)
+            .seed(1)
+            .generate(GameMode::Crazy, false)
+            .unwrap();
+        let pieces = &template.pieces;
+        assert_eq!(pieces.len(), 24);
+        for piece in pieces {
+            let neighbors = piece.neighbors.as_ref().unwrap();
+            assert!(!neighbors.is_empty());
+            for &neighbor in neighbors {
+                assert!(piece.beside(&pieces[neighbor]), "{}", piece.index);
+                assert!(pieces[neighbor].beside(piece), "{}", piece.index);
+            }
+        }
+        // the corners of the image belong to the border
+        assert!(pieces[0].is_boarder());
+        assert!(pieces[23].is_boarder());
+    }
+
+    #[test]
+    fn test_crazy_cover_the_image() {
+        let image = RgbaImage::from_pixel(300, 200, Rgba([0, 0, 0, 255]));
+        let template = JigsawGenerator::new(image.into(), 4, 3)
+            .seed(5)
+            .generate(GameMode::Crazy, false)
+            .unwrap();
+        let covered: usize = template
+            .pieces
+            .iter()
+            .map(|piece| {
+                piece
+                    .mask(0)
+                    .pixels()
+                    .filter(|pixel| pixel.0[3] != 0)
+                    .count()
+            })
+            .sum();
+        let area = 300.0 * 200.0;
+        assert!((covered as f32 - area).abs() < area * 0.05, "{covered}");
+    }
+
+    #[test]
+    fn test_triangular_needs_two_pieces_in_a_row() {
+        let image = RgbaImage::from_pixel(300, 200, Rgba([0, 0, 0, 255]));
+        assert_eq!(
+            JigsawGenerator::new(image.into(), 1, 3)
+                .generate(GameMode::Triangular, false)
+                .unwrap_err(),
+            JigsawError::TooFewPiecesInRow { needed: 2 }
+        );
+    }
+
+    #[test]
+    fn test_invalid_settings() {
+        let image = || RgbaImage::from_pixel(300, 200, Rgba([0, 0, 0, 255])).into();
+        let generate = |generator: JigsawGenerator| generator.generate(GameMode::Classic, false);
+        assert_eq!(
+            generate(JigsawGenerator::new(image(), 3, 2).tab_size(40.0)).unwrap_err(),
+            JigsawError::TabSizeOutOfRange(40.0)
+        );
+        assert_eq!(
+            generate(JigsawGenerator::new(image(), 3, 2).jitter(-1.0)).unwrap_err(),
+            JigsawError::JitterOutOfRange(-1.0)
+        );
+        assert_eq!(
+            generate(JigsawGenerator::new(image(), 0, 2)).unwrap_err(),
+            JigsawError::NoPieces
+        );
+        assert_eq!(
+            generate(JigsawGenerator::new(image(), 100, 2)).unwrap_err(),
+            JigsawError::ImageTooSmall {
+                width: 300,
+                height: 200,
+                pieces_in_column: 100,
+                pieces_in_row: 2,
+            }
+        );
+        // square pieces have no tabs, but the settings are checked all the same
+        assert!(JigsawGenerator::new(image(), 3, 2)
+            .tab_size(40.0)
+            .generate(GameMode::Square, false)
+            .is_err());
+        assert!(EdgeContourGenerator::new(10.0, 10.0, Some(5.0), None, None).is_err());
+        assert_eq!(
+            generate_columns_rows_numbers(300.0, 200.0, 0),
+            Err(JigsawError::NoPieces)
+        );
+    }
+
+    #[test]
+    fn test_build_jigsaw_pieces() {
+        let pieces = build_jigsaw_pieces(300, 200, 3, 2, None, None, Some(7)).unwrap();
+        assert_eq!(pieces.len(), 6);
+        assert_eq!(pieces[4].start_point, (100.0, 100.0));
+        // the same cut as from an image of that size
+        let image = RgbaImage::from_pixel(300, 200, Rgba([0, 0, 0, 255]));
+        let template = JigsawGenerator::new(image.into(), 3, 2)
+            .seed(7)
+            .generate(GameMode::Classic, false)
+            .unwrap();
+        for (piece, cut) in pieces.iter().zip(template.pieces.iter()) {
+            assert_eq!(piece.subpath, cut.subpath);
+        }
+    }
+
+    #[test]
+    fn test_to_svg() {
+        let image = RgbaImage::from_pixel(90, 60, Rgba([0, 0, 0, 255]));
+        let template = JigsawGenerator::new(image.into(), 3, 2)
+            .generate(GameMode::Classic, false)
+            .unwrap();
+        let svg = template.to_svg();
+        assert!(svg.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"90\" height=\"60\" viewBox=\"0 0 90 60\">"
+        ));
+        assert!(svg.trim_end().ends_with("</svg>"));
+        assert_eq!(svg.matches("<path ").count(), 6);
+        assert!(svg.contains("<path id=\"piece-5\" d=\"M"));
+        // the inner edges have tabs
+        assert!(svg.contains('C'));
+    }
+
+    #[test]
+    fn test_outline_clips_the_pieces() {
+        let image = RgbaImage::from_pixel(300, 200, Rgba([0, 0, 0, 255]));
+        let template = JigsawGenerator::new(image.into(), 3, 2)
+            .seed(2)
+            .outline(Subpath::new_ellipse(DVec2::ZERO, DVec2::new(300.0, 200.0)))
+            .generate(GameMode::Classic, false)
+            .unwrap();
+        assert_eq!(template.pieces.len(), 6);
+        assert!(template.pieces.iter().all(JigsawPiece::is_boarder));
+        let covered: usize = template
+            .pieces
+            .iter()
+            .map(|piece| {
+                piece
+                    .mask(0)
+                    .pixels()
+                    .filter(|pixel| pixel.0[3] != 0)
+                    .count()
+            })
+            .sum();
+        let area = std::f32::consts::PI * 150.0 * 100.0;
+        assert!((covered as f32 - area).abs() < area * 0.05, "{covered}");
+        assert!(template.to_svg().contains("clip-path=\"url(#outline)\""));
+    }
+
+    #[test]
+    fn test_outline_leaves_out_pieces() {
+        let image = RgbaImage::from_pixel(600, 400, Rgba([0, 0, 0, 255]));
+        let template = JigsawGenerator::new(image.into(), 6, 4)
+            .seed(2)
+            .outline(Subpath::new_ellipse(
+                DVec2::new(210.0, 110.0),
+                DVec2::new(390.0, 290.0),
+            ))
+            .generate(GameMode::Classic, false)
+            .unwrap();
+        // the corners are far outside, the middle is inside
+        let starts_at = |point: (f32, f32)| {
+            template
+                .pieces
+                .iter()
+                .position(|piece| piece.start_point == point)
+        };
+        assert_eq!(starts_at((0.0, 0.0)), None);
+        assert_eq!(starts_at((500.0, 300.0)), None);
+        assert!(starts_at((200.0, 100.0)).is_some() && starts_at((300.0, 200.0)).is_some());
+        // the pieces left are numbered again
+        assert!(template.pieces_count() < 24);
+        for (index, piece) in template.pieces.iter().enumerate() {
+            assert_eq!(piece.index, index);
+        }
+    }
+
+    #[test]
+    fn test_outline_keeps_the_neighbors() {
+        let image = RgbaImage::from_pixel(600, 400, Rgba([0, 0, 0, 255]));
+        let outline = Subpath::new_ellipse(DVec2::new(150.0, 50.0), DVec2::new(450.0, 350.0));
+        for game_mode in [GameMode::Triangular, GameMode::Crazy] {
+            let template = JigsawGenerator::new(image.clone().into(), 6, 4)
+                .seed(2)
+                .outline(outline.clone())
+                .generate(game_mode, false)
+                .unwrap();
+            let pieces = &template.pieces;
+            assert!(pieces.len() < 24, "{game_mode:?}");
+            for (index, piece) in pieces.iter().enumerate() {
+                assert_eq!(piece.index, index);
+                let neighbors = piece.neighbors.as_ref().unwrap();
+                assert!(!neighbors.is_empty(), "{game_mode:?} {index}");
+                for &neighbor in neighbors {
+                    assert!(piece.beside(&pieces[neighbor]), "{game_mode:?} {index}");
+                    assert!(pieces[neighbor].beside(piece), "{game_mode:?} {index}");
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_divide_axis() {
         let res = divide_axis(1000.0, 4);
@@ -1260,6 +2522,106 @@ mod tests {
         assert_eq!(find_divisors(given_number), vec![(1, 9), (3, 3), (9, 1),])
     }
 
+    #[test]
+    fn test_average_color() {
+        let mut image = RgbaImage::from_pixel(200, 100, Rgba([200, 40, 40, 255]));
+        for y in 0..100 {
+            for x in 100..200 {
+                image.put_pixel(x, y, Rgba([20, 60, 220, 255]));
+            }
+        }
+        let template = JigsawGenerator::new(image.into(), 2, 1)
+            .generate(GameMode::Square, false)
+            .unwrap();
+        let colors: Vec<_> = template
+            .pieces
+            .iter()
+            .map(|piece| piece.average_color(&template.origin_image, 3))
+            .collect();
+        assert_eq!(
+            colors,
+            vec![Rgba([200, 40, 40, 255]), Rgba([20, 60, 220, 255])]
+        );
+    }
+
+    #[test]
+    fn test_luma_variance() {
+        let mut image = RgbaImage::from_pixel(200, 100, Rgba([90, 90, 90, 255]));
+        for y in 0..100 {
+            for x in 100..200 {
+                let value = if (x + y) % 2 == 0 { 0 } else { 255 };
+                image.put_pixel(x, y, Rgba([value, value, value, 255]));
+            }
+        }
+        let template = JigsawGenerator::new(image.into(), 2, 1)
+            .generate(GameMode::Square, false)
+            .unwrap();
+        let plain = template.pieces[0].luma_variance(&template.origin_image, 1);
+        let checkered = template.pieces[1].luma_variance(&template.origin_image, 1);
+        assert!(plain < 1.0);
+        assert!(checkered > 10_000.0);
+    }
+
+    #[test]
+    fn test_generate_with_progress() {
+        let image = RgbaImage::from_pixel(90, 60, Rgba([0, 0, 0, 255]));
+        let reported = core::cell::RefCell::new(vec![]);
+        JigsawGenerator::new(image.into(), 3, 2)
+            .generate_with_progress(GameMode::Square, false, |done, total| {
+                reported.borrow_mut().push((done, total));
+            })
+            .unwrap();
+        assert_eq!(
+            reported.into_inner(),
+            (1..=6).map(|done| (done, 6)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_square_neighbors() {
+        let image = RgbaImage::from_pixel(90, 60, Rgba([0, 0, 0, 255]));
+        let template = JigsawGenerator::new(image.into(), 3, 2)
+            .generate(GameMode::Square, false)
+            .unwrap();
+        let pieces = &template.pieces;
+        assert!(pieces[0].on_the_left_side(&pieces[1]));
+        assert!(pieces[1].on_the_right_side(&pieces[0]));
+        assert!(pieces[1].on_the_top_side(&pieces[4]));
+        assert!(pieces[4].on_the_bottom_side(&pieces[1]));
+        // the straight edges look alike, but the pieces don't fit
+        assert!(!pieces[0].on_the_left_side(&pieces[2]));
+        assert!(!pieces[2].on_the_left_side(&pieces[3]));
+        assert!(!pieces[0].beside(&pieces[4]));
+
+        let top = pieces[0].top_edge().unwrap();
+        assert_eq!(
+            (top.starting_point(), top.end_point()),
+            ((0.0, 0.0), (30.0, 0.0))
+        );
+        let left = pieces[4].left_edge().unwrap();
+        assert_eq!(left.starting_point().0, 30.0);
+        assert_eq!(
+            pieces[4].right_edge().unwrap().starting_point(),
+            pieces[5].left_edge().unwrap().starting_point()
+        );
+        assert_eq!(pieces[1].bottom_edge().unwrap().end_point().1, 30.0);
+    }
+
+    #[test]
+    fn test_no_rectangular_edges() {
+        let image = RgbaImage::from_pixel(240, 120, Rgba([0, 0, 0, 255]));
+        for game_mode in [GameMode::Triangular, GameMode::Crazy] {
+            let template = JigsawGenerator::new(image.clone().into(), 4, 2)
+                .seed(3)
+                .generate(game_mode, false)
+                .unwrap();
+            assert!(template
+                .pieces
+                .iter()
+                .all(|piece| piece.top_edge().is_none()));
+        }
+    }
+
     #[test]
     fn test_optimal_aspect_ratio() {
         let image_width: f32 = 1024.;
@@ -1287,4 +2649,13 @@ mod tests {
             Some((6, 4))
         );
     }
+
+    #[test]
+    fn test_columns_rows_follow_the_image() {
+        // the same count is cut into more rows than columns on a portrait photo
+        let landscape = generate_columns_rows_numbers(1024., 768., 300).unwrap();
+        let portrait = generate_columns_rows_numbers(768., 1024., 300).unwrap();
+        assert_eq!(landscape, (20, 15));
+        assert_eq!(portrait, (15, 20));
+    }
 }
@@ -0,0 +1,200 @@
+//! A plain text record of how a puzzle is cut, to cut the same pieces from its image again, e.g.
+//! to resume a saved puzzle. Every line holds a key and its value. Unknown keys are skipped and
+//! missing ones keep their defaults, so manifests written by older and newer versions still load.
+//!
+//! ```text
+//! version 1
+//! grid 6 4
+//! mode classic
+//! tab_size 20
+//! jitter 5
+//! seed 1234
+//! filters grayscale posterize
+//! ```
+
+use crate::filter::ImageFilter;
+use crate::{GameMode, JigsawGenerator};
+use anyhow::{anyhow, Result};
+use image::DynamicImage;
+
+/// The version of the manifests written by this crate
+pub const MANIFEST_VERSION: u32 = 1;
+
+/// Everything deciding the shape of the pieces, besides the image
+#[derive(Debug, Clone, PartialEq)]
+pub struct PuzzleManifest {
+    /// The version the manifest was written by
+    pub version: u32,
+    pub columns: usize,
+    pub rows: usize,
+    pub game_mode: GameMode,
+    pub tab_size: Option<f32>,
+    pub jitter: Option<f32>,
+    /// Without a seed the pieces are cut differently every time
+    pub seed: Option<usize>,
+    pub filters: Vec<ImageFilter>,
+}
+
+impl PuzzleManifest {
+    /// Reads a manifest, only the grid is required
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut grid = None;
+        let mut manifest = PuzzleManifest {
+            version: MANIFEST_VERSION,
+            columns: 0,
+            rows: 0,
+            game_mode: GameMode::default(),
+            tab_size: None,
+            jitter: None,
+            seed: None,
+            filters: vec![],
+        };
+        for line in text.lines() {
+            let (key, value) = line.trim().split_once(' ').unwrap_or((line.trim(), ""));
+            match key {
+                "version" => manifest.version = value.parse().unwrap_or(MANIFEST_VERSION),
+                "grid" => {
+                    grid = value.split_once(' ').and_then(|(columns, rows)| {
+                        Some((columns.trim().parse().ok()?, rows.trim().parse().ok()?))
+                    });
+                }
+                "mode" => {
+                    manifest.game_mode = match value {
+                        "square" => GameMode::Square,
+                        "triangular" => GameMode::Triangular,
+                        "crazy" => GameMode::Crazy,
+                        _ => GameMode::Classic,
+                    };
+                }
+                "tab_size" => manifest.tab_size = value.parse().ok(),
+                "jitter" => manifest.jitter = value.parse().ok(),
+                "seed" => manifest.seed = value.parse().ok(),
+                "filters" => {
+                    manifest.filters = value
+                        .split_whitespace()
+                        .filter_map(ImageFilter::from_name)
+                        .collect();
+                }
+                _ => {}
+            }
+        }
+        let (columns, rows) = grid
+            .filter(|&(columns, rows)| columns > 0 && rows > 0)
+            .ok_or_else(|| anyhow!("The manifest has no grid"))?;
+        manifest.columns = columns;
+        manifest.rows = rows;
+        Ok(manifest)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![
+            format!("version {}", self.version),
+            format!("grid {} {}", self.columns, self.rows),
+            format!(
+                "mode {}",
+                match self.game_mode {
+                    GameMode::Classic => "classic",
+                    GameMode::Square => "square",
+                    GameMode::Triangular => "triangular",
+                    GameMode::Crazy => "crazy",
+                }
+            ),
+        ];
+        if let Some(tab_size) = self.tab_size {
+            lines.push(format!("tab_size {}", tab_size));
+        }
+        if let Some(jitter) = self.jitter {
+            lines.push(format!("jitter {}", jitter));
+        }
+        if let Some(seed) = self.seed {
+            lines.push(format!("seed {}", seed));
+        }
+        if !self.filters.is_empty() {
+            let names: Vec<&str> = self.filters.iter().map(ImageFilter::name).collect();
+            lines.push(format!("filters {}", names.join(" ")));
+        }
+        lines.join("\n")
+    }
+
+    /// The generator cutting the pieces of the manifest from `image`
+    pub fn generator(&self, image: DynamicImage) -> JigsawGenerator {
+        JigsawGenerator {
+            tab_size: self.tab_size,
+            jitter: self.jitter,
+            seed: self.seed,
+            filters: self.filters.clone(),
+            ..JigsawGenerator::new(image, self.columns, self.rows)
+        }
+    }
+}
+
+impl JigsawGenerator {
+    /// The manifest cutting the same pieces as the generator in the `game_mode`
+    pub fn manifest(&self, game_mode: GameMode) -> PuzzleManifest {
+        PuzzleManifest {
+            version: MANIFEST_VERSION,
+            columns: self.pieces_in_column,
+            rows: self.pieces_in_row,
+            game_mode,
+            tab_size: self.tab_size,
+            jitter: self.jitter,
+            seed: self.seed,
+            filters: self.filters.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+
+    fn sample() -> DynamicImage {
+        RgbaImage::from_fn(120, 80, |x, y| Rgba([x as u8, y as u8, 100, 255])).into()
+    }
+
+    #[test]
+    fn test_manifest_round_trip() {
+        let manifest = JigsawGenerator::new(sample(), 6, 4)
+            .tab_size(25.0)
+            .jitter(8.0)
+            .seed(1234)
+            .filters([ImageFilter::Grayscale, ImageFilter::Blur])
+            .manifest(GameMode::Square);
+        assert_eq!(
+            PuzzleManifest::parse(&manifest.to_text()).unwrap(),
+            manifest
+        );
+    }
+
+    #[test]
+    fn test_unknown_and_missing_keys() {
+        let manifest =
+            PuzzleManifest::parse("version 7\ngrid 3 5\nsparkles 12\nfilters sepia glitter")
+                .unwrap();
+        assert_eq!(manifest.version, 7);
+        assert_eq!((manifest.columns, manifest.rows), (3, 5));
+        assert_eq!(manifest.game_mode, GameMode::Classic);
+        assert_eq!(manifest.seed, None);
+        assert_eq!(manifest.filters, vec![ImageFilter::Sepia]);
+
+        assert!(PuzzleManifest::parse("version 1\nseed 3").is_err());
+    }
+
+    #[test]
+    fn test_manifest_cuts_the_same_pieces() {
+        let generator = JigsawGenerator::new(sample(), 4, 3).jitter(10.0);
+        let manifest =
+            PuzzleManifest::parse(&generator.manifest(GameMode::Classic).to_text()).unwrap();
+        let cut = |generator: &JigsawGenerator| -> Vec<_> {
+            generator
+                .generate(GameMode::Classic, false)
+                .unwrap()
+                .pieces
+                .iter()
+                .map(|piece| (piece.top_left_x, piece.top_left_y, piece.crop_width))
+                .collect()
+        };
+        assert_eq!(cut(&manifest.generator(sample())), cut(&generator));
+    }
+}
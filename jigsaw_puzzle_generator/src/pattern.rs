@@ -0,0 +1,248 @@
+//! Puzzle images made up from a seed, so there is always something fresh to puzzle without
+//! importing photos. The same seed and style always make the same image.
+
+use image::{Rgba, RgbaImage};
+use imageproc::drawing::{draw_filled_circle_mut, draw_filled_rect_mut, draw_polygon_mut};
+use imageproc::point::Point;
+use imageproc::rect::Rect;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::f32::consts::TAU;
+
+/// Colors of a palette, the image blends between them
+const PALETTE_SIZE: usize = 5;
+/// Layers of noise added up, each of twice the detail of the last
+const NOISE_OCTAVES: u32 = 5;
+
+/// The kinds of images [`generate_pattern`] makes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PatternStyle {
+    /// Smooth colored clouds with contour bands
+    GradientNoise,
+    /// Overlapping rectangles, circles and triangles
+    Geometric,
+    /// Rings of petals around the center
+    Mandala,
+}
+
+impl PatternStyle {
+    pub const ALL: [PatternStyle; 3] = [
+        PatternStyle::GradientNoise,
+        PatternStyle::Geometric,
+        PatternStyle::Mandala,
+    ];
+}
+
+/// Makes an image of `width` x `height` pixels in the `style`
+pub fn generate_pattern(style: PatternStyle, seed: u64, width: u32, height: u32) -> RgbaImage {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let palette = random_palette(&mut rng);
+    match style {
+        PatternStyle::GradientNoise => gradient_noise(&mut rng, &palette, width, height),
+        PatternStyle::Geometric => geometric(&mut rng, &palette, width, height),
+        PatternStyle::Mandala => mandala(&mut rng, &palette, width, height),
+    }
+}
+
+/// Colors of evenly spread hues from a random start, alternating in lightness
+fn random_palette(rng: &mut StdRng) -> [[f32; 3]; PALETTE_SIZE] {
+    let start = rng.gen_range(0.0..360.0);
+    let spread = rng.gen_range(30.0..90.0);
+    core::array::from_fn(|i| {
+        let hue = (start + spread * i as f32) % 360.0;
+        let value = if i % 2 == 1 { 0.55 } else { 0.95 };
+        hsv_to_rgb(hue, rng.gen_range(0.5..0.9), value)
+    })
+}
+
+fn hsv_to_rgb(hue: f32, saturation: f32, value: f32) -> [f32; 3] {
+    let c = value * saturation;
+    let x = c * (1.0 - ((hue / 60.0) % 2.0 - 1.0).abs());
+    let m = value - c;
+    let (r, g, b) = match (hue / 60.0) as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    [r + m, g + m, b + m]
+}
+
+/// The color at `t` of the palette laid out from 0 to 1, wrapping around
+fn palette_color(palette: &[[f32; 3]; PALETTE_SIZE], t: f32) -> Rgba<u8> {
+    let position = t.rem_euclid(1.0) * PALETTE_SIZE as f32;
+    let index = position as usize % PALETTE_SIZE;
+    let (from, to) = (palette[index], palette[(index + 1) % PALETTE_SIZE]);
+    let blend = position.fract();
+    let channel = |i: usize| ((from[i] + (to[i] - from[i]) * blend) * 255.0).round() as u8;
+    Rgba([channel(0), channel(1), channel(2), 255])
+}
+
+/// A value between 0 and 1 for every lattice point, smoothly blended between them
+struct ValueNoise {
+    seed: u32,
+}
+
+impl ValueNoise {
+    fn lattice(&self, x: i32, y: i32) -> f32 {
+        let mut hash = (x as u32)
+            .wrapping_mul(0x27d4_eb2d)
+            .wrapping_add((y as u32).wrapping_mul(0x1656_67b1))
+            .wrapping_add(self.seed);
+        hash ^= hash >> 15;
+        hash = hash.wrapping_mul(0x2c1b_3c6d);
+        hash ^= hash >> 12;
+        hash as f32 / u32::MAX as f32
+    }
+
+    fn sample(&self, x: f32, y: f32) -> f32 {
+        let (x0, y0) = (x.floor(), y.floor());
+        let smooth = |t: f32| t * t * (3.0 - 2.0 * t);
+        let (sx, sy) = (smooth(x - x0), smooth(y - y0));
+        let (x0, y0) = (x0 as i32, y0 as i32);
+        let top = self.lattice(x0, y0) + (self.lattice(x0 + 1, y0) - self.lattice(x0, y0)) * sx;
+        let bottom = self.lattice(x0, y0 + 1)
+            + (self.lattice(x0 + 1, y0 + 1) - self.lattice(x0, y0 + 1)) * sx;
+        top + (bottom - top) * sy
+    }
+
+    /// The octaves added up, between 0 and 1
+    fn fractal(&self, x: f32, y: f32) -> f32 {
+        let (mut sum, mut amplitude, mut frequency, mut total) = (0.0, 1.0, 1.0, 0.0);
+        for _ in 0..NOISE_OCTAVES {
+            sum += self.sample(x * frequency, y * frequency) * amplitude;
+            total += amplitude;
+            amplitude *= 0.5;
+            frequency *= 2.0;
+        }
+        sum / total
+    }
+}
+
+fn gradient_noise(
+    rng: &mut StdRng,
+    palette: &[[f32; 3]; PALETTE_SIZE],
+    width: u32,
+    height: u32,
+) -> RgbaImage {
+    let noise = ValueNoise { seed: rng.gen() };
+    let scale = rng.gen_range(3.0..6.0) / width.max(height) as f32;
+    // the bands make the clouds easier to tell apart than a smooth blend
+    let bands = rng.gen_range(2.0..4.0);
+    RgbaImage::from_fn(width, height, |x, y| {
+        let value = noise.fractal(x as f32 * scale, y as f32 * scale);
+        palette_color(palette, value * bands)
+    })
+}
+
+fn geometric(
+    rng: &mut StdRng,
+    palette: &[[f32; 3]; PALETTE_SIZE],
+    width: u32,
+    height: u32,
+) -> RgbaImage {
+    let angle = rng.gen_range(0.0..TAU);
+    let (dx, dy) = (angle.cos(), angle.sin());
+    let diagonal = (width as f32).hypot(height as f32);
+    let mut image = RgbaImage::from_fn(width, height, |x, y| {
+        let t = (x as f32 * dx + y as f32 * dy) / diagonal;
+        palette_color(palette, t * 0.5)
+    });
+
+    let size = width.min(height) as f32;
+    let shapes = rng.gen_range(60..100);
+    for _ in 0..shapes {
+        let color = palette_color(palette, rng.gen());
+        let x = rng.gen_range(0..width) as i32;
+        let y = rng.gen_range(0..height) as i32;
+        let extent = (size * rng.gen_range(0.03..0.2)) as i32 + 1;
+        match rng.gen_range(0..3) {
+            0 => draw_filled_rect_mut(
+                &mut image,
+                Rect::at(x - extent, y - extent)
+                    .of_size(extent as u32 * 2, rng.gen_range(1..=extent as u32 * 2)),
+                color,
+            ),
+            1 => draw_filled_circle_mut(&mut image, (x, y), extent, color),
+            _ => {
+                let turn = rng.gen_range(0.0..TAU);
+                let corners: Vec<Point<i32>> = (0..3)
+                    .map(|i| {
+                        let angle = turn + TAU / 3.0 * i as f32;
+                        Point::new(
+                            x + (angle.cos() * extent as f32) as i32,
+                            y + (angle.sin() * extent as f32) as i32,
+                        )
+                    })
+                    .collect();
+                if corners[0] != corners[2] {
+                    draw_polygon_mut(&mut image, &corners, color);
+                }
+            }
+        }
+    }
+    image
+}
+
+fn mandala(
+    rng: &mut StdRng,
+    palette: &[[f32; 3]; PALETTE_SIZE],
+    width: u32,
+    height: u32,
+) -> RgbaImage {
+    let petals = rng.gen_range(5..13) as f32;
+    let rings = rng.gen_range(6.0..14.0);
+    let wobble = rng.gen_range(0.1..0.4);
+    let twist = rng.gen_range(-1.0..1.0);
+    let center = (width as f32 / 2.0, height as f32 / 2.0);
+    let radius = center.0.min(center.1);
+    RgbaImage::from_fn(width, height, |x, y| {
+        let (px, py) = (x as f32 - center.0, y as f32 - center.1);
+        let r = px.hypot(py) / radius;
+        let theta = py.atan2(px) + twist * r;
+        // mirrored within every petal, so the rings show the same shape all around
+        let segment = TAU / petals;
+        let folded = ((theta.rem_euclid(segment) / segment) - 0.5).abs();
+        let petal = (folded * core::f32::consts::PI).cos() * wobble;
+        let ring = r * rings + petal * rings;
+        let t = (ring.floor() * 0.37 + (ring.fract() - 0.5).abs() * 0.3).fract();
+        palette_color(palette, t)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_image() {
+        for style in PatternStyle::ALL {
+            let a = generate_pattern(style, 7, 64, 48);
+            let b = generate_pattern(style, 7, 64, 48);
+            assert_eq!(a.dimensions(), (64, 48));
+            assert_eq!(a, b);
+            assert_ne!(a, generate_pattern(style, 8, 64, 48));
+        }
+    }
+
+    #[test]
+    fn test_pattern_has_detail() {
+        for style in PatternStyle::ALL {
+            let image = generate_pattern(style, 1, 64, 48);
+            let first = image.get_pixel(0, 0);
+            let differing = image.pixels().filter(|pixel| *pixel != first).count();
+            let pixels = (image.width() * image.height()) as usize;
+            assert!(differing > pixels / 4, "{:?} is too plain", style);
+        }
+    }
+
+    #[test]
+    fn test_palette_color_wraps() {
+        let palette = [[0.0; 3], [1.0; 3], [0.0; 3], [1.0; 3], [0.0; 3]];
+        assert_eq!(palette_color(&palette, 0.0), Rgba([0, 0, 0, 255]));
+        assert_eq!(palette_color(&palette, 0.2), Rgba([255, 255, 255, 255]));
+        assert_eq!(palette_color(&palette, 1.2), Rgba([255, 255, 255, 255]));
+    }
+}
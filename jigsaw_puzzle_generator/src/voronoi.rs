@@ -0,0 +1,220 @@
+//! The cells of a Voronoi diagram over a rectangle, the outlines of the pieces of
+//! [`GameMode::Crazy`](crate::GameMode::Crazy). The sites are spread over a jittered grid and
+//! moved once to the centers of their cells, so the cells come out irregular, but of a similar
+//! size.
+
+use glam::Vec2;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// How far a site may stray from the center of its grid cell, in cell sizes
+const JITTER: f32 = 0.35;
+/// The grid cells around a site whose sites may share a side with it
+const SEARCH_RADIUS: isize = 2;
+/// Corners closer than this are one, shorter sides are dropped and the cells only touch in a
+/// corner there
+const MIN_SIDE: f32 = 0.5;
+
+/// A convex cell of the diagram
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cell {
+    /// The corners of the cell, clockwise on the image
+    pub corners: Vec<Vec2>,
+    /// The cell across the side from the corner of the same index to the next, `None` on the
+    /// rectangle
+    pub neighbors: Vec<Option<usize>>,
+}
+
+impl Cell {
+    /// The sides of the cell with the cell across each
+    pub fn sides(&self) -> impl Iterator<Item = (Vec2, Vec2, Option<usize>)> + '_ {
+        let count = self.corners.len();
+        (0..count).map(move |k| {
+            (
+                self.corners[k],
+                self.corners[(k + 1) % count],
+                self.neighbors[k],
+            )
+        })
+    }
+
+    /// The center of the area of the cell
+    fn centroid(&self) -> Vec2 {
+        let mut area = 0.0;
+        let mut center = Vec2::ZERO;
+        for (a, b, _) in self.sides() {
+            let cross = a.perp_dot(b);
+            area += cross;
+            center += (a + b) * cross;
+        }
+        if area.abs() < f32::EPSILON {
+            return self.corners.iter().copied().sum::<Vec2>() / self.corners.len() as f32;
+        }
+        center / (3.0 * area)
+    }
+}
+
+/// Splits a `width` x `height` rectangle into `columns` x `rows` cells, the same `seed` always
+/// splits it the same way. The cells are in the order of the grid, row by row.
+pub fn cells(width: f32, height: f32, columns: usize, rows: usize, seed: u64) -> Vec<Cell> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let cell_size = Vec2::new(width / columns as f32, height / rows as f32);
+    let sites: Vec<Vec2> = (0..columns * rows)
+        .map(|index| {
+            let grid = Vec2::new((index % columns) as f32, (index / columns) as f32);
+            let jitter = Vec2::new(
+                rng.gen_range(-JITTER..=JITTER),
+                rng.gen_range(-JITTER..=JITTER),
+            );
+            (grid + 0.5 + jitter) * cell_size
+        })
+        .collect();
+    let cells = diagram(&sites, width, height, columns, rows);
+    // one step of Lloyd's relaxation evens out the sizes
+    let relaxed: Vec<Vec2> = cells.iter().map(Cell::centroid).collect();
+    diagram(&relaxed, width, height, columns, rows)
+}
+
+/// The cell of every site, each site is looked for near its cell of the `columns` x `rows` grid
+fn diagram(sites: &[Vec2], width: f32, height: f32, columns: usize, rows: usize) -> Vec<Cell> {
+    let rectangle = Cell {
+        corners: vec![
+            Vec2::ZERO,
+            Vec2::new(width, 0.0),
+            Vec2::new(width, height),
+            Vec2::new(0.0, height),
+        ],
+        neighbors: vec![None; 4],
+    };
+    let cells: Vec<Cell> = (0..sites.len())
+        .map(|index| {
+            let (column, row) = ((index % columns) as isize, (index / columns) as isize);
+            let mut cell = rectangle.clone();
+            for dy in -SEARCH_RADIUS..=SEARCH_RADIUS {
+                for dx in -SEARCH_RADIUS..=SEARCH_RADIUS {
+                    let (other_column, other_row) = (column + dx, row + dy);
+                    if (dx, dy) == (0, 0)
+                        || !(0..columns as isize).contains(&other_column)
+                        || !(0..rows as isize).contains(&other_row)
+                    {
+                        continue;
+                    }
+                    let other = other_row as usize * columns + other_column as usize;
+                    cell = clip(&cell, sites[index], sites[other], other);
+                }
+            }
+            cell
+        })
+        .collect();
+    weld_corners(cells)
+}
+
+/// Cuts off the part of the cell closer to `other_site` than to `site`, the new side lies
+/// across from `other`
+fn clip(cell: &Cell, site: Vec2, other_site: Vec2, other: usize) -> Cell {
+    let middle = (site + other_site) / 2.0;
+    let direction = other_site - site;
+    let outside = |point: Vec2| (point - middle).dot(direction);
+    let crossing = |a: Vec2, b: Vec2| {
+        let (from, to) = (outside(a), outside(b));
+        a + (b - a) * (from / (from - to))
+    };
+
+    let mut clipped = Cell {
+        corners: vec![],
+        neighbors: vec![],
+    };
+    for (a, b, neighbor) in cell.sides() {
+        match (outside(a) <= 0.0, outside(b) <= 0.0) {
+            (true, true) => {
+                clipped.corners.push(a);
+                clipped.neighbors.push(neighbor);
+            }
+            (true, false) => {
+                clipped.corners.push(a);
+                clipped.neighbors.push(neighbor);
+                clipped.corners.push(crossing(a, b));
+                clipped.neighbors.push(Some(other));
+            }
+            (false, true) => {
+                clipped.corners.push(crossing(a, b));
+                clipped.neighbors.push(neighbor);
+            }
+            (false, false) => {}
+        }
+    }
+    clipped
+}
+
+/// Gives the corners which are the same in different cells the same position and removes the
+/// sides where cells barely touch, which come from four sites close to a circle
+fn weld_corners(cells: Vec<Cell>) -> Vec<Cell> {
+    let mut welded: Vec<Vec2> = vec![];
+    let mut weld = |corner: Vec2| match welded.iter().find(|w| w.distance(corner) < MIN_SIDE) {
+        Some(w) => *w,
+        None => {
+            welded.push(corner);
+            corner
+        }
+    };
+    cells
+        .into_iter()
+        .map(|cell| {
+            let corners: Vec<Vec2> = cell.corners.iter().map(|corner| weld(*corner)).collect();
+            let cell = Cell {
+                corners,
+                neighbors: cell.neighbors,
+            };
+            let mut kept = Cell {
+                corners: vec![],
+                neighbors: vec![],
+            };
+            for (a, b, neighbor) in cell.sides() {
+                if a != b {
+                    kept.corners.push(a);
+                    kept.neighbors.push(neighbor);
+                }
+            }
+            kept
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cells_cover_the_rectangle() {
+        let cells = cells(400.0, 300.0, 4, 3, 7);
+        assert_eq!(cells.len(), 12);
+        let area: f32 = cells
+            .iter()
+            .map(|cell| cell.sides().map(|(a, b, _)| a.perp_dot(b)).sum::<f32>() / 2.0)
+            .sum();
+        assert!((area - 400.0 * 300.0).abs() < 1.0, "{area}");
+    }
+
+    #[test]
+    fn test_neighbors_share_a_side() {
+        let cells = cells(400.0, 300.0, 4, 3, 7);
+        for (index, cell) in cells.iter().enumerate() {
+            for (a, b, neighbor) in cell.sides() {
+                let Some(neighbor) = neighbor else {
+                    continue;
+                };
+                // the cell across has the same side the other way around
+                let shared = cells[neighbor]
+                    .sides()
+                    .any(|(c, d, across)| across == Some(index) && c == b && d == a);
+                assert!(shared, "{index} and {neighbor}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_same_seed_same_cells() {
+        assert_eq!(cells(300.0, 200.0, 3, 2, 1), cells(300.0, 200.0, 3, 2, 1));
+        assert_ne!(cells(300.0, 200.0, 3, 2, 1), cells(300.0, 200.0, 3, 2, 2));
+    }
+}
@@ -0,0 +1,220 @@
+//! Animated GIFs as puzzle images, for a living photo: the pieces are cut from the first frame
+//! while all frames play through the masks of the pieces. GIFs show up in the image strip like
+//! other images, videos have to be converted to a GIF first.
+
+use crate::atlas::PieceMaterial;
+use crate::{AppState, GameState, OriginImage};
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext, RenderAssetUsages};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use core::time::Duration;
+use jigsaw_puzzle_generator::image::codecs::gif::GifDecoder;
+use jigsaw_puzzle_generator::image::{AnimationDecoder, DynamicImage, ImageError, RgbaImage};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_asset::<AnimatedImage>()
+        .register_asset_loader(GifLoader)
+        .init_resource::<AnimatedImages>()
+        .add_systems(
+            OnTransition {
+                exited: GameState::Generating,
+                entered: GameState::Play,
+            },
+            start_animation,
+        )
+        .add_systems(OnExit(AppState::Gameplay), stop_animation)
+        .add_systems(
+            Update,
+            play_animation
+                .run_if(resource_exists::<AnimatedPuzzle>)
+                .run_if(in_state(GameState::Play)),
+        );
+}
+
+/// Frames kept of long GIFs, every frame is a texture of the full size
+const MAX_FRAMES: usize = 150;
+/// The delay of frames which don't have one, browsers play them at about 10 frames a second
+const DEFAULT_DELAY: Duration = Duration::from_millis(100);
+
+/// The frames of an animated image with how long each is shown
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct AnimatedImage {
+    pub frames: Vec<(Handle<Image>, Duration)>,
+}
+
+/// The animations of images which were decoded from bytes, by their first frame
+#[derive(Resource, Debug, Default)]
+pub struct AnimatedImages(HashMap<AssetId<Image>, AnimatedImage>);
+
+/// The animation played on the pieces of the current puzzle
+#[derive(Resource, Debug)]
+pub(crate) struct AnimatedPuzzle {
+    animation: AnimatedImage,
+    frame: usize,
+    timer: Timer,
+}
+
+/// The frames of a GIF, at most [`MAX_FRAMES`]
+fn decode_gif(bytes: &[u8]) -> Result<Vec<(RgbaImage, Duration)>, ImageError> {
+    let frames = GifDecoder::new(std::io::Cursor::new(bytes))?
+        .into_frames()
+        .take(MAX_FRAMES)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(frames
+        .into_iter()
+        .map(|frame| {
+            let delay = Duration::from(frame.delay());
+            let delay = if delay.is_zero() {
+                DEFAULT_DELAY
+            } else {
+                delay
+            };
+            (frame.into_buffer(), delay)
+        })
+        .collect())
+}
+
+fn frame_image(frame: RgbaImage, usage: RenderAssetUsages) -> Image {
+    Image::from_dynamic(DynamicImage::ImageRgba8(frame), true, usage)
+}
+
+/// Decodes an encoded image, of an animated GIF the first frame with all frames remembered in
+/// the [`AnimatedImages`]
+pub fn add_image_bytes(
+    bytes: &[u8],
+    images: &mut Assets<Image>,
+    animated: &mut AnimatedImages,
+) -> Result<Handle<Image>, ImageError> {
+    if !bytes.starts_with(b"GIF8") {
+        let image = jigsaw_puzzle_generator::image::load_from_memory(bytes)?;
+        return Ok(images.add(Image::from_dynamic(
+            image,
+            true,
+            RenderAssetUsages::default(),
+        )));
+    }
+
+    let mut frames = decode_gif(bytes)?.into_iter();
+    let Some((first, delay)) = frames.next() else {
+        return Err(ImageError::IoError(
+            std::io::ErrorKind::UnexpectedEof.into(),
+        ));
+    };
+    let first_frame = images.add(frame_image(first.clone(), RenderAssetUsages::RENDER_WORLD));
+    let handle = images.add(frame_image(first, RenderAssetUsages::default()));
+    let animation = AnimatedImage {
+        frames: core::iter::once((first_frame, delay))
+            .chain(frames.map(|(frame, delay)| {
+                (
+                    images.add(frame_image(frame, RenderAssetUsages::RENDER_WORLD)),
+                    delay,
+                )
+            }))
+            .collect(),
+    };
+    if animation.frames.len() > 1 {
+        animated.0.insert(handle.id(), animation);
+    }
+    Ok(handle)
+}
+
+/// Loads the first frame of a GIF as the image, the frames are the labeled [`AnimatedImage`]
+/// `animation`
+#[derive(Default)]
+struct GifLoader;
+
+impl AssetLoader for GifLoader {
+    type Asset = Image;
+    type Settings = ();
+    type Error = Box<dyn core::error::Error + Send + Sync>;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Image, Self::Error> {
+        let mut bytes = vec![];
+        reader.read_to_end(&mut bytes).await?;
+        let frames = decode_gif(&bytes)?;
+        let first = frames
+            .first()
+            .map(|(frame, _)| frame.clone())
+            .ok_or("the GIF has no frames")?;
+        let frames = frames
+            .into_iter()
+            .enumerate()
+            .map(|(index, (frame, delay))| {
+                let image = frame_image(frame, RenderAssetUsages::RENDER_WORLD);
+                (
+                    load_context.add_labeled_asset(format!("frame{index}"), image),
+                    delay,
+                )
+            })
+            .collect();
+        load_context.add_labeled_asset("animation".to_string(), AnimatedImage { frames });
+        Ok(frame_image(first, RenderAssetUsages::default()))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["gif"]
+    }
+}
+
+/// Plays the frames of the puzzle image, if it is animated
+fn start_animation(
+    mut commands: Commands,
+    origin_image: Res<OriginImage>,
+    animated: Res<AnimatedImages>,
+    asset_server: Res<AssetServer>,
+    animations: Res<Assets<AnimatedImage>>,
+) {
+    let id = origin_image.0.id();
+    let animation = animated.0.get(&id).cloned().or_else(|| {
+        let path = asset_server
+            .get_path(id)?
+            .into_owned()
+            .with_label("animation");
+        let handle = asset_server.get_handle::<AnimatedImage>(path)?;
+        animations.get(&handle).cloned()
+    });
+    match animation.filter(|animation| animation.frames.len() > 1) {
+        Some(animation) => {
+            let delay = animation.frames[0].1;
+            commands.insert_resource(AnimatedPuzzle {
+                animation,
+                frame: 0,
+                timer: Timer::new(delay, TimerMode::Once),
+            });
+        }
+        None => commands.remove_resource::<AnimatedPuzzle>(),
+    }
+}
+
+fn stop_animation(mut commands: Commands) {
+    commands.remove_resource::<AnimatedPuzzle>();
+}
+
+/// Shows the next frame on all pieces once the current one was shown long enough
+fn play_animation(
+    time: Res<Time>,
+    mut puzzle: ResMut<AnimatedPuzzle>,
+    mut materials: ResMut<Assets<PieceMaterial>>,
+) {
+    if !puzzle.timer.tick(time.delta()).finished() {
+        return;
+    }
+    let puzzle = &mut *puzzle;
+    puzzle.frame = (puzzle.frame + 1) % puzzle.animation.frames.len();
+    let (image, delay) = &puzzle.animation.frames[puzzle.frame];
+    puzzle.timer = Timer::new(*delay, TimerMode::Once);
+    for (_, material) in materials.iter_mut() {
+        material.set_image(image.clone());
+    }
+}
+
+/// Whether the pieces play an animation, they are drawn from its full size frames then
+pub(crate) fn is_animated(puzzle: Option<Res<AnimatedPuzzle>>) -> bool {
+    puzzle.is_some()
+}
@@ -0,0 +1,169 @@
+//! Tidies the table: the loose pieces are laid out in a grid around the board, ring by ring,
+//! optionally sorted so the edges or similar colors end up next to each other.
+
+use crate::board::Locked;
+use crate::gameplay::{Immovable, JigsawPuzzleGenerator, MoveStart, MoveTogether, ScatterTween};
+use crate::pinning::Pinned;
+use crate::tray::InTray;
+use crate::workspace::{InWorkspace, Workspaces};
+use crate::{GameState, Piece};
+use bevy::prelude::*;
+use jigsaw_puzzle_generator::image::GenericImageView;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<ArrangePieces>().add_systems(
+        Update,
+        (handle_arrange_keys, arrange_pieces)
+            .chain()
+            .run_if(in_state(GameState::Play)),
+    );
+}
+
+/// Room between the pieces of the grid and around the board
+const GAP: f32 = 10.0;
+
+/// Lays out the loose pieces in a grid around the board
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrangePieces {
+    /// The edge pieces first, the inner ones in the outer rings
+    EdgesFirst,
+    /// By their main color, gray pieces last
+    ByColor,
+}
+
+fn handle_arrange_keys(keyboard_input: Res<ButtonInput<KeyCode>>, mut commands: Commands) {
+    if keyboard_input.just_pressed(KeyCode::KeyT) {
+        if keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+            commands.send_event(ArrangePieces::ByColor);
+        } else {
+            commands.send_event(ArrangePieces::EdgesFirst);
+        }
+    }
+}
+
+fn arrange_pieces(
+    mut commands: Commands,
+    mut events: EventReader<ArrangePieces>,
+    generator: Res<JigsawPuzzleGenerator>,
+    workspaces: Res<Workspaces>,
+    pieces: Query<
+        (
+            Entity,
+            &Piece,
+            &Transform,
+            &MoveTogether,
+            Option<&InWorkspace>,
+        ),
+        (
+            Without<InTray>,
+            Without<MoveStart>,
+            Without<Immovable>,
+            Without<Locked>,
+            Without<Pinned>,
+        ),
+    >,
+    fixed: Query<(&Piece, &Transform), Or<(With<Locked>, With<Pinned>)>>,
+) {
+    let Some(order) = events.read().last().copied() else {
+        return;
+    };
+
+    // pieces sorted into the other workspaces stay there
+    let (mut loose, grouped): (Vec<_>, Vec<_>) = pieces
+        .iter()
+        .filter(|(.., in_workspace)| in_workspace.is_none_or(|in_workspace| in_workspace.0 == 0))
+        .partition(|(_, _, _, together, _)| together.is_empty());
+    if loose.is_empty() {
+        return;
+    }
+    let image = generator.origin_image();
+    match order {
+        ArrangePieces::EdgesFirst => loose.sort_by_key(|(_, piece, ..)| !piece.is_boarder),
+        ArrangePieces::ByColor => {
+            let keys: Vec<(bool, f32)> = loose
+                .iter()
+                .map(|(_, piece, ..)| {
+                    let step = (piece.width.min(piece.height) / 8.0).max(1.0) as u32;
+                    let [r, g, b, _] = piece.average_color(image, step).0;
+                    let color = Hsla::from(Srgba::rgb_u8(r, g, b));
+                    if color.saturation < 0.15 {
+                        (true, color.lightness)
+                    } else {
+                        (false, color.hue)
+                    }
+                })
+                .collect();
+            let mut order: Vec<usize> = (0..loose.len()).collect();
+            order.sort_by(|a, b| {
+                keys[*a]
+                    .0
+                    .cmp(&keys[*b].0)
+                    .then(keys[*a].1.total_cmp(&keys[*b].1))
+            });
+            loose = order.into_iter().map(|i| loose[i]).collect();
+        }
+    }
+
+    let (width, height) = image.dimensions();
+    let board = Rect::from_center_size(Vec2::ZERO, Vec2::new(width as f32, height as f32));
+    // solved groups and pinned pieces keep their place, the grid goes around them
+    let obstacles: Vec<Rect> = core::iter::once(board)
+        .chain(
+            grouped
+                .iter()
+                .map(|(_, piece, transform, ..)| piece.image_rect(transform.translation.xy())),
+        )
+        .chain(
+            fixed
+                .iter()
+                .map(|(piece, transform)| piece.image_rect(transform.translation.xy())),
+        )
+        .map(|rect| rect.inflate(GAP))
+        .collect();
+
+    let cell = loose
+        .iter()
+        .map(|(_, piece, ..)| piece.crop_width.max(piece.crop_height) as f32)
+        .fold(0.0, f32::max)
+        + GAP;
+    let cells = free_cells(board, workspaces.area(0), cell, &obstacles);
+
+    for ((entity, piece, transform, ..), center) in loose.into_iter().zip(cells) {
+        let offset = piece.image_rect(Vec2::ZERO).center();
+        commands
+            .entity(entity)
+            .insert(ScatterTween::new(transform.translation, center - offset));
+    }
+}
+
+/// The centers of the cells of size `cell` on the table which are clear of the obstacles, ring
+/// by ring around the board and clockwise from the top within a ring
+fn free_cells(board: Rect, table: Rect, cell: f32, obstacles: &[Rect]) -> Vec<Vec2> {
+    let columns = (table.width() / cell) as i32;
+    let rows = (table.height() / cell) as i32;
+    // aligned to the corner of the board
+    let origin = board.min - (board.min - table.min).div_euclid(Vec2::splat(cell)) * cell;
+
+    let mut cells: Vec<(f32, f32, Vec2)> = (0..columns)
+        .flat_map(|x| (0..rows).map(move |y| (x, y)))
+        .map(|(x, y)| origin + (Vec2::new(x as f32, y as f32) + 0.5) * cell)
+        .filter(|center| {
+            let rect = Rect::from_center_half_size(*center, Vec2::splat(cell / 2.0));
+            table.contains(rect.min)
+                && table.contains(rect.max)
+                && obstacles
+                    .iter()
+                    .all(|obstacle| obstacle.intersect(rect).is_empty())
+        })
+        .map(|center| {
+            let ring = ((center.abs() - board.half_size()) / cell)
+                .max_element()
+                .ceil();
+            // clockwise from the top
+            let angle = center.x.atan2(center.y).rem_euclid(core::f32::consts::TAU);
+            (ring, angle, center)
+        })
+        .collect();
+    cells.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    cells.into_iter().map(|(.., center)| center).collect()
+}
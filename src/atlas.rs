@@ -0,0 +1,318 @@
+//! The pieces are drawn straight from the puzzle image through small masks, which are packed into
+//! a few large texture atlases. Only the masks are made on the CPU, a shader cuts the pieces out.
+
+use crate::animated::is_animated;
+use crate::stroke::PieceStroke;
+use crate::GameState;
+use bevy::asset::RenderAssetUsages;
+use bevy::picking::mesh_picking::MeshPickingPlugin;
+use bevy::prelude::*;
+use bevy::render::render_resource::{
+    AsBindGroup, Extent3d, ShaderRef, TextureDimension, TextureFormat,
+};
+use bevy::sprite::{AlphaMode2d, Material2d, Material2dPlugin};
+use jigsaw_puzzle_generator::image::{GenericImageView, RgbaImage};
+use jigsaw_puzzle_generator::{pack_atlas, AtlasSlot, JigsawPiece, JigsawTemplate};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_plugins(Material2dPlugin::<PieceMaterial>::default());
+    // the app the game is embedded in may pick meshes already
+    if !app.is_plugin_added::<MeshPickingPlugin>() {
+        app.add_plugins(MeshPickingPlugin);
+    }
+    app.add_systems(
+        Update,
+        update_image_level
+            .run_if(resource_exists::<PieceAtlas>)
+            .run_if(not(is_animated))
+            .run_if(in_state(GameState::Play)),
+    );
+}
+
+/// How many smaller puzzle images are made for zooming out, each of half the size of the last
+pub const IMAGE_LEVELS: usize = 2;
+
+/// Largest side of an atlas page, WebGL2 only guarantees small textures
+#[cfg(not(target_arch = "wasm32"))]
+const MAX_ATLAS_SIZE: u32 = 4096;
+#[cfg(target_arch = "wasm32")]
+const MAX_ATLAS_SIZE: u32 = 2048;
+/// Transparent pixels between the masks, so filtering doesn't bleed into the neighbors
+const ATLAS_PADDING: u32 = 2;
+
+/// The images every piece has
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PieceImage {
+    /// The piece cut out of the puzzle image
+    Color,
+    /// The white silhouette behind the piece, which outlines hovered and selected pieces
+    White,
+    /// The outline shown in high contrast mode
+    Outline,
+    /// The blurred shadow below a held piece
+    Shadow,
+}
+
+/// Draws a piece of the puzzle image through its mask
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+pub struct PieceMaterial {
+    #[uniform(0)]
+    color: LinearRgba,
+    /// Offset and size of the piece in the puzzle image, in uv coordinates
+    #[uniform(0)]
+    image_rect: Vec4,
+    /// Offset and size of the mask in its atlas page, in uv coordinates
+    #[uniform(0)]
+    mask_rect: Vec4,
+    /// The color of the cut line, its alpha how much it covers the image
+    #[uniform(0)]
+    stroke_color: LinearRgba,
+    /// How much of the darkened image the cut line takes on instead of its color
+    #[uniform(0)]
+    stroke_image: f32,
+    /// Width of the outline the silhouette is grown by, in pixels of the piece
+    #[uniform(0)]
+    outline: f32,
+    /// How far the image is blurred, in pixels of the piece
+    #[uniform(0)]
+    blur: f32,
+    /// Which of the [`PieceImage`]s is drawn
+    #[uniform(0)]
+    mode: u32,
+    #[texture(1)]
+    #[sampler(2)]
+    image: Handle<Image>,
+    #[texture(3)]
+    #[sampler(4)]
+    mask: Handle<Image>,
+}
+
+impl PieceMaterial {
+    pub fn color(&self) -> Color {
+        self.color.into()
+    }
+
+    /// Draws the piece from another image of the same size, like a frame of an animation
+    pub fn set_image(&mut self, image: Handle<Image>) {
+        self.image = image;
+    }
+
+    /// Tints the drawn image
+    pub fn set_color(&mut self, color: Color) {
+        self.color = color.into();
+    }
+
+    /// Draws the cut line around the piece in another style
+    pub fn set_stroke(&mut self, stroke: PieceStroke) {
+        (self.stroke_color, self.stroke_image) = stroke.uniform();
+    }
+
+    pub fn outline(&self) -> f32 {
+        self.outline
+    }
+
+    /// Spreads the silhouette or blurs the shadow by `width` pixels of the piece, its mesh has to
+    /// be grown by as much on every side
+    pub fn set_outline(&mut self, width: f32) {
+        self.outline = width;
+    }
+
+    /// Blurs the image of the piece by `radius` pixels of the piece, its body stays sharp
+    pub fn set_blur(&mut self, radius: f32) {
+        self.blur = radius;
+    }
+}
+
+impl Material2d for PieceMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/piece.wgsl".into()
+    }
+
+    fn alpha_mode(&self) -> AlphaMode2d {
+        AlphaMode2d::Blend
+    }
+}
+
+/// Offset and size of a rect in uv coordinates of a texture of `texture_size`
+fn uv_rect(offset: Vec2, size: Vec2, texture_size: Vec2) -> Vec4 {
+    let (offset, size) = (offset / texture_size, size / texture_size);
+    Vec4::new(offset.x, offset.y, size.x, size.y)
+}
+
+/// The atlases the masks of the current puzzle are drawn into
+#[derive(Resource, Debug, Clone)]
+pub struct PieceAtlas {
+    /// The puzzle image at full resolution and at every level below
+    image_levels: Vec<Handle<Image>>,
+    /// The level the pieces are drawn at
+    level: usize,
+    /// The style of the cut lines of new materials
+    stroke: PieceStroke,
+    image_size: Vec2,
+    pages: Vec<Handle<Image>>,
+    page_sizes: Vec<Vec2>,
+    /// The slot of every piece by its index
+    slots: Vec<AtlasSlot>,
+}
+
+impl PieceAtlas {
+    /// Creates empty atlas pages with room for the mask of every piece of the template, the
+    /// pieces are drawn from the puzzle image at one of the `image_levels`, the full one first
+    pub fn new(
+        template: &JigsawTemplate,
+        image_levels: Vec<Handle<Image>>,
+        images: &mut Assets<Image>,
+    ) -> Self {
+        let sizes: Vec<(u32, u32)> = template
+            .pieces
+            .iter()
+            .map(|piece| (piece.crop_width, piece.crop_height))
+            .collect();
+        let (slots, page_sizes) = pack_atlas(&sizes, MAX_ATLAS_SIZE, ATLAS_PADDING);
+        let pages = page_sizes
+            .iter()
+            .map(|(width, height)| {
+                images.add(Image::new_fill(
+                    Extent3d {
+                        width: *width,
+                        height: *height,
+                        depth_or_array_layers: 1,
+                    },
+                    TextureDimension::D2,
+                    &[0, 0, 0, 0],
+                    TextureFormat::Rgba8Unorm,
+                    RenderAssetUsages::default(),
+                ))
+            })
+            .collect();
+        let (width, height) = template.origin_image.dimensions();
+        debug!(
+            "The masks of {} pieces take {} KiB on {} atlas pages",
+            slots.len(),
+            page_sizes
+                .iter()
+                .map(|(width, height)| (width * height * 4) as usize)
+                .sum::<usize>()
+                / 1024,
+            page_sizes.len()
+        );
+
+        PieceAtlas {
+            image_levels,
+            level: 0,
+            stroke: PieceStroke::default(),
+            image_size: Vec2::new(width as f32, height as f32),
+            pages,
+            page_sizes: page_sizes
+                .iter()
+                .map(|(width, height)| Vec2::new(*width as f32, *height as f32))
+                .collect(),
+            slots,
+        }
+    }
+
+    /// Draws the mask of the piece into its slot
+    pub fn insert(&self, images: &mut Assets<Image>, piece: &JigsawPiece, mask: &RgbaImage) {
+        let slot = self.slots[piece.index];
+        let Some(page) = images.get_mut(&self.pages[slot.page]) else {
+            return;
+        };
+        let page_width = page.width() as usize;
+        // clipped to the slot, in case the mask isn't exactly of the cropped size
+        let row = slot.width.min(mask.width()) as usize * 4;
+        let rows = mask
+            .chunks_exact(mask.width() as usize * 4)
+            .take(slot.height as usize);
+        for (y, source) in rows.enumerate() {
+            let start = ((slot.y as usize + y) * page_width + slot.x as usize) * 4;
+            page.data[start..start + row].copy_from_slice(&source[..row]);
+        }
+    }
+
+    /// A material drawing an image of the piece
+    pub fn material(&self, piece: &JigsawPiece, kind: PieceImage) -> PieceMaterial {
+        let slot = self.slots[piece.index];
+        let size = Vec2::new(piece.crop_width as f32, piece.crop_height as f32);
+        let image_offset = Vec2::new(piece.top_left_x as f32, piece.top_left_y as f32);
+        let mask_offset = Vec2::new(slot.x as f32, slot.y as f32);
+        let (stroke_color, stroke_image) = self.stroke.uniform();
+        PieceMaterial {
+            color: LinearRgba::WHITE,
+            image_rect: uv_rect(image_offset, size, self.image_size),
+            mask_rect: uv_rect(mask_offset, size, self.page_sizes[slot.page]),
+            stroke_color,
+            stroke_image,
+            outline: 0.0,
+            blur: 0.0,
+            mode: kind as u32,
+            image: self.image_levels[self.level].clone(),
+            mask: self.pages[slot.page].clone(),
+        }
+    }
+
+    /// Draws the cut lines of the materials made from now on in the `stroke`
+    pub fn set_stroke(&mut self, stroke: PieceStroke) {
+        self.stroke = stroke;
+    }
+
+    /// A rectangle of the cropped size of the piece with its top left corner at the origin
+    pub fn mesh(piece: &JigsawPiece) -> Mesh {
+        let size = Vec2::new(piece.crop_width as f32, piece.crop_height as f32);
+        Mesh::from(Rectangle::from_size(size)).translated_by(Vec3::new(
+            size.x / 2.0,
+            -size.y / 2.0,
+            0.0,
+        ))
+    }
+}
+
+/// Draws the pieces from a smaller puzzle image when zoomed out, so one pixel on the screen
+/// samples about one pixel of the image
+fn update_image_level(
+    mut atlas: ResMut<PieceAtlas>,
+    camera: Single<&OrthographicProjection, (With<Camera2d>, With<IsDefaultUiCamera>)>,
+    mut materials: ResMut<Assets<PieceMaterial>>,
+) {
+    let level = (camera.scale.log2().floor().max(0.0) as usize).min(atlas.image_levels.len() - 1);
+    if level == atlas.level {
+        return;
+    }
+    atlas.level = level;
+    for (_, material) in materials.iter_mut() {
+        material.image = atlas.image_levels[level].clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jigsaw_puzzle_generator::image::DynamicImage;
+    use jigsaw_puzzle_generator::{GameMode, JigsawGenerator};
+
+    /// The bytes of all images allocated in `images`
+    fn allocated(images: &Assets<Image>) -> usize {
+        images.iter().map(|(_, image)| image.data.len()).sum()
+    }
+
+    #[test]
+    fn test_pack_atlas_memory() {
+        let template = JigsawGenerator::new(DynamicImage::new_rgba8(1920, 1080), 25, 20)
+            .seed(1)
+            .generate(GameMode::Classic, false)
+            .unwrap();
+        let mut images = Assets::<Image>::default();
+        let before = allocated(&images);
+        let atlas = PieceAtlas::new(&template, vec![], &mut images);
+        let masks = allocated(&images) - before;
+        assert_eq!(atlas.pages.len(), 1);
+
+        // every piece had a color, a white and an outline image of its cropped size
+        let pieces: usize = template
+            .pieces
+            .iter()
+            .map(|piece| (piece.crop_width * piece.crop_height * 4) as usize)
+            .sum();
+        assert!(masks < pieces * 3 * 2 / 5, "{masks} of {pieces} bytes");
+        assert!(masks < pieces * 6 / 5, "{masks} of {pieces} bytes");
+    }
+}
@@ -0,0 +1,144 @@
+//! The strongest hint: <kbd>P</kbd> puts a few of the hardest loose pieces at their solved place,
+//! where they merge with the pieces around them. The hardest pieces are those with the fewest
+//! placed neighbors, and among them the plainest ones.
+
+use crate::board::Locked;
+use crate::gameplay::{
+    init_position, JigsawPuzzleGenerator, MoveEnd, MoveStart, MoveTogether, ScatterTween,
+};
+use crate::hints::{HintBudget, HintKind};
+use crate::pinning::Pinned;
+use crate::rotation::PieceRotation;
+use crate::tray::InTray;
+use crate::{Difficulty, GameState, Piece, SelectPlayMode};
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use core::cmp::Reverse;
+use jigsaw_puzzle_generator::image::GenericImageView;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<AutoPlace>().add_systems(
+        Update,
+        (handle_auto_place_key, auto_place)
+            .chain()
+            .run_if(in_state(GameState::Play)),
+    );
+}
+
+/// Pieces placed by one hint
+const AUTO_PLACE_COUNT: usize = 3;
+
+/// Places the hardest loose pieces for the charges of [`HintKind::AutoPlace`]
+#[derive(Event, Debug, Clone, Copy)]
+pub struct AutoPlace;
+
+fn handle_auto_place_key(keyboard_input: Res<ButtonInput<KeyCode>>, mut commands: Commands) {
+    if keyboard_input.just_pressed(KeyCode::KeyP) {
+        commands.send_event(AutoPlace);
+    }
+}
+
+fn auto_place(
+    mut commands: Commands,
+    mut events: EventReader<AutoPlace>,
+    difficulty: Res<Difficulty>,
+    mut budget: ResMut<HintBudget>,
+    generator: Res<JigsawPuzzleGenerator>,
+    loose: Query<
+        (Entity, &Piece, &MoveTogether),
+        (Without<MoveStart>, Without<Locked>, Without<Pinned>),
+    >,
+    pieces: Query<(&Piece, &MoveTogether, Has<Locked>, Has<Pinned>)>,
+) {
+    for _ in events.read() {
+        if !difficulty.hints() {
+            continue;
+        }
+        // a piece counts as placed once it is joined to others or locked on the board
+        let placed: Vec<&Piece> = pieces
+            .iter()
+            .filter(|(_, together, locked, pinned)| !together.is_empty() || *locked || *pinned)
+            .map(|(piece, ..)| piece)
+            .collect();
+        let image = generator.origin_image();
+        let mut candidates: Vec<(Entity, usize, f32)> = loose
+            .iter()
+            .filter(|(_, _, together)| together.is_empty())
+            .map(|(entity, piece, _)| {
+                let neighbors = placed.iter().filter(|other| piece.beside(other)).count();
+                let step = (piece.width.min(piece.height) / 16.0).max(1.0) as u32;
+                (entity, neighbors, piece.luma_variance(image, step))
+            })
+            .collect();
+        if candidates.is_empty() || !budget.spend(HintKind::AutoPlace) {
+            continue;
+        }
+        candidates.sort_by(|a, b| a.1.cmp(&b.1).then(a.2.total_cmp(&b.2)));
+        let chosen: Vec<Entity> = candidates
+            .into_iter()
+            .take(AUTO_PLACE_COUNT)
+            .map(|(entity, ..)| entity)
+            .collect();
+        commands.queue(move |world: &mut World| {
+            place_solved(world, &chosen);
+        });
+    }
+}
+
+/// The pieces of the largest group, the one with the lowest piece index among equally large ones
+pub(crate) fn largest_group(world: &mut World) -> HashSet<Entity> {
+    let mut groups = world.query::<(&Piece, &MoveTogether)>();
+    groups
+        .iter(world)
+        .filter(|(_, together)| !together.is_empty())
+        .max_by_key(|(piece, together)| (together.len(), Reverse(piece.index)))
+        .map(|(_, together)| together.0.clone())
+        .unwrap_or_default()
+}
+
+/// Moves the pieces to their solved place and drops them there so they merge. On the board that
+/// is their cell, otherwise their place next to the [`largest_group`].
+pub(crate) fn place_solved(world: &mut World, entities: &[Entity]) {
+    let Some(generator) = world.get_resource::<JigsawPuzzleGenerator>() else {
+        return;
+    };
+    let image_size = generator.origin_image().dimensions();
+
+    let offset = if *world.resource::<SelectPlayMode>() == SelectPlayMode::Board {
+        Vec2::ZERO
+    } else {
+        let anchor = largest_group(world).into_iter().next();
+        anchor
+            .and_then(|anchor| {
+                let piece = world.get::<Piece>(anchor)?;
+                let transform = world.get::<Transform>(anchor)?;
+                Some(transform.translation.xy() - init_position(piece, image_size))
+            })
+            .unwrap_or(Vec2::ZERO)
+    };
+
+    for entity in entities {
+        let Some(piece) = world.get::<Piece>(*entity).cloned() else {
+            continue;
+        };
+        let position = init_position(&piece, image_size) + offset;
+        let mut entity = world.entity_mut(*entity);
+        entity.remove::<(InTray, ScatterTween)>();
+        // pieces scrolled out of a tray are hidden
+        if let Some(mut visibility) = entity.get_mut::<Visibility>() {
+            *visibility = Visibility::Inherited;
+        }
+        if let Some(mut rotation) = entity.get_mut::<PieceRotation>() {
+            *rotation = PieceRotation::default();
+        }
+        if let Some(mut transform) = entity.get_mut::<Transform>() {
+            transform.translation = position.extend(transform.translation.z);
+            transform.rotation = Quat::IDENTITY;
+            transform.scale = Vec3::ONE;
+        }
+    }
+    for entity in entities {
+        world.trigger_targets(MoveEnd, *entity);
+    }
+    world.flush();
+}
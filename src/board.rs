@@ -0,0 +1,142 @@
+use crate::atlas::PieceMaterial;
+use crate::events::PuzzleCompleted;
+use crate::gameplay::{
+    init_position, ColorImage, GameTimer, JigsawPuzzleGenerator, JigsawPuzzleTemplate, MoveStart,
+    OnPlayScreen, Selected,
+};
+use crate::rotation::{is_upright, PieceRotation};
+use crate::tray::InTray;
+use crate::{GameState, Piece, SelectPlayMode};
+use bevy::prelude::*;
+use jigsaw_puzzle_generator::image::GenericImageView;
+use log::debug;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        OnEnter(GameState::Play),
+        setup_board_outline.run_if(in_board_mode),
+    )
+    .add_systems(
+        Update,
+        (draw_board_grid, lock_placed_pieces, finish_on_all_locked)
+            .chain()
+            .run_if(in_board_mode)
+            .run_if(in_state(GameState::Play)),
+    );
+}
+
+/// Distance to the correct cell at which a dropped piece is locked
+pub(crate) const LOCK_DISTANCE: f32 = 20.0;
+
+const LOCKED_TINT: Color = Color::srgb(0.8, 0.8, 0.8);
+
+/// A piece placed at its correct position on the board, it can't be moved anymore
+#[derive(Component)]
+pub struct Locked;
+
+/// The faint board showing where the solved image will be
+#[derive(Component)]
+struct BoardOutline;
+
+fn in_board_mode(play_mode: Res<SelectPlayMode>) -> bool {
+    *play_mode == SelectPlayMode::Board
+}
+
+fn setup_board_outline(
+    mut commands: Commands,
+    generator: Res<JigsawPuzzleGenerator>,
+    q_outline: Query<Entity, With<BoardOutline>>,
+) {
+    if !q_outline.is_empty() {
+        return;
+    }
+    let (width, height) = generator.origin_image().dimensions();
+    commands.spawn((
+        Sprite::from_color(
+            Color::srgba(0.0, 0.0, 0.0, 0.08),
+            Vec2::new(width as f32, height as f32),
+        ),
+        Transform::from_xyz(0.0, 0.0, -10.0),
+        BoardOutline,
+        OnPlayScreen,
+    ));
+}
+
+fn draw_board_grid(mut gizmos: Gizmos, generator: Res<JigsawPuzzleGenerator>) {
+    let (width, height) = generator.origin_image().dimensions();
+    let cells = UVec2::new(
+        generator.pieces_in_column() as u32,
+        generator.pieces_in_row() as u32,
+    );
+    let spacing = Vec2::new(width as f32, height as f32) / cells.as_vec2();
+    gizmos
+        .grid_2d(
+            Isometry2d::IDENTITY,
+            cells,
+            spacing,
+            Color::srgba(0.0, 0.0, 0.0, 0.15),
+        )
+        .outer_edges();
+}
+
+/// Snaps and locks dropped pieces close to their correct cell, and unlocks pieces moved away
+/// from it again, e.g. by undo
+fn lock_placed_pieces(
+    mut commands: Commands,
+    generator: Res<JigsawPuzzleGenerator>,
+    mut pieces: Query<
+        (
+            Entity,
+            &Piece,
+            &mut Transform,
+            &Children,
+            Has<Locked>,
+            Option<&PieceRotation>,
+        ),
+        (
+            Changed<Transform>,
+            Without<MoveStart>,
+            Without<Selected>,
+            Without<InTray>,
+        ),
+    >,
+    images: Query<&MeshMaterial2d<PieceMaterial>, With<ColorImage>>,
+    mut materials: ResMut<Assets<PieceMaterial>>,
+) {
+    let image_size = generator.origin_image().dimensions();
+    for (entity, piece, mut transform, children, locked, rotation) in pieces.iter_mut() {
+        let target = init_position(piece, image_size);
+        let distance = transform.translation.xy().distance(target);
+        let tint = if !locked && distance < LOCK_DISTANCE && is_upright(rotation) {
+            debug!("lock piece {}", piece.index);
+            transform.translation = target.extend(0.0);
+            commands.entity(entity).insert(Locked);
+            LOCKED_TINT
+        } else if locked && distance >= LOCK_DISTANCE {
+            commands.entity(entity).remove::<Locked>();
+            Color::WHITE
+        } else {
+            continue;
+        };
+
+        for material in images.iter_many(children) {
+            if let Some(material) = materials.get_mut(material) {
+                material.set_color(tint);
+            }
+        }
+    }
+}
+
+fn finish_on_all_locked(
+    template: Res<JigsawPuzzleTemplate>,
+    locked: Query<(), With<Locked>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    timer: Option<Res<GameTimer>>,
+    mut completed: EventWriter<PuzzleCompleted>,
+) {
+    if locked.iter().count() == template.pieces_count() {
+        debug!("All pieces have been locked");
+        next_state.set(GameState::Finish);
+        completed.send(PuzzleCompleted::new(timer.as_deref()));
+    }
+}
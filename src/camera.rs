@@ -0,0 +1,479 @@
+use crate::board::Locked;
+use crate::gameplay::{
+    AdjustScale, BoardBackgroundImage, JigsawPuzzleGenerator, MoveStart, MoveTogether, Selected,
+};
+use crate::pen::PenInput;
+use crate::table::Table;
+use crate::tray::InTray;
+use crate::{GameState, Piece};
+use bevy::input::mouse::MouseMotion;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy::window::WindowResized;
+use jigsaw_puzzle_generator::image::GenericImageView;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<CameraSettings>()
+        .init_resource::<CameraAnimation>()
+        .init_resource::<ZoomTarget>()
+        .add_event::<ZoomToFit>()
+        .add_systems(OnEnter(GameState::Generating), reset_camera_position)
+        .add_systems(Update, fit_board_on_setup)
+        .add_systems(
+            Update,
+            (
+                reflow_on_resize,
+                handle_zoom_keys,
+                zoom_to_fit,
+                animate_camera,
+                adjust_camera_scale,
+                animate_zoom,
+                pan_with_keyboard,
+                pan_with_middle_mouse,
+                pinch_and_pan_with_touch,
+                auto_scroll_while_dragging,
+                clamp_camera_position,
+            )
+                .chain()
+                .run_if(in_state(GameState::Play)),
+        );
+}
+
+/// Distance to the window border in which a held piece scrolls the table
+const AUTO_SCROLL_MARGIN: f32 = 40.0;
+
+/// How the camera can be moved around the table
+#[derive(Resource, Debug)]
+pub struct CameraSettings {
+    /// Keyboard panning speed in screen pixels per second
+    pub pan_speed: f32,
+    /// How far the table reaches from the board center, in multiples of the board size
+    pub play_area: f32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        Self {
+            pan_speed: 600.0,
+            play_area: 1.5,
+        }
+    }
+}
+
+/// Share of the window the framed area fills, leaving room for the UI around it
+const FIT_MARGIN: f32 = 0.6;
+
+/// Frame an area of the table with the camera
+#[derive(Event, Debug, Clone, Copy)]
+pub enum ZoomToFit {
+    /// The board the solved puzzle covers
+    Board,
+    /// The board and every piece on the table
+    AllPieces,
+    /// The selected pieces
+    Selection,
+}
+
+/// The camera translation and scale being animated to
+#[derive(Resource, Debug, Default)]
+pub(crate) struct CameraAnimation(pub(crate) Option<(Vec2, f32)>);
+
+/// The scale the camera zooms to, with the point on the screen which stays in place
+#[derive(Resource, Debug, Default)]
+struct ZoomTarget(Option<(f32, Option<Vec2>)>);
+
+/// How fast the camera zooms to the target scale, higher is faster
+const ZOOM_SPEED: f32 = 15.0;
+
+/// The camera translation and scale which frame `rect` in the window
+pub fn fit_rect(rect: Rect, window_size: Vec2) -> (Vec2, f32) {
+    let scale = (rect.size() / window_size).max_element() / FIT_MARGIN;
+    (rect.center(), scale)
+}
+
+fn board_rect(generator: &JigsawPuzzleGenerator) -> Rect {
+    let (width, height) = generator.origin_image().dimensions();
+    Rect::from_center_size(Vec2::ZERO, Vec2::new(width as f32, height as f32))
+}
+
+/// The scale range of the camera: zoomed in until a piece fills the view, zoomed out until the
+/// whole table fits
+fn zoom_range(
+    generator: &JigsawPuzzleGenerator,
+    settings: &CameraSettings,
+    window_size: Vec2,
+) -> (f32, f32) {
+    let board = board_rect(generator);
+    let (_, board_scale) = fit_rect(board, window_size);
+    let piece = board.size()
+        / Vec2::new(
+            generator.pieces_in_column() as f32,
+            generator.pieces_in_row() as f32,
+        );
+    let (_, piece_scale) = fit_rect(Rect::from_center_size(Vec2::ZERO, piece), window_size);
+    let max = board_scale * 2.0 * settings.play_area;
+    (piece_scale.min(max), max)
+}
+
+/// Frame the board as soon as the puzzle is set up
+fn fit_board_on_setup(
+    _sprite: Single<Entity, Added<BoardBackgroundImage>>,
+    camera: Single<(&mut Transform, &mut OrthographicProjection), With<IsDefaultUiCamera>>,
+    window: Single<&Window>,
+    generator: Res<JigsawPuzzleGenerator>,
+) {
+    let (mut transform, mut projection) = camera.into_inner();
+    let (translation, scale) = fit_rect(board_rect(&generator), window.size());
+    transform.translation = translation.extend(transform.translation.z);
+    projection.scale = scale;
+}
+
+/// The part of the table which is visible through the camera
+fn visible_rect(translation: Vec2, scale: f32, window_size: Vec2) -> Rect {
+    Rect::from_center_size(translation, window_size * scale)
+}
+
+/// The offset which moves `rect` inside `view`, aligned to the top left if it does not fit
+fn offset_into(rect: Rect, view: Rect) -> Vec2 {
+    let axis = |min: f32, max: f32, view_min: f32, view_max: f32| {
+        if min < view_min || max - min > view_max - view_min {
+            view_min - min
+        } else if max > view_max {
+            view_max - max
+        } else {
+            0.0
+        }
+    };
+    Vec2::new(
+        axis(rect.min.x, rect.max.x, view.min.x, view.max.x),
+        // the top edge stays visible when a group is too tall
+        -axis(-rect.max.y, -rect.min.y, -view.max.y, -view.min.y),
+    )
+}
+
+/// Frames the board again after the window is resized and brings back the loose pieces which
+/// ended up outside the new view
+fn reflow_on_resize(
+    mut resized: EventReader<WindowResized>,
+    mut animation: ResMut<CameraAnimation>,
+    window: Single<&Window>,
+    generator: Res<JigsawPuzzleGenerator>,
+    camera: Single<(&mut Transform, &mut OrthographicProjection), With<IsDefaultUiCamera>>,
+    mut pieces: Query<
+        (Entity, &Piece, &mut Transform, &MoveTogether),
+        (
+            Without<Locked>,
+            Without<InTray>,
+            Without<MoveStart>,
+            Without<IsDefaultUiCamera>,
+        ),
+    >,
+) {
+    if resized.read().last().is_none() {
+        return;
+    }
+    // a minimized window has no size to fit into
+    let window_size = window.size();
+    if window_size.min_element() <= 0.0 {
+        return;
+    }
+
+    let (mut camera_transform, mut projection) = camera.into_inner();
+    let (translation, scale) = fit_rect(board_rect(&generator), window_size);
+    camera_transform.translation = translation.extend(camera_transform.translation.z);
+    projection.scale = scale;
+    animation.0 = None;
+    let view = visible_rect(translation, scale, window_size);
+
+    let mut moved = HashSet::new();
+    let entities: Vec<Entity> = pieces.iter().map(|(entity, ..)| entity).collect();
+    for entity in entities {
+        if moved.contains(&entity) {
+            continue;
+        }
+        let Ok((_, _, _, together)) = pieces.get(entity) else {
+            continue;
+        };
+        // a group is moved as a whole so it stays snapped
+        let group: Vec<Entity> = if together.is_empty() {
+            vec![entity]
+        } else {
+            together.iter().copied().collect()
+        };
+        let Some(rect) = pieces
+            .iter_many(&group)
+            .map(|(_, piece, transform, _)| piece.image_rect(transform.translation.xy()))
+            .reduce(|rect, image| rect.union(image))
+        else {
+            continue;
+        };
+
+        let offset = offset_into(rect, view);
+        let mut group_iter = pieces.iter_many_mut(&group);
+        while let Some((entity, _, mut transform, _)) = group_iter.fetch_next() {
+            moved.insert(entity);
+            if offset != Vec2::ZERO {
+                transform.translation += offset.extend(0.0);
+            }
+        }
+    }
+}
+
+fn handle_zoom_keys(keyboard_input: Res<ButtonInput<KeyCode>>, mut commands: Commands) {
+    if keyboard_input.just_pressed(KeyCode::KeyF) {
+        commands.send_event(ZoomToFit::Board);
+    } else if keyboard_input.just_pressed(KeyCode::KeyG) {
+        commands.send_event(ZoomToFit::AllPieces);
+    } else if keyboard_input.just_pressed(KeyCode::KeyV) {
+        commands.send_event(ZoomToFit::Selection);
+    }
+}
+
+fn zoom_to_fit(
+    mut events: EventReader<ZoomToFit>,
+    mut animation: ResMut<CameraAnimation>,
+    window: Single<&Window>,
+    generator: Res<JigsawPuzzleGenerator>,
+    pieces: Query<(&Piece, &Transform, Has<Selected>), Without<InTray>>,
+) {
+    for event in events.read() {
+        let board = board_rect(&generator);
+        let mut images = pieces.iter().filter_map(|(piece, transform, selected)| {
+            let include = match event {
+                ZoomToFit::Board => false,
+                ZoomToFit::AllPieces => true,
+                ZoomToFit::Selection => selected,
+            };
+            include.then(|| piece.image_rect(transform.translation.xy()))
+        });
+        let rect = match event {
+            ZoomToFit::Board => board,
+            ZoomToFit::AllPieces => images.fold(board, |rect, image| rect.union(image)),
+            ZoomToFit::Selection => {
+                let Some(first) = images.next() else {
+                    continue;
+                };
+                images.fold(first, |rect, image| rect.union(image))
+            }
+        };
+        animation.0 = Some(fit_rect(rect, window.size()));
+    }
+}
+
+fn animate_camera(
+    time: Res<Time>,
+    mut animation: ResMut<CameraAnimation>,
+    camera: Single<(&mut Transform, &mut OrthographicProjection), With<IsDefaultUiCamera>>,
+) {
+    let Some((translation, scale)) = animation.0 else {
+        return;
+    };
+    let (mut transform, mut projection) = camera.into_inner();
+    let t = 1.0 - (-10.0 * time.delta_secs()).exp();
+    let current = transform.translation.xy().lerp(translation, t);
+    projection.scale += (scale - projection.scale) * t;
+    transform.translation = current.extend(transform.translation.z);
+
+    if current.distance(translation) < 1.0 && (projection.scale - scale).abs() < 0.01 {
+        transform.translation = translation.extend(transform.translation.z);
+        projection.scale = scale;
+        animation.0 = None;
+    }
+}
+
+fn reset_camera_position(
+    mut camera: Single<&mut Transform, With<IsDefaultUiCamera>>,
+    mut zoom: ResMut<ZoomTarget>,
+) {
+    camera.translation.x = 0.0;
+    camera.translation.y = 0.0;
+    zoom.0 = None;
+}
+
+/// Sets the scale to zoom to, the point below the cursor stays in place
+fn adjust_camera_scale(
+    mut events: EventReader<AdjustScale>,
+    mut zoom: ResMut<ZoomTarget>,
+    mut animation: ResMut<CameraAnimation>,
+    window: Single<&Window>,
+    generator: Res<JigsawPuzzleGenerator>,
+    settings: Res<CameraSettings>,
+    projection: Single<&OrthographicProjection, With<IsDefaultUiCamera>>,
+) {
+    let (min, max) = zoom_range(&generator, &settings, window.size());
+    for AdjustScale(delta) in events.read() {
+        let current = zoom.0.map_or(projection.scale, |(scale, _)| scale);
+        let scale = (current * (1.0 + delta)).clamp(min, max);
+        debug!("new scale: {}", scale);
+        zoom.0 = Some((scale, window.cursor_position()));
+        // zooming takes over from framing an area
+        animation.0 = None;
+    }
+}
+
+/// Eases the scale toward the target over a few frames, moving the camera so the world point
+/// below the cursor stays below it
+fn animate_zoom(
+    time: Res<Time>,
+    mut zoom: ResMut<ZoomTarget>,
+    window: Single<&Window>,
+    camera: Single<(&mut Transform, &mut OrthographicProjection), With<IsDefaultUiCamera>>,
+) {
+    let Some((target, anchor)) = zoom.0 else {
+        return;
+    };
+    let (mut transform, mut projection) = camera.into_inner();
+    let t = 1.0 - (-ZOOM_SPEED * time.delta_secs()).exp();
+    let scale = if (target - projection.scale).abs() < target * 0.001 {
+        zoom.0 = None;
+        target
+    } else {
+        projection.scale + (target - projection.scale) * t
+    };
+    if let Some(anchor) = anchor {
+        // screen y points down
+        let offset = (anchor - window.size() / 2.0) * Vec2::new(1.0, -1.0);
+        transform.translation += (offset * (projection.scale - scale)).extend(0.0);
+    }
+    projection.scale = scale;
+}
+
+fn pan_with_keyboard(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+    settings: Res<CameraSettings>,
+    camera: Single<(&mut Transform, &OrthographicProjection), With<IsDefaultUiCamera>>,
+    selected: Query<(), With<Selected>>,
+) {
+    // Ctrl is held for shortcuts like undo
+    if keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+    // the arrow keys nudge the selected pieces
+    let arrows = selected.is_empty();
+    let pressed = |key: KeyCode, arrow: KeyCode| {
+        keyboard_input.pressed(key) || (arrows && keyboard_input.pressed(arrow))
+    };
+    let mut direction = Vec2::ZERO;
+    if pressed(KeyCode::KeyW, KeyCode::ArrowUp) {
+        direction.y += 1.0;
+    }
+    if pressed(KeyCode::KeyS, KeyCode::ArrowDown) {
+        direction.y -= 1.0;
+    }
+    if pressed(KeyCode::KeyA, KeyCode::ArrowLeft) {
+        direction.x -= 1.0;
+    }
+    if pressed(KeyCode::KeyD, KeyCode::ArrowRight) {
+        direction.x += 1.0;
+    }
+    if direction == Vec2::ZERO {
+        return;
+    }
+
+    let (mut transform, projection) = camera.into_inner();
+    let offset = direction.normalize() * settings.pan_speed * projection.scale * time.delta_secs();
+    transform.translation += offset.extend(0.0);
+}
+
+fn pan_with_middle_mouse(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    camera: Single<(&mut Transform, &OrthographicProjection), With<IsDefaultUiCamera>>,
+) {
+    let delta: Vec2 = mouse_motion.read().map(|motion| motion.delta).sum();
+    if !mouse_input.pressed(MouseButton::Middle) {
+        return;
+    }
+
+    // the table follows the cursor, screen y points down
+    let (mut transform, projection) = camera.into_inner();
+    transform.translation.x -= delta.x * projection.scale;
+    transform.translation.y += delta.y * projection.scale;
+}
+
+/// Two fingers pinch to zoom and drag to pan the table
+fn pinch_and_pan_with_touch(
+    touches: Res<Touches>,
+    pen: Res<PenInput>,
+    window: Single<&Window>,
+    generator: Res<JigsawPuzzleGenerator>,
+    settings: Res<CameraSettings>,
+    moving: Query<(), With<MoveStart>>,
+    camera: Single<(&mut Transform, &mut OrthographicProjection), With<IsDefaultUiCamera>>,
+) {
+    // the table stays put under a held piece
+    if !moving.is_empty() {
+        return;
+    }
+    // the pen and the palm holding it don't move the table
+    let mut pressed = pen.fingers(&touches);
+    let (Some(first), Some(second), None) = (pressed.next(), pressed.next(), pressed.next()) else {
+        return;
+    };
+
+    let (mut transform, mut projection) = camera.into_inner();
+    let distance = first.position().distance(second.position());
+    let previous_distance = first
+        .previous_position()
+        .distance(second.previous_position());
+    if distance > 0.0 && previous_distance > 0.0 {
+        let (min, max) = zoom_range(&generator, &settings, window.size());
+        projection.scale = (projection.scale * previous_distance / distance).clamp(min, max);
+    }
+
+    // the table follows the fingers, screen y points down
+    let delta = (first.delta() + second.delta()) / 2.0;
+    transform.translation.x -= delta.x * projection.scale;
+    transform.translation.y += delta.y * projection.scale;
+}
+
+/// Pans the camera while a held piece is near the window border, faster the closer it gets
+fn auto_scroll_while_dragging(
+    time: Res<Time>,
+    settings: Res<CameraSettings>,
+    window: Single<&Window>,
+    moving: Query<(), With<MoveStart>>,
+    camera: Single<(&mut Transform, &OrthographicProjection), With<IsDefaultUiCamera>>,
+) {
+    if moving.is_empty() {
+        return;
+    }
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+    let size = window.size();
+    let edge = |position: f32, length: f32| {
+        if position < AUTO_SCROLL_MARGIN {
+            -(AUTO_SCROLL_MARGIN - position) / AUTO_SCROLL_MARGIN
+        } else if position > length - AUTO_SCROLL_MARGIN {
+            (position - length + AUTO_SCROLL_MARGIN) / AUTO_SCROLL_MARGIN
+        } else {
+            0.0
+        }
+    };
+    // screen y points down
+    let direction = Vec2::new(edge(cursor.x, size.x), -edge(cursor.y, size.y));
+    if direction == Vec2::ZERO {
+        return;
+    }
+
+    let (mut transform, projection) = camera.into_inner();
+    let offset =
+        direction.clamp_length_max(1.0) * settings.pan_speed * projection.scale * time.delta_secs();
+    transform.translation += offset.extend(0.0);
+}
+
+/// Keeps the camera within the play area around the board
+fn clamp_camera_position(
+    table: Res<Table>,
+    mut camera: Query<&mut Transform, (With<IsDefaultUiCamera>, Changed<Transform>)>,
+) {
+    let Ok(mut camera) = camera.get_single_mut() else {
+        return;
+    };
+    let translation = camera.translation.xy().clamp(table.min, table.max);
+    if translation != camera.translation.xy() {
+        camera.translation = translation.extend(camera.translation.z);
+    }
+}
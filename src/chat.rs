@@ -0,0 +1,336 @@
+//! Chat and pings for cooperative play. <kbd>Tab</kbd> opens the chat box, which takes the
+//! keyboard until <kbd>Enter</kbd> sends the line or <kbd>Escape</kbd> closes it again.
+//! <kbd>X</kbd> pings the place under the cursor, a ring in the player's color flashes there on
+//! every screen. Both travel as [`NetMessage`]s, the host relays them to the other clients.
+
+use crate::gameplay::OnPlayScreen;
+use crate::multiplayer::{peer_color, NetEvent, NetMessage, NetSession, PeerId};
+use crate::{despawn_screen, AppState, GameState};
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::{ButtonState, InputSystem};
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ChatLog>()
+        .init_resource::<ChatInput>()
+        .add_systems(PreUpdate, capture_keyboard.after(InputSystem))
+        .add_systems(Update, receive_chat)
+        .add_systems(
+            Update,
+            (type_into_chat, send_ping, update_chat_panel, fade_pings)
+                .chain()
+                .run_if(in_state(GameState::Play)),
+        )
+        .add_systems(
+            OnExit(GameState::Play),
+            (close_chat, despawn_screen::<ChatPanel>),
+        )
+        .add_observer(show_ping);
+}
+
+/// Lines kept in the chat
+const MAX_LINES: usize = 8;
+/// Characters of a single line
+const MAX_LINE_LENGTH: usize = 120;
+/// How long a line stays on the screen while the chat box is closed
+const LINE_SECS: f32 = 15.0;
+/// How long a ping flashes
+const PING_SECS: f32 = 2.0;
+/// How often the ring of a ping grows from its center
+const PING_PULSES: f32 = 3.0;
+/// The radius of a ping on the screen
+const PING_RADIUS: f32 = 30.0;
+/// Above the pieces and the cursors of the other players
+const PING_Z: f32 = 550.0;
+
+#[derive(Debug, Clone, PartialEq)]
+struct ChatLine {
+    peer: PeerId,
+    text: String,
+    /// When the line was written, in real seconds
+    written: f32,
+}
+
+/// The latest lines of the chat
+#[derive(Resource, Debug, Default)]
+struct ChatLog(Vec<ChatLine>);
+
+impl ChatLog {
+    fn push(&mut self, peer: PeerId, text: String, written: f32) {
+        self.0.push(ChatLine {
+            peer,
+            text,
+            written,
+        });
+        let overflow = self.0.len().saturating_sub(MAX_LINES);
+        self.0.drain(..overflow);
+    }
+}
+
+/// The line typed into the chat box while it is open
+#[derive(Resource, Debug, Default)]
+struct ChatInput(Option<String>);
+
+/// The panel with the chat, it keeps the lines it shows
+#[derive(Component, Debug, PartialEq)]
+struct ChatPanel {
+    lines: Vec<ChatLine>,
+    input: Option<String>,
+}
+
+/// Flashes a ring where a player points
+#[derive(Event, Debug, Clone, Copy)]
+struct ShowPing {
+    peer: PeerId,
+    position: Vec2,
+}
+
+#[derive(Component, Debug)]
+struct Ping {
+    timer: Timer,
+    /// The scale keeping the ring the same size on the screen at the zoom it was shown at
+    scale: f32,
+}
+
+/// The game doesn't see the keys typed into the chat box
+fn capture_keyboard(input: Res<ChatInput>, mut keyboard_input: ResMut<ButtonInput<KeyCode>>) {
+    if input.0.is_some() {
+        keyboard_input.reset_all();
+    }
+}
+
+fn close_chat(mut input: ResMut<ChatInput>) {
+    input.0 = None;
+}
+
+/// Logs the chat lines and shows the pings of the other players, the host passes them on
+fn receive_chat(
+    mut commands: Commands,
+    mut events: EventReader<NetEvent>,
+    mut session: ResMut<NetSession>,
+    mut log: ResMut<ChatLog>,
+    time: Res<Time<Real>>,
+) {
+    let relay = session.is_host();
+    for event in events.read() {
+        let NetEvent::Message(from, message) = event else {
+            continue;
+        };
+        match message {
+            NetMessage::Chat { peer, text } => {
+                // the host knows who sent it, the clients trust the host
+                let peer = if relay { *from } else { *peer };
+                if relay {
+                    session.broadcast_except(
+                        *from,
+                        &NetMessage::Chat {
+                            peer,
+                            text: text.clone(),
+                        },
+                    );
+                }
+                log.push(peer, text.clone(), time.elapsed_secs());
+            }
+            NetMessage::Ping { peer, position } => {
+                let peer = if relay { *from } else { *peer };
+                if relay {
+                    session.broadcast_except(
+                        *from,
+                        &NetMessage::Ping {
+                            peer,
+                            position: *position,
+                        },
+                    );
+                }
+                commands.trigger(ShowPing {
+                    peer,
+                    position: *position,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn type_into_chat(
+    mut events: EventReader<KeyboardInput>,
+    mut input: ResMut<ChatInput>,
+    mut session: ResMut<NetSession>,
+    mut log: ResMut<ChatLog>,
+    time: Res<Time<Real>>,
+) {
+    for event in events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        let Some(line) = input.0.as_mut() else {
+            if event.key_code == KeyCode::Tab {
+                input.0 = Some(String::new());
+            }
+            continue;
+        };
+        match &event.logical_key {
+            Key::Character(text) if line.chars().count() < MAX_LINE_LENGTH => {
+                line.push_str(text);
+            }
+            Key::Space => line.push(' '),
+            Key::Backspace => {
+                line.pop();
+            }
+            Key::Enter => {
+                let text = line.trim().to_string();
+                input.0 = None;
+                if !text.is_empty() {
+                    let peer = session.local_id();
+                    session.broadcast(&NetMessage::Chat {
+                        peer,
+                        text: text.clone(),
+                    });
+                    log.push(peer, text, time.elapsed_secs());
+                }
+            }
+            Key::Escape => input.0 = None,
+            _ => {}
+        }
+    }
+}
+
+/// <kbd>X</kbd> pings the place under the cursor
+fn send_ping(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut session: ResMut<NetSession>,
+    window: Single<&Window>,
+    camera: Single<(&Camera, &GlobalTransform), With<IsDefaultUiCamera>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyX) {
+        return;
+    }
+    let (camera, camera_transform) = *camera;
+    let Some(position) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
+    else {
+        return;
+    };
+    let peer = session.local_id();
+    session.broadcast(&NetMessage::Ping { peer, position });
+    commands.trigger(ShowPing { peer, position });
+}
+
+fn show_ping(
+    trigger: Trigger<ShowPing>,
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    app_state: Res<State<AppState>>,
+    projection: Single<&OrthographicProjection, With<IsDefaultUiCamera>>,
+) {
+    if *app_state.get() != AppState::Gameplay {
+        return;
+    }
+    let ShowPing { peer, position } = *trigger.event();
+    commands.spawn((
+        Mesh2d(meshes.add(Annulus::new(PING_RADIUS * 0.8, PING_RADIUS))),
+        MeshMaterial2d(materials.add(peer_color(peer))),
+        Transform::from_translation(position.extend(PING_Z)),
+        PickingBehavior::IGNORE,
+        Ping {
+            timer: Timer::from_seconds(PING_SECS, TimerMode::Once),
+            scale: projection.scale,
+        },
+        OnPlayScreen,
+    ));
+}
+
+/// Grows the rings of the pings from their centers and removes them when their time is up
+fn fade_pings(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut pings: Query<(
+        Entity,
+        &mut Ping,
+        &mut Transform,
+        &MeshMaterial2d<ColorMaterial>,
+    )>,
+) {
+    for (entity, mut ping, mut transform, material) in pings.iter_mut() {
+        if ping.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let phase = (ping.timer.fraction() * PING_PULSES).fract();
+        transform.scale = Vec3::splat(ping.scale * (0.3 + phase));
+        if let Some(material) = materials.get_mut(&material.0) {
+            material.color.set_alpha(1.0 - phase);
+        }
+    }
+}
+
+/// Shows the recent lines and the chat box, all lines while the box is open
+fn update_chat_panel(
+    mut commands: Commands,
+    log: Res<ChatLog>,
+    input: Res<ChatInput>,
+    time: Res<Time<Real>>,
+    panels: Query<(Entity, &ChatPanel)>,
+) {
+    let now = time.elapsed_secs();
+    let shown = ChatPanel {
+        lines: log
+            .0
+            .iter()
+            .filter(|line| input.0.is_some() || now - line.written < LINE_SECS)
+            .cloned()
+            .collect(),
+        input: input.0.clone(),
+    };
+    if let Ok((entity, panel)) = panels.get_single() {
+        if *panel == shown {
+            return;
+        }
+        commands.entity(entity).despawn_recursive();
+    }
+    if shown.lines.is_empty() && shown.input.is_none() {
+        return;
+    }
+
+    let font = TextFont {
+        font_size: 16.0,
+        ..default()
+    };
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(10.0),
+                bottom: Val::Px(90.0),
+                max_width: Val::Px(400.0),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(6.0)),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            PickingBehavior::IGNORE,
+        ))
+        .with_children(|p| {
+            for line in shown.lines.iter() {
+                p.spawn((
+                    Text::new(format!("P{}: {}", line.peer + 1, line.text)),
+                    font.clone(),
+                    TextColor(peer_color(line.peer)),
+                    PickingBehavior::IGNORE,
+                ));
+            }
+            if let Some(input) = &shown.input {
+                p.spawn((
+                    Text::new(format!("> {}_", input)),
+                    font.clone(),
+                    TextColor(Color::WHITE),
+                    PickingBehavior::IGNORE,
+                ));
+            }
+        })
+        .insert(shown);
+}
@@ -0,0 +1,211 @@
+//! How the clock is shown while playing: the time, the time only while the cursor is over it, no
+//! clock at all or the pieces placed per minute instead. "Clock" on the play screen switches it
+//! and the choice is remembered for the next start. The time is kept either way, for the stats
+//! and the finish screen.
+
+use crate::board::Locked;
+use crate::gameplay::{GameTimer, MoveTogether, TimerText};
+use crate::i18n::Language;
+use crate::progress::placed_pieces;
+use crate::relaxed::Relaxed;
+use crate::{GameState, SelectPlayMode};
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(load_clock_display())
+        .add_systems(
+            Update,
+            (
+                update_clock_display_text,
+                save_clock_display.run_if(resource_changed::<ClockDisplay>),
+            ),
+        )
+        .add_systems(Update, update_clock.run_if(in_state(GameState::Play)));
+}
+
+/// How the clock is shown while playing
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ClockDisplay {
+    #[default]
+    Time,
+    /// The time, only while the cursor is over the clock
+    Hover,
+    Hidden,
+    /// The pieces placed per minute
+    Rate,
+}
+
+impl ClockDisplay {
+    pub fn next(&mut self) {
+        *self = match self {
+            ClockDisplay::Time => ClockDisplay::Hover,
+            ClockDisplay::Hover => ClockDisplay::Hidden,
+            ClockDisplay::Hidden => ClockDisplay::Rate,
+            ClockDisplay::Rate => ClockDisplay::Time,
+        };
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            ClockDisplay::Time => "Time",
+            ClockDisplay::Hover => "On hover",
+            ClockDisplay::Hidden => "Hidden",
+            ClockDisplay::Rate => "Rate",
+        }
+    }
+
+    /// The text of the clock with `placed` pieces
+    pub(crate) fn text(&self, timer: &GameTimer, placed: usize, language: &Language) -> String {
+        match self {
+            ClockDisplay::Rate => {
+                let minutes = timer.elapsed_secs() / 60.0;
+                let rate = if minutes > 0.0 {
+                    placed as f32 / minutes
+                } else {
+                    0.0
+                };
+                format!("{:.1} {}", rate, language.tr("pieces/min"))
+            }
+            _ => timer.to_string(),
+        }
+    }
+
+    /// Whether the clock is shown where the cursor can't hover it, e.g. in the presentation mode
+    pub(crate) fn always_shown(&self) -> bool {
+        matches!(self, ClockDisplay::Time | ClockDisplay::Rate)
+    }
+}
+
+/// The text switching the [`ClockDisplay`]
+#[derive(Component)]
+struct ClockDisplayText;
+
+/// Adds "Clock", which switches the [`ClockDisplay`]
+pub(crate) fn spawn_clock_button(p: &mut ChildBuilder) {
+    p.spawn((
+        Text::default(),
+        Node {
+            align_self: AlignSelf::Center,
+            margin: UiRect::axes(Val::Px(5.), Val::Px(5.)),
+            ..default()
+        },
+        ClockDisplayText,
+    ))
+    .observe(
+        |_trigger: Trigger<Pointer<Click>>, mut clock_display: ResMut<ClockDisplay>| {
+            clock_display.next();
+        },
+    );
+}
+
+fn update_clock_display_text(
+    clock_display: Res<ClockDisplay>,
+    language: Res<Language>,
+    mut texts: Query<&mut Text, With<ClockDisplayText>>,
+    added: Query<(), Added<ClockDisplayText>>,
+) {
+    if added.is_empty() && !clock_display.is_changed() && !language.is_changed() {
+        return;
+    }
+    for mut text in texts.iter_mut() {
+        text.0 = format!(
+            "{}: {}",
+            language.tr("Clock"),
+            language.tr(clock_display.label())
+        );
+    }
+}
+
+/// Writes the clock and hides it as chosen, relaxed games have no clock
+fn update_clock(
+    clock_display: Res<ClockDisplay>,
+    relaxed: Res<Relaxed>,
+    game_timer: Res<GameTimer>,
+    play_mode: Res<SelectPlayMode>,
+    language: Res<Language>,
+    pieces: Query<(&MoveTogether, Has<Locked>)>,
+    mut clocks: Query<(&mut Text, &mut Visibility, &mut TextColor, &Interaction), With<TimerText>>,
+) {
+    for (mut text, mut visibility, mut color, interaction) in clocks.iter_mut() {
+        let shown = !relaxed.0 && *clock_display != ClockDisplay::Hidden;
+        visibility.set_if_neq(if shown {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        });
+        // the clock keeps its place to be hovered, it is only see-through
+        let alpha = if *clock_display == ClockDisplay::Hover && *interaction == Interaction::None {
+            0.0
+        } else {
+            1.0
+        };
+        if color.0.alpha() != alpha {
+            color.0.set_alpha(alpha);
+        }
+        let label = clock_display.text(&game_timer, placed_pieces(&play_mode, &pieces), &language);
+        if text.0 != label {
+            text.0 = label;
+        }
+    }
+}
+
+fn save_clock_display(clock_display: Res<ClockDisplay>) {
+    if clock_display.is_added() {
+        return;
+    }
+    store_clock_display(clock_display.label());
+}
+
+fn parse_clock_display(label: &str) -> ClockDisplay {
+    [
+        ClockDisplay::Time,
+        ClockDisplay::Hover,
+        ClockDisplay::Hidden,
+        ClockDisplay::Rate,
+    ]
+    .into_iter()
+    .find(|clock_display| clock_display.label() == label.trim())
+    .unwrap_or_default()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_clock_display() -> ClockDisplay {
+    crate::tutorial::config_path("clock")
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .map(|label| parse_clock_display(&label))
+        .unwrap_or_default()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn store_clock_display(label: &str) {
+    let Some(path) = crate::tutorial::config_path("clock") else {
+        return;
+    };
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, label));
+    if let Err(err) = written {
+        warn!("Could not remember how the clock is shown: {}", err);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load_clock_display() -> ClockDisplay {
+    parse_clock_display(&stored_clock_display())
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen::prelude::wasm_bindgen(inline_js = r#"
+export function stored_clock_display() {
+    return localStorage.getItem("jigsaw-clock") || "";
+}
+
+export function store_clock_display(label) {
+    localStorage.setItem("jigsaw-clock", label);
+}
+"#)]
+extern "C" {
+    fn stored_clock_display() -> String;
+    fn store_clock_display(label: &str);
+}
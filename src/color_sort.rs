@@ -0,0 +1,196 @@
+//! A hint which tints the loose pieces by the color group they belong to for a few seconds, so
+//! pieces of the sky or the grass are easier to gather.
+
+use crate::atlas::PieceMaterial;
+use crate::board::Locked;
+use crate::gameplay::{ColorImage, JigsawPuzzleGenerator, MoveTogether};
+use crate::hints::{HintBudget, HintKind};
+use crate::{Difficulty, GameState, Piece};
+use bevy::color::palettes::basic::{AQUA, FUCHSIA, LIME, RED, WHITE, YELLOW};
+use bevy::color::palettes::css::{DARK_ORANGE, ROYAL_BLUE};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<ShowColorGroups>()
+        .add_systems(
+            Update,
+            (handle_color_key, show_color_groups, fade_color_groups)
+                .chain()
+                .run_if(in_state(GameState::Play)),
+        )
+        .add_systems(OnExit(GameState::Play), clear_color_groups);
+}
+
+/// How long the pieces stay tinted
+const SHOW_DURATION: f32 = 4.0;
+/// Rounds of refining the color groups
+const ITERATIONS: usize = 8;
+/// Distinct tints of the color groups, also the most groups there are
+const GROUP_TINTS: [Srgba; 8] = [
+    RED,
+    LIME,
+    ROYAL_BLUE,
+    YELLOW,
+    FUCHSIA,
+    AQUA,
+    DARK_ORANGE,
+    WHITE,
+];
+/// How strongly the tint covers the piece image
+const TINT_STRENGTH: f32 = 0.6;
+
+/// Tints the loose pieces by their dominant color
+#[derive(Event, Debug)]
+pub struct ShowColorGroups;
+
+/// The tinted piece images with their color before
+#[derive(Resource, Debug)]
+struct ColorGroups {
+    timer: Timer,
+    tinted: HashMap<Entity, Color>,
+}
+
+fn handle_color_key(keyboard_input: Res<ButtonInput<KeyCode>>, mut commands: Commands) {
+    if keyboard_input.just_pressed(KeyCode::KeyK) {
+        commands.send_event(ShowColorGroups);
+    }
+}
+
+/// Sorts `colors` into at most `count` groups of similar colors, returns the group of every color
+fn group_colors(colors: &[Vec3], count: usize) -> Vec<usize> {
+    if colors.is_empty() {
+        return Vec::new();
+    }
+    // start from colors spread over the brightness range
+    let mut by_brightness: Vec<&Vec3> = colors.iter().collect();
+    by_brightness.sort_by(|a, b| a.element_sum().total_cmp(&b.element_sum()));
+    let count = count.min(colors.len());
+    let mut centers: Vec<Vec3> = (0..count)
+        .map(|i| *by_brightness[(i * 2 + 1) * colors.len() / (count * 2)])
+        .collect();
+
+    let mut groups = vec![0; colors.len()];
+    for _ in 0..ITERATIONS {
+        for (group, color) in groups.iter_mut().zip(colors) {
+            *group = centers
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| {
+                    a.distance_squared(*color)
+                        .total_cmp(&b.distance_squared(*color))
+                })
+                .map(|(i, _)| i)
+                .unwrap_or_default();
+        }
+        for (i, center) in centers.iter_mut().enumerate() {
+            let members: Vec<Vec3> = groups
+                .iter()
+                .zip(colors)
+                .filter(|(group, _)| **group == i)
+                .map(|(_, color)| *color)
+                .collect();
+            if !members.is_empty() {
+                *center = members.iter().sum::<Vec3>() / members.len() as f32;
+            }
+        }
+    }
+    groups
+}
+
+#[allow(clippy::too_many_arguments)]
+fn show_color_groups(
+    mut commands: Commands,
+    mut events: EventReader<ShowColorGroups>,
+    difficulty: Res<Difficulty>,
+    mut budget: ResMut<HintBudget>,
+    generator: Res<JigsawPuzzleGenerator>,
+    previous: Option<Res<ColorGroups>>,
+    pieces: Query<(&Piece, &MoveTogether, &Children), Without<Locked>>,
+    images: Query<&MeshMaterial2d<PieceMaterial>, With<ColorImage>>,
+    mut materials: ResMut<Assets<PieceMaterial>>,
+) {
+    if events.read().count() == 0 || !difficulty.hints() || previous.is_some() {
+        return;
+    }
+    if !budget.spend(HintKind::Colors) {
+        return;
+    }
+
+    let loose: Vec<(&Piece, &Children)> = pieces
+        .iter()
+        .filter(|(_, together, _)| together.is_empty())
+        .map(|(piece, _, children)| (piece, children))
+        .collect();
+    let image = generator.origin_image();
+    let colors: Vec<Vec3> = loose
+        .iter()
+        .map(|(piece, _)| {
+            let step = (piece.width.min(piece.height) / 8.0).max(1.0) as u32;
+            let color = piece.average_color(image, step);
+            Vec3::new(color[0] as f32, color[1] as f32, color[2] as f32) / 255.0
+        })
+        .collect();
+    let groups = group_colors(&colors, GROUP_TINTS.len());
+
+    let mut tinted = HashMap::default();
+    for ((_, children), group) in loose.iter().zip(groups) {
+        let tint = Color::WHITE.mix(&GROUP_TINTS[group].into(), TINT_STRENGTH);
+        for child in children.iter() {
+            let Ok(handle) = images.get(*child) else {
+                continue;
+            };
+            if let Some(material) = materials.get_mut(handle) {
+                tinted.insert(*child, material.color());
+                material.set_color(tint);
+            }
+        }
+    }
+    commands.insert_resource(ColorGroups {
+        timer: Timer::from_seconds(SHOW_DURATION, TimerMode::Once),
+        tinted,
+    });
+}
+
+fn restore_colors(
+    groups: &ColorGroups,
+    images: &Query<&MeshMaterial2d<PieceMaterial>, With<ColorImage>>,
+    materials: &mut Assets<PieceMaterial>,
+) {
+    for (entity, color) in groups.tinted.iter() {
+        let Ok(handle) = images.get(*entity) else {
+            continue;
+        };
+        if let Some(material) = materials.get_mut(handle) {
+            material.set_color(*color);
+        }
+    }
+}
+
+fn fade_color_groups(
+    mut commands: Commands,
+    time: Res<Time>,
+    groups: Option<ResMut<ColorGroups>>,
+    images: Query<&MeshMaterial2d<PieceMaterial>, With<ColorImage>>,
+    mut materials: ResMut<Assets<PieceMaterial>>,
+) {
+    let Some(mut groups) = groups else {
+        return;
+    };
+    if groups.timer.tick(time.delta()).just_finished() {
+        restore_colors(&groups, &images, &mut materials);
+        commands.remove_resource::<ColorGroups>();
+    }
+}
+
+fn clear_color_groups(
+    mut commands: Commands,
+    groups: Option<Res<ColorGroups>>,
+    images: Query<&MeshMaterial2d<PieceMaterial>, With<ColorImage>>,
+    mut materials: ResMut<Assets<PieceMaterial>>,
+) {
+    if let Some(groups) = groups {
+        restore_colors(&groups, &images, &mut materials);
+        commands.remove_resource::<ColorGroups>();
+    }
+}
@@ -0,0 +1,175 @@
+//! Settings given by the host application or the command line, they preselect the puzzle in the
+//! main menu or start it right away.
+
+use crate::animated::{add_image_bytes, AnimatedImages};
+use crate::main_menu::LoadedImages;
+use crate::scenario::Scenario;
+use crate::{AppState, CustomGrid, OriginImage, PuzzleSeed, SelectGameMode, SelectPiece};
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+use jigsaw_puzzle_generator::GameMode;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PuzzlePluginConfig>()
+        .add_systems(Startup, apply_config)
+        .add_systems(
+            Update,
+            start_configured_puzzle
+                .run_if(resource_exists::<PendingStart>)
+                .run_if(in_state(AppState::MainMenu)),
+        );
+}
+
+/// The puzzle to set up at startup, insert it before adding the `PuzzlePlugin`
+#[derive(Resource, Debug, Clone, Default)]
+pub struct PuzzlePluginConfig {
+    pub image: Option<ConfigImage>,
+    /// Columns and rows, takes precedence over `pieces`
+    pub grid: Option<(usize, usize)>,
+    /// The number of pieces, cut into the squarest grid for the image
+    pub pieces: Option<usize>,
+    pub game_mode: Option<GameMode>,
+    pub seed: Option<usize>,
+    /// The layout every puzzle starts with
+    pub scenario: Option<Scenario>,
+    /// Starts the puzzle without showing the main menu
+    pub skip_menu: bool,
+}
+
+/// The image of the [`PuzzlePluginConfig`]
+#[derive(Debug, Clone)]
+pub enum ConfigImage {
+    /// A path in the assets folder
+    Asset(String),
+    /// An encoded PNG, JPEG or GIF image, an animated GIF plays on the pieces
+    Bytes(Vec<u8>),
+}
+
+impl PuzzlePluginConfig {
+    /// Reads `--image <file>`, `--grid <columns>x<rows>`, `--pieces <count>`,
+    /// `--mode <classic|square|triangular|crazy>`, `--seed <seed>`, `--scenario <file>` and `--play`
+    /// from the command line
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_args() -> Self {
+        let args: Vec<String> = std::env::args().collect();
+        let value_of = |flag: &str| {
+            args.iter()
+                .position(|arg| arg == flag)
+                .and_then(|i| args.get(i + 1))
+        };
+
+        PuzzlePluginConfig {
+            image: value_of("--image").and_then(|path| {
+                std::fs::read(path)
+                    .inspect_err(|e| error!("Failed to read {}: {}", path, e))
+                    .ok()
+                    .map(ConfigImage::Bytes)
+            }),
+            grid: value_of("--grid").and_then(|grid| {
+                let (columns, rows) = grid.split_once('x')?;
+                Some((columns.parse().ok()?, rows.parse().ok()?))
+            }),
+            pieces: value_of("--pieces").and_then(|count| count.parse().ok()),
+            game_mode: value_of("--mode").and_then(|mode| match mode.as_str() {
+                "classic" => Some(GameMode::Classic),
+                "square" => Some(GameMode::Square),
+                "triangular" => Some(GameMode::Triangular),
+                "crazy" => Some(GameMode::Crazy),
+                _ => None,
+            }),
+            seed: value_of("--seed").and_then(|seed| seed.parse().ok()),
+            scenario: value_of("--scenario").and_then(|path| {
+                std::fs::read_to_string(path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|ron| Scenario::from_ron(&ron).map_err(|e| e.to_string()))
+                    .inspect_err(|e| error!("Failed to read the scenario {}: {}", path, e))
+                    .ok()
+            }),
+            skip_menu: args.iter().any(|arg| arg == "--play"),
+        }
+    }
+}
+
+/// The configured image and whether to start the puzzle, until the image is loaded
+#[derive(Resource)]
+struct PendingStart {
+    image: Option<Handle<Image>>,
+    skip_menu: bool,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn apply_config(
+    mut commands: Commands,
+    config: Res<PuzzlePluginConfig>,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+    mut animated: ResMut<AnimatedImages>,
+    mut select_piece: ResMut<SelectPiece>,
+    mut custom_grid: ResMut<CustomGrid>,
+    mut game_mode: ResMut<SelectGameMode>,
+    mut puzzle_seed: ResMut<PuzzleSeed>,
+) {
+    if let Some(pieces) = config.pieces {
+        *select_piece = SelectPiece::new(pieces);
+    }
+    if let Some((columns, rows)) = config.grid {
+        custom_grid.set(&mut select_piece, columns, rows);
+    }
+    if let Some(mode) = config.game_mode {
+        game_mode.0 = mode;
+    }
+    if config.seed.is_some() {
+        puzzle_seed.0 = config.seed;
+    }
+    if let Some(scenario) = config.scenario.clone() {
+        commands.insert_resource(scenario);
+    }
+
+    let image = config.image.as_ref().and_then(|image| match image {
+        ConfigImage::Asset(path) => Some(asset_server.load(path.clone())),
+        ConfigImage::Bytes(bytes) => add_image_bytes(bytes, &mut images, &mut animated)
+            .inspect_err(|e| error!("Failed to decode the configured image: {}", e))
+            .ok(),
+    });
+    if image.is_some() || config.skip_menu {
+        commands.insert_resource(PendingStart {
+            image,
+            skip_menu: config.skip_menu,
+        });
+    }
+}
+
+/// Selects the configured image at the front of the image strip once it is loaded, and starts
+/// the puzzle if the menu is skipped
+fn start_configured_puzzle(
+    mut commands: Commands,
+    pending: Res<PendingStart>,
+    asset_server: Res<AssetServer>,
+    images: Res<Assets<Image>>,
+    origin_image: Res<OriginImage>,
+    mut loaded_images: ResMut<LoadedImages>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    let image = pending
+        .image
+        .clone()
+        .unwrap_or_else(|| origin_image.0.clone());
+    if let Some(LoadState::Failed(e)) = asset_server.get_load_state(&image) {
+        error!("Failed to load the configured image: {}", e);
+        commands.remove_resource::<PendingStart>();
+        return;
+    }
+    if !images.contains(&image) {
+        return;
+    }
+
+    if pending.image.is_some() {
+        // the menu selects the first image of the strip once its title is shown
+        loaded_images.insert(0, image.clone());
+        commands.insert_resource(OriginImage(image));
+    }
+    if pending.skip_menu {
+        app_state.set(AppState::Gameplay);
+    }
+    commands.remove_resource::<PendingStart>();
+}
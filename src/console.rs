@@ -0,0 +1,341 @@
+//! A console for debugging, opened with the backquote key in debug builds. It takes the keyboard
+//! while it is open and runs cheat commands to reach late game states quickly, `help` lists them.
+
+#[cfg(not(target_arch = "wasm32"))]
+use crate::animated::{add_image_bytes, AnimatedImages};
+use crate::auto_place::{largest_group, place_solved};
+use crate::gameplay::{GameTimer, JigsawPuzzleGenerator, MoveEnd, MoveTogether, OnPlayScreen};
+#[cfg(not(target_arch = "wasm32"))]
+#[cfg(not(target_arch = "wasm32"))]
+use crate::photo::PhotoTaken;
+use crate::{AppState, CustomGrid, GameState, OriginImage, Piece, PuzzleSeed, SelectPiece};
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::{ButtonState, InputSystem};
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Console>()
+        .add_systems(PreUpdate, capture_keyboard.after(InputSystem))
+        .add_systems(
+            Update,
+            (type_into_console, run_console_commands, update_console_ui).chain(),
+        );
+}
+
+/// Output lines kept in the console
+const MAX_LINES: usize = 12;
+
+const HELP: &str = "solve [n] | teleport <index> <x> <y> | seed [seed] | \
+                    spawn_puzzle <path> <columns> <rows> | photo <path> | state";
+
+#[derive(Resource, Debug, Default)]
+struct Console {
+    open: bool,
+    input: String,
+    lines: Vec<String>,
+    /// Entered commands which haven't run yet
+    pending: Vec<String>,
+}
+
+impl Console {
+    fn print(&mut self, line: impl Into<String>) {
+        let line = line.into();
+        info!("console: {}", line);
+        self.lines.push(line);
+        let overflow = self.lines.len().saturating_sub(MAX_LINES);
+        self.lines.drain(..overflow);
+    }
+}
+
+#[derive(Component)]
+struct ConsoleText;
+
+/// The game doesn't see the keys typed into the console
+fn capture_keyboard(console: Res<Console>, mut keyboard_input: ResMut<ButtonInput<KeyCode>>) {
+    if console.open {
+        keyboard_input.reset_all();
+    }
+}
+
+fn type_into_console(mut events: EventReader<KeyboardInput>, mut console: ResMut<Console>) {
+    for event in events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        if event.key_code == KeyCode::Backquote {
+            console.open = !console.open;
+            continue;
+        }
+        if !console.open {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Character(text) => console.input.push_str(text),
+            Key::Space => console.input.push(' '),
+            Key::Backspace => {
+                console.input.pop();
+            }
+            Key::Enter => {
+                let line = core::mem::take(&mut console.input);
+                if !line.trim().is_empty() {
+                    console.print(format!("> {line}"));
+                    console.pending.push(line);
+                }
+            }
+            Key::Escape => console.open = false,
+            _ => {}
+        }
+    }
+}
+
+fn run_console_commands(world: &mut World) {
+    let pending = core::mem::take(&mut world.resource_mut::<Console>().pending);
+    for line in pending {
+        let output = run_command(world, &line).unwrap_or_else(|e| format!("error: {e}"));
+        world.resource_mut::<Console>().print(output);
+    }
+}
+
+fn run_command(world: &mut World, line: &str) -> Result<String, String> {
+    let args: Vec<&str> = line.split_whitespace().collect();
+    let number = |index: usize| -> Result<f32, String> {
+        args.get(index)
+            .ok_or_else(|| format!("missing argument {index}"))?
+            .parse::<f32>()
+            .map_err(|e| e.to_string())
+    };
+    match args.first().copied() {
+        Some("help") => Ok(HELP.to_string()),
+        Some("solve") => {
+            let count = match args.get(1) {
+                Some(count) => count.parse().map_err(|e| format!("{e}"))?,
+                None => usize::MAX,
+            };
+            solve(world, count)
+        }
+        Some("teleport") => teleport(
+            world,
+            number(1)? as usize,
+            Vec2::new(number(2)?, number(3)?),
+        ),
+        Some("seed") => {
+            if let Some(seed) = args.get(1) {
+                let seed = seed.parse().map_err(|e| format!("{e}"))?;
+                world.resource_mut::<PuzzleSeed>().0 = Some(seed);
+            }
+            let current = world
+                .get_resource::<JigsawPuzzleGenerator>()
+                .and_then(|generator| generator.seed_value());
+            Ok(format!(
+                "seed: {current:?}, next puzzle: {:?}",
+                world.resource::<PuzzleSeed>().0
+            ))
+        }
+        Some("spawn_puzzle") => {
+            let path = args.get(1).ok_or("missing path")?;
+            spawn_puzzle(world, path, number(2)? as usize, number(3)? as usize)
+        }
+        Some("photo") => photo(world, args.get(1).ok_or("missing path")?),
+        Some("state") => Ok(state(world)),
+        Some(command) => Err(format!("unknown command {command}, try help")),
+        None => Ok(String::new()),
+    }
+}
+
+/// Moves `count` more pieces to their solved place next to the largest group, in the order of
+/// their index, and drops them so they merge
+fn solve(world: &mut World, count: usize) -> Result<String, String> {
+    if world.get_resource::<JigsawPuzzleGenerator>().is_none() {
+        return Err("no puzzle is played".to_string());
+    }
+    let mut query = world.query::<(Entity, &Piece, &MoveTogether)>();
+    let mut pieces: Vec<(Entity, usize, HashSet<Entity>)> = query
+        .iter(world)
+        .map(|(entity, piece, together)| (entity, piece.index, together.0.clone()))
+        .collect();
+    pieces.sort_by_key(|(_, index, _)| *index);
+
+    // the largest group stays where it is
+    let mut done = largest_group(world);
+    let mut moved = vec![];
+    for (entity, _, together) in pieces.iter() {
+        if moved.len() >= count {
+            break;
+        }
+        if done.contains(entity) {
+            continue;
+        }
+        // a group is moved as a whole, its pieces already lie right to each other
+        let group: Vec<Entity> = if together.is_empty() {
+            vec![*entity]
+        } else {
+            together.iter().copied().collect()
+        };
+        done.extend(group.iter().copied());
+        moved.extend(group);
+    }
+
+    place_solved(world, &moved);
+    Ok(format!("solved {} pieces", moved.len()))
+}
+
+/// Drops the piece with `index` with its group so the piece lies at `position`
+fn teleport(world: &mut World, index: usize, position: Vec2) -> Result<String, String> {
+    let mut query = world.query::<(Entity, &Piece, &Transform, &MoveTogether)>();
+    let (entity, offset, mut group) = query
+        .iter(world)
+        .find(|(_, piece, ..)| piece.index == index)
+        .map(|(entity, _, transform, together)| {
+            (
+                entity,
+                position - transform.translation.xy(),
+                together.0.clone(),
+            )
+        })
+        .ok_or_else(|| format!("no piece {index}"))?;
+    group.insert(entity);
+    for member in group {
+        if let Some(mut transform) = world.get_mut::<Transform>(member) {
+            transform.translation += offset.extend(0.0);
+        }
+    }
+    world.trigger_targets(MoveEnd, entity);
+    world.flush();
+    Ok(format!("piece {index} at {position}"))
+}
+
+/// Starts a new puzzle of the image file at `path`, cut into `columns` x `rows` pieces
+#[cfg(not(target_arch = "wasm32"))]
+fn spawn_puzzle(
+    world: &mut World,
+    path: &str,
+    columns: usize,
+    rows: usize,
+) -> Result<String, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    let image = world.resource_scope(|world, mut animated: Mut<AnimatedImages>| {
+        add_image_bytes(
+            &bytes,
+            &mut world.resource_mut::<Assets<Image>>(),
+            &mut animated,
+        )
+    });
+    let image = image.map_err(|e| e.to_string())?;
+    world.resource_scope(|world, mut custom_grid: Mut<CustomGrid>| {
+        custom_grid.set(&mut world.resource_mut::<SelectPiece>(), columns, rows);
+    });
+    world.insert_resource(OriginImage(image));
+
+    if *world.resource::<State<AppState>>() == AppState::MainMenu {
+        world
+            .resource_mut::<NextState<AppState>>()
+            .set(AppState::Gameplay);
+    } else {
+        // like leaving through the finish screen, without showing it
+        let mut screen = world.query_filtered::<Entity, With<OnPlayScreen>>();
+        let entities: Vec<Entity> = screen.iter(world).collect();
+        for entity in entities {
+            if let Ok(entity) = world.get_entity_mut(entity) {
+                entity.despawn_recursive();
+            }
+        }
+        world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Setup);
+    }
+    Ok(format!("generating {columns}x{rows} pieces of {path}"))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn spawn_puzzle(
+    _world: &mut World,
+    _path: &str,
+    _columns: usize,
+    _rows: usize,
+) -> Result<String, String> {
+    Err("the web build can't read files".to_string())
+}
+
+/// Hands the image file at `path` over like a photo of a camera, in the main menu
+#[cfg(not(target_arch = "wasm32"))]
+fn photo(world: &mut World, path: &str) -> Result<String, String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    let image =
+        jigsaw_puzzle_generator::image::load_from_memory(&bytes).map_err(|e| e.to_string())?;
+    world.send_event(PhotoTaken(image.to_rgba8()));
+    Ok(format!("photo of {path}"))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn photo(_world: &mut World, _path: &str) -> Result<String, String> {
+    Err("the web build can't read files".to_string())
+}
+
+fn state(world: &mut World) -> String {
+    let app_state = *world.resource::<State<AppState>>().get();
+    let game_state = *world.resource::<State<GameState>>().get();
+    let mut query = world.query::<(Entity, &MoveTogether)>();
+    let mut groups = 0;
+    let mut largest = 0;
+    let mut count = 0;
+    for (entity, together) in query.iter(world) {
+        count += 1;
+        // every group is counted at its first piece
+        if together.iter().min().is_some_and(|first| *first == entity) {
+            groups += 1;
+            largest = largest.max(together.len());
+        }
+    }
+    let time = world
+        .get_resource::<GameTimer>()
+        .map(ToString::to_string)
+        .unwrap_or_default();
+    format!(
+        "{app_state:?} / {game_state:?}, {count} pieces, {groups} groups, largest {largest}, {time}"
+    )
+}
+
+fn update_console_ui(
+    mut commands: Commands,
+    console: Res<Console>,
+    mut texts: Query<(Entity, &mut Text), With<ConsoleText>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+    if !console.open {
+        for (entity, _) in texts.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+    let mut text = console.lines.join("\n");
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text.push_str(&format!("> {}_", console.input));
+    if let Ok((_, mut current)) = texts.get_single_mut() {
+        current.0 = text;
+        return;
+    }
+    commands.spawn((
+        Text::new(text),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.8, 1.0, 0.8)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(0.0),
+            width: Val::Percent(100.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+        GlobalZIndex(100),
+        PickingBehavior::IGNORE,
+        ConsoleText,
+    ));
+}
@@ -0,0 +1,88 @@
+use crate::GameState;
+use bevy::color::palettes::basic::{FUCHSIA, YELLOW};
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<HighContrast>()
+        .add_systems(
+            Update,
+            handle_contrast_keys.run_if(in_state(GameState::Play)),
+        )
+        .add_systems(
+            Update,
+            update_contrast_visibility.run_if(resource_changed::<HighContrast>),
+        );
+}
+
+/// The color of the thick piece outlines in high contrast mode
+pub const OUTLINE_COLOR: Color = Color::srgb(0.08, 0.08, 0.08);
+/// Half of the thickness of the piece outlines
+pub const OUTLINE_RADIUS: i32 = 2;
+
+/// Assists players with low vision or color blindness
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct HighContrast {
+    /// Draw thick dark outlines around the pieces and a stronger selection tint
+    pub enabled: bool,
+    /// Label every piece with its number
+    pub show_numbers: bool,
+}
+
+impl HighContrast {
+    /// The tint of the outline around selected pieces
+    pub fn selection_color(&self) -> Color {
+        if self.enabled {
+            Color::Srgba(FUCHSIA)
+        } else {
+            Color::Srgba(YELLOW)
+        }
+    }
+
+    pub fn outline_visibility(&self) -> Visibility {
+        if self.enabled {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        }
+    }
+
+    pub fn number_visibility(&self) -> Visibility {
+        if self.show_numbers {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        }
+    }
+}
+
+/// The thick outline drawn above the piece image
+#[derive(Component)]
+pub struct OutlineImage;
+
+/// The label showing the number of a piece
+#[derive(Component)]
+pub struct PieceNumber;
+
+fn handle_contrast_keys(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut high_contrast: ResMut<HighContrast>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyC) {
+        high_contrast.enabled = !high_contrast.enabled;
+    } else if keyboard_input.just_pressed(KeyCode::KeyN) {
+        high_contrast.show_numbers = !high_contrast.show_numbers;
+    }
+}
+
+fn update_contrast_visibility(
+    high_contrast: Res<HighContrast>,
+    mut outlines: Query<&mut Visibility, (With<OutlineImage>, Without<PieceNumber>)>,
+    mut numbers: Query<&mut Visibility, (With<PieceNumber>, Without<OutlineImage>)>,
+) {
+    for mut visibility in outlines.iter_mut() {
+        *visibility = high_contrast.outline_visibility();
+    }
+    for mut visibility in numbers.iter_mut() {
+        *visibility = high_contrast.number_visibility();
+    }
+}
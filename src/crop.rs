@@ -0,0 +1,287 @@
+//! Edits the selected image in the main menu before it is cut, so the subject isn't cut in half.
+//! "Rotate" turns the image a quarter clockwise. "Crop" lets the player drag a rectangle over the
+//! preview, "Fit grid" shapes the rectangle so the pieces of the grid come out square and "Apply"
+//! cuts the image down to it. The edited image takes the place of the selected one in the strip.
+
+use crate::i18n::{Language, Localized};
+use crate::main_menu::{current_grid, LoadedImages};
+use crate::theme::ThemeColor;
+use crate::{AppState, CustomGrid, OriginImage, SelectPiece};
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use jigsaw_puzzle_generator::image::DynamicImage;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<CropEdit>()
+        .add_systems(OnExit(AppState::MainMenu), stop_cropping)
+        .add_systems(
+            Update,
+            (
+                stop_cropping.run_if(resource_changed::<OriginImage>),
+                update_crop_overlay,
+            )
+                .chain()
+                .run_if(in_state(AppState::MainMenu)),
+        );
+}
+
+/// Smaller crops are taken for a click rather than a drag
+const MIN_CROP_PIXELS: u32 = 16;
+const CROP_BORDER_COLOR: Color = Color::WHITE;
+const CROP_FILL_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.1);
+
+/// The crop rectangle being edited, in fractions of the image from its top left corner
+#[derive(Resource, Debug, Default)]
+struct CropEdit {
+    editing: bool,
+    start: Vec2,
+    rect: Option<Rect>,
+}
+
+/// Catches the drags over the image preview while cropping
+#[derive(Component)]
+struct CropOverlay;
+
+#[derive(Component)]
+struct CropRectNode;
+
+#[derive(Component)]
+struct CropText;
+
+fn stop_cropping(mut crop: ResMut<CropEdit>) {
+    *crop = CropEdit::default();
+}
+
+/// Adds the crop rectangle over the image preview
+pub(crate) fn spawn_crop_overlay(p: &mut ChildBuilder) {
+    p.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        Visibility::Hidden,
+        RelativeCursorPosition::default(),
+        CropOverlay,
+    ))
+    .with_child((
+        Node {
+            position_type: PositionType::Absolute,
+            border: UiRect::all(Val::Px(2.0)),
+            ..default()
+        },
+        BorderColor(CROP_BORDER_COLOR),
+        Outline {
+            width: Val::Px(1.0),
+            color: Color::BLACK,
+            offset: Val::Px(0.0),
+        },
+        BackgroundColor(CROP_FILL_COLOR),
+        PickingBehavior::IGNORE,
+        CropRectNode,
+    ))
+    .observe(start_crop_drag)
+    .observe(crop_drag);
+}
+
+fn cursor_fraction(entity: Entity, cursors: &Query<&RelativeCursorPosition>) -> Option<Vec2> {
+    let normalized = cursors.get(entity).ok()?.normalized?;
+    Some(normalized.clamp(Vec2::ZERO, Vec2::ONE))
+}
+
+fn start_crop_drag(
+    trigger: Trigger<Pointer<DragStart>>,
+    mut crop: ResMut<CropEdit>,
+    cursors: Query<&RelativeCursorPosition>,
+) {
+    if !crop.editing {
+        return;
+    }
+    if let Some(start) = cursor_fraction(trigger.entity(), &cursors) {
+        crop.start = start;
+        crop.rect = Some(Rect::from_corners(start, start));
+    }
+}
+
+fn crop_drag(
+    trigger: Trigger<Pointer<Drag>>,
+    mut crop: ResMut<CropEdit>,
+    cursors: Query<&RelativeCursorPosition>,
+) {
+    if !crop.editing {
+        return;
+    }
+    if let Some(end) = cursor_fraction(trigger.entity(), &cursors) {
+        crop.rect = Some(Rect::from_corners(crop.start, end));
+    }
+}
+
+/// The largest rectangle inside the image around `center` on which the pieces of the grid are
+/// square, in fractions of the image
+fn grid_rect(size: Vec2, grid: (usize, usize), center: Vec2) -> Rect {
+    let aspect = grid.0 as f32 / grid.1 as f32;
+    let fraction = if size.x / size.y > aspect {
+        Vec2::new(aspect * size.y / size.x, 1.0)
+    } else {
+        Vec2::new(1.0, size.x / (aspect * size.y))
+    };
+    let half = fraction / 2.0;
+    let center = center.clamp(half, Vec2::ONE - half);
+    Rect::from_center_half_size(center, half)
+}
+
+/// Puts the edited image in place of the selected one
+fn replace_selected(
+    image: DynamicImage,
+    images: &mut Assets<Image>,
+    loaded_images: &mut LoadedImages,
+    origin_image: &mut OriginImage,
+) {
+    let handle = images.add(Image::from_dynamic(
+        image,
+        true,
+        RenderAssetUsages::default(),
+    ));
+    match loaded_images
+        .iter()
+        .position(|image| *image == origin_image.0)
+    {
+        Some(index) => loaded_images[index] = handle.clone(),
+        None => loaded_images.push(handle.clone()),
+    }
+    origin_image.0 = handle;
+}
+
+fn selected_image(images: &Assets<Image>, origin_image: &OriginImage) -> Option<DynamicImage> {
+    images
+        .get(&origin_image.0)
+        .and_then(|image| image.clone().try_into_dynamic().ok())
+}
+
+/// Adds "Rotate", "Crop" and "Fit grid"
+pub(crate) fn spawn_crop_buttons(p: &mut ChildBuilder, font: TextFont) {
+    p.spawn((
+        Text::new("Rotate"),
+        Localized("Rotate"),
+        font.clone(),
+        TextColor(Color::BLACK),
+        ThemeColor::Text,
+    ))
+    .observe(
+        |_trigger: Trigger<Pointer<Click>>,
+         mut images: ResMut<Assets<Image>>,
+         mut loaded_images: ResMut<LoadedImages>,
+         mut origin_image: ResMut<OriginImage>| {
+            let Some(image) = selected_image(&images, &origin_image) else {
+                return;
+            };
+            replace_selected(
+                image.rotate90(),
+                &mut images,
+                &mut loaded_images,
+                &mut origin_image,
+            );
+        },
+    );
+    p.spawn((
+        Text::default(),
+        font.clone(),
+        TextColor(Color::BLACK),
+        ThemeColor::Text,
+        CropText,
+    ))
+    .observe(
+        |_trigger: Trigger<Pointer<Click>>,
+         mut crop: ResMut<CropEdit>,
+         mut images: ResMut<Assets<Image>>,
+         mut loaded_images: ResMut<LoadedImages>,
+         mut origin_image: ResMut<OriginImage>| {
+            if !crop.editing {
+                crop.editing = true;
+                return;
+            }
+            let rect = crop.rect.take();
+            crop.editing = false;
+            let (Some(rect), Some(image)) = (rect, selected_image(&images, &origin_image)) else {
+                return;
+            };
+            let size = Vec2::new(image.width() as f32, image.height() as f32);
+            let min = (rect.min * size).round().as_uvec2();
+            let max = (rect.max * size).round().as_uvec2();
+            let crop_size = max - min;
+            if crop_size.min_element() < MIN_CROP_PIXELS {
+                return;
+            }
+            replace_selected(
+                image.crop_imm(min.x, min.y, crop_size.x, crop_size.y),
+                &mut images,
+                &mut loaded_images,
+                &mut origin_image,
+            );
+        },
+    );
+    p.spawn((
+        Text::new("Fit grid"),
+        Localized("Fit grid"),
+        font,
+        TextColor(Color::BLACK),
+        ThemeColor::Text,
+    ))
+    .observe(
+        |_trigger: Trigger<Pointer<Click>>,
+         mut crop: ResMut<CropEdit>,
+         images: Res<Assets<Image>>,
+         origin_image: Res<OriginImage>,
+         custom_grid: Res<CustomGrid>,
+         select_piece: Res<SelectPiece>| {
+            let image = images.get(&origin_image.0);
+            let (Some(size), Some(grid)) = (
+                image.map(|image| image.size().as_vec2()),
+                current_grid(&custom_grid, &select_piece, image),
+            ) else {
+                return;
+            };
+            let center = crop.rect.map_or(Vec2::splat(0.5), |rect| rect.center());
+            crop.editing = true;
+            crop.rect = Some(grid_rect(size, grid, center));
+        },
+    );
+}
+
+/// Shows the crop rectangle while cropping and names the step of the "Crop" button
+fn update_crop_overlay(
+    crop: Res<CropEdit>,
+    language: Res<Language>,
+    mut texts: Query<&mut Text, With<CropText>>,
+    mut overlays: Query<&mut Visibility, With<CropOverlay>>,
+    mut rect_nodes: Query<(&mut Node, &mut Visibility), (With<CropRectNode>, Without<CropOverlay>)>,
+    added: Query<(), Added<CropText>>,
+) {
+    if added.is_empty() && !crop.is_changed() && !language.is_changed() {
+        return;
+    }
+    let label = if crop.editing { "Apply" } else { "Crop" };
+    for mut text in texts.iter_mut() {
+        text.0 = language.tr(label).to_string();
+    }
+    for mut visibility in overlays.iter_mut() {
+        *visibility = if crop.editing {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    for (mut node, mut visibility) in rect_nodes.iter_mut() {
+        let Some(rect) = crop.rect else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        *visibility = Visibility::Inherited;
+        node.left = Val::Percent(rect.min.x * 100.0);
+        node.top = Val::Percent(rect.min.y * 100.0);
+        node.width = Val::Percent(rect.width() * 100.0);
+        node.height = Val::Percent(rect.height() * 100.0);
+    }
+}
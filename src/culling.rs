@@ -0,0 +1,60 @@
+//! Hides the pieces outside of the view of the camera, so they are neither drawn nor picked and
+//! dropped pieces aren't compared with them.
+
+use crate::gameplay::{ColorImage, WhiteImage};
+use crate::{GameState, Piece};
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(Update, cull_pieces.run_if(in_state(GameState::Play)));
+}
+
+/// Room around the window in screen pixels, so pieces are shown before they scroll in
+const CULL_MARGIN: f32 = 100.0;
+
+/// A piece outside of the view of the camera
+#[derive(Component, Debug)]
+pub struct OffScreen;
+
+fn cull_pieces(
+    mut commands: Commands,
+    window: Single<&Window, With<PrimaryWindow>>,
+    camera: Single<
+        (&Transform, &OrthographicProjection),
+        (With<Camera2d>, With<IsDefaultUiCamera>),
+    >,
+    pieces: Query<(Entity, &Piece, &Transform, &Children, Has<OffScreen>), Without<Camera2d>>,
+    mut images: Query<&mut Visibility, Or<(With<ColorImage>, With<WhiteImage>)>>,
+) {
+    let (camera_transform, projection) = *camera;
+    let view = Rect::from_center_half_size(
+        camera_transform.translation.xy(),
+        window.size() / 2.0 * projection.scale,
+    )
+    .inflate(CULL_MARGIN * projection.scale);
+
+    for (entity, piece, transform, children, off_screen) in pieces.iter() {
+        let size = Vec2::new(piece.width, piece.height);
+        let center = transform.translation.xy() + Vec2::new(size.x, -size.y) / 2.0;
+        // the tabs and any rotation stay within the larger side around the center
+        let outside = !view.inflate(size.max_element()).contains(center);
+        if outside == off_screen {
+            continue;
+        }
+
+        if outside {
+            commands.entity(entity).insert(OffScreen);
+        } else {
+            commands.entity(entity).remove::<OffScreen>();
+        }
+        let mut iter = images.iter_many_mut(children);
+        while let Some(mut visibility) = iter.fetch_next() {
+            *visibility = if outside {
+                Visibility::Hidden
+            } else {
+                Visibility::Inherited
+            };
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Cursors which show what the pointer does on the table: an open hand over pieces, a grabbing
+//! hand while a piece is held and a magnifier while zooming. A faded copy of the held piece
+//! follows the pointer a bit below it, so it can still be seen under a finger.
+
+use crate::atlas::PieceMaterial;
+use crate::gameplay::{AdjustScale, ColorImage, MoveStart, OnPlayScreen};
+use crate::layering::HoveredPiece;
+use crate::GameState;
+use bevy::prelude::*;
+use bevy::window::{PrimaryWindow, SystemCursorIcon};
+use bevy::winit::cursor::CursorIcon;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (update_cursor, follow_pointer).run_if(in_state(GameState::Play)),
+    )
+    .add_systems(OnExit(GameState::Play), reset_cursor)
+    .add_observer(spawn_drag_ghost)
+    .add_observer(despawn_drag_ghost);
+}
+
+/// How long the zoom cursor stays after zooming
+const ZOOM_CURSOR_DURATION: f32 = 0.4;
+/// Offset of the drag ghost from the pointer in screen pixels
+const GHOST_OFFSET: Vec2 = Vec2::new(40.0, -60.0);
+const GHOST_SCALE: f32 = 0.6;
+const GHOST_ALPHA: f32 = 0.5;
+/// Above the held pieces
+const GHOST_Z: f32 = 150.0;
+
+/// A faded copy of the held piece
+#[derive(Component, Debug)]
+struct DragGhost(Entity);
+
+/// The zoom cursor shown after the last zoom
+#[derive(Default)]
+struct ZoomCursor(Option<(SystemCursorIcon, Timer)>);
+
+fn set_cursor(commands: &mut Commands, window: Entity, icon: SystemCursorIcon) {
+    commands.entity(window).insert(CursorIcon::from(icon));
+}
+
+fn update_cursor(
+    mut commands: Commands,
+    time: Res<Time>,
+    window: Single<(Entity, Option<&CursorIcon>), With<PrimaryWindow>>,
+    hovered: Res<HoveredPiece>,
+    held: Query<(), With<MoveStart>>,
+    mut zooms: EventReader<AdjustScale>,
+    mut zoom_cursor: Local<ZoomCursor>,
+) {
+    if let Some(AdjustScale(scale)) = zooms.read().last() {
+        let icon = if *scale > 0.0 {
+            SystemCursorIcon::ZoomIn
+        } else {
+            SystemCursorIcon::ZoomOut
+        };
+        zoom_cursor.0 = Some((
+            icon,
+            Timer::from_seconds(ZOOM_CURSOR_DURATION, TimerMode::Once),
+        ));
+    }
+    if let Some((_, timer)) = zoom_cursor.0.as_mut() {
+        if timer.tick(time.delta()).finished() {
+            zoom_cursor.0 = None;
+        }
+    }
+
+    let icon = if !held.is_empty() {
+        SystemCursorIcon::Grabbing
+    } else if let Some((icon, _)) = zoom_cursor.0.as_ref() {
+        *icon
+    } else if hovered.0.is_some() {
+        SystemCursorIcon::Grab
+    } else {
+        SystemCursorIcon::Default
+    };
+    let (window, cursor) = *window;
+    if cursor != Some(&CursorIcon::from(icon)) {
+        set_cursor(&mut commands, window, icon);
+    }
+}
+
+fn reset_cursor(mut commands: Commands, window: Single<Entity, With<PrimaryWindow>>) {
+    set_cursor(&mut commands, *window, SystemCursorIcon::Default);
+}
+
+fn spawn_drag_ghost(
+    trigger: Trigger<OnAdd, MoveStart>,
+    mut commands: Commands,
+    children: Query<&Children>,
+    images: Query<(&Mesh2d, &MeshMaterial2d<PieceMaterial>), With<ColorImage>>,
+    mut materials: ResMut<Assets<PieceMaterial>>,
+) {
+    let Ok(children) = children.get(trigger.entity()) else {
+        return;
+    };
+    let Some((mesh, material)) = images.iter_many(children).next() else {
+        return;
+    };
+    let Some(mut material) = materials.get(material).cloned() else {
+        return;
+    };
+    material.set_color(Color::WHITE.with_alpha(GHOST_ALPHA));
+    commands.spawn((
+        mesh.clone(),
+        MeshMaterial2d(materials.add(material)),
+        Transform::from_xyz(0.0, 0.0, GHOST_Z).with_scale(Vec3::splat(GHOST_SCALE)),
+        Visibility::Hidden,
+        PickingBehavior::IGNORE,
+        DragGhost(trigger.entity()),
+        OnPlayScreen,
+    ));
+}
+
+fn despawn_drag_ghost(
+    trigger: Trigger<OnRemove, MoveStart>,
+    mut commands: Commands,
+    ghosts: Query<(Entity, &DragGhost)>,
+) {
+    for (entity, ghost) in ghosts.iter() {
+        if ghost.0 == trigger.entity() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Keeps the drag ghost at the same distance below the pointer at any zoom
+fn follow_pointer(
+    window: Single<&Window, With<PrimaryWindow>>,
+    touches: Res<Touches>,
+    camera: Single<
+        (&Camera, &GlobalTransform, &OrthographicProjection),
+        (With<Camera2d>, With<IsDefaultUiCamera>),
+    >,
+    mut ghosts: Query<(&mut Transform, &mut Visibility), With<DragGhost>>,
+) {
+    if ghosts.is_empty() {
+        return;
+    }
+    let (camera, camera_transform, projection) = *camera;
+    let world = window
+        .cursor_position()
+        .or_else(|| touches.first_pressed_position())
+        .and_then(|pointer| camera.viewport_to_world_2d(camera_transform, pointer).ok());
+    for (mut transform, mut visibility) in ghosts.iter_mut() {
+        let Some(world) = world else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        let position = world + GHOST_OFFSET * projection.scale;
+        transform.translation.x = position.x;
+        transform.translation.y = position.y;
+        *visibility = Visibility::Visible;
+    }
+}
@@ -0,0 +1,242 @@
+//! Shows where the pieces will be cut over the image in the main menu. The cut follows from the
+//! seed, so showing it fixes the [`PuzzleSeed`] and "New cut" rolls another one until the cut is
+//! liked. Only the outlines of the pieces are generated, on a smaller blank image of the same
+//! aspect ratio.
+
+use crate::i18n::{Language, Localized};
+use crate::main_menu::current_grid;
+use crate::piece_shape::PieceShape;
+use crate::theme::ThemeColor;
+use crate::{AppState, CustomGrid, OriginImage, PuzzleSeed, SelectGameMode, SelectPiece};
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task};
+use jigsaw_puzzle_generator::image::{DynamicImage, Rgba, RgbaImage};
+use jigsaw_puzzle_generator::{GameMode, JigsawGenerator};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<CutPreview>()
+        .add_systems(OnExit(AppState::MainMenu), stop_cut_preview)
+        .add_systems(
+            Update,
+            (
+                update_cut_preview_text,
+                start_cut_lines,
+                finish_cut_lines.run_if(resource_exists::<CutLinesTask>),
+            )
+                .chain()
+                .run_if(in_state(AppState::MainMenu)),
+        );
+}
+
+/// The width of the image the cut lines are drawn on
+const PREVIEW_WIDTH: u32 = 960;
+const CUT_LINE_COLOR: Rgba<u8> = Rgba([255, 255, 255, 230]);
+
+/// Whether the cut lines are shown over the image
+#[derive(Resource, Debug, Default)]
+struct CutPreview {
+    shown: bool,
+    /// The seed was picked for the preview rather than by the player, it is dropped again with
+    /// the preview
+    picked_seed: bool,
+}
+
+/// Everything the cut depends on
+#[derive(Debug, Clone, PartialEq)]
+struct CutKey {
+    image: AssetId<Image>,
+    size: UVec2,
+    grid: (usize, usize),
+    seed: usize,
+    game_mode: GameMode,
+    shape: PieceShape,
+}
+
+#[derive(Resource)]
+struct CutLinesTask {
+    task: Task<Option<RgbaImage>>,
+}
+
+#[derive(Component)]
+struct CutLinesOverlay;
+
+#[derive(Component)]
+struct CutPreviewText;
+
+/// Adds the overlay of the cut lines to the image preview
+pub(crate) fn spawn_cut_lines_overlay(p: &mut ChildBuilder) {
+    p.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        ImageNode::default(),
+        Visibility::Hidden,
+        PickingBehavior::IGNORE,
+        CutLinesOverlay,
+    ));
+}
+
+/// Adds the buttons showing the cut and rolling another one
+pub(crate) fn spawn_cut_preview_buttons(p: &mut ChildBuilder, font: TextFont) {
+    p.spawn((
+        Text::default(),
+        font.clone(),
+        TextColor(Color::BLACK),
+        ThemeColor::Text,
+        CutPreviewText,
+    ))
+    .observe(
+        |_trigger: Trigger<Pointer<Click>>,
+         mut preview: ResMut<CutPreview>,
+         mut puzzle_seed: ResMut<PuzzleSeed>| {
+            preview.shown = !preview.shown;
+            if preview.shown && puzzle_seed.is_none() {
+                puzzle_seed.0 = Some(rand::random());
+                preview.picked_seed = true;
+            } else if !preview.shown && preview.picked_seed {
+                puzzle_seed.0 = None;
+                preview.picked_seed = false;
+            }
+        },
+    );
+    p.spawn((
+        Text::new("New cut"),
+        Localized("New cut"),
+        font,
+        TextColor(Color::BLACK),
+        ThemeColor::Text,
+    ))
+    .observe(
+        |_trigger: Trigger<Pointer<Click>>,
+         mut preview: ResMut<CutPreview>,
+         mut puzzle_seed: ResMut<PuzzleSeed>| {
+            puzzle_seed.0 = Some(rand::random());
+            if !preview.shown {
+                preview.shown = true;
+                preview.picked_seed = true;
+            }
+        },
+    );
+}
+
+fn update_cut_preview_text(
+    preview: Res<CutPreview>,
+    language: Res<Language>,
+    mut texts: Query<&mut Text, With<CutPreviewText>>,
+    mut overlays: Query<&mut Visibility, With<CutLinesOverlay>>,
+    added: Query<(), Added<CutPreviewText>>,
+) {
+    if added.is_empty() && !preview.is_changed() && !language.is_changed() {
+        return;
+    }
+    let state = if preview.shown { "On" } else { "Off" };
+    for mut text in texts.iter_mut() {
+        text.0 = format!("{}: {}", language.tr("Cut lines"), language.tr(state));
+    }
+    for mut visibility in overlays.iter_mut() {
+        *visibility = if preview.shown {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// Cuts the outlines again whenever anything the cut depends on changes, once the image is loaded
+#[allow(clippy::too_many_arguments)]
+fn start_cut_lines(
+    mut commands: Commands,
+    preview: Res<CutPreview>,
+    origin_image: Option<Res<OriginImage>>,
+    images: Res<Assets<Image>>,
+    select_piece: Res<SelectPiece>,
+    custom_grid: Res<CustomGrid>,
+    puzzle_seed: Res<PuzzleSeed>,
+    select_game_mode: Res<SelectGameMode>,
+    piece_shape: Res<PieceShape>,
+    added: Query<(), Added<CutLinesOverlay>>,
+    mut last: Local<Option<CutKey>>,
+) {
+    // the overlay of a new visit of the menu is empty
+    if !preview.shown || !added.is_empty() {
+        *last = None;
+        return;
+    }
+    let (Some(origin_image), Some(seed)) = (origin_image, puzzle_seed.0) else {
+        return;
+    };
+    let image = images.get(&origin_image.0);
+    let (Some(size), Some(grid)) = (
+        image.map(Image::size),
+        current_grid(&custom_grid, &select_piece, image),
+    ) else {
+        return;
+    };
+    let key = CutKey {
+        image: origin_image.0.id(),
+        size,
+        grid,
+        seed,
+        game_mode: **select_game_mode,
+        shape: *piece_shape,
+    };
+    if last.as_ref() == Some(&key) {
+        return;
+    }
+    *last = Some(key.clone());
+
+    // replacing a running task drops it
+    let task = AsyncComputeTaskPool::get().spawn(async move { cut_lines(&key) });
+    commands.insert_resource(CutLinesTask { task });
+}
+
+/// The outlines of all pieces on a transparent image of [`PREVIEW_WIDTH`]
+fn cut_lines(key: &CutKey) -> Option<RgbaImage> {
+    let width = PREVIEW_WIDTH;
+    let height = ((width as f32 * key.size.y as f32 / key.size.x.max(1) as f32) as u32).max(1);
+    let blank = RgbaImage::new(width, height);
+    let generator = key
+        .shape
+        .apply(JigsawGenerator::new(blank.into(), key.grid.0, key.grid.1).seed(key.seed));
+    let template = generator
+        .generate(key.game_mode, false)
+        .inspect_err(|err| warn!("Could not cut the preview: {}", err))
+        .ok()?;
+    let mut lines = RgbaImage::new(width, height);
+    for piece in &template.pieces {
+        piece.draw_cut_lines(&mut lines, CUT_LINE_COLOR);
+    }
+    Some(lines)
+}
+
+fn finish_cut_lines(
+    mut commands: Commands,
+    mut task: ResMut<CutLinesTask>,
+    mut images: ResMut<Assets<Image>>,
+    mut overlays: Query<&mut ImageNode, With<CutLinesOverlay>>,
+) {
+    let Some(lines) = block_on(future::poll_once(&mut task.task)) else {
+        return;
+    };
+    commands.remove_resource::<CutLinesTask>();
+    let Some(lines) = lines else {
+        return;
+    };
+    let handle = images.add(Image::from_dynamic(
+        DynamicImage::ImageRgba8(lines),
+        true,
+        RenderAssetUsages::RENDER_WORLD,
+    ));
+    for mut image_node in overlays.iter_mut() {
+        image_node.image = handle.clone();
+    }
+}
+
+/// A cut still running is dropped with the menu, the seed stays for the game
+fn stop_cut_preview(mut commands: Commands) {
+    commands.remove_resource::<CutLinesTask>();
+}
@@ -0,0 +1,197 @@
+//! <kbd>F3</kbd> shows how the pieces relate for tuning the snapping: every piece is labeled with
+//! its index, its grid cell and the size of its group, lines join the pieces which are
+//! neighbors in the solved puzzle, and circles around the held piece and its snap targets show
+//! the snap radius.
+
+use crate::gameplay::{init_position, JigsawPuzzleGenerator, MoveStart, MoveTogether};
+use crate::tray::InTray;
+use crate::{GameState, Piece, SnapTolerance};
+use bevy::prelude::*;
+use jigsaw_puzzle_generator::image::GenericImageView;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<DebugOverlay>()
+        .add_systems(OnExit(GameState::Play), hide_overlay)
+        .add_systems(
+            Update,
+            (
+                toggle_overlay,
+                label_pieces,
+                draw_neighbor_lines,
+                draw_snap_radius,
+            )
+                .chain()
+                .run_if(in_state(GameState::Play)),
+        );
+}
+
+const LINKED_COLOR: Color = Color::srgb(0.2, 0.8, 0.2);
+const APART_COLOR: Color = Color::srgba(1.0, 0.3, 0.3, 0.6);
+const SNAP_COLOR: Color = Color::srgb(1.0, 0.8, 0.0);
+
+/// Whether the overlay is shown
+#[derive(Resource, Debug, Default, Deref, DerefMut)]
+pub struct DebugOverlay(pub bool);
+
+/// The debug text on a piece
+#[derive(Component)]
+struct DebugLabel;
+
+fn toggle_overlay(keyboard_input: Res<ButtonInput<KeyCode>>, mut overlay: ResMut<DebugOverlay>) {
+    if keyboard_input.just_pressed(KeyCode::F3) {
+        overlay.0 = !overlay.0;
+    }
+}
+
+fn hide_overlay(mut overlay: ResMut<DebugOverlay>) {
+    overlay.0 = false;
+}
+
+/// The pieces of the solved puzzle which share an edge, each pair once
+fn neighbors<'a>(pieces: &[&'a Piece]) -> Vec<(&'a Piece, &'a Piece)> {
+    let mut pairs = vec![];
+    for (i, a) in pieces.iter().enumerate() {
+        for b in pieces[i + 1..].iter() {
+            if a.on_the_left_side(b)
+                || b.on_the_left_side(a)
+                || a.on_the_top_side(b)
+                || b.on_the_top_side(a)
+            {
+                pairs.push((*a, *b));
+            }
+        }
+    }
+    pairs
+}
+
+/// Labels the pieces while the overlay is shown, again whenever groups change
+fn label_pieces(
+    mut commands: Commands,
+    overlay: Res<DebugOverlay>,
+    generator: Res<JigsawPuzzleGenerator>,
+    pieces: Query<(Entity, &Piece, &MoveTogether)>,
+    changed: Query<(), Changed<MoveTogether>>,
+    labels: Query<Entity, With<DebugLabel>>,
+) {
+    if !overlay.is_changed() && (changed.is_empty() || !overlay.0) {
+        return;
+    }
+    for entity in labels.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !overlay.0 {
+        return;
+    }
+
+    let columns = generator.pieces_in_column().max(1);
+    for (entity, piece, together) in pieces.iter() {
+        let (column, row) = (piece.index % columns, piece.index / columns);
+        let size = together.len().max(1);
+        let label = commands
+            .spawn((
+                Text2d::new(format!("#{} ({column}, {row})\ngroup {size}", piece.index)),
+                TextFont {
+                    font_size: (piece.width.min(piece.height) / 5.0).max(8.0),
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.2, 0.8)),
+                Transform::from_xyz(piece.width / 2.0, -piece.height / 2.0, 0.9),
+                DebugLabel,
+            ))
+            .id();
+        commands.entity(entity).add_child(label);
+    }
+}
+
+/// The center of a piece without its tabs
+fn piece_center(piece: &Piece, transform: &Transform) -> Vec2 {
+    transform.translation.xy() + Vec2::new(piece.width, -piece.height) / 2.0
+}
+
+/// Joins the neighbors, green when they are merged into one group
+fn draw_neighbor_lines(
+    mut gizmos: Gizmos,
+    overlay: Res<DebugOverlay>,
+    pieces: Query<(Entity, &Piece, &Transform, &MoveTogether), Without<InTray>>,
+    all_pieces: Query<(Entity, &Piece)>,
+    mut pairs: Local<Vec<(Entity, Entity)>>,
+) {
+    if !overlay.0 {
+        pairs.clear();
+        return;
+    }
+    // the neighbors never change during a puzzle, they are only looked up again for a new one
+    if pairs.is_empty() || !pairs.iter().all(|(a, _)| all_pieces.contains(*a)) {
+        let list: Vec<(Entity, &Piece)> = all_pieces.iter().collect();
+        let by_piece: Vec<&Piece> = list.iter().map(|(_, piece)| *piece).collect();
+        let entity_of = |piece: &Piece| {
+            list.iter()
+                .find(|(_, other)| other.index == piece.index)
+                .map(|(entity, _)| *entity)
+        };
+        *pairs = neighbors(&by_piece)
+            .into_iter()
+            .filter_map(|(a, b)| Some((entity_of(a)?, entity_of(b)?)))
+            .collect();
+    }
+
+    for (a, b) in pairs.iter() {
+        let (Ok((_, piece_a, transform_a, together)), Ok((_, piece_b, transform_b, _))) =
+            (pieces.get(*a), pieces.get(*b))
+        else {
+            continue;
+        };
+        let color = if together.contains(b) {
+            LINKED_COLOR
+        } else {
+            APART_COLOR
+        };
+        gizmos.line_2d(
+            piece_center(piece_a, transform_a),
+            piece_center(piece_b, transform_b),
+            color,
+        );
+    }
+}
+
+/// Circles the snapping point of the held piece and the points it snaps to its neighbors at
+fn draw_snap_radius(
+    mut gizmos: Gizmos,
+    overlay: Res<DebugOverlay>,
+    generator: Res<JigsawPuzzleGenerator>,
+    snap_tolerance: Res<SnapTolerance>,
+    held: Query<(&Piece, &Transform), With<MoveStart>>,
+    others: Query<(&Piece, &Transform), (Without<MoveStart>, Without<InTray>)>,
+) {
+    if !overlay.0 {
+        return;
+    }
+    let Ok((piece, transform)) = held.get_single() else {
+        return;
+    };
+    let radius = snap_tolerance.distance();
+    let image_size = generator.origin_image().dimensions();
+    gizmos.circle_2d(
+        Isometry2d::from_translation(transform.translation.xy()),
+        radius,
+        SNAP_COLOR,
+    );
+    for (other, other_transform) in others.iter() {
+        let neighbor = piece.on_the_left_side(other)
+            || other.on_the_left_side(piece)
+            || piece.on_the_top_side(other)
+            || other.on_the_top_side(piece);
+        if !neighbor {
+            continue;
+        }
+        // where the held piece lies next to this neighbor as in the solved puzzle
+        let target = other_transform.translation.xy() + init_position(piece, image_size)
+            - init_position(other, image_size);
+        gizmos.circle_2d(Isometry2d::from_translation(target), radius, SNAP_COLOR);
+        gizmos.line_2d(
+            transform.translation.xy(),
+            target,
+            SNAP_COLOR.with_alpha(0.4),
+        );
+    }
+}
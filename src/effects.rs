@@ -0,0 +1,160 @@
+use crate::gameplay::CombineTogether;
+use crate::{GameState, Piece, ANIMATION_LAYERS};
+use bevy::prelude::*;
+use bevy::render::view::RenderLayers;
+use rand::Rng;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Finish), start_fireworks)
+        .add_systems(OnExit(GameState::Finish), stop_fireworks)
+        .add_systems(
+            Update,
+            (
+                launch_fireworks.run_if(resource_exists::<Fireworks>),
+                update_particles,
+            ),
+        )
+        .add_observer(sparkle_on_merge);
+}
+
+const SPARKLE_COLORS: [Color; 3] = [
+    Color::srgb(1.0, 1.0, 0.6),
+    Color::srgb(1.0, 0.9, 0.3),
+    Color::WHITE,
+];
+
+const FIREWORK_COLORS: [Color; 5] = [
+    Color::srgb(1.0, 0.3, 0.3),
+    Color::srgb(0.3, 0.8, 1.0),
+    Color::srgb(1.0, 0.85, 0.2),
+    Color::srgb(0.5, 1.0, 0.4),
+    Color::srgb(0.9, 0.4, 1.0),
+];
+
+/// A short living sprite flying away from where it was emitted
+#[derive(Component, Debug)]
+struct Particle {
+    velocity: Vec2,
+    /// Downwards acceleration in pixels per second squared
+    gravity: f32,
+    lifetime: Timer,
+}
+
+/// The remaining fireworks bursts of the finish screen
+#[derive(Resource, Debug)]
+struct Fireworks {
+    timer: Timer,
+    remaining: u32,
+}
+
+/// Emits `count` particles in all directions from `position`
+fn spawn_burst(
+    commands: &mut Commands,
+    position: Vec3,
+    count: usize,
+    speed: f32,
+    colors: &[Color],
+    layers: RenderLayers,
+) {
+    let mut rng = rand::thread_rng();
+    for _ in 0..count {
+        let angle = rng.gen_range(0.0..core::f32::consts::TAU);
+        let velocity = Vec2::from_angle(angle) * speed * rng.gen_range(0.4..1.0);
+        let color = colors[rng.gen_range(0..colors.len())];
+        commands.spawn((
+            Sprite::from_color(color, Vec2::splat(rng.gen_range(3.0..6.0))),
+            Transform::from_translation(position),
+            Particle {
+                velocity,
+                gravity: speed,
+                lifetime: Timer::from_seconds(rng.gen_range(0.5..1.2), TimerMode::Once),
+            },
+            layers.clone(),
+        ));
+    }
+}
+
+/// A small sparkle where the dropped piece joined the other pieces
+fn sparkle_on_merge(
+    trigger: Trigger<CombineTogether>,
+    mut commands: Commands,
+    pieces: Query<(&Piece, &Transform)>,
+) {
+    // the dropped piece is put on top of the others
+    let Some((piece, transform)) = pieces
+        .iter_many(trigger.event().0.iter())
+        .max_by(|(_, a), (_, b)| a.translation.z.total_cmp(&b.translation.z))
+    else {
+        return;
+    };
+    let center = transform.translation.xy() + Vec2::new(piece.width, -piece.height) / 2.0;
+    spawn_burst(
+        &mut commands,
+        center.extend(900.0),
+        16,
+        150.0,
+        &SPARKLE_COLORS,
+        RenderLayers::default(),
+    );
+}
+
+fn start_fireworks(mut commands: Commands) {
+    commands.insert_resource(Fireworks {
+        timer: Timer::from_seconds(0.35, TimerMode::Repeating),
+        remaining: 10,
+    });
+}
+
+fn stop_fireworks(mut commands: Commands) {
+    commands.remove_resource::<Fireworks>();
+}
+
+/// Fireworks are drawn by the animation camera on top of the finish screen
+fn launch_fireworks(
+    mut commands: Commands,
+    time: Res<Time>,
+    window: Single<&Window>,
+    mut fireworks: ResMut<Fireworks>,
+) {
+    if !fireworks.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let mut rng = rand::thread_rng();
+    let half_size = window.size() / 2.0;
+    let position = Vec2::new(
+        rng.gen_range(-half_size.x * 0.8..half_size.x * 0.8),
+        rng.gen_range(0.0..half_size.y * 0.8),
+    );
+    spawn_burst(
+        &mut commands,
+        position.extend(10.0),
+        60,
+        300.0,
+        &FIREWORK_COLORS,
+        ANIMATION_LAYERS,
+    );
+
+    fireworks.remaining -= 1;
+    if fireworks.remaining == 0 {
+        commands.remove_resource::<Fireworks>();
+    }
+}
+
+fn update_particles(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut particles: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+) {
+    let delta = time.delta_secs();
+    for (entity, mut particle, mut transform, mut sprite) in particles.iter_mut() {
+        if particle.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        particle.velocity.y -= particle.gravity * delta;
+        transform.translation += (particle.velocity * delta).extend(0.0);
+        sprite
+            .color
+            .set_alpha(particle.lifetime.fraction_remaining());
+    }
+}
@@ -0,0 +1,56 @@
+//! Events sent by the gameplay for the app the game is embedded in, read them with an
+//! `EventReader` to react to the progress of the player without looking at the pieces.
+
+use crate::gameplay::GameTimer;
+use crate::GameState;
+use bevy::prelude::*;
+use core::time::Duration;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<PuzzleStarted>()
+        .add_event::<PieceSnapped>()
+        .add_event::<GroupMerged>()
+        .add_event::<PuzzleCompleted>()
+        .add_systems(
+            OnTransition {
+                exited: GameState::Generating,
+                entered: GameState::Play,
+            },
+            send_puzzle_started,
+        );
+}
+
+/// All pieces of a new puzzle are spawned and can be moved
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PuzzleStarted;
+
+/// The dropped piece `a` snapped to the matching side of piece `b`
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PieceSnapped {
+    pub a: Entity,
+    pub b: Entity,
+}
+
+/// Pieces snapped together into a group of `size` pieces
+#[derive(Event, Debug, Clone, Copy)]
+pub struct GroupMerged {
+    pub size: usize,
+}
+
+/// The puzzle is solved after `duration` of play, leaving it early doesn't send this
+#[derive(Event, Debug, Clone, Copy)]
+pub struct PuzzleCompleted {
+    pub duration: Duration,
+}
+
+impl PuzzleCompleted {
+    pub(crate) fn new(timer: Option<&GameTimer>) -> Self {
+        PuzzleCompleted {
+            duration: timer.map(|timer| timer.elapsed()).unwrap_or_default(),
+        }
+    }
+}
+
+fn send_puzzle_started(mut started: EventWriter<PuzzleStarted>) {
+    started.send(PuzzleStarted);
+}
@@ -0,0 +1,180 @@
+//! Photo filters as difficulty modifiers: grayscale, sepia and posterize change the colors of the
+//! pieces, a blurry preview makes the image hints tell less. They are picked in the main menu and
+//! raise the difficulty score shown there and on the finish screen.
+
+use crate::i18n::{Language, Localized};
+use crate::main_menu::current_grid;
+use crate::theme::ThemeColor;
+use crate::{AppState, CustomGrid, OriginImage, SelectPiece};
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use jigsaw_puzzle_generator::filter::{apply_filters, difficulty_score, ImageFilter};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<ImageFilters>().add_systems(
+        Update,
+        (update_filter_toggles, update_score_text).run_if(in_state(AppState::MainMenu)),
+    );
+}
+
+const FILTER_ON: Color = Color::srgb(0.2, 0.6, 0.25);
+const FILTER_OFF: Color = Color::srgb(0.5, 0.5, 0.5);
+
+/// The filters applied to the image of the next puzzle, in the order of [`ImageFilter::ALL`]
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq, Deref)]
+pub struct ImageFilters(pub Vec<ImageFilter>);
+
+impl ImageFilters {
+    pub fn toggle(&mut self, filter: ImageFilter) {
+        if let Some(index) = self.0.iter().position(|other| *other == filter) {
+            self.0.remove(index);
+        } else {
+            self.0.push(filter);
+            self.0.sort();
+        }
+    }
+
+    /// How hard a puzzle of `pieces` pieces is with the filters
+    pub fn score(&self, pieces: usize) -> u32 {
+        difficulty_score(pieces, &self.0)
+    }
+}
+
+fn label(filter: ImageFilter) -> &'static str {
+    match filter {
+        ImageFilter::Grayscale => "Grayscale",
+        ImageFilter::Sepia => "Sepia",
+        ImageFilter::Posterize => "Posterize",
+        ImageFilter::Blur => "Blurry preview",
+    }
+}
+
+/// The image the hints show of the solved puzzle, filtered like the pieces and blurred with
+/// [`ImageFilter::Blur`]
+#[derive(Resource, Debug, Clone)]
+pub struct PreviewImage(pub Handle<Image>);
+
+impl PreviewImage {
+    pub fn new(
+        origin_image: &Handle<Image>,
+        filters: &ImageFilters,
+        images: &mut Assets<Image>,
+    ) -> Self {
+        let filtered = images
+            .get(origin_image)
+            .filter(|_| !filters.is_empty())
+            .and_then(|image| image.clone().try_into_dynamic().ok())
+            .map(|image| apply_filters(&image, filters, true));
+        match filtered {
+            Some(filtered) => PreviewImage(images.add(Image::from_dynamic(
+                filtered,
+                true,
+                RenderAssetUsages::RENDER_WORLD,
+            ))),
+            None => PreviewImage(origin_image.clone()),
+        }
+    }
+}
+
+#[derive(Component)]
+struct FilterToggle(ImageFilter);
+
+#[derive(Component)]
+struct DifficultyScoreText;
+
+/// Adds the toggles of the filters and the difficulty score below them
+pub(crate) fn spawn_filter_buttons(p: &mut ChildBuilder, font: Handle<Font>) {
+    let toggle_font = TextFont {
+        font: font.clone(),
+        font_size: 20.0,
+        ..default()
+    };
+    p.spawn(Node {
+        margin: UiRect::new(Val::Px(20.0), Val::Px(0.0), Val::Px(5.0), Val::Px(0.0)),
+        column_gap: Val::Px(10.0),
+        align_items: AlignItems::Baseline,
+        flex_wrap: FlexWrap::Wrap,
+        ..default()
+    })
+    .with_children(|p| {
+        p.spawn((
+            Text::new("Filters"),
+            Localized("Filters"),
+            TextFont {
+                font,
+                font_size: 24.0,
+                ..default()
+            },
+            TextColor(Color::BLACK),
+            ThemeColor::Text,
+        ));
+        for filter in ImageFilter::ALL {
+            p.spawn((
+                Text::new(label(filter)),
+                Localized(label(filter)),
+                toggle_font.clone(),
+                TextColor(FILTER_OFF),
+                FilterToggle(filter),
+            ))
+            .observe(
+                move |_trigger: Trigger<Pointer<Click>>, mut filters: ResMut<ImageFilters>| {
+                    filters.toggle(filter);
+                },
+            );
+        }
+    });
+    p.spawn((
+        Text::default(),
+        toggle_font,
+        TextColor(Color::BLACK),
+        ThemeColor::Text,
+        Node {
+            margin: UiRect::left(Val::Px(20.0)),
+            ..default()
+        },
+        DifficultyScoreText,
+    ));
+}
+
+fn update_filter_toggles(
+    filters: Res<ImageFilters>,
+    mut toggles: Query<(&mut TextColor, &FilterToggle)>,
+    added: Query<(), Added<FilterToggle>>,
+) {
+    if added.is_empty() && !filters.is_changed() {
+        return;
+    }
+    for (mut color, FilterToggle(filter)) in toggles.iter_mut() {
+        color.0 = if filters.contains(filter) {
+            FILTER_ON
+        } else {
+            FILTER_OFF
+        };
+    }
+}
+
+/// The difficulty score of the grid picked for the image
+fn update_score_text(
+    filters: Res<ImageFilters>,
+    language: Res<Language>,
+    origin_image: Option<Res<OriginImage>>,
+    images: Res<Assets<Image>>,
+    select_piece: Res<SelectPiece>,
+    custom_grid: Res<CustomGrid>,
+    mut texts: Query<&mut Text, With<DifficultyScoreText>>,
+) {
+    let image = origin_image.and_then(|origin_image| images.get(&origin_image.0));
+    let Some((columns, rows)) = current_grid(&custom_grid, &select_piece, image) else {
+        return;
+    };
+    let score = format!(
+        "{}: {}",
+        language.tr("Difficulty score"),
+        filters.score(columns * rows)
+    );
+    for mut text in texts.iter_mut() {
+        if text.0 != score {
+            text.0.clone_from(&score);
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! Adds the images of a local folder to the image strip of the main menu. The folder is
+//! `~/Pictures/jigsaw` unless another one is given with `--images <dir>`. While the menu is open
+//! the folder is watched, so added and removed images show up without a restart.
+
+use crate::main_menu::LoadedImages;
+use crate::picture::pictures_dir;
+use crate::AppState;
+use bevy::asset::io::AssetSourceBuilder;
+use bevy::asset::{AssetPath, LoadState};
+use bevy::prelude::*;
+use bevy::tasks::{block_on, futures_lite::future, IoTaskPool, Task};
+use bevy::utils::HashMap;
+use notify::event::ModifyKind;
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Mutex;
+
+/// Name of the asset source reading from the images folder
+const GALLERY_SOURCE: &str = "gallery";
+/// Images which are loaded at the same time, the others wait for their turn
+const MAX_LOADING: usize = 2;
+const EXTENSIONS: [&str; 4] = ["png", "jpg", "jpeg", "gif"];
+
+/// Registers the images folder as an asset source, has to run before the `AssetPlugin` is added
+pub fn register_source(app: &mut App) {
+    let Some(dir) = images_dir() else {
+        return;
+    };
+    app.register_asset_source(
+        GALLERY_SOURCE,
+        AssetSourceBuilder::platform_default(&dir.to_string_lossy(), None),
+    )
+    .insert_resource(GalleryDir(dir));
+}
+
+pub(super) fn plugin(app: &mut App) {
+    if !app.world().contains_resource::<GalleryDir>() {
+        return;
+    }
+    app.init_resource::<GalleryImages>()
+        .add_systems(OnEnter(AppState::MainMenu), (scan_gallery, watch_gallery))
+        .add_systems(
+            Update,
+            (
+                handle_gallery_changes.run_if(resource_exists::<GalleryWatcher>),
+                finish_scan.run_if(resource_exists::<ScanTask>),
+                load_next_images,
+            )
+                .chain()
+                .run_if(in_state(AppState::MainMenu)),
+        )
+        .add_systems(OnExit(AppState::MainMenu), stop_loading);
+}
+
+/// The folder the user images are read from
+#[derive(Resource, Debug, Clone)]
+struct GalleryDir(PathBuf);
+
+/// `--images <dir>` if it is given, otherwise the jigsaw folder in the Pictures directory
+fn images_dir() -> Option<PathBuf> {
+    let args: Vec<String> = std::env::args().collect();
+    args.iter()
+        .position(|arg| arg == "--images")
+        .and_then(|i| args.get(i + 1))
+        .map(PathBuf::from)
+        .or_else(|| pictures_dir().map(|dir| dir.join("jigsaw")))
+}
+
+fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+/// The image files in the folder, sorted by name
+fn list_images(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && is_image(path))
+        .filter_map(|path| path.strip_prefix(dir).ok().map(Path::to_path_buf))
+        .collect();
+    files.sort();
+    files
+}
+
+/// The user images by their path in the folder
+#[derive(Resource, Debug, Default)]
+struct GalleryImages {
+    loaded: HashMap<PathBuf, Handle<Image>>,
+    /// Images waiting to be loaded
+    queue: Vec<PathBuf>,
+    /// Images being loaded right now
+    loading: Vec<(PathBuf, Handle<Image>)>,
+}
+
+#[derive(Resource)]
+struct ScanTask(Task<Vec<PathBuf>>);
+
+/// Reports the changes in the images folder
+#[derive(Resource)]
+struct GalleryWatcher {
+    _watcher: RecommendedWatcher,
+    events: Mutex<Receiver<notify::Result<notify::Event>>>,
+}
+
+fn scan_gallery(mut commands: Commands, dir: Res<GalleryDir>) {
+    start_scan(&mut commands, &dir.0);
+}
+
+/// Lists the folder in the background, a running scan is replaced
+fn start_scan(commands: &mut Commands, dir: &Path) {
+    let dir = dir.to_path_buf();
+    let task = IoTaskPool::get().spawn(async move { list_images(&dir) });
+    commands.insert_resource(ScanTask(task));
+}
+
+fn watch_gallery(mut commands: Commands, dir: Res<GalleryDir>) {
+    let (sender, receiver) = channel();
+    let watcher = notify::recommended_watcher(sender).and_then(|mut watcher| {
+        watcher.watch(&dir.0, RecursiveMode::NonRecursive)?;
+        Ok(watcher)
+    });
+    match watcher {
+        Ok(watcher) => commands.insert_resource(GalleryWatcher {
+            _watcher: watcher,
+            events: Mutex::new(receiver),
+        }),
+        // the folder may not exist, the bundled images are still there
+        Err(err) => info!("Not watching {}: {}", dir.0.display(), err),
+    }
+}
+
+/// Scans the folder again when images are added, removed or renamed and reloads the images which
+/// were overwritten
+fn handle_gallery_changes(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    dir: Res<GalleryDir>,
+    watcher: Res<GalleryWatcher>,
+    gallery: Res<GalleryImages>,
+) {
+    let Ok(events) = watcher.events.lock() else {
+        return;
+    };
+    let mut rescan = false;
+    for event in events.try_iter().filter_map(Result::ok) {
+        for path in event.paths.iter().filter(|path| is_image(path)) {
+            // the folder is not watched recursively
+            let Some(path) = path.file_name().map(PathBuf::from) else {
+                continue;
+            };
+            if let EventKind::Modify(ModifyKind::Data(_)) = event.kind {
+                if gallery.loaded.contains_key(&path) {
+                    asset_server.reload(AssetPath::from(path).with_source(GALLERY_SOURCE));
+                    continue;
+                }
+            }
+            // images which are still being written fail to load and are picked up again here
+            rescan |= !matches!(event.kind, EventKind::Access(_));
+        }
+    }
+
+    if rescan {
+        start_scan(&mut commands, &dir.0);
+    }
+}
+
+fn finish_scan(
+    mut commands: Commands,
+    mut task: ResMut<ScanTask>,
+    mut gallery: ResMut<GalleryImages>,
+    mut loaded_images: ResMut<LoadedImages>,
+) {
+    let Some(files) = block_on(future::poll_once(&mut task.0)) else {
+        return;
+    };
+    commands.remove_resource::<ScanTask>();
+
+    // forget the images which are gone
+    let gallery = &mut *gallery;
+    gallery.loaded.retain(|path, handle| {
+        let exists = files.contains(path);
+        if !exists {
+            loaded_images.retain(|image| image != handle);
+        }
+        exists
+    });
+    gallery.queue.retain(|path| files.contains(path));
+    gallery.loading.retain(|(path, _)| files.contains(path));
+
+    for path in files {
+        match gallery.loaded.get(&path) {
+            // loaded on an earlier visit of the menu
+            Some(handle) => {
+                if !loaded_images.contains(handle) {
+                    loaded_images.push(handle.clone());
+                }
+            }
+            None => {
+                let waiting = gallery.queue.contains(&path)
+                    || gallery.loading.iter().any(|(loading, _)| *loading == path);
+                if !waiting {
+                    gallery.queue.push(path);
+                }
+            }
+        }
+    }
+}
+
+/// Loads the queued images a few at a time and adds them to the strip once they are ready
+fn load_next_images(
+    asset_server: Res<AssetServer>,
+    mut gallery: ResMut<GalleryImages>,
+    mut loaded_images: ResMut<LoadedImages>,
+) {
+    let gallery = &mut *gallery;
+    let mut index = 0;
+    while index < gallery.loading.len() {
+        let (path, handle) = &gallery.loading[index];
+        if asset_server.is_loaded_with_dependencies(handle) {
+            loaded_images.push(handle.clone());
+            gallery.loaded.insert(path.clone(), handle.clone());
+        } else if matches!(asset_server.load_state(handle), LoadState::Failed(_)) {
+            warn!("Could not load {}", path.display());
+        } else {
+            index += 1;
+            continue;
+        }
+        gallery.loading.swap_remove(index);
+    }
+
+    while gallery.loading.len() < MAX_LOADING && !gallery.queue.is_empty() {
+        let path = gallery.queue.remove(0);
+        let handle = asset_server.load(AssetPath::from(path.clone()).with_source(GALLERY_SOURCE));
+        gallery.loading.push((path, handle));
+    }
+}
+
+fn stop_loading(mut commands: Commands, mut gallery: ResMut<GalleryImages>) {
+    commands.remove_resource::<ScanTask>();
+    commands.remove_resource::<GalleryWatcher>();
+    gallery.queue.clear();
+    gallery.loading.clear();
+}
@@ -1,20 +1,56 @@
-use crate::NORMAL_BUTTON;
+use crate::atlas::{PieceAtlas, PieceImage, PieceMaterial, IMAGE_LEVELS};
+use crate::board::Locked;
+use crate::camera::ZoomToFit;
+use crate::clock::spawn_clock_button;
+use crate::color_sort::ShowColorGroups;
+use crate::contrast::{HighContrast, OutlineImage, PieceNumber, OUTLINE_COLOR, OUTLINE_RADIUS};
+use crate::culling::OffScreen;
+use crate::events::{GroupMerged, PieceSnapped, PuzzleCompleted};
+use crate::filters::{ImageFilters, PreviewImage};
+use crate::guided::GuidedAssembly;
+use crate::hints::{HintBudget, HintKind};
+use crate::i18n::{Language, Localized};
+use crate::layering::{HoveredPiece, Restack};
+use crate::loading::{spawn_fact_text, spawn_tip_texts, GeneratingProgress};
+use crate::locate::ShowLocation;
+use crate::mystery::{spawn_mystery_badge, Mystery};
+use crate::pen::{PenInput, PEN_DRAG_THRESHOLD};
+use crate::piece_shape::PieceShape;
+use crate::pinning::Pinned;
+use crate::relaxed::Relaxed;
+use crate::replay::StartReplay;
+use crate::rotation::{is_upright, PieceRotation};
+use crate::selection::MultiSelection;
+use crate::staging::{spawn_staging_button, staging_cell, StagingGrid, STAGING_GAP};
+use crate::stroke::spawn_stroke_button;
+use crate::table::Table;
+use crate::theme::ThemeColor;
+use crate::tray::{CollectEdgePieces, HoveredTray, InTray, PutInTray};
+use crate::tutorial::{TutorialStep, TUTORIAL_GRID};
+use crate::versus::Versus;
+use crate::window_state::FullscreenMonitor;
 use crate::{despawn_screen, GameState};
-use crate::{AppState, OriginImage, Piece, SelectGameMode, SelectPiece};
+use crate::{
+    AppState, CustomGrid, Difficulty, GeneratingStep, OriginImage, Piece, PuzzleSeed,
+    SelectGameMode, SelectPiece, SelectPlayMode, SnapTolerance,
+};
+use crate::{NORMAL_BUTTON, PRESSED_BUTTON};
+use alloc::sync::Arc;
 use bevy::asset::RenderAssetUsages;
-use bevy::color::palettes::basic::{GREEN, YELLOW};
+use bevy::color::palettes::basic::GREEN;
 use bevy::ecs::world::CommandQueue;
 use bevy::input::mouse::MouseWheel;
 use bevy::prelude::*;
-use bevy::sprite::Anchor;
 use bevy::tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task};
 use bevy::time::Stopwatch;
-use bevy::utils::HashSet;
-use bevy::window::WindowMode;
-use core::ops::DerefMut;
-use jigsaw_puzzle_generator::image::GenericImageView;
-use jigsaw_puzzle_generator::{JigsawGenerator, JigsawPiece, JigsawTemplate};
+use bevy::utils::{HashMap, HashSet};
+use bevy::window::{Monitor, WindowMode};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use jigsaw_puzzle_generator::filter::ImageFilter;
+use jigsaw_puzzle_generator::image::{DynamicImage, GenericImageView};
+use jigsaw_puzzle_generator::{GameMode, JigsawGenerator, JigsawPiece, JigsawTemplate};
 use log::debug;
+use rand::seq::SliceRandom;
 use rand::Rng;
 
 pub(super) fn plugin(app: &mut App) {
@@ -32,16 +68,26 @@ pub(super) fn plugin(app: &mut App) {
     // generation piece
     app.add_systems(
         OnEnter(GameState::Generating),
-        (setup_generator, setup_generating_ui, spawn_piece).chain(),
+        (setup_generator, setup_generating_ui, generate_template).chain(),
     )
     .add_systems(
         OnExit(GameState::Generating),
         despawn_screen::<OnGeneratingScreen>,
     )
-    .add_systems(Update, (adjust_camera_on_added_sprite,))
+    .add_systems(
+        Update,
+        (
+            spawn_piece.run_if(in_state(GeneratingStep::Template)),
+            update_generating_progress.run_if(in_state(GameState::Generating)),
+        ),
+    )
     .add_systems(
         PostUpdate,
-        (handle_tasks, count_spawned_piece).run_if(in_state(GameState::Generating)),
+        (
+            // pieces of guided waves are still cropped during play
+            handle_tasks.run_if(in_state(GameState::Generating).or(in_state(GameState::Play))),
+            count_spawned_piece.run_if(in_state(GeneratingStep::Pieces)),
+        ),
     );
 
     // pause logic
@@ -55,7 +101,6 @@ pub(super) fn plugin(app: &mut App) {
         .add_event::<AdjustScale>()
         .add_event::<ToggleBackgroundHint>()
         .add_event::<TogglePuzzleHint>()
-        .add_event::<ToggleEdgeHint>()
         .add_systems(
             Update,
             (
@@ -63,13 +108,15 @@ pub(super) fn plugin(app: &mut App) {
                 move_piece,
                 cancel_all_move,
                 shuffle_pieces,
-                adjust_camera_scale,
                 handle_keyboard_input,
                 handle_mouse_wheel_input,
                 handle_toggle_background_hint,
                 handle_toggle_puzzle_hint,
                 exit_fullscreen_on_esc,
-                handle_puzzle_hint,
+                preview_snap,
+                animate_snap,
+                animate_scatter,
+                outline_pieces,
             )
                 .run_if(in_state(GameState::Play)),
         )
@@ -84,14 +131,21 @@ pub(super) fn plugin(app: &mut App) {
 }
 
 #[derive(Component)]
-struct OnFinishScreen;
+pub struct OnFinishScreen;
 
+#[allow(clippy::too_many_arguments)]
 fn setup_finish_ui(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     game_timer: Res<GameTimer>,
     select_game_mode: Res<SelectGameMode>,
-    select_piece: Res<SelectPiece>,
+    generator: Res<JigsawPuzzleGenerator>,
+    versus: Option<Res<Versus>>,
+    language: Res<Language>,
+    relaxed: Res<Relaxed>,
+    filters: Res<ImageFilters>,
+    mystery: Res<Mystery>,
+    origin_image: Res<OriginImage>,
 ) {
     commands
         .spawn((
@@ -106,6 +160,7 @@ fn setup_finish_ui(
                 ..default()
             },
             BackgroundColor(Color::srgb_u8(149, 165, 166)),
+            ThemeColor::Panel,
             OnFinishScreen,
         ))
         .with_children(|p| {
@@ -116,23 +171,68 @@ fn setup_finish_ui(
                 ..default()
             };
 
-            p.spawn((Text::new("Finish"), TextColor(Color::BLACK), text_font));
             p.spawn((
-                Text::new(format!("{} pieces {}", *select_piece, *select_game_mode)),
+                Text::new("Finish"),
+                Localized("Finish"),
+                TextColor(Color::BLACK),
+                ThemeColor::Text,
+                text_font,
+            ));
+            p.spawn((
+                Text::new(format!(
+                    "{} {} {}",
+                    generator.pieces_count(),
+                    language.tr("pieces"),
+                    language.tr(&select_game_mode.to_string())
+                )),
+                TextColor(Color::BLACK),
+                ThemeColor::Text,
+                Node {
+                    margin: UiRect::all(Val::Px(5.0)),
+                    ..default()
+                },
+            ));
+            p.spawn((
+                Text::new(format!(
+                    "{}: {}",
+                    language.tr("Difficulty score"),
+                    filters.score(generator.pieces_count())
+                )),
                 TextColor(Color::BLACK),
+                ThemeColor::Text,
                 Node {
                     margin: UiRect::all(Val::Px(5.0)),
                     ..default()
                 },
             ));
+            let time = if relaxed.0 {
+                language.tr("Solved at your own pace").to_string()
+            } else {
+                format!("{}: {}", language.tr("Use time"), *game_timer)
+            };
             p.spawn((
-                Text::new(format!("Use time: {}", *game_timer)),
+                Text::new(time),
                 TextColor(Color::BLACK),
+                ThemeColor::Text,
                 Node {
                     margin: UiRect::all(Val::Px(5.0)),
                     ..default()
                 },
             ));
+            if mystery.0 {
+                spawn_mystery_badge(p, &language, origin_image.0.clone());
+            }
+            if let Some(versus) = versus {
+                p.spawn((
+                    Text::new(versus.result(&language)),
+                    TextColor(Color::BLACK),
+                    ThemeColor::Text,
+                    Node {
+                        margin: UiRect::all(Val::Px(5.0)),
+                        ..default()
+                    },
+                ));
+            }
             p.spawn((
                 Button,
                 Node {
@@ -149,15 +249,18 @@ fn setup_finish_ui(
                 BorderColor(Color::BLACK),
                 BorderRadius::MAX,
                 BackgroundColor(NORMAL_BUTTON),
+                ThemeColor::Button,
             ))
             .with_child((
                 Text::new("Menu"),
+                Localized("Menu"),
                 TextFont {
                     font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                     font_size: 22.0,
                     ..default()
                 },
                 TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                ThemeColor::ButtonText,
             ))
             .observe(
                 |_trigger: Trigger<Pointer<Click>>, mut next_state: ResMut<NextState<AppState>>| {
@@ -179,22 +282,60 @@ fn setup_finish_ui(
                 BorderColor(Color::BLACK),
                 BorderRadius::MAX,
                 BackgroundColor(NORMAL_BUTTON),
+                ThemeColor::Button,
             ))
             .with_child((
                 Text::new("Again"),
+                Localized("Again"),
                 TextFont {
                     font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                     font_size: 22.0,
                     ..default()
                 },
                 TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                ThemeColor::ButtonText,
             ))
             .observe(
                 |_trigger: Trigger<Pointer<Click>>,
+                 mut commands: Commands,
                  mut next_state: ResMut<NextState<GameState>>| {
+                    commands.insert_resource(PlayAgain);
                     next_state.set(GameState::Setup);
                 },
             );
+
+            p.spawn((
+                Button,
+                Node {
+                    width: Val::Px(100.0),
+                    height: Val::Px(40.0),
+                    margin: UiRect::all(Val::Px(5.0)),
+                    border: UiRect::all(Val::Px(5.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BorderColor(Color::BLACK),
+                BorderRadius::MAX,
+                BackgroundColor(NORMAL_BUTTON),
+                ThemeColor::Button,
+            ))
+            .with_child((
+                Text::new("Replay"),
+                Localized("Replay"),
+                TextFont {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 22.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                ThemeColor::ButtonText,
+            ))
+            .observe(
+                |_trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+                    commands.trigger(StartReplay);
+                },
+            );
         });
 }
 
@@ -210,7 +351,9 @@ fn enter_app_gameplay(mut game_state: ResMut<NextState<GameState>>) {
     game_state.set(GameState::Setup);
 }
 
-fn exit_app_gameplay(mut game_state: ResMut<NextState<GameState>>) {
+fn exit_app_gameplay(mut commands: Commands, mut game_state: ResMut<NextState<GameState>>) {
+    // only playing again reuses the last puzzle
+    commands.remove_resource::<PuzzleCache>();
     game_state.set(GameState::Idle);
 }
 
@@ -227,18 +370,40 @@ impl core::fmt::Display for GameTimer {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn setup_generator(
     mut commands: Commands,
-    images: Res<Assets<Image>>,
+    mut images: ResMut<Assets<Image>>,
     origin_image: Res<OriginImage>,
     select_piece: Res<SelectPiece>,
+    custom_grid: Res<CustomGrid>,
+    puzzle_seed: Res<PuzzleSeed>,
+    piece_shape: Res<PieceShape>,
+    filters: Res<ImageFilters>,
+    tutorial: Res<TutorialStep>,
+    play_again: Option<Res<PlayAgain>>,
+    cache: Option<Res<PuzzleCache>>,
 ) {
     let image = images.get(&origin_image.0).unwrap();
-    let (columns, rows) = select_piece.get_columns_rows();
     let width = image.texture_descriptor.size.width;
     let height = image.texture_descriptor.size.height;
-    let generator = JigsawGenerator::from_rgba8(width, height, &image.data, columns, rows)
-        .expect("Failed to load image");
+    let (columns, rows) = if *tutorial == TutorialStep::Off {
+        custom_grid.columns_rows(&select_piece, width, height)
+    } else {
+        TUTORIAL_GRID
+    };
+    let mut generator = piece_shape.apply(
+        JigsawGenerator::from_rgba8(width, height, &image.data, columns, rows)
+            .expect("Failed to load image")
+            .filters(filters.iter().copied()),
+    );
+    // playing again cuts the last puzzle again, which is still cached
+    let seed = puzzle_seed.or(play_again.and(cache).and_then(|cache| cache.key.seed));
+    if let Some(seed) = seed {
+        generator = generator.seed(seed);
+    }
+    commands.remove_resource::<PlayAgain>();
+    let preview_image = PreviewImage::new(&origin_image.0, &filters, &mut images);
 
     commands
         .spawn((
@@ -252,11 +417,12 @@ fn setup_generator(
         ))
         .with_children(|p| {
             p.spawn((
-                Sprite::from_image(origin_image.0.clone()),
+                Sprite::from_image(preview_image.0.clone()),
                 Transform::from_xyz(0.0, 0.0, -1.0),
             ));
         });
 
+    commands.insert_resource(preview_image);
     commands.insert_resource(JigsawPuzzleGenerator(generator));
 }
 
@@ -266,131 +432,358 @@ pub struct OnGeneratingScreen;
 #[derive(Debug, Resource, Deref, DerefMut, Clone)]
 pub struct JigsawPuzzleGenerator(pub JigsawGenerator);
 
+/// The template the pieces on the table are spawned from
 #[derive(Debug, Resource, Deref, DerefMut)]
 pub struct JigsawPuzzleTemplate(pub JigsawTemplate);
 
+/// Generates the template of the puzzle, `None` if it failed
+#[derive(Component)]
+struct TemplateTask {
+    task: Task<Option<GeneratedTemplate>>,
+    /// The pieces cut out so far
+    done: Arc<AtomicUsize>,
+    /// The settings the template is generated for
+    key: PuzzleKey,
+}
+
+enum GeneratedTemplate {
+    /// A new template with the smaller puzzle images
+    New(JigsawTemplate, Vec<DynamicImage>),
+    /// The template of the [`PuzzleCache`], its atlas is still there
+    Cached(JigsawTemplate),
+}
+
+/// The settings that change the generated puzzle
+#[derive(Debug, Clone, PartialEq)]
+struct PuzzleKey {
+    image: AssetId<Image>,
+    grid: (usize, usize),
+    seed: Option<usize>,
+    game_mode: GameMode,
+    shape: PieceShape,
+    filters: Vec<ImageFilter>,
+}
+
+/// "Again" on the finish screen, the next puzzle is cut with the seed of the last one
+#[derive(Resource)]
+struct PlayAgain;
+
+/// The last generated puzzle, playing again with the same settings reuses it instead of
+/// generating and masking all pieces again. It is dropped when leaving the game
+#[derive(Resource)]
+struct PuzzleCache {
+    key: PuzzleKey,
+    template: JigsawTemplate,
+    /// The images of every piece by its index, once it is masked
+    pieces: Vec<Option<PieceImages>>,
+}
+
+/// The mesh and materials of the images of a piece
+#[derive(Clone)]
+struct PieceImages {
+    mesh: Handle<Mesh>,
+    color: Handle<PieceMaterial>,
+    white: Handle<PieceMaterial>,
+    outline: Handle<PieceMaterial>,
+}
+
 #[derive(Component)]
 struct CropTask(Task<CommandQueue>);
 
 #[derive(Component)]
-struct WhiteImage;
+pub struct WhiteImage;
 
 #[derive(Component)]
-struct ColorImage;
+pub struct ColorImage;
 
-/// Spawn the pieces of the jigsaw puzzle
-fn spawn_piece(
+/// Starts generating the template in the background, it is dropped with the generating screen
+fn generate_template(
     mut commands: Commands,
     generator: Res<JigsawPuzzleGenerator>,
     select_game_mode: Res<SelectGameMode>,
+    origin_image: Res<OriginImage>,
+    piece_shape: Res<PieceShape>,
+    cache: Option<Res<PuzzleCache>>,
 ) {
-    debug!("Start to generate pieces");
-    if let Ok(template) = generator.generate(**select_game_mode, false) {
-        // commands.insert_resource(JigsawPuzzleTemplate(template.clone()));
-        let mut wait_crops = vec![];
-        for piece in template.pieces.iter() {
-            let piece_clone = piece.clone();
-
-            // let calc_position = random_position(&piece, window.resolution.size(), camera.scale);
-            let calc_position = init_position(piece, template.origin_image.dimensions());
-            let entity = commands
-                .spawn((
-                    Piece(piece.clone()),
-                    MoveTogether::default(),
-                    Transform::from_xyz(calc_position.x, calc_position.y, piece.index as f32),
-                    Visibility::Visible,
-                    OnPlayScreen,
-                ))
-                .observe(on_click_piece)
-                .observe(on_move_end)
-                .observe(on_drag_start)
-                .observe(on_drag_end)
-                .observe(on_add_move_start)
-                .observe(on_remove_move_start)
-                .observe(on_selected)
-                .observe(on_not_selected)
-                .id();
-
-            wait_crops.push((entity, piece_clone));
+    let key = PuzzleKey {
+        image: origin_image.0.id(),
+        grid: (generator.pieces_in_column(), generator.pieces_in_row()),
+        seed: generator.seed_value(),
+        game_mode: **select_game_mode,
+        shape: *piece_shape,
+        filters: generator.filter_list().to_vec(),
+    };
+    let pool = AsyncComputeTaskPool::get();
+    let done = Arc::new(AtomicUsize::new(0));
+    let task = match cache.filter(|cache| cache.key == key) {
+        Some(cache) => {
+            debug!("Reuse the cached template");
+            let template = cache.template.clone();
+            pool.spawn(async move { Some(GeneratedTemplate::Cached(template)) })
         }
+        None => {
+            debug!("Start to generate the template");
+            let generator = generator.0.clone();
+            let game_mode = key.game_mode;
+            let progress = done.clone();
+            pool.spawn(async move {
+                generator
+                    .generate_with_progress(game_mode, false, |done, _total| {
+                        progress.store(done, Ordering::Relaxed);
+                    })
+                    .inspect_err(|err| error!("Failed to generate the puzzle: {}", err))
+                    .ok()
+                    .map(|template| {
+                        let levels = template.image_levels(IMAGE_LEVELS);
+                        GeneratedTemplate::New(template, levels)
+                    })
+            })
+        }
+    };
+    commands.spawn((TemplateTask { task, done, key }, OnGeneratingScreen));
+}
 
-        if !wait_crops.is_empty() {
-            let thread_pool = AsyncComputeTaskPool::get();
-            for (entity, piece) in wait_crops {
-                let template_clone = template.clone();
-                let task = thread_pool.spawn(async move {
-                    let mut command_queue = CommandQueue::default();
-
-                    debug!("Start to crop piece {}", piece.index);
-                    let cropped_image = piece.crop(&template_clone.origin_image);
-                    let white_image = piece.fill_white(&cropped_image);
-                    command_queue.push(move |mut world: &mut World| {
-                        let mut assets = world.deref_mut().resource_mut::<Assets<Image>>();
-                        let image = assets.add(Image::from_dynamic(
-                            cropped_image,
-                            true,
-                            RenderAssetUsages::RENDER_WORLD,
-                        ));
-                        let white_image = assets.add(Image::from_dynamic(
-                            white_image,
+/// Spawn the pieces of the jigsaw puzzle once the template is generated, in guided mode only the
+/// frame is spawned at first
+fn spawn_piece(
+    mut commands: Commands,
+    mut tasks: Query<(Entity, &mut TemplateTask)>,
+    play_mode: Res<SelectPlayMode>,
+    mut images: ResMut<Assets<Image>>,
+    origin_image: Res<OriginImage>,
+    mut generating_step: ResMut<NextState<GeneratingStep>>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    for (entity, mut task) in tasks.iter_mut() {
+        let Some(template) = block_on(future::poll_once(&mut task.task)) else {
+            continue;
+        };
+        commands.entity(entity).despawn();
+        let template = match template {
+            Some(GeneratedTemplate::New(template, levels)) => {
+                debug!("Start to generate pieces");
+                // the filters change the image the pieces are cut from
+                let full_image = if task.key.filters.iter().any(ImageFilter::changes_pieces) {
+                    images.add(Image::from_dynamic(
+                        (*template.origin_image).clone(),
+                        true,
+                        RenderAssetUsages::RENDER_WORLD,
+                    ))
+                } else {
+                    origin_image.0.clone()
+                };
+                let image_levels = core::iter::once(full_image)
+                    .chain(levels.into_iter().map(|level| {
+                        images.add(Image::from_dynamic(
+                            level,
                             true,
                             RenderAssetUsages::RENDER_WORLD,
-                        ));
-                        let color_sprite = Sprite {
-                            image,
-                            anchor: Anchor::TopLeft,
-                            custom_size: Some(Vec2::new(
-                                piece.crop_width as f32,
-                                piece.crop_height as f32,
-                            )),
-                            ..default()
-                        };
+                        ))
+                    }))
+                    .collect();
+                commands.insert_resource(PieceAtlas::new(&template, image_levels, &mut images));
+                // replacing the cache drops the images of the last puzzle
+                commands.insert_resource(PuzzleCache {
+                    key: task.key.clone(),
+                    template: template.clone(),
+                    pieces: vec![None; template.pieces.len()],
+                });
+                template
+            }
+            Some(GeneratedTemplate::Cached(template)) => template,
+            None => {
+                app_state.set(AppState::MainMenu);
+                return;
+            }
+        };
+        let image_size = template.origin_image.dimensions();
+        if *play_mode == SelectPlayMode::Guided {
+            let guided = GuidedAssembly::new(&template);
+            let frame: Vec<JigsawPiece> = template
+                .pieces
+                .iter()
+                .filter(|piece| piece.is_boarder)
+                .cloned()
+                .collect();
+            spawn_pieces(&mut commands, &frame, |piece| {
+                init_position(piece, image_size)
+            });
+            commands.insert_resource(guided);
+        } else {
+            spawn_pieces(&mut commands, &template.pieces, |piece| {
+                init_position(piece, image_size)
+            });
+        }
+        commands.insert_resource(JigsawPuzzleTemplate(template));
+        commands.send_event(Shuffle::Random);
+        generating_step.set(GeneratingStep::Pieces);
+    }
+}
 
-                        let color_id = world
-                            .spawn((
-                                ColorImage,
-                                color_sprite,
-                                Transform::from_xyz(
-                                    -piece.calc_offset().0,
-                                    piece.calc_offset().1,
-                                    0.0,
-                                ),
-                            ))
-                            .id();
-                        let white_sprite = Sprite {
-                            image: white_image,
-                            anchor: Anchor::TopLeft,
-                            custom_size: Some(Vec2::new(
-                                piece.crop_width as f32,
-                                piece.crop_height as f32,
-                            )),
-                            ..default()
-                        };
-                        let white_id = world
-                            .spawn((
-                                WhiteImage,
-                                white_sprite,
-                                Transform::from_xyz(
-                                    -piece.calc_offset().0,
-                                    piece.calc_offset().1,
-                                    -1.0,
-                                ),
-                            ))
-                            .id();
+/// Spawns the given pieces of the template at `position` and draws their masks into the
+/// [`PieceAtlas`] in the background
+pub fn spawn_pieces(
+    commands: &mut Commands,
+    pieces: &[JigsawPiece],
+    position: impl Fn(&JigsawPiece) -> Vec2,
+) -> Vec<Entity> {
+    let mut wait_crops = vec![];
+    for piece in pieces.iter() {
+        let piece_clone = piece.clone();
+
+        let calc_position = position(piece);
+        let entity = commands
+            .spawn((
+                Piece(piece.clone()),
+                MoveTogether::default(),
+                Transform::from_xyz(calc_position.x, calc_position.y, piece.index as f32),
+                Visibility::Visible,
+                OnPlayScreen,
+            ))
+            .observe(on_click_piece)
+            .observe(on_move_end)
+            .observe(on_drag_start)
+            .observe(on_drag_with_pen)
+            .observe(on_drag_end)
+            .observe(on_add_move_start)
+            .observe(on_remove_move_start)
+            .id();
 
-                        world
-                            .entity_mut(entity)
-                            .add_children(&[color_id, white_id])
-                            .remove::<CropTask>();
-                    });
+        wait_crops.push((entity, piece_clone));
+    }
 
-                    command_queue
-                });
-                commands.entity(entity).insert(CropTask(task));
+    let entities = wait_crops.iter().map(|(entity, _)| *entity).collect();
+    for (entity, piece) in wait_crops {
+        commands.queue(move |world: &mut World| {
+            let cached = world
+                .get_resource::<PuzzleCache>()
+                .and_then(|cache| cache.pieces[piece.index].clone());
+            match cached {
+                Some(piece_images) => spawn_piece_images(world, entity, &piece, piece_images),
+                None => {
+                    let task = AsyncComputeTaskPool::get().spawn(mask_piece(entity, piece));
+                    world.entity_mut(entity).insert(CropTask(task));
+                }
             }
+        });
+    }
+    entities
+}
+
+/// Draws the mask of the piece into the [`PieceAtlas`] and spawns its images, which are kept in
+/// the [`PuzzleCache`]
+async fn mask_piece(entity: Entity, piece: JigsawPiece) -> CommandQueue {
+    let mut command_queue = CommandQueue::default();
+
+    debug!("Start to mask piece {}", piece.index);
+    let mask = piece.mask(OUTLINE_RADIUS);
+    command_queue.push(move |world: &mut World| {
+        let [color, white, outline] = world.resource_scope(|world, atlas: Mut<PieceAtlas>| {
+            let mut images = world.resource_mut::<Assets<Image>>();
+            atlas.insert(&mut images, &piece, &mask);
+            [PieceImage::Color, PieceImage::White, PieceImage::Outline]
+                .map(|kind| atlas.material(&piece, kind))
+        });
+        let mut materials = world.resource_mut::<Assets<PieceMaterial>>();
+        let (color, white, outline) = (
+            materials.add(color),
+            materials.add(white),
+            materials.add(outline),
+        );
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(PieceAtlas::mesh(&piece));
+        let piece_images = PieceImages {
+            mesh,
+            color,
+            white,
+            outline,
+        };
+        if let Some(mut cache) = world.get_resource_mut::<PuzzleCache>() {
+            cache.pieces[piece.index] = Some(piece_images.clone());
         }
-        commands.send_event(Shuffle::Random);
-    };
+
+        spawn_piece_images(world, entity, &piece, piece_images);
+        world.entity_mut(entity).remove::<CropTask>();
+    });
+
+    command_queue
+}
+
+/// Spawns the images of the piece as children of its entity, with the tints of the last game
+/// reset
+fn spawn_piece_images(
+    world: &mut World,
+    entity: Entity,
+    piece: &JigsawPiece,
+    piece_images: PieceImages,
+) {
+    let PieceImages {
+        mesh,
+        color,
+        white,
+        outline,
+    } = piece_images;
+    let mut materials = world.resource_mut::<Assets<PieceMaterial>>();
+    for (handle, tint) in [
+        (&color, Color::WHITE),
+        (&white, Color::WHITE),
+        (&outline, OUTLINE_COLOR),
+    ] {
+        if let Some(material) = materials.get_mut(handle) {
+            material.set_color(tint);
+            material.set_outline(0.0);
+        }
+    }
+
+    let high_contrast = *world.resource::<HighContrast>();
+    let (offset_x, offset_y) = piece.calc_offset();
+    let color_id = world
+        .spawn((
+            ColorImage,
+            Mesh2d(mesh.clone()),
+            MeshMaterial2d(color),
+            Transform::from_xyz(-offset_x, offset_y, 0.0),
+            PickingBehavior::IGNORE,
+        ))
+        .with_children(|p| {
+            p.spawn((
+                OutlineImage,
+                Mesh2d(mesh.clone()),
+                MeshMaterial2d(outline),
+                Transform::from_xyz(0.0, 0.0, 0.5),
+                high_contrast.outline_visibility(),
+                PickingBehavior::IGNORE,
+            ));
+            p.spawn((
+                PieceNumber,
+                Text2d::new((piece.index + 1).to_string()),
+                TextFont {
+                    font_size: piece.width.min(piece.height) / 3.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                Transform::from_xyz(
+                    offset_x + piece.width / 2.0,
+                    -offset_y - piece.height / 2.0,
+                    0.6,
+                ),
+                high_contrast.number_visibility(),
+                PickingBehavior::IGNORE,
+            ));
+        })
+        .id();
+    let white_id = world
+        .spawn((
+            WhiteImage,
+            Mesh2d(mesh),
+            MeshMaterial2d(white),
+            Transform::from_xyz(-offset_x, offset_y, -1.0),
+            PickingBehavior::IGNORE,
+        ))
+        .id();
+
+    world.entity_mut(entity).add_children(&[color_id, white_id]);
 }
 
 /// Calculate the position of the piece in the world space
@@ -406,7 +799,7 @@ fn calc_position(piece: &JigsawPiece, origin_image_size: (u32, u32)) -> Vec2 {
 }
 
 #[allow(dead_code)]
-fn init_position(piece: &JigsawPiece, origin_image_size: (u32, u32)) -> Vec2 {
+pub fn init_position(piece: &JigsawPiece, origin_image_size: (u32, u32)) -> Vec2 {
     let (width, height) = origin_image_size;
     let image_top_left = (width as f32 / -2.0, height as f32 / 2.0);
     Vec2::new(
@@ -425,41 +818,140 @@ fn handle_tasks(mut commands: Commands, mut crop_tasks: Query<&mut CropTask>) {
 }
 
 fn count_spawned_piece(
-    mut text: Single<&mut Text, With<PieceCount>>,
-    generator: Res<JigsawPuzzleGenerator>,
     mut game_state: ResMut<NextState<GameState>>,
     q_pieces: Query<Entity, With<ColorImage>>,
+    q_spawned: Query<(), With<Piece>>,
 ) {
+    // guided mode spawns only some of the pieces up front
     let loaded_pieces = q_pieces.iter().count();
-    text.0 = format!("{}/{}", loaded_pieces, generator.pieces_count());
-    if loaded_pieces == generator.pieces_count() {
+    let spawned_pieces = q_spawned.iter().count();
+    if spawned_pieces > 0 && loaded_pieces == spawned_pieces {
         game_state.set(GameState::Play);
     }
 }
 
+/// Cutting the template fills the first half of the bar, cropping the pieces the second half
+fn update_generating_progress(
+    generator: Res<JigsawPuzzleGenerator>,
+    tasks: Query<&TemplateTask>,
+    q_pieces: Query<(), With<ColorImage>>,
+    q_spawned: Query<(), With<Piece>>,
+    mut text: Single<&mut Text, With<PieceCount>>,
+    mut fill: Single<&mut Node, With<ProgressFill>>,
+    mut generating_progress: ResMut<GeneratingProgress>,
+) {
+    let (done, total, offset) = match tasks.iter().next() {
+        Some(task) => (
+            task.done.load(Ordering::Relaxed),
+            generator.pieces_count(),
+            0.0,
+        ),
+        None => (q_pieces.iter().count(), q_spawned.iter().count(), 0.5),
+    };
+    let label = format!("{}/{}", done, total);
+    if text.0 != label {
+        text.0 = label;
+    }
+    let progress = offset + done as f32 / total.max(1) as f32 / 2.0;
+    fill.width = Val::Percent(progress * 100.0);
+    generating_progress.0 = progress;
+}
+
+/// Stops generating and goes back to the menu, the running tasks are dropped with their entities
+fn cancel_generating(
+    _trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    q_play: Query<Entity, With<OnPlayScreen>>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    for entity in q_play.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    app_state.set(AppState::MainMenu);
+}
+
 #[derive(Component)]
-struct MoveStart {
-    image_position: Transform,
-    click_position: Vec2,
+pub struct MoveStart {
+    pub image_position: Transform,
+    pub click_position: Vec2,
+}
+
+/// The pieces which can be picked up
+type Movable = (
+    With<Piece>,
+    Without<Immovable>,
+    Without<Locked>,
+    Without<Pinned>,
+    Without<ScatterTween>,
+);
+
+/// Lifts the piece and holds it at the pointer
+fn pick_up(
+    commands: &mut Commands,
+    entity: Entity,
+    transform: &mut Transform,
+    pointer: Vec2,
+    camera: (&Camera, &GlobalTransform),
+) {
+    let (camera, camera_global_transform) = camera;
+    let point = camera
+        .viewport_to_world_2d(camera_global_transform, pointer)
+        .unwrap();
+    transform.translation.z = 100.0;
+    commands.entity(entity).insert(MoveStart {
+        image_position: *transform,
+        click_position: point,
+    });
 }
 
 fn on_drag_start(
     trigger: Trigger<Pointer<DragStart>>,
-    mut piece: Query<&mut Transform, With<Piece>>,
+    mut piece: Query<&mut Transform, Movable>,
     camera: Single<(&Camera, &GlobalTransform), (With<Camera2d>, With<IsDefaultUiCamera>)>,
+    pen: Res<PenInput>,
     mut commands: Commands,
 ) {
+    // the pen picks up pieces once it moved far enough
+    if pen.is_pen(trigger.pointer_id) || pen.is_palm(trigger.pointer_id) {
+        return;
+    }
     if let Ok(mut transform) = piece.get_mut(trigger.entity()) {
-        let click_position = trigger.event().pointer_location.position;
-        let (camera, camera_global_transform) = camera.into_inner();
-        let point = camera
-            .viewport_to_world_2d(camera_global_transform, click_position)
-            .unwrap();
-        transform.translation.z = 100.0;
-        commands.entity(trigger.entity()).insert(MoveStart {
-            image_position: *transform,
-            click_position: point,
-        });
+        let pointer = trigger.event().pointer_location.position;
+        pick_up(
+            &mut commands,
+            trigger.entity(),
+            &mut transform,
+            pointer,
+            camera.into_inner(),
+        );
+    }
+}
+
+/// Picks up the piece once the pen pressed firmly and moved past the wobble of touching down
+#[allow(clippy::type_complexity)]
+fn on_drag_with_pen(
+    trigger: Trigger<Pointer<Drag>>,
+    mut piece: Query<&mut Transform, (Movable, Without<MoveStart>)>,
+    camera: Single<(&Camera, &GlobalTransform), (With<Camera2d>, With<IsDefaultUiCamera>)>,
+    pen: Res<PenInput>,
+    touches: Res<Touches>,
+    mut commands: Commands,
+) {
+    if !pen.is_pen(trigger.pointer_id)
+        || trigger.event().distance.length() < PEN_DRAG_THRESHOLD
+        || !pen.presses_firmly(&touches)
+    {
+        return;
+    }
+    if let Ok(mut transform) = piece.get_mut(trigger.entity()) {
+        let pointer = trigger.event().pointer_location.position;
+        pick_up(
+            &mut commands,
+            trigger.entity(),
+            &mut transform,
+            pointer,
+            camera.into_inner(),
+        );
     }
 }
 
@@ -477,27 +969,29 @@ fn on_drag_end(
 
 fn on_click_piece(
     trigger: Trigger<Pointer<Click>>,
-    mut image: Query<(&mut Transform, Option<&MoveStart>), With<Piece>>,
+    mut image: Query<(&mut Transform, Option<&MoveStart>), Movable>,
     camera: Single<(&Camera, &GlobalTransform), (With<Camera2d>, With<IsDefaultUiCamera>)>,
+    pen: Res<PenInput>,
     mut commands: Commands,
 ) {
+    // right clicks rotate pieces
+    if trigger.event().button == PointerButton::Secondary || pen.is_palm(trigger.pointer_id) {
+        return;
+    }
     if let Ok((mut transform, opt_moveable)) = image.get_mut(trigger.entity()) {
-        let click_position = trigger.event().pointer_location.position;
-        let (camera, camera_global_transform) = camera.into_inner();
-        let point = camera
-            .viewport_to_world_2d(camera_global_transform, click_position)
-            .unwrap();
-
         if opt_moveable.is_some() {
             transform.translation.z = 0.0;
             commands.entity(trigger.entity()).remove::<MoveStart>();
             commands.trigger_targets(MoveEnd, vec![trigger.entity()]);
         } else {
-            transform.translation.z = 100.0;
-            commands.entity(trigger.entity()).insert(MoveStart {
-                image_position: *transform,
-                click_position: point,
-            });
+            let pointer = trigger.event().pointer_location.position;
+            pick_up(
+                &mut commands,
+                trigger.entity(),
+                &mut transform,
+                pointer,
+                camera.into_inner(),
+            );
         }
     }
 }
@@ -507,9 +1001,15 @@ fn move_piece(
     camera_query: Single<(&Camera, &GlobalTransform), With<IsDefaultUiCamera>>,
     moveable: Single<(&mut Transform, &MoveStart, &MoveTogether)>,
     mut other_piece: Query<&mut Transform, Without<MoveStart>>,
+    selection: Res<MultiSelection>,
+    touches: Res<Touches>,
+    pen: Res<PenInput>,
 ) {
     let (camera, camera_transform) = *camera_query;
-    let Some(cursor_position) = window.cursor_position() else {
+    let Some(cursor_position) = window
+        .cursor_position()
+        .or_else(|| pen.touch_position(&touches))
+    else {
         return;
     };
     let Ok(point) = camera.viewport_to_world_2d(camera_transform, cursor_position) else {
@@ -522,7 +1022,7 @@ fn move_piece(
     let offset = move_end - transform.translation;
     transform.translation = move_end;
 
-    for other in move_together.iter() {
+    for other in move_together.union(&selection) {
         if let Ok(mut other_transform) = other_piece.get_mut(*other) {
             other_transform.translation += offset;
         }
@@ -530,84 +1030,102 @@ fn move_piece(
 }
 
 #[derive(Event)]
-struct MoveEnd;
+pub struct MoveEnd;
+
+/// Dropped pieces are snapped by someone else, like the host of a cooperative game, which sends
+/// back where they end up
+#[derive(Resource)]
+pub struct RemoteSnaps;
+
+/// A piece which can't be picked up at the moment
+#[derive(Component)]
+pub struct Immovable;
 
 #[derive(Component, Deref, DerefMut, Default)]
 pub struct MoveTogether(pub HashSet<Entity>);
 
-fn on_move_end(
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn on_move_end(
     trigger: Trigger<MoveEnd>,
     generator: Res<JigsawPuzzleGenerator>,
-    mut query: Query<(Entity, &Piece, &mut Transform, &mut MoveTogether)>,
+    template: Res<JigsawPuzzleTemplate>,
+    mut query: Query<
+        (
+            Entity,
+            &Piece,
+            &mut Transform,
+            &mut MoveTogether,
+            Has<OffScreen>,
+        ),
+        Without<InTray>,
+    >,
     mut commands: Commands,
     mut next_state: ResMut<NextState<GameState>>,
+    play_mode: Res<SelectPlayMode>,
+    snap_tolerance: Res<SnapTolerance>,
+    hovered_tray: Res<HoveredTray>,
+    rotations: Query<&PieceRotation>,
+    timer: Option<Res<GameTimer>>,
+    staging_grid: Res<StagingGrid>,
+    remote_snaps: Option<Res<RemoteSnaps>>,
 ) {
+    // single pieces dropped on a tray are sorted into it instead of snapping
+    if hovered_tray.is_some()
+        && query
+            .get(trigger.entity())
+            .is_ok_and(|(_, _, _, together, _)| together.is_empty())
+    {
+        commands.trigger_targets(PutInTray, trigger.entity());
+        return;
+    }
+    if remote_snaps.is_some() {
+        return;
+    }
+
+    let dropped_at = query
+        .get(trigger.entity())
+        .map(|(_, _, transform, _, _)| transform.translation.xy())
+        .ok();
     let mut iter = query.iter_combinations_mut();
     let end_entity = trigger.entity();
 
     let mut all_entities = HashSet::default();
-    let mut max_z = 0f32;
-    while let Some([(e1, p1, transform1, together1), (e2, p2, transform2, together2)]) =
-        iter.fetch_next()
+    while let Some(
+        [(e1, p1, transform1, together1, off_screen1), (e2, p2, transform2, together2, off_screen2)],
+    ) = iter.fetch_next()
     {
-        let (mut target_transform, compare_transform, target, compare) = if e1 == end_entity {
-            (transform1, transform2, p1, p2)
-        } else if e2 == end_entity {
-            (transform2, transform1, p2, p1)
-        } else {
+        let (mut target_transform, compare_transform, target, compare, too_far) =
+            if e1 == end_entity {
+                (transform1, transform2, p1, p2, off_screen2 && !off_screen1)
+            } else if e2 == end_entity {
+                (transform2, transform1, p2, p1, off_screen1 && !off_screen2)
+            } else {
+                continue;
+            };
+        // pieces off screen aren't next to a piece dropped on screen, other players may drop
+        // pieces off screen though
+        if too_far {
             continue;
-        };
-
-        // calculate the max z value if close enough
-        if target_transform
-            .translation
-            .xy()
-            .distance(compare_transform.translation.xy())
-            < (target.crop_width.max(target.crop_height) as f32)
-        {
-            max_z = max_z.max(compare_transform.translation.z);
-        }
-
-        let target_loc = (
-            target_transform.translation.x,
-            target_transform.translation.y,
-        );
-        let compare_loc = (
-            compare_transform.translation.x,
-            compare_transform.translation.y,
-        );
-
-        let mut has_snapped = false;
-
-        if target.is_on_the_left_side(compare, target_loc, compare_loc) {
-            debug!("{} on the left side {}", target.index, compare.index);
-            target_transform.translation.x = compare_transform.translation.x - target.width;
-            target_transform.translation.y = compare_transform.translation.y;
-            has_snapped = true;
-        }
-
-        if target.is_on_the_right_side(compare, target_loc, compare_loc) {
-            debug!("{} on the right side {}", target.index, compare.index);
-            target_transform.translation.x = compare_transform.translation.x + compare.width;
-            target_transform.translation.y = compare_transform.translation.y;
-            has_snapped = true;
-        }
-
-        if target.is_on_the_top_side(compare, target_loc, compare_loc) {
-            debug!("{} on the top side {}", target.index, compare.index);
-            target_transform.translation.x = compare_transform.translation.x;
-            target_transform.translation.y = compare_transform.translation.y + target.height;
-            has_snapped = true;
         }
 
-        if target.is_on_the_bottom_side(compare, target_loc, compare_loc) {
-            debug!("{} on the bottom side {}", target.index, compare.index);
-            target_transform.translation.x = compare_transform.translation.x;
-            target_transform.translation.y = compare_transform.translation.y - compare.height;
-            has_snapped = true;
+        if !is_upright(rotations.get(e1).ok()) || !is_upright(rotations.get(e2).ok()) {
+            continue;
         }
 
-        if has_snapped {
+        if let Some(snapped) = snap_position(
+            target,
+            target_transform.translation.xy(),
+            compare,
+            compare_transform.translation.xy(),
+            snap_tolerance.distance(),
+        ) {
+            target_transform.translation.x = snapped.x;
+            target_transform.translation.y = snapped.y;
+            let other = if e1 == end_entity { e2 } else { e1 };
+            commands.send_event(PieceSnapped {
+                a: end_entity,
+                b: other,
+            });
             let mut merged_set: HashSet<_> = together1.union(&together2).cloned().collect();
             merged_set.insert(e1);
             merged_set.insert(e2);
@@ -616,23 +1134,247 @@ fn on_move_end(
         }
     }
 
-    if all_entities.len() == generator.pieces_count() {
+    // loose pieces which didn't snap line up on the staging grid beside the board
+    if staging_grid.0 && all_entities.is_empty() {
+        let (width, height) = generator.origin_image().dimensions();
+        let board = Rect::from_center_size(Vec2::ZERO, Vec2::new(width as f32, height as f32));
+        let cell = query
+            .iter()
+            .map(|(_, piece, ..)| piece.crop_width.max(piece.crop_height) as f32)
+            .fold(0.0, f32::max)
+            + STAGING_GAP;
+        let occupied: Vec<Vec2> = query
+            .iter()
+            .filter(|(entity, _, _, together, _)| *entity != end_entity && together.is_empty())
+            .map(|(_, piece, transform, ..)| piece.image_rect(transform.translation.xy()).center())
+            .collect();
+        if let Ok((_, piece, mut transform, together, _)) = query.get_mut(end_entity) {
+            let rect = piece.image_rect(transform.translation.xy());
+            if let Some(center) = together
+                .is_empty()
+                .then(|| staging_cell(rect, board, cell, &occupied))
+                .flatten()
+            {
+                transform.translation += (center - rect.center()).extend(0.0);
+            }
+        }
+    }
+
+    // on the board the puzzle is finished once all pieces are locked
+    if all_entities.len() == template.pieces_count() && *play_mode != SelectPlayMode::Board {
         debug!("All pieces have been merged");
         next_state.set(GameState::Finish);
+        commands.send_event(PuzzleCompleted::new(timer.as_deref()));
     }
 
-    if let Ok((_e, _p, mut transform, _together)) = query.get_mut(trigger.entity()) {
-        transform.translation.z = max_z + 1.0;
+    let mut dropped = all_entities.clone();
+    if let Ok((_e, _p, transform, together, _)) = query.get(trigger.entity()) {
+        dropped.extend(together.iter().copied());
+        dropped.insert(trigger.entity());
+
+        // glide from the drop point into the snapped position
+        if let Some(from) = dropped_at {
+            if from != transform.translation.xy() {
+                commands.entity(trigger.entity()).insert(SnapTween {
+                    from: from.extend(transform.translation.z),
+                    to: transform.translation,
+                    elapsed: 0.0,
+                });
+            }
+        }
     }
 
     commands.trigger(CombineTogether(all_entities));
+    commands.trigger(Restack::Dropped(dropped));
+}
+
+/// The translation `target` snaps to when it is within `tolerance` of a matching side of
+/// `compare`
+pub fn snap_position(
+    target: &Piece,
+    target_loc: Vec2,
+    compare: &Piece,
+    compare_loc: Vec2,
+    tolerance: f32,
+) -> Option<Vec2> {
+    // where target lies next to compare in the solved puzzle, triangles overlap their cells
+    let solved = Vec2::new(
+        compare_loc.x + target.start_point.0 - compare.start_point.0,
+        compare_loc.y + compare.start_point.1 - target.start_point.1,
+    );
+    let (target_loc, compare_loc) = (target_loc.into(), compare_loc.into());
+    let mut snapped = None;
+
+    if target.is_on_the_left_side_within(compare, target_loc, compare_loc, tolerance) {
+        debug!("{} on the left side {}", target.index, compare.index);
+        snapped = Some(solved);
+    }
+
+    if target.is_on_the_right_side_within(compare, target_loc, compare_loc, tolerance) {
+        debug!("{} on the right side {}", target.index, compare.index);
+        snapped = Some(solved);
+    }
+
+    if target.is_on_the_top_side_within(compare, target_loc, compare_loc, tolerance) {
+        debug!("{} on the top side {}", target.index, compare.index);
+        snapped = Some(solved);
+    }
+
+    if target.is_on_the_bottom_side_within(compare, target_loc, compare_loc, tolerance) {
+        debug!("{} on the bottom side {}", target.index, compare.index);
+        snapped = Some(solved);
+    }
+
+    snapped
+}
+
+/// Shows a ghost outline where the held piece will land if it is dropped now
+fn preview_snap(
+    mut gizmos: Gizmos,
+    snap_tolerance: Res<SnapTolerance>,
+    moving: Single<(&Piece, &Transform, Option<&PieceRotation>), With<MoveStart>>,
+    others: Query<
+        (&Piece, &Transform, Option<&PieceRotation>),
+        (Without<MoveStart>, Without<InTray>),
+    >,
+) {
+    let (piece, transform, rotation) = *moving;
+    if !is_upright(rotation) {
+        return;
+    }
+    let Some(snapped) = others
+        .iter()
+        .find_map(|(other, other_transform, other_rotation)| {
+            if !is_upright(other_rotation) {
+                return None;
+            }
+            snap_position(
+                piece,
+                transform.translation.xy(),
+                other,
+                other_transform.translation.xy(),
+                snap_tolerance.distance(),
+            )
+        })
+    else {
+        return;
+    };
+
+    // the outline is in image coordinates, relative to the start point of the piece
+    let (start_x, start_y) = piece.start_point;
+    let points = piece
+        .outline(8)
+        .into_iter()
+        .map(|(x, y)| snapped + Vec2::new(x - start_x, start_y - y));
+    gizmos.linestrip_2d(
+        points.clone().chain(points.take(1)),
+        Color::srgba(1.0, 1.0, 1.0, 0.8),
+    );
+}
+
+/// Duration of the snap animation in seconds
+const SNAP_DURATION: f32 = 0.1;
+
+/// Animates a snapped piece from the point it was dropped to its snapped position
+#[derive(Component, Debug)]
+pub struct SnapTween {
+    from: Vec3,
+    to: Vec3,
+    elapsed: f32,
+}
+
+impl SnapTween {
+    /// Moves the animation to another layer
+    pub fn set_z(&mut self, z: f32) {
+        self.from.z = z;
+        self.to.z = z;
+    }
+}
+
+fn animate_snap(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut tweens: Query<(Entity, &mut SnapTween, &mut Transform), Without<MoveStart>>,
+) {
+    for (entity, mut tween, mut transform) in tweens.iter_mut() {
+        tween.elapsed += time.delta_secs();
+        let progress = (tween.elapsed / SNAP_DURATION).min(1.0);
+        // cubic ease out
+        let eased = 1.0 - (1.0 - progress).powi(3);
+        transform.translation = tween.from.lerp(tween.to, eased);
+        // a small pulse while the piece settles
+        transform.scale = Vec3::splat(1.0 + 0.08 * (progress * core::f32::consts::PI).sin());
+
+        if progress >= 1.0 {
+            transform.translation = tween.to;
+            transform.scale = Vec3::ONE;
+            commands.entity(entity).remove::<SnapTween>();
+        }
+    }
+}
+
+/// How long a shuffled piece flies to its new place
+const SCATTER_DURATION: f32 = 0.35;
+/// The longest a shuffled piece waits before it takes off, so they don't all move at once
+const SCATTER_MAX_DELAY: f32 = 0.15;
+
+/// Flies a shuffled piece along a short arc to its new place, it can't be picked up meanwhile
+#[derive(Component, Debug)]
+pub struct ScatterTween {
+    from: Vec3,
+    to: Vec3,
+    /// Negative while the piece waits for its turn
+    elapsed: f32,
+}
+
+impl ScatterTween {
+    pub(crate) fn new(from: Vec3, to: Vec2) -> Self {
+        ScatterTween {
+            from,
+            to: to.extend(from.z),
+            elapsed: -rand::thread_rng().gen_range(0.0..SCATTER_MAX_DELAY),
+        }
+    }
+}
+
+fn animate_scatter(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut tweens: Query<(Entity, &mut ScatterTween, &mut Transform)>,
+) {
+    for (entity, mut tween, mut transform) in tweens.iter_mut() {
+        tween.elapsed += time.delta_secs();
+        if tween.elapsed < 0.0 {
+            continue;
+        }
+        let progress = (tween.elapsed / SCATTER_DURATION).min(1.0);
+        // smooth step
+        let eased = progress * progress * (3.0 - 2.0 * progress);
+        let path = (tween.to - tween.from).xy();
+        let bulge = Vec2::new(-path.y, path.x) * 0.2 * (progress * core::f32::consts::PI).sin();
+        transform.translation = tween.from.lerp(tween.to, eased) + bulge.extend(0.0);
+
+        if progress >= 1.0 {
+            transform.translation = tween.to;
+            commands.entity(entity).remove::<ScatterTween>();
+        }
+    }
 }
 
 #[derive(Event)]
-struct CombineTogether(HashSet<Entity>);
+pub struct CombineTogether(pub HashSet<Entity>);
 
-fn combine_together(trigger: Trigger<CombineTogether>, mut query: Query<&mut MoveTogether>) {
+pub(crate) fn combine_together(
+    trigger: Trigger<CombineTogether>,
+    mut query: Query<&mut MoveTogether>,
+    mut merged: EventWriter<GroupMerged>,
+) {
     let entities: Vec<Entity> = trigger.event().0.iter().cloned().collect();
+    if entities.len() > 1 {
+        merged.send(GroupMerged {
+            size: entities.len(),
+        });
+    }
     let mut together_iter = query.iter_many_mut(&entities);
     while let Some(mut move_together) = together_iter.fetch_next() {
         move_together.0 = trigger.event().0.clone();
@@ -654,49 +1396,94 @@ fn cancel_all_move(
 #[derive(Component)]
 pub struct Selected;
 
-fn on_selected(
-    trigger: Trigger<OnInsert, Selected>,
-    query: Query<&Children>,
-    mut q_image: Query<&mut Transform, (With<ColorImage>, Without<WhiteImage>)>,
-    mut w_image: Query<&mut Sprite, (With<WhiteImage>, Without<ColorImage>)>,
+/// Width of the outline around the hovered piece, in pixels on the screen
+const HOVER_OUTLINE: f32 = 3.0;
+/// Width of the outline around the selected pieces, in pixels on the screen
+const SELECTION_OUTLINE: f32 = 5.0;
+
+/// Outlines the selected pieces in the selection color and the hovered piece in white, as wide on
+/// the screen at every zoom. The silhouettes of a selected group lie below all of its pieces, so
+/// the group is outlined as a whole and not piece by piece
+#[allow(clippy::too_many_arguments)]
+fn outline_pieces(
+    hovered: Res<HoveredPiece>,
+    high_contrast: Res<HighContrast>,
+    camera: Single<&OrthographicProjection, (With<Camera2d>, With<IsDefaultUiCamera>)>,
+    selected: Query<(Entity, &MoveTogether), With<Selected>>,
+    pieces: Query<(&Piece, &Transform, &Children)>,
+    mut silhouettes: Query<
+        (&mut Transform, &MeshMaterial2d<PieceMaterial>),
+        (With<WhiteImage>, Without<Piece>),
+    >,
+    mut materials: ResMut<Assets<PieceMaterial>>,
+    mut outlined: Local<HashSet<Entity>>,
 ) {
-    let children = query.get(trigger.entity()).unwrap();
-
-    for child in children.iter() {
-        if let Ok(mut transform) = q_image.get_mut(*child) {
-            transform.translation.x -= 4.0;
-            transform.translation.y += 4.0;
-        }
-        if let Ok(mut image) = w_image.get_mut(*child) {
-            image.color = Color::Srgba(YELLOW);
-        }
+    // the width on the screen, the tint and the z of the silhouette
+    let mut outlines = HashMap::new();
+    for (entity, together) in selected.iter() {
+        let bottom = together
+            .iter()
+            .chain([&entity])
+            .filter_map(|entity| pieces.get(*entity).ok())
+            .map(|(_, transform, _)| transform.translation.z)
+            .fold(f32::INFINITY, f32::min);
+        outlines.insert(
+            entity,
+            (SELECTION_OUTLINE, high_contrast.selection_color(), bottom),
+        );
+    }
+    if let Some((entity, Ok((_, transform, _)))) =
+        hovered.0.map(|entity| (entity, pieces.get(entity)))
+    {
+        outlines
+            .entry(entity)
+            .or_insert((HOVER_OUTLINE, Color::WHITE, transform.translation.z));
     }
-}
-
-fn on_not_selected(
-    trigger: Trigger<OnRemove, Selected>,
-    query: Query<&Children>,
-    mut q_image: Query<&mut Transform, (With<ColorImage>, Without<WhiteImage>)>,
-    mut w_image: Query<&mut Sprite, (With<WhiteImage>, Without<ColorImage>)>,
-) {
-    let children = query.get(trigger.entity()).unwrap();
 
-    for child in children.iter() {
-        if let Ok(mut transform) = q_image.get_mut(*child) {
-            transform.translation.x += 4.0;
-            transform.translation.y -= 4.0;
-        }
-        if let Ok(mut image) = w_image.get_mut(*child) {
-            image.color = Color::Srgba(Srgba::WHITE);
+    for entity in outlined.iter().chain(outlines.keys()) {
+        let Ok((piece, piece_transform, children)) = pieces.get(*entity) else {
+            continue;
+        };
+        let z = piece_transform.translation.z;
+        let (width, color, bottom) =
+            outlines
+                .get(entity)
+                .copied()
+                .unwrap_or((0.0, Color::WHITE, z));
+        let width = width * camera.scale;
+        let (offset_x, offset_y) = piece.calc_offset();
+        let size = Vec2::new(piece.crop_width as f32, piece.crop_height as f32);
+        let silhouette = Transform {
+            translation: Vec3::new(-offset_x - width, offset_y + width, bottom - z - 1.0),
+            scale: ((size + 2.0 * width) / size).extend(1.0),
+            ..default()
+        };
+        let mut iter = silhouettes.iter_many_mut(children);
+        while let Some((mut transform, material)) = iter.fetch_next() {
+            transform.set_if_neq(silhouette);
+            let unchanged = materials.get(material).is_some_and(|material| {
+                material.outline() == width && material.color().to_linear() == color.to_linear()
+            });
+            if let Some(material) = materials.get_mut(material).filter(|_| !unchanged) {
+                material.set_outline(width);
+                material.set_color(color);
+            }
         }
     }
+    *outlined = outlines.into_keys().collect();
 }
 
 fn on_add_move_start(
     trigger: Trigger<OnInsert, MoveStart>,
     query: Query<&MoveTogether>,
+    mut tweens: Query<&mut Transform, With<SnapTween>>,
     mut commands: Commands,
 ) {
+    // a piece picked up again while it's still settling stops its snap animation
+    if let Ok(mut transform) = tweens.get_mut(trigger.entity()) {
+        transform.scale = Vec3::ONE;
+        commands.entity(trigger.entity()).remove::<SnapTween>();
+    }
     let move_together = query.get(trigger.entity()).unwrap();
     commands.entity(trigger.entity()).insert(Selected);
     for entity in move_together.iter() {
@@ -736,8 +1523,7 @@ fn random_position(piece: &JigsawPiece, window_size: Vec2, scale: f32) -> Vec2 {
 }
 
 /// Calculate an edge position for the piece
-#[allow(dead_code)]
-fn edge_position(piece: &JigsawPiece, window_size: Vec2, scale: f32) -> Vec2 {
+pub fn edge_position(piece: &JigsawPiece, window_size: Vec2, scale: f32) -> Vec2 {
     let half_width = window_size.x / 2.0 * scale;
     let half_height = window_size.y / 2.0 * scale;
     let min_y = -half_height + piece.crop_height as f32;
@@ -760,39 +1546,175 @@ fn edge_position(piece: &JigsawPiece, window_size: Vec2, scale: f32) -> Vec2 {
     Vec2::new(x, y)
 }
 
-#[derive(Event)]
+/// Places a piece at a random spot of `area`, with its whole image inside if it fits
+fn random_in(piece: &JigsawPiece, area: Rect) -> Vec2 {
+    let mut rng = rand::thread_rng();
+    let max_x = (area.max.x - piece.crop_width as f32).max(area.min.x);
+    let min_y = (area.min.y + piece.crop_height as f32).min(area.max.y);
+    Vec2::new(
+        rng.gen_range(area.min.x..=max_x),
+        rng.gen_range(min_y..=area.max.y),
+    )
+}
+
+/// A random position in the quarter of the view the piece belongs to in the solved puzzle
+fn quadrant_position(piece: &JigsawPiece, view_size: Vec2, image_size: (u32, u32)) -> Vec2 {
+    let solved = init_position(piece, image_size) + Vec2::new(piece.width, -piece.height) / 2.0;
+    let corner = Vec2::new(
+        view_size.x / 2.0 * solved.x.signum(),
+        view_size.y / 2.0 * solved.y.signum(),
+    );
+    random_in(piece, Rect::from_corners(Vec2::ZERO, corner))
+}
+
+/// The `nth` position on a spiral winding out of the board center, evenly filling the area
+fn spiral_position(piece: &JigsawPiece, nth: usize) -> Vec2 {
+    const GOLDEN_ANGLE: f32 = 2.399_963;
+    let spacing = piece.width.max(piece.height);
+    let angle = nth as f32 * GOLDEN_ANGLE;
+    let center = Vec2::from_angle(angle) * spacing * (nth as f32).sqrt();
+    center + Vec2::new(-piece.width, piece.height) / 2.0
+}
+
+/// A loose stack in the top left corner of the view
+fn corner_position(piece: &JigsawPiece, view_size: Vec2, scale: f32) -> Vec2 {
+    let mut rng = rand::thread_rng();
+    let corner = Vec2::new(-view_size.x, view_size.y) / 2.0;
+    let jitter = Vec2::new(rng.gen_range(0.0..20.0), -rng.gen_range(0.0..20.0)) * scale;
+    corner + jitter + Vec2::new(piece.width, -piece.height) / 4.0
+}
+
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Shuffle {
     Random,
     Edge,
+    /// Near the quarter of the board each piece belongs to
+    Quadrant,
+    /// Along a spiral out of the board center
+    Spiral,
+    /// Stacked in a corner
+    Corner,
+    /// Into the scrollable shelf at the left of the window, see [`crate::tray`]
+    Shelf,
+}
+
+impl Shuffle {
+    pub const ALL: [Shuffle; 6] = [
+        Shuffle::Random,
+        Shuffle::Edge,
+        Shuffle::Quadrant,
+        Shuffle::Spiral,
+        Shuffle::Corner,
+        Shuffle::Shelf,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Shuffle::Random => "Random",
+            Shuffle::Edge => "Around the edge",
+            Shuffle::Quadrant => "By quadrant",
+            Shuffle::Spiral => "Spiral",
+            Shuffle::Corner => "Stack in corner",
+            Shuffle::Shelf => "Onto the shelf",
+        }
+    }
 }
 
-fn shuffle_pieces(
+pub(crate) fn shuffle_pieces(
+    mut commands: Commands,
     mut shuffle_events: EventReader<Shuffle>,
-    mut query: Query<(&Piece, &mut Transform)>,
+    query: Query<(Entity, &Piece, &Transform), Without<InTray>>,
     window: Single<&Window>,
     camera: Single<&OrthographicProjection, (With<Camera2d>, With<IsDefaultUiCamera>)>,
+    table: Res<Table>,
+    generator: Res<JigsawPuzzleGenerator>,
 ) {
+    let spread = table.spread_size(window.resolution.size(), camera.scale);
+    let view_size = spread * camera.scale;
+    let image_size = generator.origin_image().dimensions();
     for event in shuffle_events.read() {
-        match event {
-            Shuffle::Random => {
-                for (piece, mut transform) in &mut query.iter_mut() {
-                    let random_pos = random_position(piece, window.resolution.size(), camera.scale);
-                    transform.translation = random_pos.extend(piece.index as f32);
-                }
-            }
-            Shuffle::Edge => {
-                for (piece, mut transform) in &mut query.iter_mut() {
-                    let edge_pos = edge_position(piece, window.resolution.size(), camera.scale);
-                    transform.translation = edge_pos.extend(piece.index as f32);
-                }
-            }
+        // a random order, so the spiral and the stack don't follow the rows of the puzzle
+        let mut pieces: Vec<_> = query.iter().collect();
+        pieces.shuffle(&mut rand::thread_rng());
+        for (nth, (entity, piece, transform)) in pieces.into_iter().enumerate() {
+            let position = match event {
+                Shuffle::Random => random_position(piece, spread, camera.scale),
+                Shuffle::Edge => edge_position(piece, spread, camera.scale),
+                Shuffle::Quadrant => quadrant_position(piece, view_size, image_size),
+                Shuffle::Spiral => spiral_position(piece, nth),
+                Shuffle::Corner => corner_position(piece, view_size, camera.scale),
+                // the shelf lays out its pieces itself
+                Shuffle::Shelf => continue,
+            };
+            commands
+                .entity(entity)
+                .insert(ScatterTween::new(transform.translation, position));
         }
     }
 }
 
+/// The shuffle strategies below the shuffle button
+#[derive(Component)]
+struct ShuffleMenu;
+
+fn spawn_shuffle_menu(parent: &mut ChildBuilder) {
+    parent
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(45.),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(5.)),
+                ..default()
+            },
+            BackgroundColor(NORMAL_BUTTON),
+            Visibility::Hidden,
+            ShuffleMenu,
+        ))
+        .with_children(|p| {
+            for shuffle in Shuffle::ALL {
+                p.spawn((
+                    Text::new(shuffle.name()),
+                    Localized(shuffle.name()),
+                    TextFont {
+                        font_size: 20.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                    Node {
+                        margin: UiRect::all(Val::Px(4.)),
+                        ..default()
+                    },
+                ))
+                .observe(
+                    move |mut trigger: Trigger<Pointer<Click>>,
+                          mut commands: Commands,
+                          mut menu: Single<&mut Visibility, With<ShuffleMenu>>| {
+                        // the click would reopen the menu on the shuffle button
+                        trigger.propagate(false);
+                        commands.send_event(shuffle);
+                        **menu = Visibility::Hidden;
+                    },
+                );
+            }
+        });
+}
+
+fn toggle_shuffle_menu(
+    _trigger: Trigger<Pointer<Click>>,
+    mut menu: Single<&mut Visibility, With<ShuffleMenu>>,
+) {
+    **menu = match **menu {
+        Visibility::Hidden => Visibility::Visible,
+        _ => Visibility::Hidden,
+    };
+}
+
 #[derive(Component)]
 pub struct MenuIcon;
 #[derive(Component)]
+pub struct ShuffleButton;
+#[derive(Component)]
 pub struct ZoomInButton;
 #[derive(Component)]
 pub struct ZoomOutButton;
@@ -836,20 +1758,68 @@ fn setup_generating_ui(
             let font = asset_server.load("fonts/MinecraftEvenings.ttf");
             let text_font = TextFont {
                 font: font.clone(),
-                font_size: 55.0,
+                font_size: 36.0,
                 ..default()
             };
 
+            spawn_fact_text(p, text_font);
             p.spawn((
-                Text::new("Loading pieces...."),
-                TextColor(Color::BLACK),
-                text_font,
+                Node {
+                    width: Val::Px(PROGRESS_BAR_WIDTH),
+                    height: Val::Px(16.0),
+                    margin: UiRect::all(Val::Px(10.0)),
+                    border: UiRect::all(Val::Px(2.0)),
+                    ..default()
+                },
+                BorderColor(Color::BLACK),
+                BorderRadius::MAX,
+                BackgroundColor(NORMAL_BUTTON),
+            ))
+            .with_child((
+                Node {
+                    width: Val::Percent(0.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                BorderRadius::MAX,
+                BackgroundColor(PRESSED_BUTTON),
+                ProgressFill,
             ));
             p.spawn((
                 Text::new(format!("0/{}", generator.pieces_count())),
                 TextColor(Color::BLACK),
+                ThemeColor::Text,
                 PieceCount,
             ));
+            spawn_tip_texts(p);
+            p.spawn((
+                Button,
+                Node {
+                    width: Val::Px(100.0),
+                    height: Val::Px(40.0),
+                    border: UiRect::all(Val::Px(5.0)),
+                    margin: UiRect::all(Val::Px(10.0)),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                BorderColor(Color::BLACK),
+                BorderRadius::MAX,
+                BackgroundColor(NORMAL_BUTTON),
+                ThemeColor::Button,
+            ))
+            .with_child((
+                Text::new("Cancel"),
+                Localized("Cancel"),
+                TextFont {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 22.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                ThemeColor::ButtonText,
+            ))
+            .observe(cancel_generating);
         });
 }
 #[derive(Component)]
@@ -868,7 +1838,8 @@ fn setup_pause_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                 justify_content: JustifyContent::Center,
                 ..default()
             },
-            BackgroundColor(Color::srgb_u8(149, 165, 166)),
+            BackgroundColor(Color::srgba_u8(149, 165, 166, 153)),
+            ThemeColor::PausePanel,
             OnPauseScreen,
         ))
         .observe(
@@ -884,10 +1855,18 @@ fn setup_pause_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                 ..default()
             };
 
-            p.spawn((Text::new("Paused"), TextColor(Color::BLACK), text_font));
+            p.spawn((
+                Text::new("Paused"),
+                Localized("Paused"),
+                TextColor(Color::BLACK),
+                ThemeColor::Text,
+                text_font,
+            ));
             p.spawn((
                 Text::new("click or press ESC to continue"),
+                Localized("click or press ESC to continue"),
                 TextColor(Color::BLACK),
+                ThemeColor::Text,
             ));
         });
 }
@@ -904,12 +1883,21 @@ fn back_to_game(
 #[derive(Component)]
 struct PieceCount;
 
+/// The filled part of the progress bar while generating
+#[derive(Component)]
+struct ProgressFill;
+
+const PROGRESS_BAR_WIDTH: f32 = 400.0;
+
 #[derive(Component)]
-struct OnPlayScreen;
+pub struct OnPlayScreen;
 
 fn setup_game_ui(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
+    difficulty: Res<Difficulty>,
+    relaxed: Res<Relaxed>,
+    mystery: Res<Mystery>,
     q_node: Query<Entity, With<MenuIcon>>,
 ) {
     if !q_node.is_empty() {
@@ -981,12 +1969,10 @@ fn setup_game_ui(
                                     ..default()
                                 },
                                 MenuIcon,
+                                ShuffleButton,
                             ))
-                            .observe(
-                                |_trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
-                                    commands.send_event(Shuffle::Edge);
-                                },
-                            );
+                            .with_children(spawn_shuffle_menu)
+                            .observe(toggle_shuffle_menu);
 
                         // zoom out button
                         builder
@@ -1032,6 +2018,24 @@ fn setup_game_ui(
                                         commands.send_event(AdjustScale(-0.1));
                                     },
                                 );
+
+                                // fit board button
+                                builder.spawn((
+                                    Text::new("Fit"),
+                                    Localized("Fit"),
+                                    Node {
+                                        margin: UiRect {
+                                            left: Val::Px(5.),
+                                            right: Val::Px(5.),
+                                            ..default()
+                                        },
+                                        ..default()
+                                    },
+                                )).observe(
+                                    |_trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+                                        commands.send_event(ZoomToFit::Board);
+                                    },
+                                );
                             });
                     });
 
@@ -1046,6 +2050,7 @@ fn setup_game_ui(
                             ..default()
                         },
                         IdeaButton,
+                        hint_visibility(difficulty.hints()),
                     ))
                     .observe(
                         |_trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
@@ -1102,7 +2107,7 @@ fn setup_game_ui(
                         })
                         .observe(
                             |_trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
-                                commands.send_event(ToggleEdgeHint);
+                                commands.send_event(CollectEdgePieces);
                             },
                         );
 
@@ -1115,12 +2120,37 @@ fn setup_game_ui(
                             ..default()
                         },
                         BackgroundHintButton,
+                        hint_visibility(difficulty.background_hint() && !mystery.0),
                     ))
                     .observe(
                         |_trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
                             commands.send_event(ToggleBackgroundHint);
                         },
                     );
+
+                    // color groups hint
+                    p.spawn((
+                        Text::new("Colors"),
+                        Localized("Colors"),
+                        Node {
+                            align_self: AlignSelf::Center,
+                            margin: UiRect::axes(Val::Px(5.), Val::Px(5.)),
+                            ..default()
+                        },
+                        hint_visibility(difficulty.hints()),
+                    ))
+                    .observe(
+                        |_trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+                            commands.send_event(ShowColorGroups);
+                        },
+                    );
+
+                    // style of the cut lines
+                    spawn_stroke_button(p);
+                    spawn_clock_button(p);
+                    spawn_staging_button(p);
+                    #[cfg(not(target_arch = "wasm32"))]
+                    crate::save_file::spawn_export_button(p);
                 });
             })
             .id();
@@ -1165,7 +2195,7 @@ fn setup_game_ui(
                         },
                         ImageNode::new(asset_server.load("icons/photo.png")),
                         HintImageButton,
-                        Visibility::Visible,
+                        hint_visibility(difficulty.hints() && !mystery.0),
                     ))
                     .observe(hint_image_click);
                 });
@@ -1176,6 +2206,8 @@ fn setup_game_ui(
                     Text::new("00:00:00"),
                     TextColor(GREEN.into()),
                     TimerText,
+                    Interaction::default(),
+                    hint_visibility(!relaxed.0),
                     Node {
                         margin: UiRect {
                             top: Val::Px(7.0),
@@ -1215,8 +2247,11 @@ fn setup_game_ui(
                     FullscreenButton,
                 ))
                 .observe(
-                    |_trigger: Trigger<Pointer<Click>>, mut window: Single<&mut Window>| {
-                        window.mode = WindowMode::Fullscreen(MonitorSelection::Current);
+                    |_trigger: Trigger<Pointer<Click>>,
+                     mut window: Single<&mut Window>,
+                     fullscreen_monitor: Res<FullscreenMonitor>,
+                     monitors: Query<(Entity, &Monitor)>| {
+                        window.mode = fullscreen_monitor.mode(&monitors);
                     },
                 );
             });
@@ -1229,53 +2264,19 @@ fn setup_game_ui(
     commands.send_event(Shuffle::Random);
 }
 
+/// The clock, [`crate::clock`] writes it
 #[derive(Component)]
-struct TimerText;
+pub(crate) struct TimerText;
 
 #[derive(Component)]
 pub struct BoardBackgroundImage;
 
-/// Adjust the camera to fit the image
-fn adjust_camera_on_added_sprite(
-    _sprite: Single<Entity, Added<BoardBackgroundImage>>,
-    mut camera_2d: Single<&mut OrthographicProjection, (With<Camera2d>, With<IsDefaultUiCamera>)>,
-    window: Single<&Window>,
-    generator: Res<JigsawPuzzleGenerator>,
-) {
-    let window_width = window.resolution.width();
-    let image_width = generator.origin_image().width() as f32;
-    let scale = image_width / window_width;
-    let target_scale = scale / 0.6;
-    camera_2d.scale = target_scale;
-}
-
+/// Zooms the camera toward the cursor, by a share of the current scale
 #[derive(Event)]
 pub struct AdjustScale(pub f32);
 
-const MAX_SCALE: f32 = 3.0;
-const MIN_SCALE: f32 = 0.5;
-
-/// Adjust the camera scale on event
-fn adjust_camera_scale(
-    mut event: EventReader<AdjustScale>,
-    mut camera_2d: Single<&mut OrthographicProjection, (With<Camera2d>, With<IsDefaultUiCamera>)>,
-) {
-    for AdjustScale(scale) in event.read() {
-        let new_scale = camera_2d.scale + scale;
-        debug!("new scale: {}", new_scale);
-        if (MIN_SCALE..=MAX_SCALE).contains(&new_scale) {
-            camera_2d.scale = new_scale;
-        }
-    }
-}
-
-fn update_game_time(
-    mut game_timer: ResMut<GameTimer>,
-    time: Res<Time>,
-    mut text: Single<&mut Text, With<TimerText>>,
-) {
+fn update_game_time(mut game_timer: ResMut<GameTimer>, time: Res<Time>) {
     game_timer.tick(time.delta());
-    text.0 = game_timer.to_string();
 }
 
 fn handle_keyboard_input(
@@ -1303,20 +2304,45 @@ fn handle_keyboard_input(
 fn handle_mouse_wheel_input(
     mut mouse_wheel_input: EventReader<MouseWheel>,
     mut commands: Commands,
+    hovered_tray: Res<HoveredTray>,
 ) {
+    // the wheel scrolls the tray below the cursor
+    if hovered_tray.is_some() {
+        return;
+    }
     for event in mouse_wheel_input.read() {
         commands.send_event(AdjustScale(event.y * 0.1));
     }
 }
 
+/// Hint buttons are hidden when the difficulty doesn't allow the hint
+fn hint_visibility(allowed: bool) -> Visibility {
+    if allowed {
+        Visibility::Visible
+    } else {
+        Visibility::Hidden
+    }
+}
+
 #[derive(Event)]
 pub struct ToggleBackgroundHint;
 
 fn handle_toggle_background_hint(
     mut event: EventReader<ToggleBackgroundHint>,
+    difficulty: Res<Difficulty>,
+    mystery: Res<Mystery>,
+    mut budget: ResMut<HintBudget>,
     mut query: Query<&mut Visibility, With<BoardBackgroundImage>>,
 ) {
     for _ in event.read() {
+        if !difficulty.background_hint() || mystery.0 {
+            continue;
+        }
+        // hiding the background again is free
+        let revealing = query.iter().any(|visible| *visible == Visibility::Hidden);
+        if revealing && !budget.spend(HintKind::Background) {
+            continue;
+        }
         for mut visible in query.iter_mut() {
             visible.toggle_visible_hidden();
         }
@@ -1328,13 +2354,23 @@ pub struct TogglePuzzleHint;
 
 fn handle_toggle_puzzle_hint(
     mut event: EventReader<TogglePuzzleHint>,
+    difficulty: Res<Difficulty>,
+    mut budget: ResMut<HintBudget>,
     selected_query: Query<Entity, With<Selected>>,
     piece_query: Query<(Entity, &Piece, &MoveTogether), Without<Selected>>,
     mut commands: Commands,
 ) {
     for _ in event.read() {
-        for entity in selected_query.iter() {
-            commands.entity(entity).remove::<Selected>();
+        if !difficulty.hints() {
+            continue;
+        }
+        // with pieces selected the hint shows where they go
+        let selected: Vec<Entity> = selected_query.iter().collect();
+        if !selected.is_empty() {
+            if budget.spend(HintKind::Location) {
+                commands.trigger(ShowLocation(selected));
+            }
+            continue;
         }
         let mut first_piece = None;
         let mut first_entity = None;
@@ -1359,6 +2395,9 @@ fn handle_toggle_puzzle_hint(
             }
         }
         if let (Some(first_entity), Some(second_entity)) = (first_entity, second_entity) {
+            if !budget.spend(HintKind::MatchingPieces) {
+                continue;
+            }
             commands.entity(first_entity).insert(Selected);
             commands.entity(second_entity).insert(Selected);
         }
@@ -1375,35 +2414,7 @@ fn exit_fullscreen_on_esc(mut window: Single<&mut Window>, input: Res<ButtonInpu
     }
 }
 
-#[derive(Event)]
-pub struct ToggleEdgeHint;
-
-fn handle_puzzle_hint(
-    mut event: EventReader<ToggleEdgeHint>,
-    mut piece_query: Query<(&Piece, &mut Visibility), Without<PuzzleHintChildButton>>,
-    mut ui: Single<&mut Visibility, With<PuzzleHintChildButton>>,
-    mut show_all: Local<bool>,
-) {
-    for _ in event.read() {
-        ui.toggle_visible_hidden();
-        if *show_all {
-            for (_, mut visibility) in piece_query.iter_mut() {
-                *visibility = Visibility::Visible;
-            }
-        } else {
-            for (piece, mut visibility) in piece_query.iter_mut() {
-                if piece.is_boarder() {
-                    *visibility = Visibility::Visible;
-                } else {
-                    *visibility = Visibility::Hidden;
-                }
-            }
-        }
-
-        *show_all = !*show_all;
-    }
-}
-
+#[allow(clippy::too_many_arguments)]
 fn hint_image_click(
     _trigger: Trigger<Pointer<Click>>,
     mut commands: Commands,
@@ -1416,15 +2427,21 @@ fn hint_image_click(
         ),
     >,
     small_hint_image: Single<Entity, With<SmallHintImage>>,
-    origin_image: Res<OriginImage>,
+    preview_image: Res<PreviewImage>,
+    difficulty: Res<Difficulty>,
+    mystery: Res<Mystery>,
+    mut budget: ResMut<HintBudget>,
 ) {
+    if !difficulty.hints() || mystery.0 || !budget.spend(HintKind::Image) {
+        return;
+    }
     hint_visible.toggle_visible_hidden();
     // let aspect_ratio = origin_image.size.x / origin_image.size.y;
 
     commands
         .entity(*small_hint_image)
         .insert((
-            ImageNode::new(origin_image.0.clone()),
+            ImageNode::new(preview_image.0.clone()),
             Node {
                 width: Val::Px(400.0),
                 // aspect_ratio: Some(aspect_ratio),
@@ -0,0 +1,203 @@
+//! Guided assembly: only the frame is handed out at first, its outline is drawn where the puzzle
+//! goes, and once everything on the table forms one group the inner pieces follow in waves, one
+//! region of the puzzle at a time.
+
+use crate::gameplay::{edge_position, spawn_pieces, MoveTogether, OnPlayScreen};
+use crate::i18n::Language;
+use crate::layering::Restack;
+use crate::table::Table;
+use crate::theme::ThemeColor;
+use crate::{GameState, Piece, SelectPlayMode};
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use jigsaw_puzzle_generator::image::GenericImageView;
+use jigsaw_puzzle_generator::{JigsawPiece, JigsawTemplate};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Generating), clear_guided_assembly)
+        .add_systems(
+            OnEnter(GameState::Play),
+            setup_guided_text.run_if(resource_exists::<GuidedAssembly>),
+        )
+        .add_systems(
+            Update,
+            (draw_frame_outline, release_next_wave, update_guided_text)
+                .chain()
+                .run_if(resource_exists::<GuidedAssembly>)
+                .run_if(in_state(GameState::Play)),
+        );
+}
+
+/// Regions per side the inner pieces are split into, each region is one wave
+const WAVE_SPLITS: f32 = 2.0;
+const FRAME_OUTLINE_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.3);
+
+/// The pieces which are not on the table yet, and the ones handed out last
+#[derive(Resource)]
+pub struct GuidedAssembly {
+    /// Size of the puzzle image
+    image_size: Vec2,
+    /// Inner pieces of the regions still to come, the next one first
+    waves: Vec<Vec<JigsawPiece>>,
+    /// The pieces of the wave handed out last
+    current: Vec<Entity>,
+    /// Waves handed out so far, the frame doesn't count
+    released: usize,
+    total: usize,
+}
+
+impl GuidedAssembly {
+    /// Splits the inner pieces of the template into waves by region, the frame comes first
+    pub fn new(template: &JigsawTemplate) -> Self {
+        let (width, height) = template.origin_image.dimensions();
+        let region = |piece: &JigsawPiece| {
+            let column = (piece.start_point.0 / width as f32 * WAVE_SPLITS) as usize;
+            let row = (piece.start_point.1 / height as f32 * WAVE_SPLITS) as usize;
+            row * WAVE_SPLITS as usize + column
+        };
+        let regions = (WAVE_SPLITS * WAVE_SPLITS) as usize;
+        let mut waves = vec![Vec::new(); regions];
+        for piece in template.pieces.iter().filter(|piece| !piece.is_boarder) {
+            waves[region(piece).min(regions - 1)].push(piece.clone());
+        }
+        waves.retain(|wave| !wave.is_empty());
+
+        GuidedAssembly {
+            image_size: Vec2::new(width as f32, height as f32),
+            total: waves.len(),
+            waves,
+            current: Vec::new(),
+            released: 0,
+        }
+    }
+
+    fn frame_complete(&self) -> bool {
+        self.released > 0
+    }
+}
+
+#[derive(Component)]
+struct GuidedText;
+
+fn clear_guided_assembly(mut commands: Commands, play_mode: Res<SelectPlayMode>) {
+    // the resource of a guided game is inserted while its pieces spawn
+    if *play_mode != SelectPlayMode::Guided {
+        commands.remove_resource::<GuidedAssembly>();
+    }
+}
+
+fn setup_guided_text(mut commands: Commands, q_text: Query<Entity, With<GuidedText>>) {
+    if !q_text.is_empty() {
+        return;
+    }
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(56.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            PickingBehavior::IGNORE,
+            OnPlayScreen,
+        ))
+        .with_child((
+            Text::default(),
+            TextFont {
+                font_size: 18.0,
+                ..default()
+            },
+            TextColor(Color::BLACK),
+            ThemeColor::Text,
+            GuidedText,
+        ));
+}
+
+/// Outlines the frame where the puzzle goes until the frame is complete
+fn draw_frame_outline(mut gizmos: Gizmos, guided: Res<GuidedAssembly>, pieces: Query<&Piece>) {
+    if guided.frame_complete() {
+        return;
+    }
+    let size = guided.image_size;
+    gizmos.rect_2d(Isometry2d::IDENTITY, size, FRAME_OUTLINE_COLOR);
+    if let Some(piece) = pieces.iter().next() {
+        let inner = size - Vec2::new(piece.width, piece.height) * 2.0;
+        gizmos.rect_2d(Isometry2d::IDENTITY, inner, FRAME_OUTLINE_COLOR);
+    }
+}
+
+/// The pieces on the table which belong to the largest group
+fn assembled(pieces: &Query<(Entity, &MoveTogether), With<Piece>>) -> HashSet<Entity> {
+    pieces
+        .iter()
+        .map(|(_, together)| &together.0)
+        .max_by_key(|together| together.len())
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// Hands out the next wave once every piece on the table is joined into one group
+fn release_next_wave(
+    mut commands: Commands,
+    mut guided: ResMut<GuidedAssembly>,
+    changed: Query<(), Changed<MoveTogether>>,
+    pieces: Query<(Entity, &MoveTogether), With<Piece>>,
+    window: Single<&Window>,
+    camera: Single<&OrthographicProjection, (With<Camera2d>, With<IsDefaultUiCamera>)>,
+    table: Res<Table>,
+) {
+    if changed.is_empty() || guided.waves.is_empty() {
+        return;
+    }
+    if pieces.is_empty() || assembled(&pieces).len() != pieces.iter().count() {
+        return;
+    }
+
+    let wave = guided.waves.remove(0);
+    // around the edges of the table, so the assembled frame stays clear
+    let spread = table.spread_size(window.resolution.size(), camera.scale);
+    let entities = spawn_pieces(&mut commands, &wave, |piece| {
+        edge_position(piece, spread, camera.scale)
+    });
+    commands.trigger(Restack::Front(entities.iter().copied().collect()));
+    guided.current = entities;
+    guided.released += 1;
+}
+
+fn update_guided_text(
+    guided: Res<GuidedAssembly>,
+    language: Res<Language>,
+    pieces: Query<(Entity, &MoveTogether), With<Piece>>,
+    changed: Query<(), Changed<MoveTogether>>,
+    added: Query<(), Added<GuidedText>>,
+    mut texts: Query<&mut Text, With<GuidedText>>,
+) {
+    if changed.is_empty() && added.is_empty() {
+        return;
+    }
+    let assembled = assembled(&pieces);
+    let label = if guided.frame_complete() {
+        let placed = guided
+            .current
+            .iter()
+            .filter(|entity| assembled.contains(*entity))
+            .count();
+        format!(
+            "{} {}/{}: {}/{}",
+            language.tr("Wave"),
+            guided.released,
+            guided.total,
+            placed,
+            guided.current.len()
+        )
+    } else {
+        let frame = pieces.iter().count();
+        format!("{}: {}/{}", language.tr("Frame"), assembled.len(), frame)
+    };
+    for mut text in texts.iter_mut() {
+        if text.0 != label {
+            text.0 = label.clone();
+        }
+    }
+}
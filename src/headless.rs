@@ -0,0 +1,103 @@
+//! Runs the snapping of the pieces without a window or rendering, so merging and finishing a
+//! puzzle can be tested with `MinimalPlugins`. Pieces only carry their shape, no images, and are
+//! dropped with [`drop_piece`] instead of the pointer.
+
+use crate::events;
+use crate::gameplay::{
+    combine_together, init_position, on_move_end, JigsawPuzzleGenerator, JigsawPuzzleTemplate,
+    MoveEnd, MoveTogether,
+};
+use crate::staging::StagingGrid;
+use crate::tray::HoveredTray;
+use crate::{AppState, GameState, Piece, SelectPlayMode, SnapTolerance};
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use jigsaw_puzzle_generator::image::GenericImageView;
+use jigsaw_puzzle_generator::{GameMode, JigsawGenerator};
+
+/// The game logic without the UI, the app has to provide the `MinimalPlugins`
+pub struct HeadlessPuzzlePlugin;
+
+impl Plugin for HeadlessPuzzlePlugin {
+    fn build(&self, app: &mut App) {
+        if !app.is_plugin_added::<StatesPlugin>() {
+            app.add_plugins(StatesPlugin);
+        }
+        app.init_resource::<SelectPlayMode>()
+            .init_resource::<SnapTolerance>()
+            .init_resource::<HoveredTray>()
+            .init_resource::<StagingGrid>()
+            .init_state::<AppState>()
+            .init_state::<GameState>()
+            .add_plugins(events::plugin)
+            .add_observer(combine_together);
+    }
+}
+
+/// Generates the puzzle and spawns all pieces at their place in the finished puzzle, unmerged.
+/// Returns the entity of every piece by its index, the game starts at the next update.
+pub fn spawn_puzzle(
+    world: &mut World,
+    generator: JigsawGenerator,
+    game_mode: GameMode,
+) -> Result<Vec<Entity>, String> {
+    let template = generator
+        .generate(game_mode, false)
+        .map_err(|err| err.to_string())?;
+    let image_size = template.origin_image.dimensions();
+    let entities = template
+        .pieces
+        .iter()
+        .map(|piece| {
+            let position = init_position(piece, image_size);
+            world
+                .spawn((
+                    Piece(piece.clone()),
+                    MoveTogether::default(),
+                    Transform::from_xyz(position.x, position.y, piece.index as f32),
+                ))
+                .observe(on_move_end)
+                .id()
+        })
+        .collect();
+
+    world.insert_resource(JigsawPuzzleGenerator(generator));
+    world.insert_resource(JigsawPuzzleTemplate(template));
+    world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Gameplay);
+    world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Play);
+    Ok(entities)
+}
+
+/// Moves a piece together with the pieces merged with it, so its translation is at `position`,
+/// and drops it there. The snap, the merge and the finish are applied before it returns, so
+/// pieces can be dropped one after another, the state changes at the next update
+pub fn drop_piece(world: &mut World, piece: Entity, position: Vec2) {
+    let Some(translation) = world.get::<Transform>(piece).map(|t| t.translation) else {
+        return;
+    };
+    let offset = position.extend(translation.z) - translation;
+    let mut moved: Vec<Entity> = world
+        .get::<MoveTogether>(piece)
+        .map(|together| {
+            together
+                .iter()
+                .copied()
+                .filter(|other| *other != piece)
+                .collect()
+        })
+        .unwrap_or_default();
+    moved.push(piece);
+    for entity in moved {
+        if let Some(mut transform) = world.get_mut::<Transform>(entity) {
+            transform.translation += offset;
+        }
+    }
+
+    world.trigger_targets(MoveEnd, piece);
+    // the snap queues the merge and the finish as commands
+    world.flush();
+}
@@ -0,0 +1,197 @@
+//! Hints cost charges, which come back over time and with every few snapped pieces. The
+//! difficulty decides how many charges there are and how fast they return.
+
+use crate::board::Locked;
+use crate::gameplay::{CombineTogether, OnPlayScreen};
+use crate::i18n::Language;
+use crate::relaxed::Relaxed;
+use crate::theme::ThemeColor;
+use crate::{Difficulty, GameState};
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<HintBudget>()
+        .add_systems(OnEnter(GameState::Generating), reset_budget)
+        .add_systems(OnEnter(GameState::Play), setup_budget_text)
+        .add_systems(
+            Update,
+            (regenerate_charges, update_budget_text)
+                .chain()
+                .run_if(in_state(GameState::Play)),
+        )
+        .add_observer(count_snaps)
+        .add_observer(count_locks);
+}
+
+/// Snapped or locked pieces which earn a charge back
+const SNAPS_PER_CHARGE: u32 = 10;
+
+/// The kinds of hints and what they cost
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintKind {
+    /// Two matching pieces are selected
+    MatchingPieces,
+    /// The solved location of the selected pieces flashes
+    Location,
+    /// The solved image shown behind the board
+    Background,
+    /// The small image of the solved puzzle
+    Image,
+    /// The loose pieces are tinted by their colors
+    Colors,
+    /// A few of the hardest pieces are put at their solved place
+    AutoPlace,
+}
+
+impl HintKind {
+    pub fn cost(&self) -> u32 {
+        match self {
+            HintKind::MatchingPieces | HintKind::Location | HintKind::Colors => 1,
+            HintKind::Background | HintKind::Image => 2,
+            HintKind::AutoPlace => 3,
+        }
+    }
+}
+
+/// The hint charges left in the current game
+#[derive(Resource, Debug)]
+pub struct HintBudget {
+    pub charges: u32,
+    pub max: u32,
+    /// Time until the next charge returns, `None` when they don't return over time
+    regenerate: Option<Timer>,
+    snaps: u32,
+}
+
+impl Default for HintBudget {
+    fn default() -> Self {
+        HintBudget::from_difficulty(Difficulty::default())
+    }
+}
+
+impl HintBudget {
+    pub fn from_difficulty(difficulty: Difficulty) -> Self {
+        let (max, seconds) = match difficulty {
+            Difficulty::Easy => (10, Some(20.0)),
+            Difficulty::Normal => (5, Some(60.0)),
+            Difficulty::Hard => (3, Some(120.0)),
+            Difficulty::Expert => (0, None),
+        };
+        HintBudget {
+            charges: max,
+            max,
+            regenerate: seconds.map(|seconds| Timer::from_seconds(seconds, TimerMode::Repeating)),
+            snaps: 0,
+        }
+    }
+
+    /// Takes the charges of the hint, returns false when there are not enough left
+    pub fn spend(&mut self, hint: HintKind) -> bool {
+        if self.charges < hint.cost() {
+            return false;
+        }
+        self.charges -= hint.cost();
+        true
+    }
+
+    fn earn(&mut self) {
+        self.charges = (self.charges + 1).min(self.max);
+    }
+
+    fn is_full(&self) -> bool {
+        self.charges >= self.max
+    }
+}
+
+#[derive(Component)]
+struct HintBudgetText;
+
+fn reset_budget(mut budget: ResMut<HintBudget>, difficulty: Res<Difficulty>) {
+    *budget = HintBudget::from_difficulty(*difficulty);
+}
+
+fn setup_budget_text(
+    mut commands: Commands,
+    difficulty: Res<Difficulty>,
+    q_text: Query<Entity, With<HintBudgetText>>,
+) {
+    if !q_text.is_empty() || !difficulty.hints() {
+        return;
+    }
+    commands.spawn((
+        Text::default(),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        TextColor(Color::BLACK),
+        ThemeColor::Text,
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(10.0),
+            bottom: Val::Px(60.0),
+            ..default()
+        },
+        PickingBehavior::IGNORE,
+        HintBudgetText,
+        OnPlayScreen,
+    ));
+}
+
+fn regenerate_charges(time: Res<Time>, mut budget: ResMut<HintBudget>) {
+    if budget.is_full() {
+        return;
+    }
+    let Some(timer) = budget.regenerate.as_mut() else {
+        return;
+    };
+    if timer.tick(time.delta()).just_finished() {
+        budget.earn();
+    }
+}
+
+fn earn_by_snaps(budget: &mut HintBudget, count: u32) {
+    budget.snaps += count;
+    while budget.snaps >= SNAPS_PER_CHARGE {
+        budget.snaps -= SNAPS_PER_CHARGE;
+        budget.earn();
+    }
+}
+
+fn count_snaps(trigger: Trigger<CombineTogether>, mut budget: ResMut<HintBudget>) {
+    // every snap merges at least the dropped piece and one other
+    if trigger.event().0.len() >= 2 {
+        earn_by_snaps(&mut budget, 1);
+    }
+}
+
+fn count_locks(_trigger: Trigger<OnAdd, Locked>, mut budget: ResMut<HintBudget>) {
+    earn_by_snaps(&mut budget, 1);
+}
+
+fn update_budget_text(
+    budget: Res<HintBudget>,
+    language: Res<Language>,
+    relaxed: Res<Relaxed>,
+    mut texts: Query<&mut Text, With<HintBudgetText>>,
+) {
+    let mut label = format!(
+        "{}: {}/{}",
+        language.tr("Hints"),
+        budget.charges,
+        budget.max
+    );
+    // the countdown is time pressure as well
+    if let Some(timer) = budget
+        .regenerate
+        .as_ref()
+        .filter(|_| !budget.is_full() && !relaxed.0)
+    {
+        label.push_str(&format!(" (+1 {:.0}s)", timer.remaining_secs().ceil()));
+    }
+    for mut text in texts.iter_mut() {
+        if text.0 != label {
+            text.0 = label.clone();
+        }
+    }
+}
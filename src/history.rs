@@ -0,0 +1,178 @@
+use crate::gameplay::{MoveStart, MoveTogether, ScatterTween, SnapTween};
+use crate::tray::InTray;
+use crate::{GameState, Piece};
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use log::debug;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<MoveHistory>()
+        .add_event::<Undo>()
+        .add_event::<Redo>()
+        .add_systems(OnEnter(GameState::Generating), reset_history)
+        .add_systems(
+            Update,
+            (handle_history_keys, handle_undo, handle_redo)
+                .chain()
+                .run_if(in_state(GameState::Play)),
+        )
+        .add_systems(PostUpdate, record_history.run_if(in_state(GameState::Play)));
+}
+
+/// The maximum number of steps that can be undone
+const MAX_HISTORY: usize = 100;
+
+/// The position of every piece and the groups they belong to at one point in time
+#[derive(Debug, Clone, Default)]
+pub struct BoardSnapshot {
+    translations: Vec<(Entity, Vec3)>,
+    groups: Vec<HashSet<Entity>>,
+}
+
+impl BoardSnapshot {
+    fn capture<'a>(
+        pieces: impl Iterator<Item = (Entity, &'a Transform, &'a MoveTogether)>,
+    ) -> Self {
+        let mut snapshot = BoardSnapshot::default();
+        let mut grouped = HashSet::default();
+        for (entity, transform, move_together) in pieces {
+            snapshot.translations.push((entity, transform.translation));
+            if !move_together.is_empty() && !grouped.contains(&entity) {
+                grouped.extend(move_together.iter().copied());
+                snapshot.groups.push(move_together.0.clone());
+            }
+        }
+        snapshot
+    }
+
+    fn restore(
+        &self,
+        pieces: &mut Query<(Entity, &mut Transform, &mut MoveTogether), With<Piece>>,
+    ) {
+        for (entity, translation) in self.translations.iter() {
+            if let Ok((_, mut transform, mut move_together)) = pieces.get_mut(*entity) {
+                transform.translation = *translation;
+                move_together.0.clear();
+            }
+        }
+        for group in self.groups.iter() {
+            for entity in group.iter() {
+                if let Ok((_, _, mut move_together)) = pieces.get_mut(*entity) {
+                    move_together.0 = group.clone();
+                }
+            }
+        }
+    }
+}
+
+/// Undo and redo stacks of board snapshots
+#[derive(Resource, Debug, Default)]
+pub struct MoveHistory {
+    undo: Vec<BoardSnapshot>,
+    redo: Vec<BoardSnapshot>,
+    /// The snapshot of the board after the last recorded change
+    current: Option<BoardSnapshot>,
+    /// Skip the next recording because the change came from an undo or redo
+    restoring: bool,
+}
+
+#[derive(Event)]
+pub struct Undo;
+
+#[derive(Event)]
+pub struct Redo;
+
+fn reset_history(mut history: ResMut<MoveHistory>) {
+    *history = MoveHistory::default();
+}
+
+fn handle_history_keys(keyboard_input: Res<ButtonInput<KeyCode>>, mut commands: Commands) {
+    if !keyboard_input.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]) {
+        return;
+    }
+    let shift = keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+    if keyboard_input.just_pressed(KeyCode::KeyZ) && !shift {
+        commands.send_event(Undo);
+    } else if keyboard_input.just_pressed(KeyCode::KeyY)
+        || (keyboard_input.just_pressed(KeyCode::KeyZ) && shift)
+    {
+        commands.send_event(Redo);
+    }
+}
+
+/// Record a new snapshot once pieces have been moved, snapped or shuffled. Nothing is recorded
+/// while pieces are dragged or fly to their place, so an animation is a single step once it
+/// ends
+fn record_history(
+    mut history: ResMut<MoveHistory>,
+    moving: Query<(), Or<(With<MoveStart>, With<SnapTween>, With<ScatterTween>)>>,
+    changed: Query<
+        (),
+        (
+            With<Piece>,
+            Without<InTray>,
+            Or<(Changed<Transform>, Changed<MoveTogether>)>,
+        ),
+    >,
+    pieces: Query<(Entity, &Transform, &MoveTogether), With<Piece>>,
+) {
+    if changed.is_empty() || !moving.is_empty() {
+        return;
+    }
+    if history.restoring {
+        history.restoring = false;
+        return;
+    }
+
+    let snapshot = BoardSnapshot::capture(pieces.iter());
+    if let Some(previous) = history.current.replace(snapshot) {
+        history.undo.push(previous);
+        if history.undo.len() > MAX_HISTORY {
+            history.undo.remove(0);
+        }
+        history.redo.clear();
+        debug!("record history, {} steps to undo", history.undo.len());
+    }
+}
+
+fn handle_undo(
+    mut events: EventReader<Undo>,
+    mut history: ResMut<MoveHistory>,
+    moving: Query<(), With<MoveStart>>,
+    mut pieces: Query<(Entity, &mut Transform, &mut MoveTogether), With<Piece>>,
+) {
+    for _ in events.read() {
+        if !moving.is_empty() {
+            continue;
+        }
+        let Some(previous) = history.undo.pop() else {
+            continue;
+        };
+        previous.restore(&mut pieces);
+        if let Some(current) = history.current.replace(previous) {
+            history.redo.push(current);
+        }
+        history.restoring = true;
+    }
+}
+
+fn handle_redo(
+    mut events: EventReader<Redo>,
+    mut history: ResMut<MoveHistory>,
+    moving: Query<(), With<MoveStart>>,
+    mut pieces: Query<(Entity, &mut Transform, &mut MoveTogether), With<Piece>>,
+) {
+    for _ in events.read() {
+        if !moving.is_empty() {
+            continue;
+        }
+        let Some(next) = history.redo.pop() else {
+            continue;
+        };
+        next.restore(&mut pieces);
+        if let Some(current) = history.current.replace(next) {
+            history.undo.push(current);
+        }
+        history.restoring = true;
+    }
+}
@@ -0,0 +1,272 @@
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Language>().add_systems(
+        Update,
+        (
+            update_localized_texts.run_if(resource_changed::<Language>),
+            localize_added_texts,
+        ),
+    );
+}
+
+/// The language of the user interface. Texts are looked up by their English version, which
+/// is shown if a translation is missing.
+#[derive(Debug, Resource, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    #[default]
+    English,
+    Indonesian,
+}
+
+impl core::fmt::Display for Language {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Language::English => "English",
+                Language::Indonesian => "Bahasa Indonesia",
+            }
+        )
+    }
+}
+
+/// English texts and their Indonesian translations
+const INDONESIAN: &[(&str, &str)] = &[
+    ("Jigsaw Puzzle", "Puzzle Jigsaw"),
+    ("pieces", "keping"),
+    ("Loose", "Lepas"),
+    ("Groups", "Kelompok"),
+    ("Largest", "Terbesar"),
+    ("Start", "Mulai"),
+    ("Classic", "Klasik"),
+    ("Square", "Persegi"),
+    ("Triangular", "Segitiga"),
+    ("Crazy", "Acak"),
+    ("Solo", "Solo"),
+    ("Versus", "Lawan"),
+    ("Free", "Bebas"),
+    ("Board", "Papan"),
+    ("Guided", "Terpandu"),
+    ("Frame", "Bingkai"),
+    ("Wave", "Gelombang"),
+    ("Tutorial", "Tutorial"),
+    ("Skip tutorial", "Lewati tutorial"),
+    (
+        "Drag a piece to move it",
+        "Seret kepingan untuk memindahkannya",
+    ),
+    (
+        "Drop a piece next to its neighbor to snap them together",
+        "Letakkan kepingan di samping tetangganya agar menyatu",
+    ),
+    (
+        "Scroll or click the zoom buttons to zoom",
+        "Gulir atau klik tombol zoom untuk memperbesar",
+    ),
+    (
+        "Click the arrows to spread the pieces around the table",
+        "Klik panah untuk menyebarkan kepingan di meja",
+    ),
+    (
+        "The lamp and the ghost show hints when you are stuck",
+        "Lampu dan hantu memberi petunjuk saat kamu buntu",
+    ),
+    (
+        "Put all pieces together to finish the puzzle",
+        "Satukan semua kepingan untuk menyelesaikan puzzle",
+    ),
+    ("Easy", "Mudah"),
+    ("Normal", "Normal"),
+    ("Strict", "Ketat"),
+    ("Hard", "Sulit"),
+    ("Expert", "Ahli"),
+    ("Auto grid", "Grid otomatis"),
+    ("Custom grid", "Grid kustom"),
+    ("on the edge", "di tepi"),
+    ("inside", "di dalam"),
+    (
+        "joins to find between the pieces",
+        "sambungan untuk ditemukan antar keping",
+    ),
+    ("Time left:", "Sisa waktu:"),
+    ("Tip:", "Tips:"),
+    (
+        "Start with the edge pieces, the frame holds the rest together",
+        "Mulailah dengan keping tepi, bingkai menyatukan sisanya",
+    ),
+    (
+        "Press T to lay out the loose pieces, Shift + T sorts them by color",
+        "Tekan T untuk menata keping lepas, Shift + T mengurutkannya menurut warna",
+    ),
+    (
+        "Press H when you are stuck to see two pieces which fit",
+        "Tekan H saat kamu buntu untuk melihat dua keping yang cocok",
+    ),
+    (
+        "Press / and type a number to find a piece",
+        "Tekan / dan ketik angka untuk menemukan keping",
+    ),
+    (
+        "Press Space to peek at the image",
+        "Tekan Spasi untuk mengintip gambar",
+    ),
+    (
+        "Drag on the empty table to move several pieces together",
+        "Seret di meja kosong untuk memindahkan beberapa keping sekaligus",
+    ),
+    (
+        "Drop pieces on the tray to keep them apart",
+        "Letakkan keping di baki untuk memisahkannya",
+    ),
+    (
+        "Press F to fit the board into view",
+        "Tekan F untuk menampilkan seluruh papan",
+    ),
+    ("Cancel", "Batal"),
+    ("Paused", "Dijeda"),
+    (
+        "click or press ESC to continue",
+        "klik atau tekan ESC untuk melanjutkan",
+    ),
+    ("Finish", "Selesai"),
+    ("Use time", "Waktu"),
+    ("Menu", "Menu"),
+    ("Again", "Lagi"),
+    ("Replay", "Putar ulang"),
+    ("Theme", "Tema"),
+    ("UI scale", "Skala UI"),
+    ("Use photo", "Gunakan foto"),
+    ("Take photo", "Ambil foto"),
+    ("Close", "Tutup"),
+    ("Failed to take a photo", "Gagal mengambil foto"),
+    (
+        "This build can not take photos, build it with the camera feature",
+        "Versi ini tidak dapat mengambil foto, buat dengan fitur camera",
+    ),
+    ("Full", "Penuh"),
+    ("Surprise me", "Kejutkan aku"),
+    ("Regenerate", "Buat ulang"),
+    ("Shuffle", "Acak"),
+    ("Quick play", "Main cepat"),
+    ("Rotate", "Putar"),
+    ("Crop", "Potong"),
+    ("Apply", "Terapkan"),
+    ("Fit grid", "Sesuaikan kisi"),
+    ("New cut", "Potongan baru"),
+    ("Relaxed", "Santai"),
+    ("Mystery", "Misteri"),
+    ("Fullscreen on", "Layar penuh di"),
+    ("Current monitor", "Monitor saat ini"),
+    ("Mystery solved!", "Misteri terpecahkan!"),
+    ("Advanced", "Lanjutan"),
+    ("Knob size", "Ukuran tonjolan"),
+    ("Filters", "Filter"),
+    ("Grayscale", "Hitam putih"),
+    ("Sepia", "Sepia"),
+    ("Posterize", "Posterisasi"),
+    ("Blurry preview", "Pratinjau buram"),
+    ("Difficulty score", "Skor kesulitan"),
+    ("Irregularity", "Ketidakteraturan"),
+    ("Solved", "Selesai"),
+    ("Solved at your own pace", "Diselesaikan dengan santai"),
+    ("Upload image", "Unggah gambar"),
+    ("Colors", "Warna"),
+    ("Hints", "Petunjuk"),
+    ("Light", "Terang"),
+    ("Dark", "Gelap"),
+    ("Save picture", "Simpan gambar"),
+    ("Cut lines", "Garis potong"),
+    ("Lines", "Garis"),
+    ("Grid", "Kisi"),
+    ("Export", "Ekspor"),
+    ("Import", "Impor"),
+    ("White", "Putih"),
+    ("Black", "Hitam"),
+    ("Image", "Gambar"),
+    ("None", "Tidak ada"),
+    ("On", "Aktif"),
+    ("Off", "Mati"),
+    ("Saved", "Tersimpan"),
+    ("Could not save the picture", "Gambar tidak bisa disimpan"),
+    ("Fit", "Pas"),
+    ("Tray", "Baki"),
+    ("Edges", "Tepi"),
+    ("Player 1", "Pemain 1"),
+    ("Player 2", "Pemain 2"),
+    ("wins", "menang"),
+    ("Draw", "Seri"),
+    ("Random", "Acak"),
+    ("Around the edge", "Di sekeliling tepi"),
+    ("By quadrant", "Per kuadran"),
+    ("Spiral", "Spiral"),
+    ("Stack in corner", "Tumpuk di sudut"),
+    ("Onto the shelf", "Ke rak"),
+    ("Shelf", "Rak"),
+    ("Clock", "Jam"),
+    ("Time", "Waktu"),
+    ("On hover", "Saat disorot"),
+    ("Hidden", "Tersembunyi"),
+    ("Rate", "Laju"),
+    ("pieces/min", "keping/menit"),
+    ("Placed", "Terpasang"),
+    ("Not placed", "Belum terpasang"),
+];
+
+impl Language {
+    /// Translates an English text
+    pub fn tr<'a>(&self, text: &'a str) -> &'a str {
+        let table = match self {
+            Language::English => return text,
+            Language::Indonesian => INDONESIAN,
+        };
+        table
+            .iter()
+            .find(|(english, _)| *english == text)
+            .map_or(text, |(_, translated)| translated)
+    }
+
+    pub fn toggle(&mut self) {
+        *self = match self {
+            Language::English => Language::Indonesian,
+            Language::Indonesian => Language::English,
+        };
+    }
+}
+
+/// A static UI text which follows the selected language, holding its English version
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Localized(pub &'static str);
+
+fn localize_added_texts(
+    language: Res<Language>,
+    mut texts: Query<(&Localized, AnyOf<(&mut Text, &mut Text2d)>), Added<Localized>>,
+) {
+    for (localized, (text, text_2d)) in texts.iter_mut() {
+        set_text(*language, localized, text, text_2d);
+    }
+}
+
+fn update_localized_texts(
+    language: Res<Language>,
+    mut texts: Query<(&Localized, AnyOf<(&mut Text, &mut Text2d)>)>,
+) {
+    for (localized, (text, text_2d)) in texts.iter_mut() {
+        set_text(*language, localized, text, text_2d);
+    }
+}
+
+fn set_text(
+    language: Language,
+    localized: &Localized,
+    text: Option<Mut<Text>>,
+    text_2d: Option<Mut<Text2d>>,
+) {
+    let translated = language.tr(localized.0).to_string();
+    if let Some(mut text) = text {
+        text.0 = translated;
+    } else if let Some(mut text) = text_2d {
+        text.0 = translated;
+    }
+}
@@ -0,0 +1,162 @@
+//! Keeps the stacking order of the loose pieces within a fixed z range. Dropped pieces come to the
+//! front, large solved groups sink below the loose pieces so nothing stays buried under them, and
+//! <kbd>]</kbd> / <kbd>[</kbd> bring the hovered or selected pieces to the front or send them to
+//! the back.
+
+use crate::board::Locked;
+use crate::gameplay::{MoveStart, MoveTogether, SnapTween};
+use crate::pen::PenInput;
+use crate::selection::MultiSelection;
+use crate::tray::InTray;
+use crate::{GameState, Piece};
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<HoveredPiece>()
+        .add_systems(OnExit(GameState::Generating), normalize_layers)
+        .add_systems(Update, handle_layer_keys.run_if(in_state(GameState::Play)))
+        .add_observer(restack)
+        .add_observer(hover_piece)
+        .add_observer(leave_piece);
+}
+
+/// Lowest z of a loose piece, locked pieces and the board lie below
+const MIN_Z: f32 = 1.0;
+/// Highest z of a loose piece, held pieces are lifted above it
+const MAX_Z: f32 = 99.0;
+/// Groups of at least this many pieces stay below the loose pieces when they are dropped
+const LARGE_GROUP: usize = 30;
+
+/// The piece under the pointer
+#[derive(Resource, Debug, Default)]
+pub struct HoveredPiece(pub Option<Entity>);
+
+/// Changes the stacking order of pieces
+#[derive(Event, Debug)]
+pub enum Restack {
+    /// On top of every other loose piece
+    Front(HashSet<Entity>),
+    /// Below every other loose piece
+    Back(HashSet<Entity>),
+    /// On top, unless the pieces form a large group
+    Dropped(HashSet<Entity>),
+}
+
+fn hover_piece(
+    trigger: Trigger<Pointer<Over>>,
+    pieces: Query<(), With<Piece>>,
+    pen: Res<PenInput>,
+    mut hovered: ResMut<HoveredPiece>,
+) {
+    if pieces.contains(trigger.entity()) && !pen.is_palm(trigger.pointer_id) {
+        hovered.0 = Some(trigger.entity());
+    }
+}
+
+fn leave_piece(trigger: Trigger<Pointer<Out>>, mut hovered: ResMut<HoveredPiece>) {
+    if hovered.0 == Some(trigger.entity()) {
+        hovered.0 = None;
+    }
+}
+
+fn normalize_layers(mut commands: Commands) {
+    commands.trigger(Restack::Front(HashSet::default()));
+}
+
+/// The selected pieces, otherwise the hovered piece with its group
+fn layer_targets(
+    selection: &MultiSelection,
+    hovered: &HoveredPiece,
+    groups: &Query<&MoveTogether>,
+) -> HashSet<Entity> {
+    if !selection.is_empty() {
+        return selection.0.clone();
+    }
+    let Some(entity) = hovered.0 else {
+        return HashSet::default();
+    };
+    let mut targets: HashSet<Entity> = groups
+        .get(entity)
+        .map(|together| together.0.clone())
+        .unwrap_or_default();
+    targets.insert(entity);
+    targets
+}
+
+fn handle_layer_keys(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    selection: Res<MultiSelection>,
+    hovered: Res<HoveredPiece>,
+    groups: Query<&MoveTogether>,
+) {
+    if keyboard_input.just_pressed(KeyCode::BracketRight) {
+        let targets = layer_targets(&selection, &hovered, &groups);
+        if !targets.is_empty() {
+            commands.trigger(Restack::Front(targets));
+        }
+    } else if keyboard_input.just_pressed(KeyCode::BracketLeft) {
+        let targets = layer_targets(&selection, &hovered, &groups);
+        if !targets.is_empty() {
+            commands.trigger(Restack::Back(targets));
+        }
+    }
+}
+
+/// Sorts the loose pieces by layer and spreads them evenly over the z range
+fn restack(
+    trigger: Trigger<Restack>,
+    mut pieces: Query<
+        (
+            Entity,
+            &mut Transform,
+            &MoveTogether,
+            Option<&mut SnapTween>,
+        ),
+        (
+            With<Piece>,
+            Without<Locked>,
+            Without<InTray>,
+            Without<MoveStart>,
+        ),
+    >,
+) {
+    let (targets, front) = match trigger.event() {
+        Restack::Front(targets) | Restack::Dropped(targets) => (targets, true),
+        Restack::Back(targets) => (targets, false),
+    };
+    let lower_large_groups = !matches!(trigger.event(), Restack::Front(_));
+
+    // layers from the bottom: sent to the back, large groups, loose pieces, brought to the front
+    let layer = |entity: Entity, together: &MoveTogether| {
+        let large = together.len() >= LARGE_GROUP;
+        match (targets.contains(&entity), front) {
+            (true, false) => 0,
+            (true, true) if !(large && lower_large_groups) => 3,
+            _ if large => 1,
+            _ => 2,
+        }
+    };
+    let mut order: Vec<(u8, f32, Entity)> = pieces
+        .iter()
+        .map(|(entity, transform, together, _)| {
+            (layer(entity, together), transform.translation.z, entity)
+        })
+        .collect();
+    order.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+
+    let step = (MAX_Z - MIN_Z) / order.len().max(1) as f32;
+    for (rank, (_, _, entity)) in order.into_iter().enumerate() {
+        let Ok((_, mut transform, _, tween)) = pieces.get_mut(entity) else {
+            continue;
+        };
+        let z = MIN_Z + rank as f32 * step;
+        if transform.translation.z != z {
+            transform.translation.z = z;
+        }
+        if let Some(mut tween) = tween {
+            tween.set_z(z);
+        }
+    }
+}
@@ -1,17 +1,116 @@
+extern crate alloc;
+
 use bevy::asset::AssetMetaCheck;
 use bevy::prelude::*;
 use bevy::render::view::RenderLayers;
 use core::fmt::Formatter;
 use jigsaw_puzzle_generator::{GameMode, JigsawPiece};
 
+mod animated;
+mod arrange;
+mod atlas;
+mod auto_place;
+mod board;
+mod camera;
+mod chat;
+mod clock;
+mod color_sort;
+mod config;
+#[cfg(debug_assertions)]
+mod console;
+mod contrast;
+mod crop;
+mod culling;
+mod cursor;
+mod cut_preview;
+mod debug_overlay;
+mod effects;
+mod events;
+mod filters;
+#[cfg(not(target_arch = "wasm32"))]
+mod gallery;
 mod gameplay;
+mod guided;
+mod headless;
+mod hints;
+mod history;
+mod i18n;
+mod layering;
+mod loading;
+mod locate;
+mod lucky;
+mod magnifier;
 mod main_menu;
+mod minimap;
+mod multiplayer;
+mod mystery;
+mod nudge;
+mod pause;
+mod pen;
+mod perf_overlay;
+#[cfg(not(target_arch = "wasm32"))]
+mod photo;
+#[cfg(not(target_arch = "wasm32"))]
+mod picture;
+mod piece_picking;
+mod piece_shape;
+mod pinning;
+mod presentation;
+mod progress;
+mod relaxed;
+mod replay;
+mod rotation;
+#[cfg(not(target_arch = "wasm32"))]
+mod save_file;
+mod scenario;
+mod selection;
+mod shadow;
+mod staging;
+mod stats;
+mod stroke;
+mod surprise;
+mod table;
+mod theme;
+mod tooltip;
+mod tray;
+mod tutorial;
+mod ui_scale;
+mod ungroup;
+#[cfg(target_arch = "wasm32")]
+mod upload;
+mod versus;
+mod window_state;
+mod workspace;
 
-pub struct PuzzlePlugin;
+pub use config::{ConfigImage, PuzzlePluginConfig};
+pub use events::{GroupMerged, PieceSnapped, PuzzleCompleted, PuzzleStarted};
+pub use filters::ImageFilters;
+pub use headless::{drop_piece, spawn_puzzle, HeadlessPuzzlePlugin};
+pub use locate::FindPiece;
+pub use mystery::Mystery;
+#[cfg(not(target_arch = "wasm32"))]
+pub use photo::{PhotoTaken, TakePhoto};
+pub use piece_shape::PieceShape;
+pub use presentation::Presentation;
+pub use relaxed::Relaxed;
+pub use scenario::{PieceSet, Placement, Scenario};
+pub use stroke::PieceStroke;
+pub use workspace::{CreateWorkspace, SwitchWorkspace};
 
-impl Plugin for PuzzlePlugin {
+/// Runs the game as its own app, with the window and asset settings of the standalone build
+pub struct StandalonePuzzleApp;
+
+impl Plugin for StandalonePuzzleApp {
     fn build(&self, app: &mut App) {
-        app.add_plugins(
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            register_gallery_source(app);
+            if !app.world().contains_resource::<PuzzlePluginConfig>() {
+                app.insert_resource(PuzzlePluginConfig::from_args());
+            }
+        }
+
+        app.add_plugins((
             DefaultPlugins
                 .set(AssetPlugin {
                     // Wasm builds will check for meta files (that don't exist) if this isn't set.
@@ -33,15 +132,116 @@ impl Plugin for PuzzlePlugin {
                     }),
                     ..default()
                 }),
-        )
-        .insert_resource(ClearColor(Color::srgb(0.9, 0.9, 0.9)))
-        .init_resource::<SelectPiece>()
-        .init_resource::<SelectGameMode>()
-        .init_state::<AppState>()
-        .init_state::<GameState>()
-        .add_systems(Startup, setup_camera);
+            PuzzlePlugin,
+        ));
+    }
+}
 
-        app.add_plugins((main_menu::menu_plugin, gameplay::plugin));
+/// Registers the folder of the player's own images as an asset source, it has to be called before
+/// the `DefaultPlugins` are added or the gallery stays empty
+#[cfg(not(target_arch = "wasm32"))]
+pub fn register_gallery_source(app: &mut App) {
+    gallery::register_source(app);
+}
+
+/// The game itself, the app has to provide the `DefaultPlugins` with a primary window
+pub struct PuzzlePlugin;
+
+impl Plugin for PuzzlePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(Color::srgb(0.9, 0.9, 0.9)))
+            .init_resource::<SelectPiece>()
+            .init_resource::<CustomGrid>()
+            .init_resource::<SelectGameMode>()
+            .init_resource::<PuzzleSeed>()
+            .init_resource::<SelectPlayers>()
+            .init_resource::<SelectPlayMode>()
+            .init_resource::<SnapTolerance>()
+            .init_resource::<Difficulty>()
+            .init_state::<AppState>()
+            .init_state::<GameState>()
+            .add_sub_state::<GeneratingStep>()
+            .add_systems(Startup, setup_camera);
+
+        app.add_plugins((
+            main_menu::menu_plugin,
+            config::plugin,
+            atlas::plugin,
+            culling::plugin,
+            gameplay::plugin,
+            board::plugin,
+            history::plugin,
+            versus::plugin,
+            tray::plugin,
+            selection::plugin,
+            camera::plugin,
+            minimap::plugin,
+            effects::plugin,
+            events::plugin,
+            scenario::plugin,
+        ));
+        app.add_plugins((
+            contrast::plugin,
+            i18n::plugin,
+            rotation::plugin,
+            progress::plugin,
+            replay::plugin,
+            theme::plugin,
+            layering::plugin,
+            pinning::plugin,
+            color_sort::plugin,
+            locate::plugin,
+            hints::plugin,
+            guided::plugin,
+            tutorial::plugin,
+            magnifier::plugin,
+            cursor::plugin,
+        ));
+        app.add_plugins((
+            table::plugin,
+            arrange::plugin,
+            workspace::plugin,
+            debug_overlay::plugin,
+            auto_place::plugin,
+            relaxed::plugin,
+            presentation::plugin,
+            animated::plugin,
+            surprise::plugin,
+            ui_scale::plugin,
+            perf_overlay::plugin,
+            stats::plugin,
+            piece_shape::plugin,
+            cut_preview::plugin,
+        ));
+        app.add_plugins((
+            stroke::plugin,
+            shadow::plugin,
+            pen::plugin,
+            filters::plugin,
+            mystery::plugin,
+            piece_picking::plugin,
+            ungroup::plugin,
+            nudge::plugin,
+            staging::plugin,
+            pause::plugin,
+            window_state::plugin,
+            crop::plugin,
+            loading::plugin,
+            clock::plugin,
+            tooltip::plugin,
+        ));
+        #[cfg(debug_assertions)]
+        app.add_plugins(console::plugin);
+        app.add_plugins(multiplayer::plugin);
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_plugins((
+            picture::plugin,
+            gallery::plugin,
+            save_file::plugin,
+            photo::plugin,
+        ));
+        #[cfg(target_arch = "wasm32")]
+        app.add_plugins(upload::plugin);
     }
 }
 
@@ -66,6 +266,17 @@ pub enum GameState {
     Finish,
 }
 
+/// The steps of generating a puzzle, both run in the background so the window stays responsive
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, SubStates)]
+#[source(GameState = GameState::Generating)]
+pub enum GeneratingStep {
+    /// Cutting the image into the shapes of the pieces
+    #[default]
+    Template,
+    /// Cropping the image of every piece
+    Pieces,
+}
+
 const NORMAL_BUTTON: Color = Color::srgb(0.15, 0.15, 0.15);
 const HOVERED_BUTTON: Color = Color::srgb(0.25, 0.25, 0.25);
 const PRESSED_BUTTON: Color = Color::srgb(0.35, 0.75, 0.35);
@@ -81,6 +292,18 @@ pub struct OriginImage(pub Handle<Image>);
 #[derive(Debug, Component, Deref, DerefMut, Clone)]
 pub struct Piece(pub JigsawPiece);
 
+impl Piece {
+    /// The area covered by the piece image, including its tabs, for a piece entity at `translation`
+    pub fn image_rect(&self, translation: Vec2) -> Rect {
+        let (offset_x, offset_y) = self.calc_offset();
+        let top_left = translation + Vec2::new(-offset_x, offset_y);
+        Rect::from_corners(
+            top_left,
+            top_left + Vec2::new(self.crop_width as f32, -(self.crop_height as f32)),
+        )
+    }
+}
+
 // Generic system that takes a component as a parameter, and will despawn all entities with that component
 fn despawn_screen<T: Component>(to_despawn: Query<Entity, With<T>>, mut commands: Commands) {
     for entity in &to_despawn {
@@ -103,86 +326,313 @@ fn setup_camera(mut commands: Commands) {
     commands.insert_resource(AnimeCamera(anime_camera));
 }
 
-#[derive(Resource, Default, Clone, Copy, Debug)]
-enum SelectPiece {
-    #[default]
-    P20,
-    P50,
-    P100,
-    P150,
-    P200,
-    P250,
-    P300,
-    P400,
-    P500,
+/// The number of pieces the puzzle is cut into
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+struct SelectPiece(usize);
+
+impl Default for SelectPiece {
+    fn default() -> Self {
+        Self(20)
+    }
 }
 
 impl core::fmt::Display for SelectPiece {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl SelectPiece {
+    const MIN: usize = 12;
+    const MAX: usize = 2000;
+
+    fn new(count: usize) -> Self {
+        Self(count.clamp(Self::MIN, Self::MAX))
+    }
+
+    /// Columns and rows giving the squarest pieces for an image of the given size
+    fn get_columns_rows(&self, width: u32, height: u32) -> (usize, usize) {
+        jigsaw_puzzle_generator::generate_columns_rows_numbers(width as f32, height as f32, self.0)
+            .unwrap_or((self.0, 1))
+    }
+
+    /// The distance between two selectable counts around `count`
+    fn step(count: usize) -> usize {
+        match count {
+            ..100 => 4,
+            100..500 => 10,
+            _ => 50,
+        }
+    }
+
+    /// The count on a logarithmic scale from [`Self::MIN`] at 0.0 to [`Self::MAX`] at 1.0
+    fn from_fraction(fraction: f32) -> Self {
+        let ratio = Self::MAX as f32 / Self::MIN as f32;
+        let count = Self::MIN as f32 * ratio.powf(fraction.clamp(0.0, 1.0));
+        let step = Self::step(count as usize) as f32;
+        Self::new(((count / step).round() * step) as usize)
+    }
+
+    fn fraction(&self) -> f32 {
+        let ratio = Self::MAX as f32 / Self::MIN as f32;
+        (self.0 as f32 / Self::MIN as f32).ln() / ratio.ln()
+    }
+
+    fn next(&mut self) {
+        let step = Self::step(self.0);
+        *self = Self::new((self.0 / step + 1) * step);
+    }
+
+    fn previous(&mut self) {
+        let step = Self::step(self.0 - 1);
+        *self = Self::new((self.0 - 1) / step * step);
+    }
+}
+
+/// Columns and rows picked directly in the menu instead of deriving them from [`SelectPiece`]
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct CustomGrid(Option<(usize, usize)>);
+
+impl CustomGrid {
+    const MIN: usize = 4;
+    const MAX: usize = 50;
+
+    /// The columns and rows to cut an image of the given size into
+    fn columns_rows(&self, select_piece: &SelectPiece, width: u32, height: u32) -> (usize, usize) {
+        self.0
+            .unwrap_or_else(|| select_piece.get_columns_rows(width, height))
+    }
+
+    /// Uses the given grid if its number of pieces can be selected, keeping `select_piece` in sync
+    fn set(&mut self, select_piece: &mut SelectPiece, columns: usize, rows: usize) {
+        let columns = columns.clamp(Self::MIN, Self::MAX);
+        let rows = rows.clamp(Self::MIN, Self::MAX);
+        if columns * rows > SelectPiece::MAX {
+            return;
+        }
+        self.0 = Some((columns, rows));
+        *select_piece = SelectPiece::new(columns * rows);
+    }
+}
+
+#[derive(Debug, Resource, Deref, DerefMut, Default)]
+pub struct SelectGameMode(pub GameMode);
+
+/// Whether a single player solves the puzzle or two players compete on it
+#[derive(Debug, Resource, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SelectPlayers {
+    #[default]
+    Solo,
+    Versus,
+}
+
+impl core::fmt::Display for SelectPlayers {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "{}",
             match self {
-                SelectPiece::P20 => 20,
-                SelectPiece::P50 => 50,
-                SelectPiece::P100 => 100,
-                SelectPiece::P150 => 150,
-                SelectPiece::P200 => 200,
-                SelectPiece::P250 => 250,
-                SelectPiece::P300 => 300,
-                SelectPiece::P400 => 400,
-                SelectPiece::P500 => 500,
+                SelectPlayers::Solo => "Solo",
+                SelectPlayers::Versus => "Versus",
             }
         )
     }
 }
 
-impl SelectPiece {
-    fn get_columns_rows(&self) -> (usize, usize) {
+impl SelectPlayers {
+    pub fn toggle(&mut self) {
+        *self = match self {
+            SelectPlayers::Solo => SelectPlayers::Versus,
+            SelectPlayers::Versus => SelectPlayers::Solo,
+        };
+    }
+}
+
+/// Whether pieces are assembled anywhere on the table, placed onto a board, or assembled frame
+/// first with the inner pieces handed out in waves
+#[derive(Debug, Resource, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SelectPlayMode {
+    #[default]
+    Free,
+    Board,
+    Guided,
+}
+
+impl core::fmt::Display for SelectPlayMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                SelectPlayMode::Free => "Free",
+                SelectPlayMode::Board => "Board",
+                SelectPlayMode::Guided => "Guided",
+            }
+        )
+    }
+}
+
+impl SelectPlayMode {
+    pub fn toggle(&mut self) {
+        *self = match self {
+            SelectPlayMode::Free => SelectPlayMode::Board,
+            SelectPlayMode::Board => SelectPlayMode::Guided,
+            SelectPlayMode::Guided => SelectPlayMode::Free,
+        };
+    }
+
+    pub fn previous(&mut self) {
+        *self = match self {
+            SelectPlayMode::Free => SelectPlayMode::Guided,
+            SelectPlayMode::Board => SelectPlayMode::Free,
+            SelectPlayMode::Guided => SelectPlayMode::Board,
+        };
+    }
+}
+
+/// How close matching pieces have to be dropped to snap together
+#[derive(Debug, Resource, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SnapTolerance {
+    Easy,
+    #[default]
+    Normal,
+    Strict,
+}
+
+impl core::fmt::Display for SnapTolerance {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                SnapTolerance::Easy => "Easy",
+                SnapTolerance::Normal => "Normal",
+                SnapTolerance::Strict => "Strict",
+            }
+        )
+    }
+}
+
+impl SnapTolerance {
+    /// The snapping distance in pixels
+    pub fn distance(&self) -> f32 {
         match self {
-            SelectPiece::P20 => (5, 4),
-            SelectPiece::P50 => (10, 5),
-            SelectPiece::P100 => (10, 10),
-            SelectPiece::P150 => (15, 10),
-            SelectPiece::P200 => (20, 10),
-            SelectPiece::P250 => (25, 10),
-            SelectPiece::P300 => (30, 10),
-            SelectPiece::P400 => (20, 20),
-            SelectPiece::P500 => (25, 20),
+            SnapTolerance::Easy => 2.0 * jigsaw_puzzle_generator::COMPARE_THRESHOLD,
+            SnapTolerance::Normal => jigsaw_puzzle_generator::COMPARE_THRESHOLD,
+            SnapTolerance::Strict => jigsaw_puzzle_generator::COMPARE_THRESHOLD / 2.0,
         }
     }
 
     fn next(&mut self) {
         *self = match self {
-            SelectPiece::P20 => SelectPiece::P50,
-            SelectPiece::P50 => SelectPiece::P100,
-            SelectPiece::P100 => SelectPiece::P150,
-            SelectPiece::P150 => SelectPiece::P200,
-            SelectPiece::P200 => SelectPiece::P250,
-            SelectPiece::P250 => SelectPiece::P300,
-            SelectPiece::P300 => SelectPiece::P400,
-            SelectPiece::P400 => SelectPiece::P500,
-            SelectPiece::P500 => SelectPiece::P20,
+            SnapTolerance::Easy => SnapTolerance::Normal,
+            SnapTolerance::Normal => SnapTolerance::Strict,
+            SnapTolerance::Strict => SnapTolerance::Easy,
         };
     }
 
     fn previous(&mut self) {
         *self = match self {
-            SelectPiece::P20 => SelectPiece::P500,
-            SelectPiece::P50 => SelectPiece::P20,
-            SelectPiece::P100 => SelectPiece::P50,
-            SelectPiece::P150 => SelectPiece::P100,
-            SelectPiece::P200 => SelectPiece::P150,
-            SelectPiece::P250 => SelectPiece::P200,
-            SelectPiece::P300 => SelectPiece::P250,
-            SelectPiece::P400 => SelectPiece::P300,
-            SelectPiece::P500 => SelectPiece::P400,
+            SnapTolerance::Easy => SnapTolerance::Strict,
+            SnapTolerance::Normal => SnapTolerance::Easy,
+            SnapTolerance::Strict => SnapTolerance::Normal,
         };
     }
 }
 
-#[derive(Debug, Resource, Deref, DerefMut, Default)]
-pub struct SelectGameMode(pub GameMode);
+/// A preset of several options picked with one click in the menu
+#[derive(Debug, Resource, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    Expert,
+}
+
+impl core::fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Difficulty::Easy => "Easy",
+                Difficulty::Normal => "Normal",
+                Difficulty::Hard => "Hard",
+                Difficulty::Expert => "Expert",
+            }
+        )
+    }
+}
+
+impl Difficulty {
+    /// The number of pieces selected with the preset
+    fn piece_count(&self) -> usize {
+        match self {
+            Difficulty::Easy => 20,
+            Difficulty::Normal => 100,
+            Difficulty::Hard => 300,
+            Difficulty::Expert => 500,
+        }
+    }
+
+    fn snap_tolerance(&self) -> SnapTolerance {
+        match self {
+            Difficulty::Easy => SnapTolerance::Easy,
+            Difficulty::Normal | Difficulty::Hard => SnapTolerance::Normal,
+            Difficulty::Expert => SnapTolerance::Strict,
+        }
+    }
+
+    /// Pieces start turned by random quarter turns and have to be rotated upright
+    pub fn rotation(&self) -> bool {
+        matches!(self, Difficulty::Hard | Difficulty::Expert)
+    }
+
+    /// The matching pieces hint and the small image of the solved puzzle
+    pub fn hints(&self) -> bool {
+        !matches!(self, Difficulty::Expert)
+    }
+
+    /// The solved image shown faintly behind the board
+    pub fn background_hint(&self) -> bool {
+        matches!(self, Difficulty::Easy | Difficulty::Normal)
+    }
+
+    /// Selects the piece count and snap tolerance of the preset
+    fn apply(
+        &self,
+        select_piece: &mut SelectPiece,
+        custom_grid: &mut CustomGrid,
+        snap_tolerance: &mut SnapTolerance,
+    ) {
+        *select_piece = SelectPiece::new(self.piece_count());
+        custom_grid.0 = None;
+        *snap_tolerance = self.snap_tolerance();
+    }
+
+    fn next(&mut self) {
+        *self = match self {
+            Difficulty::Easy => Difficulty::Normal,
+            Difficulty::Normal => Difficulty::Hard,
+            Difficulty::Hard => Difficulty::Expert,
+            Difficulty::Expert => Difficulty::Easy,
+        };
+    }
+
+    fn previous(&mut self) {
+        *self = match self {
+            Difficulty::Easy => Difficulty::Expert,
+            Difficulty::Normal => Difficulty::Easy,
+            Difficulty::Hard => Difficulty::Normal,
+            Difficulty::Expert => Difficulty::Hard,
+        };
+    }
+}
+
+/// Fixed seed for the next generated puzzle, a random one is used if not set
+#[derive(Debug, Resource, Deref, DerefMut, Default, Clone, Copy)]
+pub struct PuzzleSeed(pub Option<usize>);
 
 impl core::fmt::Display for SelectGameMode {
     fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
@@ -192,6 +642,8 @@ impl core::fmt::Display for SelectGameMode {
             match self.0 {
                 GameMode::Classic => "Classic",
                 GameMode::Square => "Square",
+                GameMode::Triangular => "Triangular",
+                GameMode::Crazy => "Crazy",
             }
         )
     }
@@ -201,14 +653,18 @@ impl SelectGameMode {
     pub fn next(&mut self) {
         *self = match self.0 {
             GameMode::Classic => SelectGameMode(GameMode::Square),
-            GameMode::Square => SelectGameMode(GameMode::Classic),
+            GameMode::Square => SelectGameMode(GameMode::Triangular),
+            GameMode::Triangular => SelectGameMode(GameMode::Crazy),
+            GameMode::Crazy => SelectGameMode(GameMode::Classic),
         };
     }
 
     pub fn previous(&mut self) {
         *self = match self.0 {
-            GameMode::Classic => SelectGameMode(GameMode::Square),
+            GameMode::Classic => SelectGameMode(GameMode::Crazy),
             GameMode::Square => SelectGameMode(GameMode::Classic),
+            GameMode::Triangular => SelectGameMode(GameMode::Square),
+            GameMode::Crazy => SelectGameMode(GameMode::Triangular),
         };
     }
 }
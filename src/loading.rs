@@ -0,0 +1,208 @@
+//! Keeps the player company while the pieces are cut: a fact about the puzzle above the progress
+//! bar, the time left estimated from the progress so far and a gameplay tip below, which changes
+//! every few seconds.
+
+use crate::gameplay::JigsawPuzzleGenerator;
+use crate::i18n::Language;
+use crate::theme::ThemeColor;
+use crate::GameState;
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<GeneratingProgress>()
+        .add_systems(OnEnter(GameState::Generating), start_loading)
+        .add_systems(
+            Update,
+            (update_fact_text, update_time_left, rotate_tips)
+                .run_if(in_state(GameState::Generating)),
+        );
+}
+
+/// How long a tip is shown
+const TIP_SECS: f32 = 6.0;
+/// The progress and time needed before the time left is estimated, the first moments say little
+/// about the rest
+const MIN_ESTIMATE_PROGRESS: f32 = 0.05;
+const MIN_ESTIMATE_SECS: f32 = 1.0;
+
+const TIPS: [&str; 8] = [
+    "Start with the edge pieces, the frame holds the rest together",
+    "Press T to lay out the loose pieces, Shift + T sorts them by color",
+    "Press H when you are stuck to see two pieces which fit",
+    "Press / and type a number to find a piece",
+    "Press Space to peek at the image",
+    "Drag on the empty table to move several pieces together",
+    "Drop pieces on the tray to keep them apart",
+    "Press F to fit the board into view",
+];
+
+/// The share of the puzzle generated so far, from 0.0 to 1.0
+#[derive(Resource, Debug, Default)]
+pub struct GeneratingProgress(pub f32);
+
+/// When the generating started and the tip shown
+#[derive(Resource, Debug)]
+struct Loading {
+    started: f32,
+    tip: usize,
+    tip_timer: Timer,
+}
+
+/// A fact about the puzzle, the heading of the generating screen
+#[derive(Component)]
+struct FactText;
+
+#[derive(Component)]
+struct TimeLeftText;
+
+#[derive(Component)]
+struct TipText;
+
+fn start_loading(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut progress: ResMut<GeneratingProgress>,
+) {
+    progress.0 = 0.0;
+    commands.insert_resource(Loading {
+        started: time.elapsed_secs(),
+        tip: rand::random::<usize>() % TIPS.len(),
+        tip_timer: Timer::from_seconds(TIP_SECS, TimerMode::Repeating),
+    });
+}
+
+/// Adds the fact about the puzzle
+pub(crate) fn spawn_fact_text(p: &mut ChildBuilder, font: TextFont) {
+    p.spawn((
+        Text::default(),
+        font,
+        TextColor(Color::BLACK),
+        ThemeColor::Text,
+        FactText,
+    ));
+}
+
+/// Adds the time left and the tip below it
+pub(crate) fn spawn_tip_texts(p: &mut ChildBuilder) {
+    p.spawn((
+        Text::default(),
+        TextColor(Color::BLACK),
+        ThemeColor::Text,
+        TimeLeftText,
+    ));
+    p.spawn((
+        Text::default(),
+        TextFont {
+            font_size: 20.0,
+            ..default()
+        },
+        TextColor(Color::BLACK),
+        ThemeColor::Text,
+        Node {
+            margin: UiRect::top(Val::Px(30.0)),
+            ..default()
+        },
+        TipText,
+    ));
+}
+
+/// The pieces on the edge and inside, the joins or the grid of a puzzle, picked by `variant`
+fn puzzle_fact(columns: usize, rows: usize, variant: usize, language: Language) -> String {
+    let total = columns * rows;
+    // a single row or column has no inner pieces
+    let edges = if columns < 2 || rows < 2 {
+        total
+    } else {
+        2 * (columns + rows) - 4
+    };
+    let joins = columns * rows.saturating_sub(1) + rows * columns.saturating_sub(1);
+    match variant % 3 {
+        0 => format!(
+            "{} {}: {} {}, {} {}",
+            total,
+            language.tr("pieces"),
+            edges,
+            language.tr("on the edge"),
+            total - edges,
+            language.tr("inside"),
+        ),
+        1 => format!(
+            "{} {}",
+            joins,
+            language.tr("joins to find between the pieces")
+        ),
+        _ => format!(
+            "{} {}: {} x {}",
+            total,
+            language.tr("pieces"),
+            columns,
+            rows
+        ),
+    }
+}
+
+fn update_fact_text(
+    generator: Res<JigsawPuzzleGenerator>,
+    language: Res<Language>,
+    mut texts: Query<&mut Text, With<FactText>>,
+    added: Query<(), Added<FactText>>,
+    mut variant: Local<usize>,
+) {
+    if !added.is_empty() {
+        *variant = rand::random();
+    } else if !language.is_changed() {
+        return;
+    }
+    let fact = puzzle_fact(
+        generator.pieces_in_column(),
+        generator.pieces_in_row(),
+        *variant,
+        *language,
+    );
+    for mut text in texts.iter_mut() {
+        text.0 = fact.clone();
+    }
+}
+
+/// Estimates the time left from the time the progress so far took
+fn update_time_left(
+    time: Res<Time<Real>>,
+    loading: Res<Loading>,
+    progress: Res<GeneratingProgress>,
+    language: Res<Language>,
+    mut text: Single<&mut Text, With<TimeLeftText>>,
+) {
+    let elapsed = time.elapsed_secs() - loading.started;
+    let label = if progress.0 < MIN_ESTIMATE_PROGRESS || elapsed < MIN_ESTIMATE_SECS {
+        String::new()
+    } else {
+        let left = (elapsed * (1.0 - progress.0) / progress.0).ceil() as u32;
+        let left = if left >= 60 {
+            format!("{}:{:02}", left / 60, left % 60)
+        } else {
+            format!("{}s", left)
+        };
+        format!("{} {}", language.tr("Time left:"), left)
+    };
+    if text.0 != label {
+        text.0 = label;
+    }
+}
+
+fn rotate_tips(
+    time: Res<Time<Real>>,
+    mut loading: ResMut<Loading>,
+    language: Res<Language>,
+    mut texts: Query<&mut Text, With<TipText>>,
+    added: Query<(), Added<TipText>>,
+) {
+    if loading.tip_timer.tick(time.delta()).just_finished() {
+        loading.tip = (loading.tip + 1) % TIPS.len();
+    } else if added.is_empty() && !language.is_changed() {
+        return;
+    }
+    let tip = format!("{} {}", language.tr("Tip:"), language.tr(TIPS[loading.tip]));
+    for mut text in texts.iter_mut() {
+        text.0 = tip.clone();
+    }
+}
@@ -0,0 +1,272 @@
+//! Flashes the outline of a piece at the place it belongs to in the solved puzzle, or at the
+//! place it lies when the player looks for it. <kbd>/</kbd> opens a box to find a piece by its
+//! number.
+
+use crate::camera::CameraAnimation;
+use crate::gameplay::{init_position, JigsawPuzzleGenerator, MoveTogether, OnPlayScreen};
+use crate::layering::Restack;
+use crate::tray::InTray;
+use crate::{GameState, Piece};
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+use bevy::utils::HashSet;
+use jigsaw_puzzle_generator::image::{GenericImageView, Rgba};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PieceNumberInput>()
+        .add_systems(OnExit(GameState::Play), close_find_box)
+        .add_systems(
+            Update,
+            (
+                enter_piece_number,
+                update_find_box,
+                follow_found_pieces,
+                fade_ghosts,
+            )
+                .chain()
+                .run_if(in_state(GameState::Play)),
+        )
+        .add_observer(show_location)
+        .add_observer(find_piece);
+}
+
+/// How long the outline is shown
+const GHOST_DURATION: f32 = 2.0;
+const GHOST_COLOR: Rgba<u8> = Rgba([255, 220, 0, 255]);
+const GHOST_RADIUS: i32 = 3;
+/// Above the loose pieces, below a held piece
+const GHOST_Z: f32 = 99.5;
+
+/// Shows where the pieces go
+#[derive(Event, Debug)]
+pub struct ShowLocation(pub Vec<Entity>);
+
+/// The outline of a piece at its solved position
+#[derive(Component, Debug)]
+struct LocationGhost(Timer);
+
+/// Moves the camera to a piece, brings its group to the front and pulses its outline
+#[derive(Event, Debug, Clone, Copy)]
+pub struct FindPiece(pub Entity);
+
+/// The outline of a found piece, which moves along with it
+#[derive(Component, Debug)]
+struct FoundOutline(Entity);
+
+/// The number typed into the find box while it is open
+#[derive(Resource, Debug, Default)]
+pub struct PieceNumberInput(Option<String>);
+
+/// The number keys are typed into the find box while it is open
+pub fn entering_number(input: Res<PieceNumberInput>) -> bool {
+    input.0.is_some()
+}
+
+#[derive(Component)]
+struct FindBox;
+
+const NUMBER_KEYS: [(KeyCode, KeyCode, char); 10] = [
+    (KeyCode::Digit0, KeyCode::Numpad0, '0'),
+    (KeyCode::Digit1, KeyCode::Numpad1, '1'),
+    (KeyCode::Digit2, KeyCode::Numpad2, '2'),
+    (KeyCode::Digit3, KeyCode::Numpad3, '3'),
+    (KeyCode::Digit4, KeyCode::Numpad4, '4'),
+    (KeyCode::Digit5, KeyCode::Numpad5, '5'),
+    (KeyCode::Digit6, KeyCode::Numpad6, '6'),
+    (KeyCode::Digit7, KeyCode::Numpad7, '7'),
+    (KeyCode::Digit8, KeyCode::Numpad8, '8'),
+    (KeyCode::Digit9, KeyCode::Numpad9, '9'),
+];
+
+fn show_location(
+    trigger: Trigger<ShowLocation>,
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    generator: Res<JigsawPuzzleGenerator>,
+    pieces: Query<&Piece>,
+) {
+    let image_size = generator.origin_image().dimensions();
+    for piece in pieces.iter_many(&trigger.event().0) {
+        let outline = piece.outline_image(GHOST_COLOR, GHOST_RADIUS);
+        let (offset_x, offset_y) = piece.calc_offset();
+        let position = init_position(piece, image_size) + Vec2::new(-offset_x, offset_y);
+        commands.spawn((
+            Sprite {
+                image: images.add(Image::from_dynamic(
+                    outline,
+                    true,
+                    RenderAssetUsages::RENDER_WORLD,
+                )),
+                anchor: Anchor::TopLeft,
+                custom_size: Some(Vec2::new(piece.crop_width as f32, piece.crop_height as f32)),
+                ..default()
+            },
+            Transform::from_translation(position.extend(GHOST_Z)),
+            PickingBehavior::IGNORE,
+            LocationGhost(Timer::from_seconds(GHOST_DURATION, TimerMode::Once)),
+            OnPlayScreen,
+        ));
+    }
+}
+
+/// Pulses the outlines and removes them when their time is up
+fn fade_ghosts(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut ghosts: Query<(Entity, &mut LocationGhost, &mut Sprite)>,
+) {
+    for (entity, mut ghost, mut sprite) in ghosts.iter_mut() {
+        if ghost.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        let pulse = (ghost.0.elapsed_secs() * core::f32::consts::TAU * 2.0).cos() * 0.5 + 0.5;
+        sprite.color = Color::WHITE.with_alpha(0.4 + 0.6 * pulse);
+    }
+}
+
+/// <kbd>/</kbd> opens the find box, <kbd>Enter</kbd> finds the piece with the typed number and
+/// <kbd>Escape</kbd> closes the box again
+fn enter_piece_number(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut input: ResMut<PieceNumberInput>,
+    mut commands: Commands,
+    pieces: Query<(Entity, &Piece)>,
+) {
+    let Some(number) = input.0.as_mut() else {
+        if keyboard_input.just_pressed(KeyCode::Slash) {
+            input.0 = Some(String::new());
+        }
+        return;
+    };
+    for (digit, numpad, char) in NUMBER_KEYS {
+        if keyboard_input.any_just_pressed([digit, numpad]) && number.len() < 5 {
+            number.push(char);
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Backspace) {
+        number.pop();
+    }
+    if keyboard_input.any_just_pressed([KeyCode::Enter, KeyCode::NumpadEnter]) {
+        // the pieces are numbered from one
+        let index = number.parse::<usize>().ok().and_then(|n| n.checked_sub(1));
+        if let Some((entity, _)) = pieces.iter().find(|(_, piece)| Some(piece.index) == index) {
+            commands.trigger(FindPiece(entity));
+        }
+        input.0 = None;
+    } else if keyboard_input.just_pressed(KeyCode::Escape) {
+        input.0 = None;
+    }
+}
+
+fn update_find_box(
+    mut commands: Commands,
+    input: Res<PieceNumberInput>,
+    find_box: Query<Entity, With<FindBox>>,
+    mut texts: Query<&mut Text, With<FindBox>>,
+) {
+    if !input.is_changed() {
+        return;
+    }
+    let Some(number) = input.0.as_ref() else {
+        for entity in find_box.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    };
+    let text = format!("# {number}_");
+    if let Ok(mut current) = texts.get_single_mut() {
+        current.0 = text;
+        return;
+    }
+    commands.spawn((
+        Text::new(text),
+        TextFont {
+            font_size: 24.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(60.0),
+            left: Val::Percent(45.0),
+            padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        BorderRadius::all(Val::Px(4.0)),
+        FindBox,
+        OnPlayScreen,
+    ));
+}
+
+fn close_find_box(mut input: ResMut<PieceNumberInput>) {
+    input.0 = None;
+}
+
+fn find_piece(
+    trigger: Trigger<FindPiece>,
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut animation: ResMut<CameraAnimation>,
+    projection: Single<&OrthographicProjection, With<IsDefaultUiCamera>>,
+    pieces: Query<(&Piece, &Transform, &MoveTogether, Has<InTray>)>,
+) {
+    let FindPiece(entity) = *trigger.event();
+    let Ok((piece, transform, together, in_tray)) = pieces.get(entity) else {
+        return;
+    };
+    // a piece in a tray is already on the screen
+    if !in_tray {
+        let center = piece.image_rect(transform.translation.xy()).center();
+        animation.0 = Some((center, projection.scale.min(1.0)));
+        let mut group: HashSet<Entity> = together.iter().copied().collect();
+        group.insert(entity);
+        commands.trigger(Restack::Front(group));
+    }
+
+    let outline = piece.outline_image(GHOST_COLOR, GHOST_RADIUS);
+    commands.spawn((
+        Sprite {
+            image: images.add(Image::from_dynamic(
+                outline,
+                true,
+                RenderAssetUsages::RENDER_WORLD,
+            )),
+            anchor: Anchor::TopLeft,
+            custom_size: Some(Vec2::new(piece.crop_width as f32, piece.crop_height as f32)),
+            ..default()
+        },
+        outline_transform(piece, transform),
+        PickingBehavior::IGNORE,
+        LocationGhost(Timer::from_seconds(GHOST_DURATION, TimerMode::Once)),
+        FoundOutline(entity),
+        OnPlayScreen,
+    ));
+}
+
+/// Keeps the outlines on their found pieces, also while they are dragged, rotated or scaled
+/// down in a tray
+fn follow_found_pieces(
+    mut commands: Commands,
+    mut outlines: Query<(Entity, &FoundOutline, &mut Transform)>,
+    pieces: Query<(&Piece, &Transform), Without<FoundOutline>>,
+) {
+    for (entity, FoundOutline(piece_entity), mut transform) in outlines.iter_mut() {
+        let Ok((piece, piece_transform)) = pieces.get(*piece_entity) else {
+            commands.entity(entity).despawn();
+            continue;
+        };
+        *transform = outline_transform(piece, piece_transform);
+    }
+}
+
+/// The outline covers the piece image, above the piece and the loose pieces around it
+fn outline_transform(piece: &Piece, piece_transform: &Transform) -> Transform {
+    let (offset_x, offset_y) = piece.calc_offset();
+    let mut transform =
+        piece_transform.mul_transform(Transform::from_xyz(-offset_x, offset_y, 0.0));
+    transform.translation.z = (piece_transform.translation.z + 1.0).max(GHOST_Z);
+    transform
+}
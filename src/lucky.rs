@@ -0,0 +1,95 @@
+//! "Shuffle" in the main menu picks a random image of the strip, the bundled ones as well as the
+//! ones of the picture folder, and a random piece count. "Quick play" starts a puzzle with such a
+//! pick right away.
+
+use crate::i18n::Localized;
+use crate::main_menu::LoadedImages;
+use crate::theme::ThemeColor;
+use crate::{AppState, CustomGrid, OriginImage, SelectPiece};
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+
+/// The piece counts picked from, large enough to be a puzzle and small enough to finish in one go
+const LUCKY_PIECE_COUNTS: [usize; 6] = [24, 48, 100, 150, 200, 300];
+
+/// Picks a loaded image other than the current one and a piece count, false without an image
+fn pick_lucky(
+    loaded_images: &LoadedImages,
+    images: &Assets<Image>,
+    origin_image: &mut OriginImage,
+    select_piece: &mut SelectPiece,
+    custom_grid: &mut CustomGrid,
+) -> bool {
+    let mut rng = rand::thread_rng();
+    let loaded: Vec<&Handle<Image>> = loaded_images
+        .iter()
+        .filter(|image| images.contains(*image))
+        .collect();
+    let others: Vec<&Handle<Image>> = loaded
+        .iter()
+        .copied()
+        .filter(|image| **image != origin_image.0)
+        .collect();
+    let Some(image) = others.choose(&mut rng).or_else(|| loaded.choose(&mut rng)) else {
+        return false;
+    };
+    origin_image.0 = (*image).clone();
+    if let Some(count) = LUCKY_PIECE_COUNTS.choose(&mut rng) {
+        *select_piece = SelectPiece::new(*count);
+        custom_grid.0 = None;
+    }
+    true
+}
+
+/// Adds "Shuffle" and "Quick play"
+pub(crate) fn spawn_lucky_buttons(p: &mut ChildBuilder, font: TextFont) {
+    p.spawn((
+        Text::new("Shuffle"),
+        Localized("Shuffle"),
+        font.clone(),
+        TextColor(Color::BLACK),
+        ThemeColor::Text,
+    ))
+    .observe(
+        |_trigger: Trigger<Pointer<Click>>,
+         loaded_images: Res<LoadedImages>,
+         images: Res<Assets<Image>>,
+         mut origin_image: ResMut<OriginImage>,
+         mut select_piece: ResMut<SelectPiece>,
+         mut custom_grid: ResMut<CustomGrid>| {
+            pick_lucky(
+                &loaded_images,
+                &images,
+                &mut origin_image,
+                &mut select_piece,
+                &mut custom_grid,
+            );
+        },
+    );
+    p.spawn((
+        Text::new("Quick play"),
+        Localized("Quick play"),
+        font,
+        TextColor(Color::BLACK),
+        ThemeColor::Text,
+    ))
+    .observe(
+        |_trigger: Trigger<Pointer<Click>>,
+         loaded_images: Res<LoadedImages>,
+         images: Res<Assets<Image>>,
+         mut origin_image: ResMut<OriginImage>,
+         mut select_piece: ResMut<SelectPiece>,
+         mut custom_grid: ResMut<CustomGrid>,
+         mut app_state: ResMut<NextState<AppState>>| {
+            if pick_lucky(
+                &loaded_images,
+                &images,
+                &mut origin_image,
+                &mut select_piece,
+                &mut custom_grid,
+            ) {
+                app_state.set(AppState::Gameplay);
+            }
+        },
+    );
+}
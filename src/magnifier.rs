@@ -0,0 +1,143 @@
+//! A round magnifier which follows the cursor, toggled with <kbd>L</kbd>. A second camera renders
+//! the table under the cursor into an image at a higher zoom, the animation camera draws that
+//! image as a lens on top, so the zoom of the table stays as it is.
+
+use crate::gameplay::OnPlayScreen;
+use crate::{GameState, ANIMATION_LAYERS};
+use bevy::asset::RenderAssetUsages;
+use bevy::input::touch::Touch;
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        (toggle_magnifier, follow_pointer)
+            .chain()
+            .run_if(in_state(GameState::Play)),
+    );
+}
+
+const LENS_RADIUS: f32 = 90.0;
+const LENS_BORDER: f32 = 3.0;
+/// How much larger the table looks in the lens
+const LENS_ZOOM: f32 = 2.5;
+/// Above everything else the animation camera draws during play
+const LENS_Z: f32 = 500.0;
+
+/// Renders the table under the pointer into the image of the lens
+#[derive(Component)]
+struct MagnifierCamera;
+
+/// Shows the magnified table at the pointer
+#[derive(Component)]
+struct MagnifierLens;
+
+fn toggle_magnifier(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut images: ResMut<Assets<Image>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    magnifier: Query<Entity, Or<(With<MagnifierCamera>, With<MagnifierLens>)>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::KeyL) {
+        return;
+    }
+    if !magnifier.is_empty() {
+        for entity in magnifier.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    let size = Extent3d {
+        width: (LENS_RADIUS * 2.0) as u32,
+        height: (LENS_RADIUS * 2.0) as u32,
+        depth_or_array_layers: 1,
+    };
+    let mut image = Image::new_fill(
+        size,
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Bgra8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.texture_descriptor.usage =
+        TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST | TextureUsages::RENDER_ATTACHMENT;
+    let target = images.add(image);
+
+    commands.spawn((
+        Camera2d,
+        Camera {
+            order: -1,
+            target: RenderTarget::Image(target.clone()),
+            ..default()
+        },
+        MagnifierCamera,
+        OnPlayScreen,
+    ));
+    commands
+        .spawn((
+            Mesh2d(meshes.add(Circle::new(LENS_RADIUS))),
+            MeshMaterial2d(materials.add(ColorMaterial::from(target))),
+            Transform::from_xyz(0.0, 0.0, LENS_Z),
+            Visibility::Hidden,
+            ANIMATION_LAYERS,
+            PickingBehavior::IGNORE,
+            MagnifierLens,
+            OnPlayScreen,
+        ))
+        .with_child((
+            Mesh2d(meshes.add(Annulus::new(LENS_RADIUS, LENS_RADIUS + LENS_BORDER))),
+            MeshMaterial2d(materials.add(Color::BLACK)),
+            Transform::from_xyz(0.0, 0.0, 0.1),
+            ANIMATION_LAYERS,
+            PickingBehavior::IGNORE,
+        ));
+}
+
+/// Moves the lens to the cursor, or to the first finger on touch screens
+fn follow_pointer(
+    window: Single<&Window>,
+    touches: Res<Touches>,
+    main_camera: Single<
+        (&Camera, &GlobalTransform, &OrthographicProjection),
+        (With<Camera2d>, With<IsDefaultUiCamera>),
+    >,
+    mut magnifier_camera: Query<
+        (&mut Transform, &mut OrthographicProjection),
+        (With<MagnifierCamera>, Without<IsDefaultUiCamera>),
+    >,
+    mut lens: Query<(&mut Transform, &mut Visibility), (With<MagnifierLens>, Without<Camera>)>,
+) {
+    let Ok((mut lens_transform, mut visibility)) = lens.get_single_mut() else {
+        return;
+    };
+    let pointer = window
+        .cursor_position()
+        .or_else(|| touches.iter().next().map(Touch::position));
+    let (camera, camera_transform, projection) = *main_camera;
+    let Some((pointer, world)) = pointer.and_then(|pointer| {
+        camera
+            .viewport_to_world_2d(camera_transform, pointer)
+            .ok()
+            .map(|world| (pointer, world))
+    }) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+
+    // the animation camera looks at the window center at its original zoom
+    let half_size = window.size() / 2.0;
+    lens_transform.translation.x = pointer.x - half_size.x;
+    lens_transform.translation.y = half_size.y - pointer.y;
+    *visibility = Visibility::Visible;
+
+    for (mut transform, mut magnified) in magnifier_camera.iter_mut() {
+        transform.translation.x = world.x;
+        transform.translation.y = world.y;
+        magnified.scale = projection.scale / LENS_ZOOM;
+    }
+}
@@ -1,6 +1,6 @@
 use bevy::prelude::*;
-use jigsaw_puzzle::PuzzlePlugin;
+use jigsaw_puzzle::StandalonePuzzleApp;
 
 fn main() {
-    App::new().add_plugins(PuzzlePlugin).run();
+    App::new().add_plugins(StandalonePuzzleApp).run();
 }
@@ -1,6 +1,21 @@
+use crate::crop::{spawn_crop_buttons, spawn_crop_overlay};
+use crate::cut_preview::{spawn_cut_lines_overlay, spawn_cut_preview_buttons};
+use crate::filters::spawn_filter_buttons;
+use crate::i18n::{Language, Localized};
+use crate::lucky::spawn_lucky_buttons;
+use crate::mystery::{spawn_mystery_button, spawn_mystery_cover};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::photo::TakePhoto;
+use crate::piece_shape::spawn_advanced_button;
+use crate::relaxed::Relaxed;
+use crate::surprise::SurpriseMe;
+use crate::theme::{Theme, ThemeColor};
+use crate::tutorial::StartTutorial;
+use crate::ui_scale::spawn_ui_scale_slider;
+use crate::window_state::spawn_monitor_button;
 use crate::{
-    despawn_screen, AnimeCamera, AppState, OriginImage, SelectGameMode, SelectPiece,
-    ANIMATION_LAYERS, HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON,
+    despawn_screen, AnimeCamera, AppState, CustomGrid, Difficulty, OriginImage, SelectGameMode,
+    SelectPiece, SelectPlayMode, SelectPlayers, SnapTolerance, ANIMATION_LAYERS,
 };
 use bevy::animation::{
     animated_field, AnimationEntityMut, AnimationEvaluationError, AnimationTarget,
@@ -8,6 +23,7 @@ use bevy::animation::{
 };
 use bevy::color::palettes::basic::BLACK;
 use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
 use bevy::window::WindowResized;
 use core::any::TypeId;
 
@@ -26,7 +42,23 @@ pub(crate) fn menu_plugin(app: &mut App) {
                 button_interaction,
                 show_origin_image.run_if(resource_changed::<OriginImage>),
                 update_piece_text.run_if(resource_changed::<SelectPiece>),
-                update_game_mode_text.run_if(resource_changed::<SelectGameMode>),
+                reset_custom_grid.run_if(resource_changed::<SelectPiece>),
+                update_grid_texts,
+                update_game_mode_text
+                    .run_if(resource_changed::<SelectGameMode>.or(resource_changed::<Language>)),
+                update_players_text
+                    .run_if(resource_changed::<SelectPlayers>.or(resource_changed::<Language>)),
+                update_play_mode_text
+                    .run_if(resource_changed::<SelectPlayMode>.or(resource_changed::<Language>)),
+                update_difficulty_text
+                    .run_if(resource_changed::<Difficulty>.or(resource_changed::<Language>)),
+                update_language_text.run_if(resource_changed::<Language>),
+                update_theme_text
+                    .run_if(resource_changed::<Theme>.or(resource_changed::<Language>)),
+                update_relaxed_text
+                    .run_if(resource_changed::<Relaxed>.or(resource_changed::<Language>)),
+                update_snap_tolerance_text
+                    .run_if(resource_changed::<SnapTolerance>.or(resource_changed::<Language>)),
                 show_images.run_if(resource_changed::<LoadedImages>),
             )
                 .run_if(in_state(AppState::MainMenu)),
@@ -157,6 +189,7 @@ fn show_title(
     let title_id = commands
         .spawn((
             Text2d::new("Jigsaw Puzzle"),
+            Localized("Jigsaw Puzzle"),
             text_font.clone(),
             TextLayout::new_with_justify(text_justification),
             TextColor(BLACK.into()),
@@ -177,11 +210,19 @@ fn show_title(
     });
 }
 
+#[allow(clippy::too_many_arguments)]
 fn setup_menu(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     select_piece: Res<SelectPiece>,
     select_mode: Res<SelectGameMode>,
+    select_players: Res<SelectPlayers>,
+    select_play_mode: Res<SelectPlayMode>,
+    snap_tolerance: Res<SnapTolerance>,
+    difficulty: Res<Difficulty>,
+    relaxed: Res<Relaxed>,
+    ui_scale: Res<UiScale>,
+    language: Res<Language>,
 ) {
     let image = asset_server.load("images/raw.jpg");
     commands.insert_resource(OriginImage(image));
@@ -242,6 +283,7 @@ fn setup_menu(
                 },
                 // BackgroundColor(Color::srgba(0.5, 0.0, 0.0, 0.5)),
                 BackgroundColor(Color::srgba(0.2, 0.7, 0.5, 0.5)),
+                ThemeColor::MenuPanel,
             ))
             .with_children(|p| {
                 // selector container
@@ -294,6 +336,7 @@ fn setup_menu(
                                 ..default()
                             },
                             TextColor(Color::BLACK),
+                            ThemeColor::Text,
                             Node {
                                 margin: UiRect::axes(Val::Px(10.0), Val::Px(0.0)),
                                 ..default()
@@ -319,12 +362,14 @@ fn setup_menu(
                     // text
                     p.spawn((
                         Text::new("pieces"),
+                        Localized("pieces"),
                         TextFont {
                             font: text_font.clone(),
                             font_size: 28.0,
                             ..default()
                         },
                         TextColor(Color::BLACK),
+                        ThemeColor::Text,
                         Node {
                             margin: UiRect::axes(Val::Px(0.0), Val::Px(31.0)),
                             ..default()
@@ -362,13 +407,14 @@ fn setup_menu(
                         );
                         p.spawn((
                             GameModeText,
-                            Text::new(select_mode.to_string()),
+                            Text::new(language.tr(&select_mode.to_string())),
                             TextFont {
                                 font: text_font.clone(),
                                 font_size: 28.0,
                                 ..default()
                             },
                             TextColor(Color::BLACK),
+                            ThemeColor::Text,
                             Node {
                                 margin: UiRect::axes(Val::Px(10.0), Val::Px(0.0)),
                                 ..default()
@@ -390,8 +436,482 @@ fn setup_menu(
                             },
                         );
                     });
+
+                    // players selection
+                    p.spawn(Node {
+                        height: Val::Percent(100.0),
+                        justify_content: JustifyContent::SpaceBetween,
+                        display: Display::Flex,
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    })
+                    .with_children(|p| {
+                        // up arrow
+                        p.spawn((
+                            ImageNode {
+                                image: down_arrow.clone(),
+                                flip_y: true,
+                                ..default()
+                            },
+                            Node {
+                                width: Val::Px(30.0),
+                                height: Val::Px(30.0),
+                                ..default()
+                            },
+                        ))
+                        .observe(
+                            |_trigger: Trigger<Pointer<Click>>,
+                             mut select_players: ResMut<SelectPlayers>| {
+                                select_players.toggle();
+                            },
+                        );
+                        p.spawn((
+                            PlayersText,
+                            Text::new(language.tr(&select_players.to_string())),
+                            TextFont {
+                                font: text_font.clone(),
+                                font_size: 28.0,
+                                ..default()
+                            },
+                            TextColor(Color::BLACK),
+                            ThemeColor::Text,
+                            Node {
+                                margin: UiRect::axes(Val::Px(10.0), Val::Px(0.0)),
+                                ..default()
+                            },
+                        ));
+                        // down arrow
+                        p.spawn((
+                            ImageNode::new(down_arrow.clone()),
+                            Node {
+                                width: Val::Px(30.0),
+                                height: Val::Px(30.0),
+                                ..default()
+                            },
+                        ))
+                        .observe(
+                            |_trigger: Trigger<Pointer<Click>>,
+                             mut select_players: ResMut<SelectPlayers>| {
+                                select_players.toggle();
+                            },
+                        );
+                    });
+
+                    // play mode selection
+                    p.spawn(Node {
+                        height: Val::Percent(100.0),
+                        justify_content: JustifyContent::SpaceBetween,
+                        display: Display::Flex,
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    })
+                    .with_children(|p| {
+                        // up arrow
+                        p.spawn((
+                            ImageNode {
+                                image: down_arrow.clone(),
+                                flip_y: true,
+                                ..default()
+                            },
+                            Node {
+                                width: Val::Px(30.0),
+                                height: Val::Px(30.0),
+                                ..default()
+                            },
+                        ))
+                        .observe(
+                            |_trigger: Trigger<Pointer<Click>>,
+                             mut select_play_mode: ResMut<SelectPlayMode>| {
+                                select_play_mode.previous();
+                            },
+                        );
+                        p.spawn((
+                            PlayModeText,
+                            Text::new(language.tr(&select_play_mode.to_string())),
+                            TextFont {
+                                font: text_font.clone(),
+                                font_size: 28.0,
+                                ..default()
+                            },
+                            TextColor(Color::BLACK),
+                            ThemeColor::Text,
+                            Node {
+                                margin: UiRect::axes(Val::Px(10.0), Val::Px(0.0)),
+                                ..default()
+                            },
+                        ));
+                        // down arrow
+                        p.spawn((
+                            ImageNode::new(down_arrow.clone()),
+                            Node {
+                                width: Val::Px(30.0),
+                                height: Val::Px(30.0),
+                                ..default()
+                            },
+                        ))
+                        .observe(
+                            |_trigger: Trigger<Pointer<Click>>,
+                             mut select_play_mode: ResMut<SelectPlayMode>| {
+                                select_play_mode.toggle();
+                            },
+                        );
+                    });
+                    // difficulty selection, applies the preset of the difficulty
+                    p.spawn(Node {
+                        height: Val::Percent(100.0),
+                        justify_content: JustifyContent::SpaceBetween,
+                        display: Display::Flex,
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    })
+                    .with_children(|p| {
+                        // up arrow
+                        p.spawn((
+                            ImageNode {
+                                image: down_arrow.clone(),
+                                flip_y: true,
+                                ..default()
+                            },
+                            Node {
+                                width: Val::Px(30.0),
+                                height: Val::Px(30.0),
+                                ..default()
+                            },
+                        ))
+                        .observe(
+                            |_trigger: Trigger<Pointer<Click>>,
+                             mut difficulty: ResMut<Difficulty>,
+                             mut select_piece: ResMut<SelectPiece>,
+                             mut custom_grid: ResMut<CustomGrid>,
+                             mut snap_tolerance: ResMut<SnapTolerance>| {
+                                difficulty.previous();
+                                difficulty.apply(
+                                    &mut select_piece,
+                                    &mut custom_grid,
+                                    &mut snap_tolerance,
+                                );
+                            },
+                        );
+                        p.spawn((
+                            DifficultyText,
+                            Text::new(language.tr(&difficulty.to_string())),
+                            TextFont {
+                                font: text_font.clone(),
+                                font_size: 28.0,
+                                ..default()
+                            },
+                            TextColor(Color::BLACK),
+                            ThemeColor::Text,
+                            Node {
+                                margin: UiRect::axes(Val::Px(10.0), Val::Px(0.0)),
+                                ..default()
+                            },
+                        ));
+                        // down arrow
+                        p.spawn((
+                            ImageNode::new(down_arrow.clone()),
+                            Node {
+                                width: Val::Px(30.0),
+                                height: Val::Px(30.0),
+                                ..default()
+                            },
+                        ))
+                        .observe(
+                            |_trigger: Trigger<Pointer<Click>>,
+                             mut difficulty: ResMut<Difficulty>,
+                             mut select_piece: ResMut<SelectPiece>,
+                             mut custom_grid: ResMut<CustomGrid>,
+                             mut snap_tolerance: ResMut<SnapTolerance>| {
+                                difficulty.next();
+                                difficulty.apply(
+                                    &mut select_piece,
+                                    &mut custom_grid,
+                                    &mut snap_tolerance,
+                                );
+                            },
+                        );
+                    });
+                    // snap tolerance selection
+                    p.spawn(Node {
+                        height: Val::Percent(100.0),
+                        justify_content: JustifyContent::SpaceBetween,
+                        display: Display::Flex,
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    })
+                    .with_children(|p| {
+                        // up arrow
+                        p.spawn((
+                            ImageNode {
+                                image: down_arrow.clone(),
+                                flip_y: true,
+                                ..default()
+                            },
+                            Node {
+                                width: Val::Px(30.0),
+                                height: Val::Px(30.0),
+                                ..default()
+                            },
+                        ))
+                        .observe(
+                            |_trigger: Trigger<Pointer<Click>>,
+                             mut snap_tolerance: ResMut<SnapTolerance>| {
+                                snap_tolerance.previous();
+                            },
+                        );
+                        p.spawn((
+                            SnapToleranceText,
+                            Text::new(language.tr(&snap_tolerance.to_string())),
+                            TextFont {
+                                font: text_font.clone(),
+                                font_size: 28.0,
+                                ..default()
+                            },
+                            TextColor(Color::BLACK),
+                            ThemeColor::Text,
+                            Node {
+                                margin: UiRect::axes(Val::Px(10.0), Val::Px(0.0)),
+                                ..default()
+                            },
+                        ));
+                        // down arrow
+                        p.spawn((
+                            ImageNode::new(down_arrow.clone()),
+                            Node {
+                                width: Val::Px(30.0),
+                                height: Val::Px(30.0),
+                                ..default()
+                            },
+                        ))
+                        .observe(
+                            |_trigger: Trigger<Pointer<Click>>,
+                             mut snap_tolerance: ResMut<SnapTolerance>| {
+                                snap_tolerance.next();
+                            },
+                        );
+                    });
                 });
 
+                // piece count slider
+                p.spawn((
+                    Node {
+                        width: Val::Px(300.0),
+                        height: Val::Px(12.0),
+                        margin: UiRect::top(Val::Px(10.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.3)),
+                    BorderRadius::MAX,
+                    RelativeCursorPosition::default(),
+                ))
+                .with_child((
+                    PieceSliderHandle,
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Percent(select_piece.fraction() * 100.0),
+                        top: Val::Px(-4.0),
+                        width: Val::Px(20.0),
+                        height: Val::Px(20.0),
+                        margin: UiRect::left(Val::Px(-10.0)),
+                        ..default()
+                    },
+                    BackgroundColor(Color::BLACK),
+                    BorderRadius::MAX,
+                    PickingBehavior::IGNORE,
+                ))
+                .observe(slide_piece_count::<Click>)
+                .observe(slide_piece_count::<Drag>);
+
+                // columns x rows, the arrows switch to a custom grid
+                p.spawn(Node {
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(8.0),
+                    margin: UiRect::top(Val::Px(10.0)),
+                    ..default()
+                })
+                .with_children(|p| {
+                    let grid_font = TextFont {
+                        font: text_font.clone(),
+                        font_size: 22.0,
+                        ..default()
+                    };
+                    p.spawn((
+                        GridText::Mode,
+                        Text::default(),
+                        grid_font.clone(),
+                        TextColor(Color::BLACK),
+                        ThemeColor::Text,
+                    ))
+                    .observe(
+                        |_trigger: Trigger<Pointer<Click>>,
+                         origin_image: Res<OriginImage>,
+                         images: Res<Assets<Image>>,
+                         mut custom_grid: ResMut<CustomGrid>,
+                         mut select_piece: ResMut<SelectPiece>| {
+                            if custom_grid.0.is_some() {
+                                custom_grid.0 = None;
+                            } else if let Some((columns, rows)) = current_grid(
+                                &custom_grid,
+                                &select_piece,
+                                images.get(&origin_image.0),
+                            ) {
+                                custom_grid.set(&mut select_piece, columns, rows);
+                            }
+                        },
+                    );
+                    spawn_grid_arrow(p, "<", (-1, 0), grid_font.clone());
+                    p.spawn((
+                        GridText::Columns,
+                        Text::default(),
+                        grid_font.clone(),
+                        TextColor(Color::BLACK),
+                        ThemeColor::Text,
+                    ));
+                    spawn_grid_arrow(p, ">", (1, 0), grid_font.clone());
+                    p.spawn((
+                        Text::new("x"),
+                        grid_font.clone(),
+                        TextColor(Color::BLACK),
+                        ThemeColor::Text,
+                    ));
+                    spawn_grid_arrow(p, "<", (0, -1), grid_font.clone());
+                    p.spawn((
+                        GridText::Rows,
+                        Text::default(),
+                        grid_font.clone(),
+                        TextColor(Color::BLACK),
+                        ThemeColor::Text,
+                    ));
+                    spawn_grid_arrow(p, ">", (0, 1), grid_font.clone());
+                    p.spawn((
+                        GridText::PieceSize,
+                        Text::default(),
+                        grid_font,
+                        TextColor(Color::BLACK),
+                        ThemeColor::Text,
+                    ));
+                });
+
+                // language, click to switch
+                p.spawn((
+                    LanguageText,
+                    Text::new(language.to_string()),
+                    TextFont {
+                        font: text_font.clone(),
+                        font_size: 24.0,
+                        ..default()
+                    },
+                    TextColor(Color::BLACK),
+                    ThemeColor::Text,
+                    Node {
+                        margin: UiRect::new(
+                            Val::Px(20.0),
+                            Val::Px(0.0),
+                            Val::Px(10.0),
+                            Val::Px(0.0),
+                        ),
+                        ..default()
+                    },
+                ))
+                .observe(
+                    |_trigger: Trigger<Pointer<Click>>, mut language: ResMut<Language>| {
+                        language.toggle();
+                    },
+                );
+
+                // theme, click to switch
+                p.spawn((
+                    ThemeText,
+                    Text::default(),
+                    TextFont {
+                        font: text_font.clone(),
+                        font_size: 24.0,
+                        ..default()
+                    },
+                    TextColor(Color::BLACK),
+                    ThemeColor::Text,
+                    Node {
+                        margin: UiRect::new(
+                            Val::Px(20.0),
+                            Val::Px(0.0),
+                            Val::Px(5.0),
+                            Val::Px(0.0),
+                        ),
+                        ..default()
+                    },
+                ))
+                .observe(
+                    |_trigger: Trigger<Pointer<Click>>, mut theme: ResMut<Theme>| {
+                        theme.toggle();
+                    },
+                );
+
+                // relaxed mode, click to switch
+                p.spawn((
+                    RelaxedText,
+                    Text::new(relaxed_label(&relaxed, &language)),
+                    TextFont {
+                        font: text_font.clone(),
+                        font_size: 24.0,
+                        ..default()
+                    },
+                    TextColor(Color::BLACK),
+                    ThemeColor::Text,
+                    Node {
+                        margin: UiRect::new(
+                            Val::Px(20.0),
+                            Val::Px(0.0),
+                            Val::Px(5.0),
+                            Val::Px(0.0),
+                        ),
+                        ..default()
+                    },
+                ))
+                .observe(
+                    |_trigger: Trigger<Pointer<Click>>, mut relaxed: ResMut<Relaxed>| {
+                        relaxed.toggle();
+                    },
+                );
+
+                spawn_mystery_button(p, text_font.clone());
+                spawn_ui_scale_slider(p, text_font.clone(), ui_scale.0);
+                spawn_monitor_button(p, text_font.clone());
+                spawn_advanced_button(p, text_font.clone());
+                spawn_filter_buttons(p, text_font.clone());
+                #[cfg(not(target_arch = "wasm32"))]
+                crate::save_file::spawn_import_button(p, text_font.clone());
+
+                // plays the tutorial again
+                p.spawn((
+                    Text::new("Tutorial"),
+                    Localized("Tutorial"),
+                    TextFont {
+                        font: text_font.clone(),
+                        font_size: 24.0,
+                        ..default()
+                    },
+                    TextColor(Color::BLACK),
+                    ThemeColor::Text,
+                    Node {
+                        margin: UiRect::new(
+                            Val::Px(20.0),
+                            Val::Px(0.0),
+                            Val::Px(5.0),
+                            Val::Px(0.0),
+                        ),
+                        ..default()
+                    },
+                ))
+                .observe(
+                    |_trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+                        commands.trigger(StartTutorial);
+                    },
+                );
+
                 // start button
                 p.spawn((
                     Button,
@@ -412,12 +932,14 @@ fn setup_menu(
                 ))
                 .with_child((
                     Text::new("Start"),
+                    Localized("Start"),
                     TextFont {
                         font: text_font.clone(),
                         font_size: 33.0,
                         ..default()
                     },
                     TextColor(Color::BLACK),
+                    ThemeColor::Text,
                 ))
                 .observe(
                     |_trigger: Trigger<Pointer<Click>>,
@@ -463,7 +985,124 @@ fn setup_menu(
                             color: Color::BLACK,
                             offset: Val::Px(2.0),
                         },
-                    ));
+                    ))
+                    .with_children(|p| {
+                        spawn_cut_lines_overlay(p);
+                        spawn_crop_overlay(p);
+                        spawn_mystery_cover(p);
+                    });
+
+                    // made up images
+                    p.spawn((
+                        HiddenItem,
+                        Visibility::Hidden,
+                        Node {
+                            position_type: PositionType::Absolute,
+                            top: Val::Px(15.0),
+                            left: Val::Px(15.0),
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Px(5.0),
+                            ..default()
+                        },
+                    ))
+                    .with_children(|p| {
+                        for (label, event) in [
+                            ("Surprise me", SurpriseMe::New),
+                            ("Regenerate", SurpriseMe::Regenerate),
+                        ] {
+                            p.spawn((
+                                Text::new(label),
+                                Localized(label),
+                                TextFont {
+                                    font: text_font.clone(),
+                                    font_size: 24.0,
+                                    ..default()
+                                },
+                                TextColor(Color::BLACK),
+                                ThemeColor::Text,
+                            ))
+                            .observe(
+                                move |_trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+                                    commands.send_event(event);
+                                },
+                            );
+                        }
+                        spawn_cut_preview_buttons(
+                            p,
+                            TextFont {
+                                font: text_font.clone(),
+                                font_size: 24.0,
+                                ..default()
+                            },
+                        );
+                        spawn_lucky_buttons(
+                            p,
+                            TextFont {
+                                font: text_font.clone(),
+                                font_size: 24.0,
+                                ..default()
+                            },
+                        );
+                        spawn_crop_buttons(
+                            p,
+                            TextFont {
+                                font: text_font.clone(),
+                                font_size: 24.0,
+                                ..default()
+                            },
+                        );
+                    });
+
+                    // images from elsewhere
+                    p.spawn((
+                        HiddenItem,
+                        Visibility::Hidden,
+                        Node {
+                            position_type: PositionType::Absolute,
+                            top: Val::Px(15.0),
+                            right: Val::Px(15.0),
+                            flex_direction: FlexDirection::Column,
+                            align_items: AlignItems::End,
+                            row_gap: Val::Px(5.0),
+                            ..default()
+                        },
+                    ))
+                    .with_children(|p| {
+                        // the web build can not read local folders, images are uploaded instead
+                        #[cfg(target_arch = "wasm32")]
+                        p.spawn((
+                            Text::new("Upload image"),
+                            Localized("Upload image"),
+                            TextFont {
+                                font: text_font.clone(),
+                                font_size: 24.0,
+                                ..default()
+                            },
+                            TextColor(Color::BLACK),
+                            ThemeColor::Text,
+                        ))
+                        .observe(|_trigger: Trigger<Pointer<Click>>| {
+                            crate::upload::open_file_picker();
+                        });
+                        // photos are taken with the camera of the desktop
+                        #[cfg(not(target_arch = "wasm32"))]
+                        p.spawn((
+                            Text::new("Take photo"),
+                            Localized("Take photo"),
+                            TextFont {
+                                font: text_font.clone(),
+                                font_size: 24.0,
+                                ..default()
+                            },
+                            TextColor(Color::BLACK),
+                            ThemeColor::Text,
+                        ))
+                        .observe(
+                            |_trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+                                commands.send_event(TakePhoto);
+                            },
+                        );
+                    });
                 });
 
             // images collection container
@@ -520,10 +1159,15 @@ struct OriginImageContainer;
 #[derive(Component)]
 struct ImagesContainer;
 
+/// An image in the strip of images to pick from
+#[derive(Component)]
+pub(crate) struct ImageThumbnail;
+
 #[derive(Resource, Default, Deref, DerefMut)]
 pub struct LoadedImages(Vec<Handle<Image>>);
 
 fn load_default_images(asset_server: Res<AssetServer>, mut loaded_images: ResMut<LoadedImages>) {
+    loaded_images.0.clear();
     for path in IMAGE_PATHS {
         let image_handle = asset_server.load(path);
 
@@ -556,18 +1200,20 @@ fn menu_countdown(
 fn button_interaction(
     interaction_query: Query<(&Interaction, &Children), (Changed<Interaction>, With<Button>)>,
     mut text_query: Query<&mut TextColor>,
+    theme: Res<Theme>,
 ) {
+    let palette = theme.palette();
     for (interaction, children) in interaction_query.iter() {
         let mut text_color = text_query.get_mut(children[0]).unwrap();
         match *interaction {
             Interaction::Pressed => {
-                text_color.0 = PRESSED_BUTTON;
+                text_color.0 = palette.button_pressed;
             }
             Interaction::Hovered => {
-                text_color.0 = HOVERED_BUTTON;
+                text_color.0 = palette.button_hovered;
             }
             Interaction::None => {
-                text_color.0 = NORMAL_BUTTON;
+                text_color.0 = palette.text;
             }
         }
     }
@@ -583,12 +1229,31 @@ fn show_origin_image(
         .insert(ImageNode::new(origin_image.0.clone()));
 }
 
+/// Adds the new images to the strip and removes the ones which are gone
 fn show_images(
-    container: Single<Entity, With<ImagesContainer>>,
+    container: Single<(Entity, Option<&Children>), With<ImagesContainer>>,
     mut commands: Commands,
     loaded_images: Res<LoadedImages>,
+    image_nodes: Query<&ImageNode>,
 ) {
-    for image in loaded_images.0.iter() {
+    let (container, children) = container.into_inner();
+    let mut shown = Vec::new();
+    for child in children.into_iter().flatten() {
+        let Ok(image_node) = image_nodes.get(*child) else {
+            continue;
+        };
+        if loaded_images.0.contains(&image_node.image) {
+            shown.push(image_node.image.id());
+        } else {
+            commands.entity(*child).despawn_recursive();
+        }
+    }
+
+    for image in loaded_images
+        .0
+        .iter()
+        .filter(|image| !shown.contains(&image.id()))
+    {
         let child_node = commands
             .spawn((
                 ImageNode::new(image.clone()),
@@ -596,6 +1261,7 @@ fn show_images(
                     margin: UiRect::axes(Val::Px(10.0), Val::Px(0.0)),
                     ..default()
                 },
+                ImageThumbnail,
                 // Outline {
                 //     width: Val::Px(2.0),
                 //     color: Color::BLACK,
@@ -616,32 +1282,253 @@ fn show_images(
             )
             .id();
 
-        commands.entity(*container).add_child(child_node);
+        commands.entity(container).add_child(child_node);
     }
 }
 
 #[derive(Component)]
 struct PieceNumText;
 
+#[derive(Component)]
+struct PieceSliderHandle;
+
+/// The texts of the grid selection
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum GridText {
+    Mode,
+    Columns,
+    Rows,
+    PieceSize,
+}
+
 #[derive(Component)]
 struct GameModeText;
 
+#[derive(Component)]
+struct PlayersText;
+
+#[derive(Component)]
+struct PlayModeText;
+
+#[derive(Component)]
+struct SnapToleranceText;
+
+#[derive(Component)]
+struct DifficultyText;
+
+#[derive(Component)]
+struct LanguageText;
+
+#[derive(Component)]
+struct ThemeText;
+
+#[derive(Component)]
+struct RelaxedText;
+
 fn update_game_mode_text(
     select_mode: Res<SelectGameMode>,
+    language: Res<Language>,
     mut mode_query: Query<&mut Text, With<GameModeText>>,
 ) {
     for mut text in mode_query.iter_mut() {
-        text.0 = select_mode.to_string();
+        text.0 = language.tr(&select_mode.to_string()).to_string();
+    }
+}
+
+fn update_players_text(
+    select_players: Res<SelectPlayers>,
+    language: Res<Language>,
+    mut players_query: Query<&mut Text, With<PlayersText>>,
+) {
+    for mut text in players_query.iter_mut() {
+        text.0 = language.tr(&select_players.to_string()).to_string();
+    }
+}
+
+fn update_play_mode_text(
+    select_play_mode: Res<SelectPlayMode>,
+    language: Res<Language>,
+    mut play_mode_query: Query<&mut Text, With<PlayModeText>>,
+) {
+    for mut text in play_mode_query.iter_mut() {
+        text.0 = language.tr(&select_play_mode.to_string()).to_string();
+    }
+}
+
+fn update_snap_tolerance_text(
+    snap_tolerance: Res<SnapTolerance>,
+    language: Res<Language>,
+    mut snap_tolerance_query: Query<&mut Text, With<SnapToleranceText>>,
+) {
+    for mut text in snap_tolerance_query.iter_mut() {
+        text.0 = language.tr(&snap_tolerance.to_string()).to_string();
+    }
+}
+
+fn update_difficulty_text(
+    difficulty: Res<Difficulty>,
+    language: Res<Language>,
+    mut difficulty_query: Query<&mut Text, With<DifficultyText>>,
+) {
+    for mut text in difficulty_query.iter_mut() {
+        text.0 = language.tr(&difficulty.to_string()).to_string();
+    }
+}
+
+fn update_theme_text(
+    theme: Res<Theme>,
+    language: Res<Language>,
+    mut theme_query: Query<&mut Text, With<ThemeText>>,
+) {
+    for mut text in theme_query.iter_mut() {
+        text.0 = format!(
+            "{}: {}",
+            language.tr("Theme"),
+            language.tr(&theme.to_string())
+        );
+    }
+}
+
+fn update_relaxed_text(
+    relaxed: Res<Relaxed>,
+    language: Res<Language>,
+    mut relaxed_query: Query<&mut Text, With<RelaxedText>>,
+) {
+    for mut text in relaxed_query.iter_mut() {
+        text.0 = relaxed_label(&relaxed, &language);
+    }
+}
+
+fn relaxed_label(relaxed: &Relaxed, language: &Language) -> String {
+    format!(
+        "{}: {}",
+        language.tr("Relaxed"),
+        language.tr(if relaxed.0 { "On" } else { "Off" })
+    )
+}
+
+fn update_language_text(
+    language: Res<Language>,
+    mut language_query: Query<&mut Text, With<LanguageText>>,
+) {
+    for mut text in language_query.iter_mut() {
+        text.0 = language.to_string();
     }
 }
 
 fn update_piece_text(
     select_piece: Res<SelectPiece>,
     mut piece_query: Query<&mut Text, With<PieceNumText>>,
+    mut handle_query: Query<&mut Node, With<PieceSliderHandle>>,
 ) {
     for mut text in piece_query.iter_mut() {
         text.0 = select_piece.to_string();
     }
+    for mut node in handle_query.iter_mut() {
+        node.left = Val::Percent(select_piece.fraction() * 100.0);
+    }
+}
+
+/// The grid the puzzle will be cut into, unknown until the selected image is loaded
+pub(crate) fn current_grid(
+    custom_grid: &CustomGrid,
+    select_piece: &SelectPiece,
+    image: Option<&Image>,
+) -> Option<(usize, usize)> {
+    custom_grid.0.or_else(|| {
+        let size = image?.size();
+        Some(select_piece.get_columns_rows(size.x, size.y))
+    })
+}
+
+/// Spawns a clickable text changing the columns and rows of the grid by `step`
+fn spawn_grid_arrow(p: &mut ChildBuilder, label: &str, step: (isize, isize), font: TextFont) {
+    p.spawn((
+        Text::new(label),
+        font,
+        TextColor(Color::BLACK),
+        ThemeColor::Text,
+    ))
+    .observe(
+        move |_trigger: Trigger<Pointer<Click>>,
+              origin_image: Res<OriginImage>,
+              images: Res<Assets<Image>>,
+              mut custom_grid: ResMut<CustomGrid>,
+              mut select_piece: ResMut<SelectPiece>| {
+            let Some((columns, rows)) =
+                current_grid(&custom_grid, &select_piece, images.get(&origin_image.0))
+            else {
+                return;
+            };
+            custom_grid.set(
+                &mut select_piece,
+                columns.saturating_add_signed(step.0),
+                rows.saturating_add_signed(step.1),
+            );
+        },
+    );
+}
+
+/// Picking a piece count with the slider or arrows goes back to the automatic grid
+fn reset_custom_grid(select_piece: Res<SelectPiece>, mut custom_grid: ResMut<CustomGrid>) {
+    if let Some((columns, rows)) = custom_grid.0 {
+        if columns * rows != select_piece.0 {
+            custom_grid.0 = None;
+        }
+    }
+}
+
+/// Shows the grid and the resulting size of a piece in pixels
+fn update_grid_texts(
+    custom_grid: Res<CustomGrid>,
+    select_piece: Res<SelectPiece>,
+    language: Res<Language>,
+    origin_image: Res<OriginImage>,
+    images: Res<Assets<Image>>,
+    mut texts: Query<(&mut Text, &GridText)>,
+) {
+    let image = images.get(&origin_image.0);
+    let grid = current_grid(&custom_grid, &select_piece, image);
+    for (mut text, grid_text) in texts.iter_mut() {
+        let value = match grid_text {
+            GridText::Mode if custom_grid.0.is_some() => language.tr("Custom grid").to_string(),
+            GridText::Mode => language.tr("Auto grid").to_string(),
+            GridText::Columns => grid
+                .map(|(columns, _)| columns.to_string())
+                .unwrap_or_default(),
+            GridText::Rows => grid.map(|(_, rows)| rows.to_string()).unwrap_or_default(),
+            GridText::PieceSize => match (grid, image) {
+                (Some((columns, rows)), Some(image)) => {
+                    let size = image.size();
+                    format!(
+                        "{} x {} px",
+                        size.x as usize / columns,
+                        size.y as usize / rows
+                    )
+                }
+                _ => String::new(),
+            },
+        };
+        if text.0 != value {
+            text.0 = value;
+        }
+    }
+}
+
+/// Picks the number of pieces at the clicked or dragged position of the slider
+fn slide_piece_count<E: core::fmt::Debug + Clone + Reflect>(
+    trigger: Trigger<Pointer<E>>,
+    q_slider: Query<&RelativeCursorPosition>,
+    mut select_piece: ResMut<SelectPiece>,
+) {
+    let Some(normalized) = q_slider
+        .get(trigger.entity())
+        .ok()
+        .and_then(|cursor| cursor.normalized)
+    else {
+        return;
+    };
+    select_piece.set_if_neq(SelectPiece::from_fraction(normalized.x));
 }
 
 #[derive(Resource, Default)]
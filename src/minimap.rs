@@ -0,0 +1,226 @@
+use crate::board::Locked;
+use crate::gameplay::{JigsawPuzzleGenerator, MoveTogether, OnPlayScreen};
+use crate::locate::FindPiece;
+use crate::tray::InTray;
+use crate::{GameState, Piece};
+use bevy::prelude::*;
+use bevy::time::common_conditions::on_timer;
+use bevy::ui::RelativeCursorPosition;
+use bevy::utils::HashSet;
+use core::time::Duration;
+use jigsaw_puzzle_generator::image::GenericImageView;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Play), setup_minimap)
+        .add_systems(
+            Update,
+            update_minimap
+                .run_if(on_timer(Duration::from_secs_f32(REFRESH_INTERVAL)))
+                .run_if(in_state(GameState::Play)),
+        );
+}
+
+const MINIMAP_SIZE: Vec2 = Vec2::new(200.0, 120.0);
+/// How often the minimap is redrawn, in seconds
+const REFRESH_INTERVAL: f32 = 0.2;
+
+const LOOSE_COLOR: Color = Color::srgb(0.9, 0.9, 0.9);
+const SOLVED_COLOR: Color = Color::srgb(0.3, 0.85, 0.3);
+
+/// A small overview of the table in the corner of the screen
+#[derive(Component, Debug, Default)]
+struct Minimap {
+    /// The area of the table shown in the minimap, in world coordinates
+    world: Rect,
+}
+
+impl Minimap {
+    /// Converts a world position into a position in the minimap node
+    fn to_minimap(&self, position: Vec2) -> Vec2 {
+        let normalized = (position - self.world.min) / self.world.size();
+        Vec2::new(normalized.x, 1.0 - normalized.y) * MINIMAP_SIZE
+    }
+
+    /// Converts a normalized position in the minimap node into a world position
+    fn to_world(&self, normalized: Vec2) -> Vec2 {
+        self.world.min + Vec2::new(normalized.x, 1.0 - normalized.y) * self.world.size()
+    }
+}
+
+fn setup_minimap(mut commands: Commands, q_minimap: Query<Entity, With<Minimap>>) {
+    if !q_minimap.is_empty() {
+        return;
+    }
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(10.0),
+                bottom: Val::Px(70.0),
+                width: Val::Px(MINIMAP_SIZE.x),
+                height: Val::Px(MINIMAP_SIZE.y),
+                overflow: Overflow::clip(),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            RelativeCursorPosition::default(),
+            Minimap::default(),
+            OnPlayScreen,
+        ))
+        .observe(jump_to_click);
+}
+
+/// Moves the camera to the clicked point of the table, a right click on the board finds the
+/// piece which belongs there
+fn jump_to_click(
+    trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    generator: Res<JigsawPuzzleGenerator>,
+    q_minimap: Query<(&Minimap, &RelativeCursorPosition)>,
+    pieces: Query<(Entity, &Piece)>,
+    mut camera: Single<&mut Transform, With<IsDefaultUiCamera>>,
+) {
+    let Ok((minimap, cursor)) = q_minimap.get(trigger.entity()) else {
+        return;
+    };
+    let Some(normalized) = cursor.normalized else {
+        return;
+    };
+    let target = minimap.to_world(normalized);
+    match trigger.event().button {
+        PointerButton::Primary => {
+            camera.translation = target.extend(camera.translation.z);
+        }
+        PointerButton::Secondary => {
+            let (width, height) = generator.origin_image().dimensions();
+            // in image coordinates, from the top left of the solved puzzle
+            let spot = Vec2::new(
+                target.x + width as f32 / 2.0,
+                height as f32 / 2.0 - target.y,
+            );
+            if let Some((entity, _)) = pieces.iter().find(|(_, piece)| {
+                Rect::new(
+                    piece.start_point.0,
+                    piece.start_point.1,
+                    piece.start_point.0 + piece.width,
+                    piece.start_point.1 + piece.height,
+                )
+                .contains(spot)
+            }) {
+                commands.trigger(FindPiece(entity));
+            }
+        }
+        PointerButton::Middle => {}
+    }
+}
+
+/// Spawns a child node covering `rect` of the minimap
+fn spawn_rect(parent: &mut ChildBuilder, rect: Rect, color: Color, outline: bool) {
+    let mut node = parent.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(rect.min.x),
+            top: Val::Px(rect.min.y),
+            width: Val::Px(rect.width()),
+            height: Val::Px(rect.height()),
+            border: UiRect::all(Val::Px(1.0)),
+            ..default()
+        },
+        PickingBehavior::IGNORE,
+    ));
+    if outline {
+        node.insert(BorderColor(color));
+    } else {
+        node.insert(BackgroundColor(color));
+    }
+}
+
+/// Redraws the board outline, a dot for every cluster of pieces and the visible area
+fn update_minimap(
+    mut commands: Commands,
+    generator: Res<JigsawPuzzleGenerator>,
+    window: Single<&Window>,
+    camera: Single<(&Transform, &OrthographicProjection), With<IsDefaultUiCamera>>,
+    mut q_minimap: Query<(Entity, &mut Minimap)>,
+    pieces: Query<(Entity, &Piece, &Transform, &MoveTogether, Has<Locked>), Without<InTray>>,
+) {
+    let Ok((minimap_entity, mut minimap)) = q_minimap.get_single_mut() else {
+        return;
+    };
+
+    // one dot per loose piece or group, at the center of the pieces
+    let mut visited = HashSet::default();
+    let mut clusters = vec![];
+    for (entity, piece, transform, move_together, locked) in pieces.iter() {
+        if visited.contains(&entity) {
+            continue;
+        }
+        let center = |piece: &Piece, transform: &Transform| {
+            transform.translation.xy() + Vec2::new(piece.width, -piece.height) / 2.0
+        };
+        if move_together.is_empty() {
+            clusters.push((center(piece, transform), 1, locked));
+            continue;
+        }
+        visited.extend(move_together.iter().copied());
+        let members: Vec<_> = pieces
+            .iter_many(move_together.iter())
+            .map(|(_, piece, transform, ..)| center(piece, transform))
+            .collect();
+        let sum: Vec2 = members.iter().sum();
+        clusters.push((sum / members.len() as f32, members.len(), true));
+    }
+
+    // fit the board and all pieces, keeping the aspect ratio of the minimap
+    let (width, height) = generator.origin_image().dimensions();
+    let board = Rect::from_center_size(Vec2::ZERO, Vec2::new(width as f32, height as f32));
+    let mut world = clusters
+        .iter()
+        .fold(board, |rect, (center, ..)| rect.union_point(*center))
+        .inflate(50.0);
+    let aspect = MINIMAP_SIZE.x / MINIMAP_SIZE.y;
+    if world.width() / world.height() < aspect {
+        world = Rect::from_center_size(
+            world.center(),
+            Vec2::new(world.height() * aspect, world.height()),
+        );
+    } else {
+        world = Rect::from_center_size(
+            world.center(),
+            Vec2::new(world.width(), world.width() / aspect),
+        );
+    }
+    minimap.world = world;
+
+    let (camera_transform, projection) = *camera;
+    let view = Rect::from_center_size(
+        camera_transform.translation.xy(),
+        window.size() * projection.scale,
+    );
+    let to_minimap_rect =
+        |rect: Rect| Rect::from_corners(minimap.to_minimap(rect.min), minimap.to_minimap(rect.max));
+
+    commands
+        .entity(minimap_entity)
+        .despawn_descendants()
+        .with_children(|p| {
+            spawn_rect(
+                p,
+                to_minimap_rect(board),
+                Color::srgba(1.0, 1.0, 1.0, 0.5),
+                true,
+            );
+            for (center, count, solved) in clusters.iter() {
+                let size = if *count > 1 { 6.0 } else { 3.0 };
+                let color = if *solved { SOLVED_COLOR } else { LOOSE_COLOR };
+                spawn_rect(
+                    p,
+                    Rect::from_center_size(minimap.to_minimap(*center), Vec2::splat(size)),
+                    color,
+                    false,
+                );
+            }
+            spawn_rect(p, to_minimap_rect(view), Color::srgb(1.0, 0.85, 0.2), true);
+        });
+}
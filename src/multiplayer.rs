@@ -0,0 +1,1447 @@
+//! LAN cooperative play. Start one game with `--host <addr>` and the others with
+//! `--join <addr>`, or open the web build with `?join=ws://<addr>`. The host shares the image,
+//! seed, grid, piece shape and filters, replicates piece moves to every client and decides about snaps, the clients
+//! only tell it where they dropped a piece. Chat lines and pings are relayed by the host as well,
+//! see [`crate::chat`].
+//!
+//! Native games send one message per line over TCP. Browsers can't open TCP connections, so the
+//! host answers their WebSocket handshake on the same port and sends one message per text frame.
+
+use crate::events::PuzzleCompleted;
+use crate::filters::ImageFilters;
+use crate::gameplay::{
+    GameTimer, JigsawPuzzleTemplate, MoveEnd, MoveStart, MoveTogether, RemoteSnaps, Selected,
+};
+use crate::piece_shape::PieceShape;
+use crate::{
+    despawn_screen, AppState, CustomGrid, GameState, OriginImage, Piece, PuzzleSeed,
+    SelectGameMode, SelectPiece, SelectPlayMode,
+};
+use bevy::prelude::*;
+use bevy::utils::{HashMap, HashSet};
+use jigsaw_puzzle_generator::filter::ImageFilter;
+use jigsaw_puzzle_generator::GameMode;
+use log::{error, info, warn};
+use std::io::ErrorKind;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::{Read, Write};
+#[cfg(not(target_arch = "wasm32"))]
+use std::net::{TcpListener, TcpStream};
+
+pub(super) fn plugin(app: &mut App) {
+    let Some(session) = NetSession::from_args() else {
+        return;
+    };
+
+    if !session.is_host() {
+        app.insert_resource(RemoteSnaps);
+    }
+    app.insert_resource(session)
+        .add_event::<NetEvent>()
+        .add_event::<RemoteCursorMoved>()
+        .add_systems(PreUpdate, poll_network)
+        .add_systems(
+            Update,
+            (
+                host_handle_events.run_if(is_host),
+                client_handle_events.run_if(not(is_host)),
+                start_remote_puzzle.run_if(not(is_host)),
+                update_remote_cursors,
+            ),
+        )
+        .add_systems(
+            OnEnter(GameState::Setup),
+            (
+                choose_seed.run_if(is_host),
+                apply_remote_puzzle.run_if(not(is_host)),
+            ),
+        )
+        .add_systems(OnEnter(GameState::Generating), share_puzzle.run_if(is_host))
+        .add_systems(OnEnter(GameState::Play), request_sync.run_if(not(is_host)))
+        .add_systems(
+            PostUpdate,
+            (
+                host_broadcast_changes.run_if(is_host),
+                // the host snaps a dropped piece where it was moved to last
+                (client_send_changes, client_send_drops)
+                    .chain()
+                    .run_if(not(is_host)),
+                send_cursor,
+            )
+                .run_if(in_state(GameState::Play)),
+        )
+        .add_systems(PostUpdate, flush_network)
+        .add_systems(OnExit(AppState::Gameplay), despawn_screen::<RemoteCursor>)
+        .add_plugins(crate::chat::plugin);
+}
+
+/// Id of a player in the session, the host is always `0`
+pub type PeerId = u8;
+
+const HOST_ID: PeerId = 0;
+
+/// A message exchanged between host and clients, encoded as a single line of text
+#[derive(Debug, Clone, PartialEq)]
+pub enum NetMessage {
+    /// A native client greets the host, browsers start with the WebSocket handshake instead
+    Hello,
+    /// Host tells a new client its id
+    Welcome { peer: PeerId },
+    /// Host shares the puzzle everybody plays, with every setting that changes how it is cut
+    Puzzle {
+        seed: usize,
+        columns: usize,
+        rows: usize,
+        mode: GameMode,
+        shape: PieceShape,
+        filters: Vec<ImageFilter>,
+        image: String,
+    },
+    /// Client asks for the position of every piece
+    Sync,
+    /// Position of a piece
+    Piece { index: usize, translation: Vec3 },
+    /// The pieces moving together with a piece
+    Group { index: usize, members: Vec<usize> },
+    /// Client released a piece, the host checks for snaps
+    Drop { index: usize },
+    /// World position of a player's cursor
+    Cursor { peer: PeerId, position: Vec2 },
+    /// A player left the session
+    Leave { peer: PeerId },
+    /// A line a player wrote into the chat
+    Chat { peer: PeerId, text: String },
+    /// A player points at a place on the table
+    Ping { peer: PeerId, position: Vec2 },
+}
+
+impl NetMessage {
+    fn to_line(&self) -> String {
+        match self {
+            NetMessage::Hello => "hello".to_string(),
+            NetMessage::Welcome { peer } => format!("welcome {}", peer),
+            NetMessage::Puzzle {
+                seed,
+                columns,
+                rows,
+                mode,
+                shape,
+                filters,
+                image,
+            } => {
+                let mode = match mode {
+                    GameMode::Classic => "classic",
+                    GameMode::Square => "square",
+                    GameMode::Triangular => "triangular",
+                    GameMode::Crazy => "crazy",
+                };
+                // the image comes last, its path may have spaces
+                let filters = if filters.is_empty() {
+                    "-".to_string()
+                } else {
+                    filters
+                        .iter()
+                        .map(ImageFilter::name)
+                        .collect::<Vec<_>>()
+                        .join(",")
+                };
+                format!(
+                    "puzzle {} {} {} {} {} {} {} {}",
+                    seed, columns, rows, mode, shape.tab_size, shape.jitter, filters, image
+                )
+            }
+            NetMessage::Sync => "sync".to_string(),
+            NetMessage::Piece { index, translation } => format!(
+                "piece {} {} {} {}",
+                index, translation.x, translation.y, translation.z
+            ),
+            NetMessage::Group { index, members } => {
+                let mut line = format!("group {}", index);
+                for member in members {
+                    line.push_str(&format!(" {}", member));
+                }
+                line
+            }
+            NetMessage::Drop { index } => format!("drop {}", index),
+            NetMessage::Cursor { peer, position } => {
+                format!("cursor {} {} {}", peer, position.x, position.y)
+            }
+            NetMessage::Leave { peer } => format!("leave {}", peer),
+            NetMessage::Chat { peer, text } => {
+                // a line break would end the message
+                format!("chat {} {}", peer, text.replace(['\n', '\r'], " "))
+            }
+            NetMessage::Ping { peer, position } => {
+                format!("ping {} {} {}", peer, position.x, position.y)
+            }
+        }
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let mut parts = line.split_whitespace();
+        let message = match parts.next()? {
+            "hello" => NetMessage::Hello,
+            "welcome" => NetMessage::Welcome {
+                peer: parts.next()?.parse().ok()?,
+            },
+            "puzzle" => NetMessage::Puzzle {
+                seed: parts.next()?.parse().ok()?,
+                columns: parts.next()?.parse().ok()?,
+                rows: parts.next()?.parse().ok()?,
+                mode: match parts.next()? {
+                    "classic" => GameMode::Classic,
+                    "square" => GameMode::Square,
+                    "triangular" => GameMode::Triangular,
+                    "crazy" => GameMode::Crazy,
+                    _ => return None,
+                },
+                shape: PieceShape {
+                    tab_size: parts.next()?.parse().ok()?,
+                    jitter: parts.next()?.parse().ok()?,
+                },
+                filters: match parts.next()? {
+                    "-" => Vec::new(),
+                    filters => filters
+                        .split(',')
+                        .map(ImageFilter::from_name)
+                        .collect::<Option<_>>()?,
+                },
+                image: parts.collect::<Vec<_>>().join(" "),
+            },
+            "sync" => NetMessage::Sync,
+            "piece" => NetMessage::Piece {
+                index: parts.next()?.parse().ok()?,
+                translation: Vec3::new(
+                    parts.next()?.parse().ok()?,
+                    parts.next()?.parse().ok()?,
+                    parts.next()?.parse().ok()?,
+                ),
+            },
+            "group" => NetMessage::Group {
+                index: parts.next()?.parse().ok()?,
+                members: parts.map(str::parse).collect::<Result<_, _>>().ok()?,
+            },
+            "drop" => NetMessage::Drop {
+                index: parts.next()?.parse().ok()?,
+            },
+            "cursor" => NetMessage::Cursor {
+                peer: parts.next()?.parse().ok()?,
+                position: Vec2::new(parts.next()?.parse().ok()?, parts.next()?.parse().ok()?),
+            },
+            "leave" => NetMessage::Leave {
+                peer: parts.next()?.parse().ok()?,
+            },
+            "chat" => NetMessage::Chat {
+                peer: parts.next()?.parse().ok()?,
+                text: parts.collect::<Vec<_>>().join(" "),
+            },
+            "ping" => NetMessage::Ping {
+                peer: parts.next()?.parse().ok()?,
+                position: Vec2::new(parts.next()?.parse().ok()?, parts.next()?.parse().ok()?),
+            },
+            _ => return None,
+        };
+        Some(message)
+    }
+}
+
+/// Parses received lines, invalid ones are skipped
+fn parse_messages(lines: impl IntoIterator<Item = String>) -> Vec<NetMessage> {
+    lines
+        .into_iter()
+        .filter_map(|line| {
+            let message = NetMessage::parse(line.trim());
+            if message.is_none() {
+                warn!("ignore invalid message {:?}", line.trim());
+            }
+            message
+        })
+        .collect()
+}
+
+/// How the messages of a connection are framed
+#[cfg(not(target_arch = "wasm32"))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Framing {
+    /// Not known until the first bytes of the other player arrive
+    Unknown,
+    /// One message per line, between native games
+    Lines,
+    /// One message per WebSocket text frame, with the web build
+    WebSocket,
+}
+
+/// A non-blocking TCP connection to another player
+#[cfg(not(target_arch = "wasm32"))]
+struct Connection {
+    stream: TcpStream,
+    framing: Framing,
+    read_buf: Vec<u8>,
+    /// Messages waiting until the framing is known
+    outbox: Vec<String>,
+    write_buf: Vec<u8>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Connection {
+    fn new(stream: TcpStream, framing: Framing) -> std::io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+        Ok(Connection {
+            stream,
+            framing,
+            read_buf: vec![],
+            outbox: vec![],
+            write_buf: vec![],
+        })
+    }
+
+    fn send(&mut self, message: &NetMessage) {
+        self.outbox.push(message.to_line());
+        self.frame_outbox();
+    }
+
+    /// Frames the waiting messages once the framing is known
+    fn frame_outbox(&mut self) {
+        match self.framing {
+            Framing::Unknown => {}
+            Framing::Lines => {
+                for line in self.outbox.drain(..) {
+                    self.write_buf.extend_from_slice(line.as_bytes());
+                    self.write_buf.push(b'\n');
+                }
+            }
+            Framing::WebSocket => {
+                for line in self.outbox.drain(..) {
+                    self.write_buf
+                        .extend(websocket_frame(OPCODE_TEXT, line.as_bytes()));
+                }
+            }
+        }
+    }
+
+    /// Writes as much of the pending data as possible without blocking
+    fn flush(&mut self) -> std::io::Result<()> {
+        while !self.write_buf.is_empty() {
+            match self.stream.write(&self.write_buf) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(written) => {
+                    self.write_buf.drain(..written);
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads every complete message received so far
+    fn receive(&mut self) -> std::io::Result<Vec<NetMessage>> {
+        let mut buf = [0u8; 4096];
+        loop {
+            match self.stream.read(&mut buf) {
+                Ok(0) => return Err(ErrorKind::ConnectionAborted.into()),
+                Ok(read) => self.read_buf.extend_from_slice(&buf[..read]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            }
+        }
+
+        if self.framing == Framing::Unknown {
+            self.detect_framing()?;
+        }
+        let lines = match self.framing {
+            Framing::Unknown => vec![],
+            Framing::Lines => self.take_lines(),
+            Framing::WebSocket => self.take_frames()?,
+        };
+        Ok(parse_messages(lines))
+    }
+
+    /// Browsers open a WebSocket with an HTTP request, which is answered right away, native
+    /// games start with a line
+    fn detect_framing(&mut self) -> std::io::Result<()> {
+        const REQUEST: &[u8] = b"GET ";
+        if self.read_buf.len() < REQUEST.len() {
+            return Ok(());
+        }
+        if !self.read_buf.starts_with(REQUEST) {
+            self.framing = Framing::Lines;
+        } else if let Some(end) = self.read_buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            let request: Vec<u8> = self.read_buf.drain(..end + 4).collect();
+            let request = String::from_utf8_lossy(&request);
+            let key = request.lines().find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.trim()
+                    .eq_ignore_ascii_case("sec-websocket-key")
+                    .then(|| value.trim().to_string())
+            });
+            let Some(key) = key else {
+                return Err(ErrorKind::InvalidData.into());
+            };
+            self.write_buf.extend_from_slice(
+                format!(
+                    "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\
+                     Connection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+                    websocket_accept(&key)
+                )
+                .as_bytes(),
+            );
+            self.framing = Framing::WebSocket;
+        }
+        self.frame_outbox();
+        Ok(())
+    }
+
+    fn take_lines(&mut self) -> Vec<String> {
+        let mut lines = vec![];
+        while let Some(end) = self.read_buf.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.read_buf.drain(..=end).collect();
+            lines.push(String::from_utf8_lossy(&line).into_owned());
+        }
+        lines
+    }
+
+    /// The text of every complete frame, pings are answered and a close ends the connection
+    fn take_frames(&mut self) -> std::io::Result<Vec<String>> {
+        let mut lines = vec![];
+        while let Some((opcode, payload, length)) = parse_websocket_frame(&self.read_buf) {
+            self.read_buf.drain(..length);
+            match opcode {
+                OPCODE_TEXT => lines.push(String::from_utf8_lossy(&payload).into_owned()),
+                OPCODE_CLOSE => return Err(ErrorKind::ConnectionAborted.into()),
+                OPCODE_PING => self
+                    .write_buf
+                    .extend(websocket_frame(OPCODE_PONG, &payload)),
+                _ => {}
+            }
+        }
+        Ok(lines)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+const OPCODE_TEXT: u8 = 0x1;
+#[cfg(not(target_arch = "wasm32"))]
+const OPCODE_CLOSE: u8 = 0x8;
+#[cfg(not(target_arch = "wasm32"))]
+const OPCODE_PING: u8 = 0x9;
+#[cfg(not(target_arch = "wasm32"))]
+const OPCODE_PONG: u8 = 0xa;
+
+/// A final, unmasked WebSocket frame, as a server sends them
+#[cfg(not(target_arch = "wasm32"))]
+fn websocket_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        length @ 0..=125 => frame.push(length as u8),
+        length @ 126..=0xffff => {
+            frame.push(126);
+            frame.extend((length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(127);
+            frame.extend((length as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// The opcode and the unmasked payload of the frame at the start of `bytes`, and how many bytes
+/// it takes. `None` until the whole frame is received
+#[cfg(not(target_arch = "wasm32"))]
+fn parse_websocket_frame(bytes: &[u8]) -> Option<(u8, Vec<u8>, usize)> {
+    let (first, second) = (*bytes.first()?, *bytes.get(1)?);
+    let (mut length, mut at) = ((second & 0x7f) as usize, 2);
+    if length == 126 {
+        length = u16::from_be_bytes(bytes.get(2..4)?.try_into().ok()?) as usize;
+        at = 4;
+    } else if length == 127 {
+        length = u64::from_be_bytes(bytes.get(2..10)?.try_into().ok()?) as usize;
+        at = 10;
+    }
+    let mask: Option<[u8; 4]> = if second & 0x80 != 0 {
+        let mask = bytes.get(at..at + 4)?.try_into().ok()?;
+        at += 4;
+        Some(mask)
+    } else {
+        None
+    };
+    let mut payload = bytes.get(at..at.checked_add(length)?)?.to_vec();
+    if let Some(mask) = mask {
+        for (i, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[i % 4];
+        }
+    }
+    Some((first & 0x0f, payload, at + length))
+}
+
+/// The `Sec-WebSocket-Accept` answer to the key of a browser's handshake
+#[cfg(not(target_arch = "wasm32"))]
+fn websocket_accept(key: &str) -> String {
+    const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+    base64(&sha1(format!("{}{}", key, GUID).as_bytes()))
+}
+
+/// SHA-1, which the WebSocket handshake asks for
+#[cfg(not(target_arch = "wasm32"))]
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend((data.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks_exact(64) {
+        let mut words = [0u32; 80];
+        for (word, bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        for i in 16..80 {
+            words[i] = (words[i - 3] ^ words[i - 8] ^ words[i - 14] ^ words[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (i, word) in words.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5a827999),
+                20..=39 => (b ^ c ^ d, 0x6ed9eba1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+                _ => (b ^ c ^ d, 0xca62c1d6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            (e, d, c, b, a) = (d, c, b.rotate_left(30), a, temp);
+        }
+        for (state, value) in state.iter_mut().zip([a, b, c, d, e]) {
+            *state = state.wrapping_add(value);
+        }
+    }
+
+    let mut digest = [0; 20];
+    for (bytes, value) in digest.chunks_exact_mut(4).zip(state) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut text = String::new();
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, byte)| {
+            bits | ((*byte as u32) << (16 - 8 * i))
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(ALPHABET[((bits >> (18 - 6 * i)) & 0x3f) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+/// A WebSocket of the browser to the host
+#[cfg(target_arch = "wasm32")]
+struct Connection {
+    socket: u32,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl Connection {
+    fn open(address: &str) -> Self {
+        Connection {
+            socket: net_open(address),
+        }
+    }
+
+    fn send(&mut self, message: &NetMessage) {
+        net_send(self.socket, &message.to_line());
+    }
+
+    /// The browser sends on its own
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    /// Takes every message received so far
+    fn receive(&mut self) -> std::io::Result<Vec<NetMessage>> {
+        let lines: Vec<String> = core::iter::from_fn(|| net_take(self.socket)).collect();
+        if lines.is_empty() && net_closed(self.socket) {
+            return Err(ErrorKind::ConnectionAborted.into());
+        }
+        Ok(parse_messages(lines))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen::prelude::wasm_bindgen(inline_js = r#"
+const sockets = [];
+
+export function join_address() {
+    return new URLSearchParams(window.location.search).get("join") ?? undefined;
+}
+
+export function net_open(address) {
+    const socket = { ws: new WebSocket(address), outbox: [], inbox: [], closed: false };
+    socket.ws.onopen = () => {
+        socket.outbox.forEach((line) => socket.ws.send(line));
+        socket.outbox = [];
+    };
+    socket.ws.onmessage = (event) => {
+        if (typeof event.data === "string") {
+            socket.inbox.push(event.data);
+        }
+    };
+    socket.ws.onclose = () => {
+        socket.closed = true;
+    };
+    sockets.push(socket);
+    return sockets.length - 1;
+}
+
+export function net_send(id, line) {
+    const socket = sockets[id];
+    if (socket.ws.readyState === WebSocket.CONNECTING) {
+        socket.outbox.push(line);
+    } else if (socket.ws.readyState === WebSocket.OPEN) {
+        socket.ws.send(line);
+    }
+}
+
+export function net_take(id) {
+    return sockets[id].inbox.shift();
+}
+
+export function net_closed(id) {
+    return sockets[id].closed;
+}
+"#)]
+extern "C" {
+    fn join_address() -> Option<String>;
+    fn net_open(address: &str) -> u32;
+    fn net_send(id: u32, line: &str);
+    fn net_take(id: u32) -> Option<String>;
+    fn net_closed(id: u32) -> bool;
+}
+
+/// The connections of this game to the other players
+#[derive(Resource)]
+pub struct NetSession {
+    #[cfg(not(target_arch = "wasm32"))]
+    listener: Option<TcpListener>,
+    peers: Vec<(PeerId, Connection)>,
+    local_id: PeerId,
+    #[cfg(not(target_arch = "wasm32"))]
+    next_peer: PeerId,
+}
+
+impl NetSession {
+    /// Creates a session from the `--host <addr>` or `--join <addr>` command line arguments
+    #[cfg(not(target_arch = "wasm32"))]
+    fn from_args() -> Option<Self> {
+        let args: Vec<String> = std::env::args().collect();
+        let value_of = |flag: &str| {
+            args.iter()
+                .position(|arg| arg == flag)
+                .and_then(|i| args.get(i + 1))
+        };
+
+        if let Some(addr) = value_of("--host") {
+            let listener = match TcpListener::bind(addr) {
+                Ok(listener) => listener,
+                Err(e) => {
+                    error!("Failed to host on {}: {}", addr, e);
+                    return None;
+                }
+            };
+            if let Err(e) = listener.set_nonblocking(true) {
+                error!("Failed to host on {}: {}", addr, e);
+                return None;
+            }
+            info!("hosting cooperative game on {}", addr);
+            return Some(NetSession {
+                listener: Some(listener),
+                peers: vec![],
+                local_id: HOST_ID,
+                next_peer: HOST_ID + 1,
+            });
+        }
+
+        if let Some(addr) = value_of("--join") {
+            let mut connection = match TcpStream::connect(addr)
+                .and_then(|stream| Connection::new(stream, Framing::Lines))
+            {
+                Ok(connection) => connection,
+                Err(e) => {
+                    error!("Failed to join {}: {}", addr, e);
+                    return None;
+                }
+            };
+            connection.send(&NetMessage::Hello);
+            info!("joined cooperative game on {}", addr);
+            return Some(NetSession {
+                listener: None,
+                peers: vec![(HOST_ID, connection)],
+                local_id: HOST_ID,
+                next_peer: HOST_ID,
+            });
+        }
+
+        None
+    }
+
+    /// Joins the game at the `join` parameter of the page address, like
+    /// `?join=ws://192.168.1.2:7878`. Browsers can't host
+    #[cfg(target_arch = "wasm32")]
+    fn from_args() -> Option<Self> {
+        let address = join_address()?;
+        info!("joining cooperative game on {}", address);
+        Some(NetSession {
+            peers: vec![(HOST_ID, Connection::open(&address))],
+            local_id: HOST_ID,
+        })
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn is_host(&self) -> bool {
+        self.listener.is_some()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn is_host(&self) -> bool {
+        false
+    }
+
+    pub fn local_id(&self) -> PeerId {
+        self.local_id
+    }
+
+    /// Sends a message to every connected player
+    pub fn broadcast(&mut self, message: &NetMessage) {
+        for (_, connection) in self.peers.iter_mut() {
+            connection.send(message);
+        }
+    }
+
+    /// Sends a message to every connected player except one
+    pub fn broadcast_except(&mut self, except: PeerId, message: &NetMessage) {
+        for (peer, connection) in self.peers.iter_mut() {
+            if *peer != except {
+                connection.send(message);
+            }
+        }
+    }
+
+    /// Sends a message to a single player
+    pub fn send_to(&mut self, peer: PeerId, message: &NetMessage) {
+        if let Some((_, connection)) = self.peers.iter_mut().find(|(id, _)| *id == peer) {
+            connection.send(message);
+        }
+    }
+}
+
+/// Something happened in the session
+#[derive(Event, Debug, Clone)]
+pub enum NetEvent {
+    Connected(PeerId),
+    Message(PeerId, NetMessage),
+    Disconnected(PeerId),
+}
+
+fn is_host(session: Res<NetSession>) -> bool {
+    session.is_host()
+}
+
+fn poll_network(mut session: ResMut<NetSession>, mut events: EventWriter<NetEvent>) {
+    let session = session.as_mut();
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(listener) = &session.listener {
+        while let Ok((stream, addr)) = listener.accept() {
+            match Connection::new(stream, Framing::Unknown) {
+                Ok(connection) => {
+                    let peer = session.next_peer;
+                    session.next_peer = session.next_peer.wrapping_add(1).max(HOST_ID + 1);
+                    info!("player {} connected from {}", peer, addr);
+                    session.peers.push((peer, connection));
+                    events.send(NetEvent::Connected(peer));
+                }
+                Err(e) => error!("Failed to accept {}: {}", addr, e),
+            }
+        }
+    }
+
+    let mut disconnected = vec![];
+    for (peer, connection) in session.peers.iter_mut() {
+        match connection.receive() {
+            Ok(messages) => {
+                for message in messages {
+                    events.send(NetEvent::Message(*peer, message));
+                }
+            }
+            Err(e) => {
+                info!("player {} disconnected: {}", peer, e);
+                disconnected.push(*peer);
+            }
+        }
+    }
+    session
+        .peers
+        .retain(|(peer, _)| !disconnected.contains(peer));
+    for peer in disconnected {
+        events.send(NetEvent::Disconnected(peer));
+    }
+}
+
+fn flush_network(mut session: ResMut<NetSession>) {
+    for (peer, connection) in session.peers.iter_mut() {
+        if let Err(e) = connection.flush() {
+            warn!("Failed to send to player {}: {}", peer, e);
+        }
+    }
+}
+
+/// The puzzle currently played in the session
+#[derive(Resource, Debug, Clone)]
+struct SharedPuzzle(NetMessage);
+
+fn choose_seed(mut puzzle_seed: ResMut<PuzzleSeed>) {
+    puzzle_seed.0 = Some(rand::random());
+}
+
+#[allow(clippy::too_many_arguments)]
+fn share_puzzle(
+    mut commands: Commands,
+    mut session: ResMut<NetSession>,
+    asset_server: Res<AssetServer>,
+    images: Res<Assets<Image>>,
+    origin_image: Res<OriginImage>,
+    puzzle_seed: Res<PuzzleSeed>,
+    select_piece: Res<SelectPiece>,
+    custom_grid: Res<CustomGrid>,
+    select_game_mode: Res<SelectGameMode>,
+    piece_shape: Res<PieceShape>,
+    filters: Res<ImageFilters>,
+) {
+    let (Some(seed), Some(image)) = (**puzzle_seed, asset_server.get_path(origin_image.id()))
+    else {
+        warn!("Only puzzles from asset images can be shared");
+        return;
+    };
+    let Some(size) = images.get(&origin_image.0).map(Image::size) else {
+        return;
+    };
+    let (columns, rows) = custom_grid.columns_rows(&select_piece, size.x, size.y);
+    let puzzle = NetMessage::Puzzle {
+        seed,
+        columns,
+        rows,
+        mode: **select_game_mode,
+        shape: *piece_shape,
+        filters: filters.0.clone(),
+        image: image.to_string(),
+    };
+    session.broadcast(&puzzle);
+    commands.insert_resource(SharedPuzzle(puzzle));
+}
+
+type HostPieceQuery = (
+    Entity,
+    &'static Piece,
+    &'static mut Transform,
+    &'static MoveTogether,
+    Has<MoveStart>,
+);
+
+/// The full state of the board for a newly synced client
+fn board_messages(pieces: &Query<HostPieceQuery>) -> Vec<NetMessage> {
+    let indices: HashMap<Entity, usize> = pieces
+        .iter()
+        .map(|(entity, piece, ..)| (entity, piece.index))
+        .collect();
+    let mut messages = vec![];
+    for (_entity, piece, transform, move_together, _moving) in pieces.iter() {
+        messages.push(NetMessage::Piece {
+            index: piece.index,
+            translation: transform.translation,
+        });
+        messages.push(NetMessage::Group {
+            index: piece.index,
+            members: move_together
+                .iter()
+                .filter_map(|e| indices.get(e).copied())
+                .collect(),
+        });
+    }
+    messages
+}
+
+#[allow(clippy::too_many_arguments)]
+fn host_handle_events(
+    mut events: EventReader<NetEvent>,
+    mut session: ResMut<NetSession>,
+    mut commands: Commands,
+    shared_puzzle: Option<Res<SharedPuzzle>>,
+    game_state: Res<State<GameState>>,
+    mut pieces: Query<HostPieceQuery>,
+    mut cursor_events: EventWriter<RemoteCursorMoved>,
+) {
+    for event in events.read() {
+        match event {
+            NetEvent::Connected(peer) => {
+                session.send_to(*peer, &NetMessage::Welcome { peer: *peer });
+                if let Some(puzzle) = &shared_puzzle {
+                    session.send_to(*peer, &puzzle.0);
+                }
+            }
+            NetEvent::Disconnected(peer) => {
+                cursor_events.send(RemoteCursorMoved::Left(*peer));
+                session.broadcast(&NetMessage::Leave { peer: *peer });
+            }
+            NetEvent::Message(peer, message) => match message {
+                NetMessage::Sync if *game_state.get() == GameState::Play => {
+                    for message in board_messages(&pieces) {
+                        session.send_to(*peer, &message);
+                    }
+                }
+                NetMessage::Piece { index, translation } => {
+                    // pieces held by the host follow the host's cursor
+                    if let Some((_, _, mut transform, _, _)) = pieces
+                        .iter_mut()
+                        .find(|(_, piece, .., moving)| piece.index == *index && !moving)
+                    {
+                        transform.translation = *translation;
+                    }
+                }
+                NetMessage::Drop { index } => {
+                    if let Some((entity, ..)) = pieces
+                        .iter()
+                        .find(|(_, piece, .., moving)| piece.index == *index && !moving)
+                    {
+                        commands.trigger_targets(MoveEnd, entity);
+                    }
+                }
+                NetMessage::Cursor { position, .. } => {
+                    cursor_events.send(RemoteCursorMoved::Moved(*peer, *position));
+                    session.broadcast_except(
+                        *peer,
+                        &NetMessage::Cursor {
+                            peer: *peer,
+                            position: *position,
+                        },
+                    );
+                }
+                _ => {}
+            },
+        }
+    }
+}
+
+/// The puzzle the host asked this client to play
+#[derive(Resource, Debug)]
+struct RemotePuzzle {
+    seed: usize,
+    columns: usize,
+    rows: usize,
+    mode: GameMode,
+    shape: PieceShape,
+    filters: Vec<ImageFilter>,
+    image: Handle<Image>,
+    started: bool,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn client_handle_events(
+    mut events: EventReader<NetEvent>,
+    mut session: ResMut<NetSession>,
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut pieces: Query<(
+        Entity,
+        &Piece,
+        &mut Transform,
+        &mut MoveTogether,
+        Has<Selected>,
+    )>,
+    mut cursor_events: EventWriter<RemoteCursorMoved>,
+    template: Option<Res<JigsawPuzzleTemplate>>,
+    play_mode: Res<SelectPlayMode>,
+    game_state: Res<State<GameState>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    timer: Option<Res<GameTimer>>,
+) {
+    let mut finished = *game_state.get() != GameState::Play;
+    let mut entities = None;
+    for event in events.read() {
+        let NetEvent::Message(_, message) = event else {
+            if let NetEvent::Disconnected(_) = event {
+                warn!("Lost connection to the host");
+            }
+            continue;
+        };
+        let entities = entities.get_or_insert_with(|| {
+            pieces
+                .iter()
+                .map(|(entity, piece, ..)| (piece.index, entity))
+                .collect::<HashMap<usize, Entity>>()
+        });
+
+        match message {
+            NetMessage::Welcome { peer } => {
+                info!("joined as player {}", peer);
+                session.local_id = *peer;
+            }
+            NetMessage::Puzzle {
+                seed,
+                columns,
+                rows,
+                mode,
+                shape,
+                filters,
+                image,
+            } => {
+                commands.insert_resource(RemotePuzzle {
+                    seed: *seed,
+                    columns: *columns,
+                    rows: *rows,
+                    mode: *mode,
+                    shape: *shape,
+                    filters: filters.clone(),
+                    image: asset_server.load(image.clone()),
+                    started: false,
+                });
+            }
+            NetMessage::Piece { index, translation } => {
+                let Some(entity) = entities.get(index) else {
+                    continue;
+                };
+                if let Ok((_, _, mut transform, _, selected)) = pieces.get_mut(*entity) {
+                    // pieces held by this player follow the local cursor
+                    if !selected {
+                        transform.bypass_change_detection().translation = *translation;
+                    }
+                }
+            }
+            NetMessage::Group { index, members } => {
+                let Some(entity) = entities.get(index) else {
+                    continue;
+                };
+                let members: HashSet<Entity> = members
+                    .iter()
+                    .filter_map(|index| entities.get(index).copied())
+                    .collect();
+                // the host merged the last pieces, on the board they are locked instead
+                let all = template
+                    .as_ref()
+                    .is_some_and(|template| members.len() == template.pieces_count());
+                if all && !finished && *play_mode != SelectPlayMode::Board {
+                    finished = true;
+                    next_state.set(GameState::Finish);
+                    commands.send_event(PuzzleCompleted::new(timer.as_deref()));
+                }
+                if let Ok((_, _, _, mut move_together, _)) = pieces.get_mut(*entity) {
+                    move_together.bypass_change_detection().0 = members;
+                }
+            }
+            NetMessage::Cursor { peer, position } => {
+                cursor_events.send(RemoteCursorMoved::Moved(*peer, *position));
+            }
+            NetMessage::Leave { peer } => {
+                cursor_events.send(RemoteCursorMoved::Left(*peer));
+            }
+            NetMessage::Hello
+            | NetMessage::Sync
+            | NetMessage::Drop { .. }
+            | NetMessage::Chat { .. }
+            | NetMessage::Ping { .. } => {}
+        }
+    }
+}
+
+/// Starts the puzzle shared by the host once its image is loaded
+fn start_remote_puzzle(
+    remote_puzzle: Option<ResMut<RemotePuzzle>>,
+    asset_server: Res<AssetServer>,
+    app_state: Res<State<AppState>>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
+) {
+    let Some(mut remote_puzzle) = remote_puzzle else {
+        return;
+    };
+    if remote_puzzle.started || !asset_server.is_loaded_with_dependencies(&remote_puzzle.image) {
+        return;
+    }
+
+    remote_puzzle.started = true;
+    match app_state.get() {
+        AppState::MainMenu => next_app_state.set(AppState::Gameplay),
+        AppState::Gameplay => next_game_state.set(GameState::Setup),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn apply_remote_puzzle(
+    remote_puzzle: Option<Res<RemotePuzzle>>,
+    mut origin_image: ResMut<OriginImage>,
+    mut puzzle_seed: ResMut<PuzzleSeed>,
+    mut select_piece: ResMut<SelectPiece>,
+    mut custom_grid: ResMut<CustomGrid>,
+    mut select_game_mode: ResMut<SelectGameMode>,
+    mut piece_shape: ResMut<PieceShape>,
+    mut filters: ResMut<ImageFilters>,
+) {
+    let Some(remote_puzzle) = remote_puzzle else {
+        return;
+    };
+    origin_image.0 = remote_puzzle.image.clone();
+    puzzle_seed.0 = Some(remote_puzzle.seed);
+    *select_piece = SelectPiece::new(remote_puzzle.columns * remote_puzzle.rows);
+    custom_grid.0 = Some((remote_puzzle.columns, remote_puzzle.rows));
+    select_game_mode.0 = remote_puzzle.mode;
+    *piece_shape = remote_puzzle.shape;
+    filters.0.clone_from(&remote_puzzle.filters);
+}
+
+fn request_sync(mut session: ResMut<NetSession>) {
+    session.broadcast(&NetMessage::Sync);
+}
+
+fn host_broadcast_changes(
+    mut session: ResMut<NetSession>,
+    changed_transforms: Query<(&Piece, &Transform), Changed<Transform>>,
+    changed_groups: Query<(&Piece, &MoveTogether), Changed<MoveTogether>>,
+    pieces: Query<&Piece>,
+) {
+    for (piece, transform) in changed_transforms.iter() {
+        session.broadcast(&NetMessage::Piece {
+            index: piece.index,
+            translation: transform.translation,
+        });
+    }
+    for (piece, move_together) in changed_groups.iter() {
+        session.broadcast(&NetMessage::Group {
+            index: piece.index,
+            members: move_together
+                .iter()
+                .filter_map(|entity| pieces.get(*entity).ok())
+                .map(|piece| piece.index)
+                .collect(),
+        });
+    }
+}
+
+fn client_send_changes(
+    mut session: ResMut<NetSession>,
+    changed_transforms: Query<(&Piece, &Transform), Changed<Transform>>,
+) {
+    for (piece, transform) in changed_transforms.iter() {
+        session.broadcast(&NetMessage::Piece {
+            index: piece.index,
+            translation: transform.translation,
+        });
+    }
+}
+
+fn client_send_drops(
+    mut session: ResMut<NetSession>,
+    mut removed: RemovedComponents<MoveStart>,
+    pieces: Query<&Piece>,
+) {
+    for entity in removed.read() {
+        if let Ok(piece) = pieces.get(entity) {
+            session.broadcast(&NetMessage::Drop { index: piece.index });
+        }
+    }
+}
+
+fn send_cursor(
+    mut session: ResMut<NetSession>,
+    window: Single<&Window>,
+    camera: Single<(&Camera, &GlobalTransform), With<IsDefaultUiCamera>>,
+    mut last_position: Local<Option<Vec2>>,
+) {
+    let (camera, camera_transform) = *camera;
+    let Some(position) = window
+        .cursor_position()
+        .and_then(|cursor| camera.viewport_to_world_2d(camera_transform, cursor).ok())
+    else {
+        return;
+    };
+    if *last_position == Some(position) {
+        return;
+    }
+    *last_position = Some(position);
+
+    let peer = session.local_id();
+    session.broadcast(&NetMessage::Cursor { peer, position });
+}
+
+/// The cursor of another player
+#[derive(Component)]
+pub struct RemoteCursor(pub PeerId);
+
+#[derive(Event, Debug)]
+enum RemoteCursorMoved {
+    Moved(PeerId, Vec2),
+    Left(PeerId),
+}
+
+const CURSOR_COLORS: [Color; 4] = [
+    Color::srgb(0.9, 0.3, 0.3),
+    Color::srgb(0.3, 0.5, 0.9),
+    Color::srgb(0.3, 0.8, 0.4),
+    Color::srgb(0.9, 0.7, 0.2),
+];
+
+/// The color of a player's cursor, chat lines and pings
+pub fn peer_color(peer: PeerId) -> Color {
+    CURSOR_COLORS[peer as usize % CURSOR_COLORS.len()]
+}
+
+fn update_remote_cursors(
+    mut events: EventReader<RemoteCursorMoved>,
+    mut commands: Commands,
+    mut cursors: Query<(Entity, &RemoteCursor, &mut Transform)>,
+    app_state: Res<State<AppState>>,
+) {
+    for event in events.read() {
+        match event {
+            RemoteCursorMoved::Moved(peer, position) => {
+                if let Some((_, _, mut transform)) =
+                    cursors.iter_mut().find(|(_, cursor, _)| cursor.0 == *peer)
+                {
+                    transform.translation = position.extend(transform.translation.z);
+                    continue;
+                }
+                if *app_state.get() != AppState::Gameplay {
+                    continue;
+                }
+                let color = peer_color(*peer);
+                commands
+                    .spawn((
+                        Sprite::from_color(color, Vec2::splat(12.0)),
+                        Transform::from_translation(position.extend(500.0)),
+                        RemoteCursor(*peer),
+                    ))
+                    .with_child((
+                        Text2d::new(format!("P{}", peer + 1)),
+                        TextColor(color),
+                        Transform::from_xyz(0.0, 20.0, 0.0),
+                    ));
+            }
+            RemoteCursorMoved::Left(peer) => {
+                for (entity, cursor, _) in cursors.iter() {
+                    if cursor.0 == *peer {
+                        commands.entity(entity).despawn_recursive();
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_roundtrip() {
+        let messages = [
+            NetMessage::Hello,
+            NetMessage::Welcome { peer: 3 },
+            NetMessage::Puzzle {
+                seed: 42,
+                columns: 8,
+                rows: 6,
+                mode: GameMode::Crazy,
+                shape: PieceShape {
+                    tab_size: 25.0,
+                    jitter: 2.5,
+                },
+                filters: vec![ImageFilter::Sepia, ImageFilter::Blur],
+                image: "images/a b.png".to_string(),
+            },
+            NetMessage::Puzzle {
+                seed: 7,
+                columns: 3,
+                rows: 2,
+                mode: GameMode::Classic,
+                shape: PieceShape::default(),
+                filters: vec![],
+                image: "images/c.png".to_string(),
+            },
+            NetMessage::Sync,
+            NetMessage::Piece {
+                index: 7,
+                translation: Vec3::new(-1.5, 20.25, 3.0),
+            },
+            NetMessage::Group {
+                index: 7,
+                members: vec![1, 7, 12],
+            },
+            NetMessage::Group {
+                index: 2,
+                members: vec![],
+            },
+            NetMessage::Drop { index: 0 },
+            NetMessage::Cursor {
+                peer: 1,
+                position: Vec2::new(10.0, -0.5),
+            },
+            NetMessage::Leave { peer: 2 },
+            NetMessage::Chat {
+                peer: 1,
+                text: "hello there".to_string(),
+            },
+            NetMessage::Ping {
+                peer: 0,
+                position: Vec2::new(1.0, 2.0),
+            },
+        ];
+        for message in messages {
+            assert_eq!(NetMessage::parse(&message.to_line()), Some(message));
+        }
+
+        // a line break would split the chat line into two messages
+        let chat = NetMessage::Chat {
+            peer: 1,
+            text: "two\nlines".to_string(),
+        };
+        assert_eq!(chat.to_line(), "chat 1 two lines");
+    }
+
+    #[test]
+    fn test_parse_malformed_messages() {
+        for line in [
+            "",
+            "   ",
+            "teleport 1",
+            "welcome",
+            "welcome 256",
+            "welcome -1",
+            "puzzle 1 2 3 hexagon image.png",
+            "puzzle 1 2",
+            "piece 1 2.0 3.0",
+            "piece one 2.0 3.0 4.0",
+            "piece 1 2.0 x 4.0",
+            "group 1 2 three",
+            "drop",
+            "cursor 1 2.0",
+            "leave me",
+            "chat",
+            "ping 1 nan-ish 2.0",
+        ] {
+            assert_eq!(NetMessage::parse(line), None, "{:?}", line);
+        }
+        assert_eq!(
+            parse_messages(["sync\n".to_string(), "garbage".to_string()]),
+            vec![NetMessage::Sync]
+        );
+    }
+
+    #[test]
+    fn test_websocket_accept() {
+        assert_eq!(
+            sha1(b"abc"),
+            [
+                0xa9, 0x99, 0x3e, 0x36, 0x47, 0x06, 0x81, 0x6a, 0xba, 0x3e, 0x25, 0x71, 0x78, 0x50,
+                0xc2, 0x6c, 0x9c, 0xd0, 0xd8, 0x9d
+            ]
+        );
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        // the example of RFC 6455
+        assert_eq!(
+            websocket_accept("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn test_websocket_frames() {
+        // a masked "Hello" of a browser, from RFC 6455
+        let masked = [
+            0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58,
+        ];
+        assert_eq!(
+            parse_websocket_frame(&masked),
+            Some((OPCODE_TEXT, b"Hello".to_vec(), masked.len()))
+        );
+        assert_eq!(parse_websocket_frame(&masked[..8]), None);
+
+        let long = vec![b'x'; 300];
+        let frame = websocket_frame(OPCODE_TEXT, &long);
+        assert_eq!(frame[..4], [0x81, 126, 1, 44]);
+        assert_eq!(
+            parse_websocket_frame(&frame),
+            Some((OPCODE_TEXT, long, frame.len()))
+        );
+    }
+
+    /// Receives on the non-blocking `connection` until something arrives
+    fn receive_some(connection: &mut Connection) -> Vec<NetMessage> {
+        for _ in 0..200 {
+            let messages = connection.receive().unwrap();
+            if !messages.is_empty() {
+                return messages;
+            }
+            std::thread::sleep(core::time::Duration::from_millis(5));
+        }
+        panic!("nothing received");
+    }
+
+    #[test]
+    fn test_host_speaks_lines_and_websocket() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let mut native =
+            Connection::new(TcpStream::connect(address).unwrap(), Framing::Lines).unwrap();
+        native.send(&NetMessage::Hello);
+        native.flush().unwrap();
+        let mut host = Connection::new(listener.accept().unwrap().0, Framing::Unknown).unwrap();
+        host.send(&NetMessage::Welcome { peer: 1 });
+        assert_eq!(receive_some(&mut host), vec![NetMessage::Hello]);
+        host.flush().unwrap();
+        assert_eq!(
+            receive_some(&mut native),
+            vec![NetMessage::Welcome { peer: 1 }]
+        );
+
+        let mut browser = TcpStream::connect(address).unwrap();
+        let mut host = Connection::new(listener.accept().unwrap().0, Framing::Unknown).unwrap();
+        host.send(&NetMessage::Welcome { peer: 2 });
+        let mask = [1, 2, 3, 4];
+        let mut request = b"GET / HTTP/1.1\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+            Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n"
+            .to_vec();
+        request.extend([0x81, 0x84].into_iter().chain(mask));
+        request.extend(b"sync".iter().zip(mask.iter().cycle()).map(|(b, m)| b ^ m));
+        browser.write_all(&request).unwrap();
+        assert_eq!(receive_some(&mut host), vec![NetMessage::Sync]);
+        host.flush().unwrap();
+
+        let answer = b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\
+            Connection: Upgrade\r\nSec-WebSocket-Accept: s3pPLMBiTxaQ9kYGzzhZRbK+xOo=\r\n\r\n";
+        let welcome = websocket_frame(OPCODE_TEXT, b"welcome 2");
+        let mut received = vec![0; answer.len() + welcome.len()];
+        browser
+            .set_read_timeout(Some(core::time::Duration::from_secs(1)))
+            .unwrap();
+        browser.read_exact(&mut received).unwrap();
+        assert_eq!(received[..answer.len()], answer[..]);
+        assert_eq!(received[answer.len()..], welcome[..]);
+    }
+}
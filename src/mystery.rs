@@ -0,0 +1,133 @@
+//! Mystery mode: the image is never shown before the puzzle is solved. The image in the main menu
+//! is covered, the image hint, the background hint and the picture in picture of the presentation
+//! mode are off, and the finish screen reveals the image with a badge.
+
+use crate::i18n::Language;
+use crate::theme::ThemeColor;
+use crate::AppState;
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Mystery>()
+        .add_systems(Update, update_mystery.run_if(in_state(AppState::MainMenu)));
+}
+
+const COVER_COLOR: Color = Color::srgb(0.15, 0.15, 0.2);
+const BADGE_COLOR: Color = Color::srgb(0.85, 0.65, 0.1);
+
+/// Whether the next puzzle is solved without seeing its image
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Deref, DerefMut)]
+pub struct Mystery(pub bool);
+
+impl Mystery {
+    pub fn toggle(&mut self) {
+        self.0 = !self.0;
+    }
+}
+
+#[derive(Component)]
+struct MysteryText;
+
+#[derive(Component)]
+struct MysteryCover;
+
+/// Adds "Mystery", which switches the [`Mystery`] mode
+pub(crate) fn spawn_mystery_button(p: &mut ChildBuilder, font: Handle<Font>) {
+    p.spawn((
+        MysteryText,
+        Text::default(),
+        TextFont {
+            font,
+            font_size: 24.0,
+            ..default()
+        },
+        TextColor(Color::BLACK),
+        ThemeColor::Text,
+        Node {
+            margin: UiRect::new(Val::Px(20.0), Val::Px(0.0), Val::Px(5.0), Val::Px(0.0)),
+            ..default()
+        },
+    ))
+    .observe(
+        |_trigger: Trigger<Pointer<Click>>, mut mystery: ResMut<Mystery>| {
+            mystery.toggle();
+        },
+    );
+}
+
+/// Adds the cover hiding the image preview in mystery mode
+pub(crate) fn spawn_mystery_cover(p: &mut ChildBuilder) {
+    p.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        BackgroundColor(COVER_COLOR),
+        Visibility::Hidden,
+        PickingBehavior::IGNORE,
+        MysteryCover,
+    ))
+    .with_child((
+        Text::new("?"),
+        TextFont {
+            font_size: 160.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+    ));
+}
+
+fn update_mystery(
+    mystery: Res<Mystery>,
+    language: Res<Language>,
+    mut texts: Query<&mut Text, With<MysteryText>>,
+    mut covers: Query<&mut Visibility, With<MysteryCover>>,
+    added: Query<(), Or<(Added<MysteryText>, Added<MysteryCover>)>>,
+) {
+    if added.is_empty() && !mystery.is_changed() && !language.is_changed() {
+        return;
+    }
+    let state = if mystery.0 { "On" } else { "Off" };
+    for mut text in texts.iter_mut() {
+        text.0 = format!("{}: {}", language.tr("Mystery"), language.tr(state));
+    }
+    for mut visibility in covers.iter_mut() {
+        *visibility = if mystery.0 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// Reveals the solved image on the finish screen with the badge of the mystery mode
+pub(crate) fn spawn_mystery_badge(p: &mut ChildBuilder, language: &Language, image: Handle<Image>) {
+    p.spawn((
+        Text::new(language.tr("Mystery solved!")),
+        TextFont {
+            font_size: 28.0,
+            ..default()
+        },
+        TextColor(BADGE_COLOR),
+        Node {
+            margin: UiRect::all(Val::Px(5.0)),
+            padding: UiRect::axes(Val::Px(12.0), Val::Px(4.0)),
+            border: UiRect::all(Val::Px(2.0)),
+            ..default()
+        },
+        BorderColor(BADGE_COLOR),
+        BorderRadius::MAX,
+    ));
+    p.spawn((
+        ImageNode::new(image),
+        Node {
+            width: Val::Px(320.0),
+            margin: UiRect::all(Val::Px(5.0)),
+            ..default()
+        },
+    ));
+}
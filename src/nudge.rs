@@ -0,0 +1,83 @@
+//! Moves the selected pieces with the arrow keys for precise placement, by one pixel or by ten
+//! with <kbd>Shift</kbd>. <kbd>Enter</kbd> drops them and checks whether they snap. While pieces
+//! are selected the arrow keys don't pan the table.
+
+use crate::board::Locked;
+use crate::gameplay::{MoveEnd, MoveStart, MoveTogether, Selected};
+use crate::GameState;
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(Update, nudge_pieces.run_if(in_state(GameState::Play)));
+}
+
+/// How far a press of an arrow key moves the pieces, in pixels of the image
+const NUDGE_STEP: f32 = 1.0;
+/// How far a press of an arrow key moves the pieces with Shift held
+const NUDGE_STEP_LARGE: f32 = 10.0;
+
+/// The direction an arrow key moves the pieces
+fn arrow_direction(key: KeyCode) -> Option<Vec2> {
+    match key {
+        KeyCode::ArrowUp => Some(Vec2::Y),
+        KeyCode::ArrowDown => Some(Vec2::NEG_Y),
+        KeyCode::ArrowLeft => Some(Vec2::NEG_X),
+        KeyCode::ArrowRight => Some(Vec2::X),
+        _ => None,
+    }
+}
+
+/// The held piece follows the pointer from where it was picked up, so that place is moved instead
+/// of the piece. Pieces selected with the selection box are moved directly
+fn nudge_pieces(
+    mut commands: Commands,
+    mut key_events: EventReader<KeyboardInput>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut held: Query<(Entity, &mut Transform, &mut MoveStart)>,
+    mut selected: Query<
+        (Entity, &mut Transform, &MoveTogether),
+        (With<Selected>, Without<MoveStart>, Without<Locked>),
+    >,
+) {
+    let step = if keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        NUDGE_STEP_LARGE
+    } else {
+        NUDGE_STEP
+    };
+    // the key repeat of the system keeps moving the pieces while an arrow key is held
+    let offset: Vec2 = key_events
+        .read()
+        .filter(|event| event.state == ButtonState::Pressed)
+        .filter_map(|event| arrow_direction(event.key_code))
+        .sum::<Vec2>()
+        * step;
+
+    if let Ok((entity, mut transform, mut move_start)) = held.get_single_mut() {
+        move_start.image_position.translation += offset.extend(0.0);
+        if keyboard_input.any_just_pressed([KeyCode::Enter, KeyCode::NumpadEnter]) {
+            transform.translation.z = 0.0;
+            commands.entity(entity).remove::<MoveStart>();
+            commands.trigger_targets(MoveEnd, vec![entity]);
+        }
+        return;
+    }
+
+    if offset != Vec2::ZERO {
+        for (_, mut transform, _) in selected.iter_mut() {
+            transform.translation += offset.extend(0.0);
+        }
+    }
+    if keyboard_input.any_just_pressed([KeyCode::Enter, KeyCode::NumpadEnter]) {
+        // one snap check for every group, it takes along the rest of the group
+        let mut checked = HashSet::new();
+        for (entity, _, together) in selected.iter() {
+            if checked.insert(entity) {
+                checked.extend(together.iter().copied());
+                commands.trigger_targets(MoveEnd, vec![entity]);
+            }
+        }
+    }
+}
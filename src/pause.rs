@@ -0,0 +1,58 @@
+//! While a timed game is paused the pieces are blurred and the image hint on the board is hidden,
+//! so the stopped clock can't be used to study the puzzle. Relaxed games have no clock and are
+//! paused without the blur.
+
+use crate::atlas::PieceMaterial;
+use crate::gameplay::BoardBackgroundImage;
+use crate::relaxed::Relaxed;
+use crate::GameState;
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Pause), blur_table)
+        .add_systems(OnExit(GameState::Pause), unblur_table);
+}
+
+/// How far the pieces are blurred, in pixels of the piece
+const PAUSE_BLUR: f32 = 16.0;
+
+/// An image hint hidden until the game goes on
+#[derive(Component)]
+struct HiddenWhilePaused;
+
+fn blur_table(
+    mut commands: Commands,
+    relaxed: Res<Relaxed>,
+    mut materials: ResMut<Assets<PieceMaterial>>,
+    mut hints: Query<(Entity, &mut Visibility), With<BoardBackgroundImage>>,
+) {
+    if relaxed.0 {
+        return;
+    }
+    for (_, material) in materials.iter_mut() {
+        material.set_blur(PAUSE_BLUR);
+    }
+    for (entity, mut visibility) in hints.iter_mut() {
+        if *visibility != Visibility::Hidden {
+            *visibility = Visibility::Hidden;
+            commands.entity(entity).insert(HiddenWhilePaused);
+        }
+    }
+}
+
+fn unblur_table(
+    mut commands: Commands,
+    relaxed: Res<Relaxed>,
+    mut materials: ResMut<Assets<PieceMaterial>>,
+    mut hints: Query<(Entity, &mut Visibility), With<HiddenWhilePaused>>,
+) {
+    if !relaxed.0 {
+        for (_, material) in materials.iter_mut() {
+            material.set_blur(0.0);
+        }
+    }
+    for (entity, mut visibility) in hints.iter_mut() {
+        *visibility = Visibility::Visible;
+        commands.entity(entity).remove::<HiddenWhilePaused>();
+    }
+}
@@ -0,0 +1,142 @@
+//! Stylus support. A pen hovering over the screen moves the cursor like a mouse, so the piece
+//! under it is highlighted before it touches down. It picks up a piece only once it presses
+//! firmly and moves a little, so the wobble of touching down doesn't pull pieces aside, and the
+//! palm resting on the screen while the pen is used is ignored.
+
+use bevy::input::touch::{ForceTouch, Touch};
+use bevy::input::InputSystem;
+use bevy::picking::pointer::PointerId;
+use bevy::picking::PickSet;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PenInput>().add_systems(
+        PreUpdate,
+        track_pen.after(InputSystem).before(PickSet::ProcessInput),
+    );
+}
+
+/// A touch this close to where the cursor hovered just before comes from a pen, fingers don't
+/// hover
+const HOVER_DISTANCE: f32 = 24.0;
+/// How long the cursor counts as hovering after it moved, in seconds
+const HOVER_TIME: f64 = 0.3;
+/// Touches are taken for the palm until this long after the pen was lifted, in seconds
+const PALM_TIME: f64 = 1.0;
+/// How far the pen moves before it picks up a piece, in pixels on the screen
+pub const PEN_DRAG_THRESHOLD: f32 = 8.0;
+/// How firmly the pen has to press to pick up a piece, of its full pressure
+pub const PEN_PRESSURE: f32 = 0.15;
+
+/// Which touches come from a pen and which from the palm holding it
+#[derive(Resource, Debug, Default)]
+pub struct PenInput {
+    /// The touch of the pen on the screen
+    pen: Option<u64>,
+    /// Touches taken for the palm
+    palms: HashSet<u64>,
+    /// Where and when the cursor last moved while nothing touched the screen
+    hover: Option<(Vec2, f64)>,
+    /// When the pen last touched the screen, once it did
+    last_used: Option<f64>,
+}
+
+impl PenInput {
+    pub fn is_pen(&self, pointer: PointerId) -> bool {
+        matches!(pointer, PointerId::Touch(id) if self.pen == Some(id))
+    }
+
+    pub fn is_palm(&self, pointer: PointerId) -> bool {
+        matches!(pointer, PointerId::Touch(id) if self.palms.contains(&id))
+    }
+
+    /// The touch moving the pieces: the pen while it touches the screen, otherwise the first
+    /// finger
+    pub fn touch_position(&self, touches: &Touches) -> Option<Vec2> {
+        match self.pen {
+            Some(pen) => touches.get_pressed(pen).map(Touch::position),
+            None => self.fingers(touches).next().map(Touch::position),
+        }
+    }
+
+    /// The touches which are neither the pen nor the palm
+    pub fn fingers<'a>(&'a self, touches: &'a Touches) -> impl Iterator<Item = &'a Touch> {
+        touches
+            .iter()
+            .filter(|touch| self.pen != Some(touch.id()) && !self.palms.contains(&touch.id()))
+    }
+
+    /// Whether the pen presses firmly enough to pick up a piece, pens without pressure always do
+    pub fn presses_firmly(&self, touches: &Touches) -> bool {
+        let pressure = self
+            .pen
+            .and_then(|pen| touches.get_pressed(pen))
+            .and_then(Touch::force)
+            .map(|force| match force {
+                ForceTouch::Calibrated {
+                    force,
+                    max_possible_force,
+                    ..
+                } => (force / max_possible_force) as f32,
+                ForceTouch::Normalized(force) => force as f32,
+            });
+        pressure.is_none_or(|pressure| pressure >= PEN_PRESSURE)
+    }
+}
+
+fn track_pen(
+    time: Res<Time>,
+    touches: Res<Touches>,
+    mut cursor_moved: EventReader<CursorMoved>,
+    mut pen: ResMut<PenInput>,
+) {
+    let now = time.elapsed_secs_f64();
+    let moved = cursor_moved.read().last();
+    if let (Some(moved), None) = (moved, touches.iter().next()) {
+        pen.hover = Some((moved.position, now));
+    }
+
+    for touch in touches.iter_just_pressed() {
+        let stylus = matches!(
+            touch.force(),
+            Some(ForceTouch::Calibrated {
+                altitude_angle: Some(_),
+                ..
+            })
+        );
+        let hovering = pen.hover.filter(|(_, at)| now - at < HOVER_TIME);
+        let below_hover = hovering.is_some_and(|(position, _)| {
+            position.distance(touch.start_position()) < HOVER_DISTANCE
+        });
+        if pen.pen.is_none() && (stylus || below_hover) {
+            pen.pen = Some(touch.id());
+            // the palm may have come down first
+            let palms: Vec<u64> = touches
+                .iter()
+                .map(Touch::id)
+                .filter(|id| *id != touch.id())
+                .collect();
+            pen.palms.extend(palms);
+        } else if pen.pen.is_some()
+            || pen.last_used.is_some_and(|at| now - at < PALM_TIME)
+            // a touch away from a hovering pen
+            || (pen.last_used.is_some() && hovering.is_some())
+        {
+            pen.palms.insert(touch.id());
+        }
+    }
+    if pen.pen.is_some() {
+        pen.last_used = Some(now);
+    }
+
+    for touch in touches
+        .iter_just_released()
+        .chain(touches.iter_just_canceled())
+    {
+        if pen.pen == Some(touch.id()) {
+            pen.pen = None;
+        }
+        pen.palms.remove(&touch.id());
+    }
+}
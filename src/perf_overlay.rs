@@ -0,0 +1,118 @@
+//! <kbd>F11</kbd> shows how hard the game works, to find out what slows down large puzzles: the
+//! frame rate and frame time, the number of entities, the pieces on the screen of all pieces and
+//! an estimate of the memory the textures take.
+
+use crate::culling::OffScreen;
+use crate::Piece;
+use bevy::diagnostic::{Diagnostic, DiagnosticPath, DiagnosticsStore, FrameTimeDiagnosticsPlugin};
+use bevy::ecs::entity::Entities;
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    if !app.is_plugin_added::<FrameTimeDiagnosticsPlugin>() {
+        app.add_plugins(FrameTimeDiagnosticsPlugin);
+    }
+    app.init_resource::<PerformanceOverlay>().add_systems(
+        Update,
+        (
+            toggle_overlay,
+            show_overlay.run_if(resource_changed::<PerformanceOverlay>),
+            update_overlay.run_if(|overlay: Res<PerformanceOverlay>| overlay.0),
+        )
+            .chain(),
+    );
+}
+
+/// How often the numbers are updated, every frame they are too restless to read
+const UPDATE_INTERVAL: f32 = 0.25;
+
+/// Whether the overlay is shown
+#[derive(Resource, Debug, Default, Deref, DerefMut)]
+pub struct PerformanceOverlay(pub bool);
+
+#[derive(Component)]
+struct PerformanceText;
+
+fn toggle_overlay(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<PerformanceOverlay>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F11) {
+        overlay.0 = !overlay.0;
+    }
+}
+
+fn show_overlay(
+    mut commands: Commands,
+    overlay: Res<PerformanceOverlay>,
+    texts: Query<Entity, With<PerformanceText>>,
+) {
+    for entity in texts.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !overlay.0 {
+        return;
+    }
+    commands.spawn((
+        Text::default(),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.6, 1.0, 0.6)),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(5.0),
+            right: Val::Px(5.0),
+            padding: UiRect::all(Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.7)),
+        GlobalZIndex(20),
+        PickingBehavior::IGNORE,
+        PerformanceText,
+    ));
+}
+
+/// The bytes of the images still held by the main world, textures which only live on the GPU
+/// aren't counted
+fn texture_bytes(images: &Assets<Image>) -> usize {
+    images.iter().map(|(_, image)| image.data.len()).sum()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_overlay(
+    time: Res<Time>,
+    mut timer: Local<Option<Timer>>,
+    diagnostics: Res<DiagnosticsStore>,
+    entities: &Entities,
+    pieces: Query<Has<OffScreen>, With<Piece>>,
+    images: Res<Assets<Image>>,
+    mut texts: Query<&mut Text, With<PerformanceText>>,
+    added: Query<(), Added<PerformanceText>>,
+) {
+    let timer =
+        timer.get_or_insert_with(|| Timer::from_seconds(UPDATE_INTERVAL, TimerMode::Repeating));
+    if !timer.tick(time.delta()).just_finished() && added.is_empty() {
+        return;
+    }
+
+    let smoothed = |path: &DiagnosticPath| {
+        diagnostics
+            .get(path)
+            .and_then(Diagnostic::smoothed)
+            .unwrap_or_default()
+    };
+    let fps = smoothed(&FrameTimeDiagnosticsPlugin::FPS);
+    let frame_time = smoothed(&FrameTimeDiagnosticsPlugin::FRAME_TIME);
+    let total = pieces.iter().count();
+    let visible = pieces.iter().filter(|off_screen| !off_screen).count();
+    let text = format!(
+        "FPS: {fps:.0} ({frame_time:.1} ms)\nEntities: {}\nVisible pieces: {visible} / {total}\nTextures: ~{:.1} MiB",
+        entities.len(),
+        texture_bytes(&images) as f32 / (1024.0 * 1024.0),
+    );
+    for mut value in texts.iter_mut() {
+        value.0.clone_from(&text);
+    }
+}
@@ -0,0 +1,402 @@
+//! Photos as puzzle images: a photo handed over with [`PhotoTaken`], e.g. a snapshot of a camera,
+//! is shown over the main menu to confirm it first, where it can be cropped to the common aspect
+//! ratios. The confirmed photo goes through the raw RGBA path like an upload and is selected.
+//! "Take photo" in the main menu snaps one with the default camera in desktop builds with the
+//! `camera` feature, desktop builds without it tell the player so. The web build has no photos.
+
+use crate::i18n::{Language, Localized};
+use crate::main_menu::LoadedImages;
+use crate::{AppState, OriginImage, NORMAL_BUTTON};
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+#[cfg(feature = "camera")]
+use bevy::tasks::{block_on, futures_lite::future, IoTaskPool, Task};
+use jigsaw_puzzle_generator::image::{imageops, RgbaImage};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<PhotoTaken>()
+        .add_event::<TakePhoto>()
+        .add_systems(
+            Update,
+            (take_photo, receive_photo, update_photo_preview)
+                .chain()
+                .run_if(in_state(AppState::MainMenu)),
+        )
+        .add_systems(OnExit(AppState::MainMenu), close_photo_dialog);
+    #[cfg(feature = "camera")]
+    app.add_systems(
+        Update,
+        finish_capture
+            .before(receive_photo)
+            .run_if(resource_exists::<CaptureTask>)
+            .run_if(in_state(AppState::MainMenu)),
+    );
+}
+
+/// A photo to puzzle, in RGBA
+#[derive(Event, Debug, Clone)]
+pub struct PhotoTaken(pub RgbaImage);
+
+/// Snaps a photo with the default camera, which is handed over as [`PhotoTaken`]
+#[derive(Event, Debug, Clone, Copy)]
+pub struct TakePhoto;
+
+/// Why there is no camera to take photos with
+#[cfg(not(feature = "camera"))]
+const NO_CAMERA: &str = "This build can not take photos, build it with the camera feature";
+
+/// The frame being captured, the camera is opened and read off the main thread
+#[cfg(feature = "camera")]
+#[derive(Resource)]
+struct CaptureTask(Task<Result<RgbaImage, String>>);
+
+/// The crops offered for a photo, centered in it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Crop {
+    #[default]
+    Full,
+    FourThree,
+    ThreeTwo,
+    Square,
+}
+
+impl Crop {
+    fn next(self) -> Self {
+        match self {
+            Crop::Full => Crop::FourThree,
+            Crop::FourThree => Crop::ThreeTwo,
+            Crop::ThreeTwo => Crop::Square,
+            Crop::Square => Crop::Full,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Crop::Full => "Full",
+            Crop::FourThree => "4:3",
+            Crop::ThreeTwo => "3:2",
+            Crop::Square => "1:1",
+        }
+    }
+
+    /// The largest part of the photo of this aspect ratio, the long side of the photo stays long
+    fn apply(&self, photo: &RgbaImage) -> RgbaImage {
+        let (width, height) = photo.dimensions();
+        let ratio = match self {
+            Crop::Full => return photo.clone(),
+            Crop::FourThree => 4.0 / 3.0,
+            Crop::ThreeTwo => 3.0 / 2.0,
+            Crop::Square => 1.0,
+        };
+        let ratio = if width >= height { ratio } else { 1.0 / ratio };
+        let (crop_width, crop_height) = if width as f32 / height as f32 > ratio {
+            ((height as f32 * ratio) as u32, height)
+        } else {
+            (width, (width as f32 / ratio) as u32)
+        };
+        imageops::crop_imm(
+            photo,
+            (width - crop_width) / 2,
+            (height - crop_height) / 2,
+            crop_width.max(1),
+            crop_height.max(1),
+        )
+        .to_image()
+    }
+}
+
+/// The photo waiting to be confirmed
+#[derive(Resource)]
+struct PendingPhoto {
+    photo: RgbaImage,
+    crop: Crop,
+}
+
+impl PendingPhoto {
+    fn cropped(&self) -> RgbaImage {
+        self.crop.apply(&self.photo)
+    }
+}
+
+#[derive(Component)]
+struct PhotoDialog;
+
+#[derive(Component)]
+struct PhotoPreview;
+
+#[derive(Component)]
+struct CropText;
+
+/// A text button of the dialog
+fn dialog_button(label: &'static str) -> impl Bundle {
+    (
+        Text::new(label),
+        TextFont {
+            font_size: 24.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            padding: UiRect::axes(Val::Px(16.0), Val::Px(6.0)),
+            ..default()
+        },
+        BackgroundColor(NORMAL_BUTTON),
+        BorderRadius::all(Val::Px(4.0)),
+    )
+}
+
+/// The dark layer over the main menu the dialog is shown on
+fn dialog_layer() -> impl Bundle {
+    (
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: Val::Px(12.0),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.8)),
+        GlobalZIndex(10),
+        PhotoDialog,
+    )
+}
+
+/// Tells the player why no photo was taken, with the `detail` of the error below
+fn show_photo_message(commands: &mut Commands, message: &'static str, detail: Option<String>) {
+    commands.queue(close_photo_dialog);
+    commands.spawn(dialog_layer()).with_children(|p| {
+        p.spawn((
+            Text::new(message),
+            Localized(message),
+            TextFont {
+                font_size: 24.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+        ));
+        if let Some(detail) = detail {
+            p.spawn((
+                Text::new(detail),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.8, 0.8, 0.8)),
+            ));
+        }
+        p.spawn((dialog_button("Close"), Localized("Close")))
+            .observe(
+                |_trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+                    commands.queue(close_photo_dialog);
+                },
+            );
+    });
+}
+
+#[cfg(feature = "camera")]
+fn take_photo(
+    mut commands: Commands,
+    mut events: EventReader<TakePhoto>,
+    task: Option<Res<CaptureTask>>,
+) {
+    if events.read().last().is_none() {
+        return;
+    }
+    // one photo at a time
+    if task.is_some() {
+        return;
+    }
+    let task = IoTaskPool::get().spawn(async move { capture_frame() });
+    commands.insert_resource(CaptureTask(task));
+}
+
+#[cfg(not(feature = "camera"))]
+fn take_photo(mut commands: Commands, mut events: EventReader<TakePhoto>) {
+    if events.read().last().is_none() {
+        return;
+    }
+    show_photo_message(&mut commands, NO_CAMERA, None);
+}
+
+#[cfg(feature = "camera")]
+fn finish_capture(
+    mut commands: Commands,
+    mut task: ResMut<CaptureTask>,
+    mut photos: EventWriter<PhotoTaken>,
+) {
+    let Some(result) = block_on(future::poll_once(&mut task.0)) else {
+        return;
+    };
+    commands.remove_resource::<CaptureTask>();
+    match result {
+        Ok(photo) => {
+            photos.send(PhotoTaken(photo));
+        }
+        Err(e) => {
+            warn!("Failed to take a photo: {e}");
+            show_photo_message(&mut commands, "Failed to take a photo", Some(e));
+        }
+    }
+}
+
+/// How many frames are read before the photo, so the camera has adjusted its exposure
+#[cfg(feature = "camera")]
+const WARM_UP_FRAMES: usize = 5;
+
+/// Reads a frame of the default camera at its highest resolution
+#[cfg(feature = "camera")]
+fn capture_frame() -> Result<RgbaImage, String> {
+    use nokhwa::pixel_format::RgbAFormat;
+    use nokhwa::utils::{CameraIndex, RequestedFormat, RequestedFormatType};
+    use nokhwa::Camera;
+
+    // macOS asks the user for the camera first, the photo can be taken once it is allowed
+    #[cfg(target_os = "macos")]
+    if !nokhwa::nokhwa_check() {
+        nokhwa::nokhwa_initialize(|_| {});
+        return Err("Allow the camera and take the photo again".to_string());
+    }
+
+    let format = RequestedFormat::new::<RgbAFormat>(RequestedFormatType::AbsoluteHighestResolution);
+    let mut camera = Camera::new(CameraIndex::Index(0), format).map_err(|e| e.to_string())?;
+    camera.open_stream().map_err(|e| e.to_string())?;
+    let mut frame = camera.frame();
+    for _ in 1..WARM_UP_FRAMES {
+        if frame.is_err() {
+            break;
+        }
+        frame = camera.frame();
+    }
+    let _ = camera.stop_stream();
+    let image = frame
+        .and_then(|frame| frame.decode_image::<RgbAFormat>())
+        .map_err(|e| e.to_string())?;
+    let (width, height) = image.dimensions();
+    // nokhwa may use another version of the image crate
+    RgbaImage::from_raw(width, height, image.into_raw())
+        .ok_or_else(|| "The camera frame is cut off".to_string())
+}
+
+/// The raw RGBA path the uploads of the web build take as well
+fn rgba_image(image: RgbaImage) -> Image {
+    let (width, height) = image.dimensions();
+    Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        image.into_raw(),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+fn receive_photo(
+    mut commands: Commands,
+    mut events: EventReader<PhotoTaken>,
+    dialogs: Query<Entity, With<PhotoDialog>>,
+) {
+    let Some(PhotoTaken(photo)) = events.read().last() else {
+        return;
+    };
+    if photo.width() == 0 || photo.height() == 0 {
+        return;
+    }
+    for entity in dialogs.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.insert_resource(PendingPhoto {
+        photo: photo.clone(),
+        crop: Crop::default(),
+    });
+
+    commands.spawn(dialog_layer()).with_children(|p| {
+        p.spawn((
+            Node {
+                max_width: Val::Percent(80.0),
+                max_height: Val::Percent(70.0),
+                ..default()
+            },
+            ImageNode::default(),
+            PhotoPreview,
+        ));
+        p.spawn(Node {
+            column_gap: Val::Px(12.0),
+            ..default()
+        })
+        .with_children(|p| {
+            p.spawn((dialog_button("Full"), CropText)).observe(
+                |_trigger: Trigger<Pointer<Click>>, mut pending: ResMut<PendingPhoto>| {
+                    pending.crop = pending.crop.next();
+                },
+            );
+            p.spawn((dialog_button("Use photo"), Localized("Use photo")))
+                .observe(use_photo);
+            p.spawn((dialog_button("Cancel"), Localized("Cancel")))
+                .observe(
+                    |_trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+                        commands.queue(close_photo_dialog);
+                    },
+                );
+        });
+    });
+}
+
+/// Shows the photo as it is cropped
+fn update_photo_preview(
+    pending: Option<Res<PendingPhoto>>,
+    mut images: ResMut<Assets<Image>>,
+    mut previews: Query<&mut ImageNode, With<PhotoPreview>>,
+    mut texts: Query<&mut Text, With<CropText>>,
+    added: Query<(), Added<PhotoPreview>>,
+    language: Res<Language>,
+) {
+    let Some(pending) = pending else {
+        return;
+    };
+    if !pending.is_changed() && added.is_empty() {
+        return;
+    }
+    let preview = images.add(rgba_image(pending.cropped()));
+    for mut image_node in previews.iter_mut() {
+        image_node.image = preview.clone();
+    }
+    for mut text in texts.iter_mut() {
+        text.0 = language.tr(pending.crop.label()).to_string();
+    }
+}
+
+fn use_photo(
+    _trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    pending: Option<Res<PendingPhoto>>,
+    mut images: ResMut<Assets<Image>>,
+    mut loaded_images: ResMut<LoadedImages>,
+) {
+    let Some(pending) = pending else {
+        return;
+    };
+    let handle = images.add(rgba_image(pending.cropped()));
+    loaded_images.push(handle.clone());
+    commands.insert_resource(OriginImage(handle));
+    commands.queue(close_photo_dialog);
+}
+
+fn close_photo_dialog(world: &mut World) {
+    world.remove_resource::<PendingPhoto>();
+    #[cfg(feature = "camera")]
+    world.remove_resource::<CaptureTask>();
+    let mut dialogs = world.query_filtered::<Entity, With<PhotoDialog>>();
+    let entities: Vec<Entity> = dialogs.iter(world).collect();
+    for entity in entities {
+        if let Ok(entity) = world.get_entity_mut(entity) {
+            entity.despawn_recursive();
+        }
+    }
+}
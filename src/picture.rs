@@ -0,0 +1,269 @@
+//! Saves a picture of the finished puzzle to the user's Pictures directory.
+
+use crate::gameplay::{GameTimer, JigsawPuzzleGenerator, JigsawPuzzleTemplate, OnFinishScreen};
+use crate::i18n::{Language, Localized};
+use crate::theme::ThemeColor;
+use crate::{GameState, Piece, NORMAL_BUTTON};
+use ab_glyph::FontRef;
+use bevy::prelude::*;
+use bevy::tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task};
+use jigsaw_puzzle_generator::image::{DynamicImage, Rgba, RgbaImage};
+use jigsaw_puzzle_generator::{imageproc, JigsawPiece};
+use std::path::PathBuf;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PictureOptions>()
+        .add_systems(
+            OnEnter(GameState::Finish),
+            (keep_finished_pieces, setup_picture_ui),
+        )
+        .add_systems(
+            Update,
+            (
+                update_cut_lines_text
+                    .run_if(resource_changed::<PictureOptions>.or(resource_changed::<Language>)),
+                finish_saving.run_if(resource_exists::<SavePictureTask>),
+            )
+                .run_if(in_state(GameState::Finish)),
+        )
+        .add_systems(OnExit(GameState::Finish), forget_finished_pieces);
+}
+
+const CUT_LINE_COLOR: Rgba<u8> = Rgba([30, 30, 30, 160]);
+const CAPTION_BACKGROUND: Rgba<u8> = Rgba([30, 30, 30, 255]);
+const CAPTION_COLOR: Rgba<u8> = Rgba([240, 240, 240, 255]);
+
+/// What is drawn on the saved picture
+#[derive(Resource, Debug)]
+struct PictureOptions {
+    cut_lines: bool,
+}
+
+impl Default for PictureOptions {
+    fn default() -> Self {
+        Self { cut_lines: true }
+    }
+}
+
+/// The pieces of the finished puzzle, the play screen is gone on the finish screen
+#[derive(Resource, Debug)]
+struct FinishedPieces(Vec<JigsawPiece>);
+
+#[derive(Resource)]
+struct PictureFont(Handle<Font>);
+
+#[derive(Resource)]
+struct SavePictureTask(Task<Result<PathBuf, String>>);
+
+#[derive(Component)]
+struct CutLinesText;
+
+#[derive(Component)]
+struct SaveStatusText;
+
+fn keep_finished_pieces(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    pieces: Query<&Piece>,
+) {
+    let pieces = pieces.iter().map(|piece| piece.0.clone()).collect();
+    commands.insert_resource(FinishedPieces(pieces));
+    commands.insert_resource(PictureFont(asset_server.load("fonts/FiraSans-Bold.ttf")));
+}
+
+fn forget_finished_pieces(mut commands: Commands) {
+    commands.remove_resource::<FinishedPieces>();
+    commands.remove_resource::<SavePictureTask>();
+}
+
+fn setup_picture_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let text_font = TextFont {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 18.0,
+        ..default()
+    };
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                right: Val::Px(10.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::End,
+                row_gap: Val::Px(5.0),
+                ..default()
+            },
+            OnFinishScreen,
+        ))
+        .with_children(|p| {
+            p.spawn((
+                Button,
+                Node {
+                    padding: UiRect::axes(Val::Px(15.0), Val::Px(8.0)),
+                    border: UiRect::all(Val::Px(3.0)),
+                    ..default()
+                },
+                BorderColor(Color::BLACK),
+                BorderRadius::MAX,
+                BackgroundColor(NORMAL_BUTTON),
+                ThemeColor::Button,
+            ))
+            .with_child((
+                Text::new("Save picture"),
+                Localized("Save picture"),
+                text_font.clone(),
+                TextColor(Color::srgb(0.9, 0.9, 0.9)),
+                ThemeColor::ButtonText,
+            ))
+            .observe(save_picture);
+            p.spawn((
+                Text::default(),
+                text_font.clone(),
+                TextColor(Color::BLACK),
+                ThemeColor::Text,
+                CutLinesText,
+            ))
+            .observe(
+                |_trigger: Trigger<Pointer<Click>>, mut options: ResMut<PictureOptions>| {
+                    options.cut_lines = !options.cut_lines;
+                },
+            );
+            p.spawn((
+                Text::default(),
+                text_font,
+                TextColor(Color::BLACK),
+                ThemeColor::Text,
+                SaveStatusText,
+            ));
+        });
+}
+
+fn update_cut_lines_text(
+    options: Res<PictureOptions>,
+    language: Res<Language>,
+    mut texts: Query<&mut Text, With<CutLinesText>>,
+) {
+    let state = if options.cut_lines { "On" } else { "Off" };
+    for mut text in texts.iter_mut() {
+        text.0 = format!("{}: {}", language.tr("Cut lines"), language.tr(state));
+    }
+}
+
+/// `XDG_PICTURES_DIR` if it is set, otherwise the Pictures directory in the home directory
+pub fn pictures_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("XDG_PICTURES_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(|home| PathBuf::from(home).join("Pictures"))
+}
+
+/// The solved image with the cut lines of the pieces and a caption below it
+fn compose_picture(
+    image: &DynamicImage,
+    pieces: &[JigsawPiece],
+    cut_lines: bool,
+    font: Option<&FontRef>,
+    caption: &str,
+) -> RgbaImage {
+    let mut puzzle = image.to_rgba8();
+    if cut_lines {
+        for piece in pieces {
+            piece.draw_cut_lines(&mut puzzle, CUT_LINE_COLOR);
+        }
+    }
+    let Some(font) = font else {
+        return puzzle;
+    };
+
+    let band = (puzzle.width() / 25).max(30);
+    let mut picture =
+        RgbaImage::from_pixel(puzzle.width(), puzzle.height() + band, CAPTION_BACKGROUND);
+    jigsaw_puzzle_generator::image::imageops::overlay(&mut picture, &puzzle, 0, 0);
+    let scale = band as f32 * 0.6;
+    let (_, text_height) = imageproc::drawing::text_size(scale, font, caption);
+    imageproc::drawing::draw_text_mut(
+        &mut picture,
+        CAPTION_COLOR,
+        (band / 3) as i32,
+        (puzzle.height() + (band - text_height) / 2) as i32,
+        scale,
+        font,
+        caption,
+    );
+    picture
+}
+
+#[allow(clippy::too_many_arguments)]
+fn save_picture(
+    _trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    generator: Res<JigsawPuzzleGenerator>,
+    template: Res<JigsawPuzzleTemplate>,
+    pieces: Option<Res<FinishedPieces>>,
+    options: Res<PictureOptions>,
+    game_timer: Res<GameTimer>,
+    language: Res<Language>,
+    font: Res<PictureFont>,
+    fonts: Res<Assets<Font>>,
+    running: Option<Res<SavePictureTask>>,
+) {
+    if running.is_some() {
+        return;
+    }
+    let Some(dir) = pictures_dir() else {
+        warn!("Unknown pictures directory");
+        return;
+    };
+
+    let image = generator.origin_image().clone();
+    let pieces = pieces.map(|pieces| pieces.0.clone()).unwrap_or_default();
+    let cut_lines = options.cut_lines;
+    let font_data = fonts.get(&font.0).map(|font| font.data.clone());
+    let caption = format!(
+        "{} {}  {}",
+        template.pieces_count(),
+        language.tr("pieces"),
+        *game_timer
+    );
+    let task = AsyncComputeTaskPool::get().spawn(async move {
+        let font = font_data
+            .as_ref()
+            .and_then(|data| FontRef::try_from_slice(data).ok());
+        let picture = compose_picture(&image, &pieces, cut_lines, font.as_ref(), &caption);
+
+        std::fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+        let seconds = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        let path = dir.join(format!("jigsaw_puzzle_{}.png", seconds));
+        picture.save(&path).map_err(|err| err.to_string())?;
+        Ok(path)
+    });
+    commands.insert_resource(SavePictureTask(task));
+}
+
+fn finish_saving(
+    mut commands: Commands,
+    mut task: ResMut<SavePictureTask>,
+    language: Res<Language>,
+    mut texts: Query<&mut Text, With<SaveStatusText>>,
+) {
+    let Some(result) = block_on(future::poll_once(&mut task.0)) else {
+        return;
+    };
+    commands.remove_resource::<SavePictureTask>();
+
+    let status = match result {
+        Ok(path) => format!("{}: {}", language.tr("Saved"), path.display()),
+        Err(err) => {
+            error!("Failed to save the picture: {}", err);
+            language.tr("Could not save the picture").to_string()
+        }
+    };
+    for mut text in texts.iter_mut() {
+        text.0 = status.clone();
+    }
+}
@@ -0,0 +1,96 @@
+//! Picks pieces by their outline instead of the rectangles of their images. The tabs of a piece
+//! reach into the rectangles of its neighbours, so the meshes overlap and the mesh backend may hit
+//! a piece lying below the one under the pointer. Here every piece whose outline contains the
+//! pointer is found and only the topmost one is hit, the entity of the piece itself, which takes
+//! its group along.
+
+use crate::Piece;
+use bevy::picking::backend::prelude::*;
+use bevy::picking::PickSet;
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_observer(trace_outline)
+        .add_systems(PreUpdate, pick_pieces.in_set(PickSet::Backend));
+}
+
+/// How many points are sampled on every edge of the outline
+const OUTLINE_SAMPLES: usize = 8;
+
+/// The outline of the piece, relative to the piece entity
+#[derive(Component, Debug, Clone)]
+struct PickOutline(Vec<Vec2>);
+
+impl PickOutline {
+    /// Whether `point`, relative to the piece entity, lies in the outline
+    fn contains(&self, point: Vec2) -> bool {
+        let mut inside = false;
+        let mut previous = match self.0.last() {
+            Some(last) => *last,
+            None => return false,
+        };
+        for current in &self.0 {
+            if (current.y > point.y) != (previous.y > point.y)
+                && point.x
+                    < (previous.x - current.x) * (point.y - current.y) / (previous.y - current.y)
+                        + current.x
+            {
+                inside = !inside;
+            }
+            previous = *current;
+        }
+        inside
+    }
+}
+
+fn trace_outline(trigger: Trigger<OnInsert, Piece>, mut commands: Commands, pieces: Query<&Piece>) {
+    let Ok(piece) = pieces.get(trigger.entity()) else {
+        return;
+    };
+    // the outline is in image coordinates, relative to the start point of the piece
+    let (start_x, start_y) = piece.start_point;
+    let points = piece
+        .outline(OUTLINE_SAMPLES)
+        .into_iter()
+        .map(|(x, y)| Vec2::new(x - start_x, start_y - y))
+        .collect();
+    commands
+        .entity(trigger.entity())
+        .insert(PickOutline(points));
+}
+
+/// Hits the topmost piece under every pointer, pieces below it are left out
+fn pick_pieces(
+    ray_map: Res<RayMap>,
+    cameras: Query<&Camera, (With<Camera2d>, With<IsDefaultUiCamera>)>,
+    pieces: Query<(Entity, &PickOutline, &GlobalTransform, &InheritedVisibility), With<Children>>,
+    mut output: EventWriter<PointerHits>,
+) {
+    for (&ray_id, ray) in ray_map.iter() {
+        let Ok(camera) = cameras.get(ray_id.camera) else {
+            continue;
+        };
+        let topmost = pieces
+            .iter()
+            .filter(|(_, _, _, visibility)| visibility.get())
+            .filter_map(|(entity, outline, transform, _)| {
+                let z = transform.translation().z;
+                let depth = ray.intersect_plane(Vec3::Z * z, InfinitePlane3d::new(Vec3::Z))?;
+                let position = ray.get_point(depth);
+                let local = transform.affine().inverse().transform_point3(position);
+                outline
+                    .contains(local.xy())
+                    .then_some((entity, z, depth, position))
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        let Some((entity, _, depth, position)) = topmost else {
+            continue;
+        };
+        let hit = HitData::new(ray_id.camera, depth, Some(position), Some(Vec3::Z));
+        output.send(PointerHits::new(
+            ray_id.pointer,
+            vec![(entity, hit)],
+            camera.order as f32,
+        ));
+    }
+}
@@ -0,0 +1,296 @@
+//! The shape of the classic pieces: "Advanced" in the main menu opens sliders for the size of the
+//! knobs and how irregular the pieces are cut, with a sample piece showing the shape.
+
+use crate::i18n::{Language, Localized};
+use crate::theme::ThemeColor;
+use crate::{AppState, SelectGameMode};
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+use jigsaw_puzzle_generator::pattern::{generate_pattern, PatternStyle};
+use jigsaw_puzzle_generator::{
+    JigsawGenerator, DEFAULT_JITTER, DEFAULT_TAB_SIZE, JITTERS, TAB_SIZES,
+};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<PieceShape>().add_systems(
+        Update,
+        (update_sliders, update_preview).run_if(in_state(AppState::MainMenu)),
+    );
+}
+
+const SLIDER_WIDTH: f32 = 180.0;
+/// The sample puzzle the previewed piece is cut from, the piece in its middle is shown
+const PREVIEW_IMAGE_SIZE: u32 = 240;
+const PREVIEW_SIZE: f32 = 140.0;
+const PREVIEW_SEED: usize = 7;
+
+/// The size of the knobs and the irregularity of the cuts of the classic pieces
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct PieceShape {
+    /// Between 10 and 30
+    pub tab_size: f32,
+    /// Between 0 and 13
+    pub jitter: f32,
+}
+
+impl Default for PieceShape {
+    fn default() -> Self {
+        PieceShape {
+            tab_size: DEFAULT_TAB_SIZE,
+            jitter: DEFAULT_JITTER,
+        }
+    }
+}
+
+impl PieceShape {
+    /// The generator cutting the pieces in this shape
+    pub fn apply(&self, generator: JigsawGenerator) -> JigsawGenerator {
+        generator
+            .tab_size(self.tab_size.clamp(*TAB_SIZES.start(), *TAB_SIZES.end()))
+            .jitter(self.jitter.clamp(*JITTERS.start(), *JITTERS.end()))
+    }
+}
+
+/// A slider for one value of the [`PieceShape`]
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+enum ShapeSlider {
+    KnobSize,
+    Irregularity,
+}
+
+impl ShapeSlider {
+    fn label(&self) -> &'static str {
+        match self {
+            ShapeSlider::KnobSize => "Knob size",
+            ShapeSlider::Irregularity => "Irregularity",
+        }
+    }
+
+    fn range(&self) -> (f32, f32) {
+        let range = match self {
+            ShapeSlider::KnobSize => TAB_SIZES,
+            ShapeSlider::Irregularity => JITTERS,
+        };
+        (*range.start(), *range.end())
+    }
+
+    fn value(&self, shape: &PieceShape) -> f32 {
+        match self {
+            ShapeSlider::KnobSize => shape.tab_size,
+            ShapeSlider::Irregularity => shape.jitter,
+        }
+    }
+
+    /// The fraction of the slider the value fills
+    fn fraction(&self, shape: &PieceShape) -> f32 {
+        let (min, max) = self.range();
+        (self.value(shape) - min) / (max - min)
+    }
+
+    /// Sets the whole number at the `fraction` of the slider
+    fn set(&self, shape: &mut PieceShape, fraction: f32) {
+        let (min, max) = self.range();
+        let value = (min + fraction.clamp(0.0, 1.0) * (max - min)).round();
+        match self {
+            ShapeSlider::KnobSize => shape.tab_size = value,
+            ShapeSlider::Irregularity => shape.jitter = value,
+        }
+    }
+}
+
+#[derive(Component)]
+struct SliderFill(ShapeSlider);
+
+#[derive(Component)]
+struct SliderLabel(ShapeSlider);
+
+#[derive(Component)]
+struct AdvancedPanel;
+
+#[derive(Component)]
+struct ShapePreview;
+
+/// Adds "Advanced", which opens the panel of the piece shape next to it
+pub(crate) fn spawn_advanced_button(p: &mut ChildBuilder, font: Handle<Font>) {
+    let text_font = TextFont {
+        font,
+        font_size: 24.0,
+        ..default()
+    };
+    p.spawn(Node {
+        margin: UiRect::new(Val::Px(20.0), Val::Px(0.0), Val::Px(5.0), Val::Px(0.0)),
+        ..default()
+    })
+    .with_children(|p| {
+        p.spawn((
+            Text::new("Advanced"),
+            Localized("Advanced"),
+            text_font.clone(),
+            TextColor(Color::BLACK),
+            ThemeColor::Text,
+        ))
+        .observe(
+            |_trigger: Trigger<Pointer<Click>>,
+             mut panels: Query<&mut Node, With<AdvancedPanel>>| {
+                for mut node in panels.iter_mut() {
+                    node.display = match node.display {
+                        Display::None => Display::Flex,
+                        _ => Display::None,
+                    };
+                }
+            },
+        );
+
+        p.spawn((
+            Node {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                left: Val::Percent(100.0),
+                bottom: Val::Px(0.0),
+                margin: UiRect::left(Val::Px(20.0)),
+                padding: UiRect::all(Val::Px(12.0)),
+                column_gap: Val::Px(16.0),
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            BackgroundColor(Color::srgba(1.0, 1.0, 1.0, 0.85)),
+            BorderRadius::all(Val::Px(8.0)),
+            ZIndex(1),
+            AdvancedPanel,
+        ))
+        .with_children(|p| {
+            p.spawn(Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                ..default()
+            })
+            .with_children(|p| {
+                for slider in [ShapeSlider::KnobSize, ShapeSlider::Irregularity] {
+                    spawn_slider(p, slider, text_font.clone());
+                }
+            });
+            // the piece at its own size, the knobs make it larger
+            p.spawn(Node {
+                width: Val::Px(PREVIEW_SIZE),
+                height: Val::Px(PREVIEW_SIZE),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            })
+            .with_child((ImageNode::default(), ShapePreview));
+        });
+    });
+}
+
+fn spawn_slider(p: &mut ChildBuilder, slider: ShapeSlider, font: TextFont) {
+    p.spawn((
+        Text::default(),
+        TextFont {
+            font_size: 20.0,
+            ..font
+        },
+        TextColor(Color::BLACK),
+        SliderLabel(slider),
+    ));
+    p.spawn((
+        Node {
+            width: Val::Px(SLIDER_WIDTH),
+            height: Val::Px(12.0),
+            margin: UiRect::bottom(Val::Px(4.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.3)),
+        BorderRadius::MAX,
+        RelativeCursorPosition::default(),
+        slider,
+    ))
+    .with_child((
+        Node {
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        BackgroundColor(Color::srgb(0.3, 0.75, 0.35)),
+        BorderRadius::MAX,
+        PickingBehavior::IGNORE,
+        SliderFill(slider),
+    ))
+    .observe(slide::<Click>)
+    .observe(slide::<Drag>);
+}
+
+/// Sets the value at the clicked or dragged position of the slider
+fn slide<E: core::fmt::Debug + Clone + Reflect>(
+    trigger: Trigger<Pointer<E>>,
+    sliders: Query<(&ShapeSlider, &RelativeCursorPosition)>,
+    mut shape: ResMut<PieceShape>,
+) {
+    let Ok((slider, position)) = sliders.get(trigger.entity()) else {
+        return;
+    };
+    let Some(normalized) = position.normalized else {
+        return;
+    };
+    let mut changed = *shape;
+    slider.set(&mut changed, normalized.x);
+    shape.set_if_neq(changed);
+}
+
+fn update_sliders(
+    shape: Res<PieceShape>,
+    language: Res<Language>,
+    mut fills: Query<(&mut Node, &SliderFill)>,
+    mut labels: Query<(&mut Text, &SliderLabel)>,
+    added: Query<(), Added<SliderLabel>>,
+) {
+    if added.is_empty() && !shape.is_changed() && !language.is_changed() {
+        return;
+    }
+    for (mut node, SliderFill(slider)) in fills.iter_mut() {
+        node.width = Val::Percent(slider.fraction(&shape) * 100.0);
+    }
+    for (mut text, SliderLabel(slider)) in labels.iter_mut() {
+        text.0 = format!(
+            "{}: {:.0}",
+            language.tr(slider.label()),
+            slider.value(&shape)
+        );
+    }
+}
+
+/// Cuts a small sample puzzle in the shape and shows its middle piece
+fn update_preview(
+    shape: Res<PieceShape>,
+    select_game_mode: Res<SelectGameMode>,
+    mut images: ResMut<Assets<Image>>,
+    mut previews: Query<&mut ImageNode, With<ShapePreview>>,
+    added: Query<(), Added<ShapePreview>>,
+) {
+    if previews.is_empty()
+        || (added.is_empty() && !shape.is_changed() && !select_game_mode.is_changed())
+    {
+        return;
+    }
+    let sample = generate_pattern(
+        PatternStyle::GradientNoise,
+        PREVIEW_SEED as u64,
+        PREVIEW_IMAGE_SIZE,
+        PREVIEW_IMAGE_SIZE,
+    );
+    let generator = shape.apply(JigsawGenerator::new(sample.into(), 3, 3).seed(PREVIEW_SEED));
+    let piece = match generator.generate(**select_game_mode, false) {
+        Ok(template) => template.pieces[4].crop(&template.origin_image),
+        Err(err) => {
+            warn!("Could not cut the sample piece: {}", err);
+            return;
+        }
+    };
+    let preview = images.add(Image::from_dynamic(
+        piece,
+        true,
+        RenderAssetUsages::RENDER_WORLD,
+    ));
+    for mut image_node in previews.iter_mut() {
+        image_node.image = preview.clone();
+    }
+}
@@ -0,0 +1,105 @@
+//! Right clicking a group pins it to the table. Pinned groups can't be dragged, loose pieces still
+//! snap to them, and pieces which join a pinned group are pinned as well.
+
+use crate::atlas::PieceMaterial;
+use crate::board::Locked;
+use crate::gameplay::{ColorImage, CombineTogether, MoveTogether};
+use crate::Piece;
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_observer(pin_on_right_click)
+        .add_observer(pin_joined_pieces)
+        .add_observer(tint_pinned)
+        .add_observer(untint_unpinned);
+}
+
+const PINNED_TINT: Color = Color::srgb(0.85, 0.9, 1.0);
+
+/// A piece of a group pinned in place
+#[derive(Component, Debug)]
+pub struct Pinned;
+
+/// Toggles the pin of a group, single pieces are rotated instead
+fn pin_on_right_click(
+    trigger: Trigger<Pointer<Click>>,
+    mut commands: Commands,
+    pieces: Query<(&MoveTogether, Has<Pinned>), (With<Piece>, Without<Locked>)>,
+) {
+    if trigger.event().button != PointerButton::Secondary {
+        return;
+    }
+    let Ok((together, pinned)) = pieces.get(trigger.entity()) else {
+        return;
+    };
+    for entity in together.iter() {
+        if pinned {
+            commands.entity(*entity).remove::<Pinned>();
+        } else {
+            commands.entity(*entity).insert(Pinned);
+        }
+    }
+}
+
+fn pin_joined_pieces(
+    trigger: Trigger<CombineTogether>,
+    mut commands: Commands,
+    pinned: Query<(), With<Pinned>>,
+) {
+    let group = &trigger.event().0;
+    if !group.iter().any(|entity| pinned.contains(*entity)) {
+        return;
+    }
+    for entity in group.iter() {
+        if !pinned.contains(*entity) {
+            commands.entity(*entity).insert(Pinned);
+        }
+    }
+}
+
+fn tint_piece(
+    entity: Entity,
+    color: Color,
+    children: &Query<&Children>,
+    images: &Query<&MeshMaterial2d<PieceMaterial>, With<ColorImage>>,
+    materials: &mut Assets<PieceMaterial>,
+) {
+    let Ok(children) = children.get(entity) else {
+        return;
+    };
+    for material in images.iter_many(children) {
+        if let Some(material) = materials.get_mut(material) {
+            material.set_color(color);
+        }
+    }
+}
+
+fn tint_pinned(
+    trigger: Trigger<OnAdd, Pinned>,
+    children: Query<&Children>,
+    images: Query<&MeshMaterial2d<PieceMaterial>, With<ColorImage>>,
+    mut materials: ResMut<Assets<PieceMaterial>>,
+) {
+    tint_piece(
+        trigger.entity(),
+        PINNED_TINT,
+        &children,
+        &images,
+        &mut materials,
+    );
+}
+
+fn untint_unpinned(
+    trigger: Trigger<OnRemove, Pinned>,
+    children: Query<&Children>,
+    images: Query<&MeshMaterial2d<PieceMaterial>, With<ColorImage>>,
+    mut materials: ResMut<Assets<PieceMaterial>>,
+) {
+    tint_piece(
+        trigger.entity(),
+        Color::WHITE,
+        &children,
+        &images,
+        &mut materials,
+    );
+}
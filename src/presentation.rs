@@ -0,0 +1,292 @@
+//! A presentation mode for recording and streaming solves: <kbd>F10</kbd> hides the buttons and
+//! panels, shows a large progress bar with the elapsed time in a corner and a large ring in place
+//! of the cursor. <kbd>Shift</kbd> + <kbd>F10</kbd> adds the image of the puzzle in a
+//! picture-in-picture frame.
+
+use crate::board::Locked;
+use crate::clock::ClockDisplay;
+use crate::gameplay::{GameTimer, JigsawPuzzleTemplate, MoveTogether, OnPlayScreen};
+use crate::i18n::Language;
+use crate::mystery::Mystery;
+use crate::progress::placed_pieces;
+use crate::relaxed::Relaxed;
+use crate::{GameState, OriginImage, SelectPlayMode};
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Presentation>()
+        .add_systems(OnExit(GameState::Play), leave_presentation)
+        .add_systems(
+            Update,
+            (
+                toggle_presentation.run_if(in_state(GameState::Play)),
+                (show_overlay, show_cursor).run_if(resource_changed::<Presentation>),
+                hide_chrome,
+                (update_overlay, follow_cursor).run_if(in_state(GameState::Play)),
+            )
+                .chain(),
+        );
+}
+
+const BAR_WIDTH: f32 = 360.0;
+const BAR_HEIGHT: f32 = 20.0;
+const RING_SIZE: f32 = 48.0;
+const RING_COLOR: Color = Color::srgba(1.0, 0.85, 0.0, 0.35);
+const OVERLAY_BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.6);
+
+/// Whether the presentation mode is on
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct Presentation {
+    pub enabled: bool,
+    /// Shows the image of the puzzle in a corner
+    pub picture_in_picture: bool,
+}
+
+/// A UI root of the play screen hidden by the presentation mode, with its visibility before
+#[derive(Component)]
+struct HiddenChrome(Visibility);
+
+#[derive(Component)]
+struct PresentationOverlay;
+
+#[derive(Component)]
+struct PresentationTime;
+
+#[derive(Component)]
+struct PresentationProgress;
+
+#[derive(Component)]
+struct PresentationBar;
+
+#[derive(Component)]
+struct PictureInPicture;
+
+#[derive(Component)]
+struct CursorRing;
+
+fn toggle_presentation(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut presentation: ResMut<Presentation>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F10) {
+        return;
+    }
+    if keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        presentation.picture_in_picture = !presentation.picture_in_picture;
+    } else {
+        presentation.enabled = !presentation.enabled;
+    }
+}
+
+fn leave_presentation(mut presentation: ResMut<Presentation>) {
+    presentation.enabled = false;
+}
+
+fn show_overlay(
+    mut commands: Commands,
+    presentation: Res<Presentation>,
+    origin_image: Option<Res<OriginImage>>,
+    mystery: Res<Mystery>,
+    overlays: Query<Entity, With<PresentationOverlay>>,
+) {
+    for entity in overlays.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if !presentation.enabled {
+        return;
+    }
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(20.0),
+                left: Val::Px(20.0),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(8.0),
+                padding: UiRect::all(Val::Px(12.0)),
+                ..default()
+            },
+            BackgroundColor(OVERLAY_BACKGROUND),
+            BorderRadius::all(Val::Px(8.0)),
+            PickingBehavior::IGNORE,
+            PresentationOverlay,
+            OnPlayScreen,
+        ))
+        .with_children(|p| {
+            p.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: 36.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                PresentationTime,
+            ));
+            p.spawn((
+                Node {
+                    width: Val::Px(BAR_WIDTH),
+                    height: Val::Px(BAR_HEIGHT),
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(1.0, 1.0, 1.0, 0.2)),
+                BorderRadius::MAX,
+            ))
+            .with_child((
+                Node {
+                    width: Val::Percent(0.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                BackgroundColor(Color::srgb(0.3, 0.75, 0.35)),
+                BorderRadius::MAX,
+                PresentationBar,
+            ));
+            p.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                PresentationProgress,
+            ));
+        });
+
+    // the image stays a mystery until the puzzle is solved
+    if let Some(origin_image) =
+        origin_image.filter(|_| presentation.picture_in_picture && !mystery.0)
+    {
+        commands.spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(20.0),
+                right: Val::Px(20.0),
+                width: Val::Percent(25.0),
+                border: UiRect::all(Val::Px(4.0)),
+                ..default()
+            },
+            ImageNode::new(origin_image.0.clone()),
+            BorderColor(Color::WHITE),
+            PickingBehavior::IGNORE,
+            PictureInPicture,
+            PresentationOverlay,
+            OnPlayScreen,
+        ));
+    }
+
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Px(RING_SIZE),
+            height: Val::Px(RING_SIZE),
+            border: UiRect::all(Val::Px(4.0)),
+            ..default()
+        },
+        BackgroundColor(RING_COLOR),
+        BorderColor(Color::BLACK),
+        BorderRadius::MAX,
+        GlobalZIndex(90),
+        Visibility::Hidden,
+        PickingBehavior::IGNORE,
+        CursorRing,
+        PresentationOverlay,
+        OnPlayScreen,
+    ));
+}
+
+/// The ring takes the place of the cursor
+fn show_cursor(
+    presentation: Res<Presentation>,
+    mut window: Single<&mut Window, With<PrimaryWindow>>,
+) {
+    window.cursor_options.visible = !presentation.enabled;
+}
+
+/// Hides the UI roots of the play screen in the presentation mode, also those spawned later, and
+/// shows them again as they were afterwards
+fn hide_chrome(
+    mut commands: Commands,
+    presentation: Res<Presentation>,
+    mut roots: Query<
+        (Entity, &mut Visibility, Option<&HiddenChrome>),
+        (
+            With<Node>,
+            With<OnPlayScreen>,
+            Without<Parent>,
+            Without<PresentationOverlay>,
+        ),
+    >,
+) {
+    for (entity, mut visibility, hidden) in roots.iter_mut() {
+        match (presentation.enabled, hidden) {
+            (true, None) => {
+                commands.entity(entity).insert(HiddenChrome(*visibility));
+                *visibility = Visibility::Hidden;
+            }
+            (false, Some(HiddenChrome(before))) => {
+                *visibility = *before;
+                commands.entity(entity).remove::<HiddenChrome>();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_overlay(
+    presentation: Res<Presentation>,
+    relaxed: Res<Relaxed>,
+    clock_display: Res<ClockDisplay>,
+    game_timer: Res<GameTimer>,
+    template: Res<JigsawPuzzleTemplate>,
+    play_mode: Res<SelectPlayMode>,
+    language: Res<Language>,
+    pieces: Query<(&MoveTogether, Has<Locked>)>,
+    mut texts: ParamSet<(
+        Query<(&mut Text, &mut Visibility), With<PresentationTime>>,
+        Query<&mut Text, With<PresentationProgress>>,
+    )>,
+    mut bars: Query<&mut Node, With<PresentationBar>>,
+) {
+    if !presentation.enabled {
+        return;
+    }
+    let placed = placed_pieces(&play_mode, &pieces);
+    for (mut text, mut visibility) in texts.p0().iter_mut() {
+        // relaxed players don't see the clock here either, nor the ones who only peek at it
+        *visibility = if relaxed.0 || !clock_display.always_shown() {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+        text.0 = clock_display.text(&game_timer, placed, &language);
+    }
+
+    let total = template.pieces_count();
+    let label = format!("{}/{} {}", placed, total, language.tr("pieces"));
+    for mut text in texts.p1().iter_mut() {
+        if text.0 != label {
+            text.0 = label.clone();
+        }
+    }
+    for mut node in bars.iter_mut() {
+        node.width = Val::Percent(placed as f32 / total.max(1) as f32 * 100.0);
+    }
+}
+
+fn follow_cursor(
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut rings: Query<(&mut Node, &mut Visibility), With<CursorRing>>,
+) {
+    for (mut node, mut visibility) in rings.iter_mut() {
+        let Some(position) = window.cursor_position() else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        node.left = Val::Px(position.x - RING_SIZE / 2.0);
+        node.top = Val::Px(position.y - RING_SIZE / 2.0);
+        *visibility = Visibility::Inherited;
+    }
+}
@@ -0,0 +1,183 @@
+use crate::board::Locked;
+use crate::gameplay::{JigsawPuzzleTemplate, MoveTogether, OnPlayScreen};
+use crate::i18n::Language;
+use crate::theme::ThemeColor;
+use crate::{GameState, SelectPlayMode};
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Play), setup_progress)
+        .add_systems(
+            Update,
+            (update_progress, update_group_stats).run_if(in_state(GameState::Play)),
+        );
+}
+
+const BAR_WIDTH: f32 = 200.0;
+
+#[derive(Component)]
+struct ProgressText;
+
+#[derive(Component)]
+struct ProgressBar;
+
+/// The loose pieces, the groups and the size of the largest group
+#[derive(Component)]
+struct GroupStatsText;
+
+fn setup_progress(mut commands: Commands, q_text: Query<Entity, With<ProgressText>>) {
+    if !q_text.is_empty() {
+        return;
+    }
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            PickingBehavior::IGNORE,
+            OnPlayScreen,
+        ))
+        .with_children(|p| {
+            p.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: 14.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                Node {
+                    padding: UiRect::axes(Val::Px(8.0), Val::Px(2.0)),
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.4)),
+                BorderRadius::all(Val::Px(4.0)),
+                GroupStatsText,
+            ));
+            p.spawn((
+                Text::default(),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(Color::BLACK),
+                ThemeColor::Text,
+                ProgressText,
+            ));
+            p.spawn((
+                Node {
+                    width: Val::Px(BAR_WIDTH),
+                    height: Val::Px(8.0),
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.3)),
+                BorderRadius::MAX,
+            ))
+            .with_child((
+                Node {
+                    width: Val::Percent(0.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                BackgroundColor(Color::srgb(0.3, 0.75, 0.35)),
+                BorderRadius::MAX,
+                ProgressBar,
+            ));
+        });
+}
+
+/// The pieces in the largest group, or the locked pieces on the board
+pub(crate) fn placed_pieces(
+    play_mode: &SelectPlayMode,
+    pieces: &Query<(&MoveTogether, Has<Locked>)>,
+) -> usize {
+    match play_mode {
+        SelectPlayMode::Board => pieces.iter().filter(|(_, locked)| *locked).count(),
+        SelectPlayMode::Free | SelectPlayMode::Guided => pieces
+            .iter()
+            .map(|(together, _)| together.len())
+            .max()
+            .unwrap_or_default(),
+    }
+}
+
+/// Counts the pieces in the largest group, or the locked pieces on the board, whenever pieces
+/// snap, lock or are separated by undo
+#[allow(clippy::too_many_arguments)]
+fn update_progress(
+    template: Res<JigsawPuzzleTemplate>,
+    play_mode: Res<SelectPlayMode>,
+    language: Res<Language>,
+    pieces: Query<(&MoveTogether, Has<Locked>)>,
+    changed: Query<(), Or<(Changed<MoveTogether>, Added<Locked>)>>,
+    mut unlocked: RemovedComponents<Locked>,
+    added: Query<(), Added<ProgressText>>,
+    mut texts: Query<&mut Text, With<ProgressText>>,
+    mut bars: Query<&mut Node, With<ProgressBar>>,
+) {
+    let unlocked = unlocked.read().count() > 0;
+    if changed.is_empty() && !unlocked && added.is_empty() {
+        return;
+    }
+
+    let placed = placed_pieces(&play_mode, &pieces);
+    let total = template.pieces_count();
+
+    let label = format!("{}/{} {}", placed, total, language.tr("pieces"));
+    for mut text in texts.iter_mut() {
+        text.0 = label.clone();
+    }
+    for mut node in bars.iter_mut() {
+        node.width = Val::Percent(placed as f32 / total.max(1) as f32 * 100.0);
+    }
+}
+
+/// Counts the loose pieces and the groups whenever pieces snap, lock or are separated
+fn update_group_stats(
+    language: Res<Language>,
+    pieces: Query<(Entity, &MoveTogether, Has<Locked>)>,
+    changed: Query<(), Or<(Changed<MoveTogether>, Added<Locked>)>>,
+    mut unlocked: RemovedComponents<Locked>,
+    added: Query<(), Added<GroupStatsText>>,
+    mut texts: Query<&mut Text, With<GroupStatsText>>,
+) {
+    let unlocked = unlocked.read().count() > 0;
+    if changed.is_empty() && !unlocked && added.is_empty() && !language.is_changed() {
+        return;
+    }
+
+    let (mut loose, mut groups, mut largest) = (0, 0, 0);
+    for (entity, together, locked) in pieces.iter() {
+        if together.is_empty() {
+            if !locked {
+                loose += 1;
+            }
+            largest = largest.max(1);
+            continue;
+        }
+        // every group is counted at its first piece
+        if together.iter().min().is_some_and(|first| *first == entity) {
+            groups += 1;
+            largest = largest.max(together.len());
+        }
+    }
+
+    let label = format!(
+        "{}: {}  {}: {}  {}: {}",
+        language.tr("Loose"),
+        loose,
+        language.tr("Groups"),
+        groups,
+        language.tr("Largest"),
+        largest
+    );
+    for mut text in texts.iter_mut() {
+        text.0 = label.clone();
+    }
+}
@@ -0,0 +1,69 @@
+//! Relaxed mode: no clock is shown while playing or on the finish screen and the hints don't
+//! count down. It is switched in the main menu and remembered for the next start.
+
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(Relaxed(load_relaxed()))
+        .add_systems(Update, save_relaxed.run_if(resource_changed::<Relaxed>));
+}
+
+/// Whether the game is played without time pressure
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Deref, DerefMut)]
+pub struct Relaxed(pub bool);
+
+impl Relaxed {
+    pub fn toggle(&mut self) {
+        self.0 = !self.0;
+    }
+}
+
+fn save_relaxed(relaxed: Res<Relaxed>) {
+    if relaxed.is_added() {
+        return;
+    }
+    store_relaxed(relaxed.0);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_relaxed() -> bool {
+    crate::tutorial::config_path("relaxed").is_some_and(|path| path.exists())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn store_relaxed(relaxed: bool) {
+    let Some(path) = crate::tutorial::config_path("relaxed") else {
+        return;
+    };
+    let stored = if relaxed {
+        path.parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, ""))
+    } else if path.exists() {
+        std::fs::remove_file(&path)
+    } else {
+        Ok(())
+    };
+    if let Err(err) = stored {
+        warn!("Could not remember the relaxed mode: {}", err);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen::prelude::wasm_bindgen(inline_js = r#"
+export function load_relaxed() {
+    return localStorage.getItem("jigsaw-relaxed") !== null;
+}
+
+export function store_relaxed(relaxed) {
+    if (relaxed) {
+        localStorage.setItem("jigsaw-relaxed", "1");
+    } else {
+        localStorage.removeItem("jigsaw-relaxed");
+    }
+}
+"#)]
+extern "C" {
+    fn load_relaxed() -> bool;
+    fn store_relaxed(relaxed: bool);
+}
@@ -0,0 +1,254 @@
+use crate::atlas::PieceMaterial;
+use crate::board::Locked;
+use crate::camera::fit_rect;
+use crate::gameplay::{ColorImage, CombineTogether, GameTimer, OnFinishScreen};
+use crate::{GameState, Piece};
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use rand::Rng;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<SolveRecord>()
+        .init_resource::<SolvedPieces>()
+        .add_systems(OnEnter(GameState::Generating), reset_record)
+        .add_systems(OnEnter(GameState::Finish), capture_solved_pieces)
+        .add_systems(
+            Update,
+            (skip_replay, play_replay)
+                .chain()
+                .run_if(resource_exists::<Replay>)
+                .run_if(in_state(GameState::Finish)),
+        )
+        .add_systems(OnExit(GameState::Finish), stop_replay)
+        .add_observer(record_snap)
+        .add_observer(record_lock)
+        .add_observer(start_replay);
+}
+
+/// Length of the whole replay in seconds
+const REPLAY_DURATION: f32 = 10.0;
+/// Time a single piece takes to fly into place
+const FLIGHT_DURATION: f32 = 0.6;
+/// The finished puzzle is shown a moment before the finish screen returns
+const HOLD_DURATION: f32 = 1.0;
+
+/// Replays how the puzzle was solved on the finish screen
+#[derive(Event, Debug)]
+pub struct StartReplay;
+
+/// The play time at which every piece joined the puzzle
+#[derive(Resource, Debug, Default)]
+struct SolveRecord(HashMap<Entity, f32>);
+
+/// A piece image as it was when the puzzle was finished, in the order the pieces were solved
+#[derive(Debug, Clone)]
+struct SolvedPiece {
+    mesh: Handle<Mesh>,
+    material: Handle<PieceMaterial>,
+    size: Vec2,
+    /// Top left corner of the image
+    position: Vec3,
+    time: f32,
+}
+
+#[derive(Resource, Debug, Default)]
+struct SolvedPieces(Vec<SolvedPiece>);
+
+#[derive(Debug)]
+struct Flight {
+    entity: Entity,
+    from: Vec3,
+    to: Vec3,
+    start: f32,
+}
+
+#[derive(Resource, Debug)]
+struct Replay {
+    elapsed: f32,
+    flights: Vec<Flight>,
+}
+
+#[derive(Component)]
+struct ReplayPiece;
+
+fn reset_record(mut record: ResMut<SolveRecord>) {
+    record.0.clear();
+}
+
+fn record_snap(
+    trigger: Trigger<CombineTogether>,
+    timer: Option<Res<GameTimer>>,
+    mut record: ResMut<SolveRecord>,
+) {
+    let Some(timer) = timer else {
+        return;
+    };
+    let time = timer.elapsed_secs();
+    for entity in trigger.event().0.iter() {
+        record.0.entry(*entity).or_insert(time);
+    }
+}
+
+fn record_lock(
+    trigger: Trigger<OnAdd, Locked>,
+    timer: Option<Res<GameTimer>>,
+    mut record: ResMut<SolveRecord>,
+) {
+    if let Some(timer) = timer {
+        record
+            .0
+            .entry(trigger.entity())
+            .or_insert(timer.elapsed_secs());
+    }
+}
+
+/// Keeps the piece images before the play screen is despawned
+fn capture_solved_pieces(
+    record: Res<SolveRecord>,
+    mut solved: ResMut<SolvedPieces>,
+    pieces: Query<(Entity, &Piece, &Transform, &Children)>,
+    images: Query<(&Mesh2d, &MeshMaterial2d<PieceMaterial>, &Transform), With<ColorImage>>,
+    mut materials: ResMut<Assets<PieceMaterial>>,
+) {
+    solved.0.clear();
+    for (entity, piece, transform, children) in pieces.iter() {
+        let Some((mesh, material, image_transform)) = images.iter_many(children).next() else {
+            continue;
+        };
+        // without the tints of the table
+        let Some(mut material) = materials.get(material).cloned() else {
+            continue;
+        };
+        material.set_color(Color::WHITE);
+        solved.0.push(SolvedPiece {
+            mesh: mesh.0.clone(),
+            material: materials.add(material),
+            size: Vec2::new(piece.crop_width as f32, piece.crop_height as f32),
+            position: transform.translation + image_transform.translation,
+            // pieces which never snapped come last
+            time: record.0.get(&entity).copied().unwrap_or(f32::MAX),
+        });
+    }
+    solved.0.sort_by(|a, b| a.time.total_cmp(&b.time));
+}
+
+fn start_replay(
+    _trigger: Trigger<StartReplay>,
+    mut commands: Commands,
+    solved: Res<SolvedPieces>,
+    window: Single<&Window>,
+    camera: Single<(&mut Transform, &mut OrthographicProjection), With<IsDefaultUiCamera>>,
+    old_pieces: Query<Entity, With<ReplayPiece>>,
+    mut finish_screen: Query<&mut Visibility, With<OnFinishScreen>>,
+) {
+    let Some(first) = solved.0.first() else {
+        return;
+    };
+    for entity in old_pieces.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let image_rect = |piece: &SolvedPiece| {
+        Rect::from_corners(
+            piece.position.xy(),
+            piece.position.xy() + Vec2::new(piece.size.x, -piece.size.y),
+        )
+    };
+    let rect = solved.0.iter().fold(image_rect(first), |rect, piece| {
+        rect.union(image_rect(piece))
+    });
+    let radius = rect.size().length() * 0.75;
+
+    // spread the solve times over the replay, pieces which never snapped join at the end
+    let times: Vec<f32> = solved.0.iter().map(|piece| piece.time).collect();
+    let first_time = times[0];
+    let last_time = times
+        .iter()
+        .copied()
+        .filter(|time| *time < f32::MAX)
+        .fold(first_time, f32::max);
+    let span = (last_time - first_time).max(f32::EPSILON);
+
+    let mut rng = rand::thread_rng();
+    let flights = solved
+        .0
+        .iter()
+        .map(|piece| {
+            let direction = Vec2::from_angle(rng.gen_range(0.0..core::f32::consts::TAU));
+            let from = (rect.center() + direction * radius).extend(piece.position.z);
+            let entity = commands
+                .spawn((
+                    Mesh2d(piece.mesh.clone()),
+                    MeshMaterial2d(piece.material.clone()),
+                    Transform::from_translation(from),
+                    ReplayPiece,
+                ))
+                .id();
+            let progress = ((piece.time.min(last_time) - first_time) / span).clamp(0.0, 1.0);
+            Flight {
+                entity,
+                from,
+                to: piece.position,
+                start: progress * (REPLAY_DURATION - FLIGHT_DURATION),
+            }
+        })
+        .collect();
+
+    let (mut transform, mut projection) = camera.into_inner();
+    let (translation, scale) = fit_rect(rect, window.size());
+    transform.translation = translation.extend(transform.translation.z);
+    projection.scale = scale;
+
+    for mut visibility in finish_screen.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+    commands.insert_resource(Replay {
+        elapsed: 0.0,
+        flights,
+    });
+}
+
+/// A click or any key jumps to the end of the replay
+fn skip_replay(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut replay: ResMut<Replay>,
+) {
+    if mouse_input.get_just_pressed().next().is_some()
+        || keyboard_input.get_just_pressed().next().is_some()
+    {
+        replay.elapsed = replay.elapsed.max(REPLAY_DURATION);
+    }
+}
+
+fn play_replay(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut replay: ResMut<Replay>,
+    mut pieces: Query<&mut Transform, With<ReplayPiece>>,
+    mut finish_screen: Query<&mut Visibility, With<OnFinishScreen>>,
+) {
+    replay.elapsed += time.delta_secs();
+    for flight in replay.flights.iter() {
+        let Ok(mut transform) = pieces.get_mut(flight.entity) else {
+            continue;
+        };
+        let progress = ((replay.elapsed - flight.start) / FLIGHT_DURATION).clamp(0.0, 1.0);
+        let eased = 1.0 - (1.0 - progress).powi(3);
+        transform.translation = flight.from.lerp(flight.to, eased);
+    }
+
+    if replay.elapsed >= REPLAY_DURATION + HOLD_DURATION {
+        for mut visibility in finish_screen.iter_mut() {
+            *visibility = Visibility::Inherited;
+        }
+        commands.remove_resource::<Replay>();
+    }
+}
+
+fn stop_replay(mut commands: Commands, pieces: Query<Entity, With<ReplayPiece>>) {
+    commands.remove_resource::<Replay>();
+    for entity in pieces.iter() {
+        commands.entity(entity).despawn();
+    }
+}
@@ -0,0 +1,85 @@
+use crate::board::Locked;
+use crate::gameplay::{Immovable, MoveStart, MoveTogether, ScatterTween};
+use crate::tray::InTray;
+use crate::{Difficulty, GameState, Piece};
+use bevy::prelude::*;
+use rand::Rng;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        OnExit(GameState::Generating),
+        scatter_rotations.run_if(|difficulty: Res<Difficulty>| difficulty.rotation()),
+    )
+    .add_observer(rotate_on_right_click);
+}
+
+/// Quarter turns of a piece clockwise, only upright pieces snap to each other
+#[derive(Component, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PieceRotation(pub u8);
+
+/// Pieces without a rotation are always upright
+pub fn is_upright(rotation: Option<&PieceRotation>) -> bool {
+    rotation.is_none_or(|rotation| rotation.0 == 0)
+}
+
+/// Turns the piece clockwise around the center of its image, returns how far its origin moved
+fn rotate_piece(
+    piece: &Piece,
+    transform: &mut Transform,
+    rotation: &mut PieceRotation,
+    quarter_turns: u8,
+) -> Vec3 {
+    let center = piece.image_rect(Vec2::ZERO).center().extend(0.0);
+    let before = transform.rotation * center;
+    rotation.0 = (rotation.0 + quarter_turns) % 4;
+    transform.rotation = Quat::from_rotation_z(-core::f32::consts::FRAC_PI_2 * rotation.0 as f32);
+    let offset = before - transform.rotation * center;
+    transform.translation += offset;
+    offset
+}
+
+fn scatter_rotations(mut commands: Commands, mut pieces: Query<(Entity, &Piece, &mut Transform)>) {
+    let mut rng = rand::thread_rng();
+    for (entity, piece, mut transform) in pieces.iter_mut() {
+        let mut rotation = PieceRotation::default();
+        rotate_piece(piece, &mut transform, &mut rotation, rng.gen_range(0..4));
+        commands.entity(entity).insert(rotation);
+    }
+}
+
+/// Right clicking a loose piece turns it by a quarter, also while it is held
+fn rotate_on_right_click(
+    trigger: Trigger<Pointer<Click>>,
+    mut pieces: Query<
+        (
+            &Piece,
+            &mut Transform,
+            &mut PieceRotation,
+            &MoveTogether,
+            Option<&mut MoveStart>,
+        ),
+        (
+            Without<Locked>,
+            Without<InTray>,
+            Without<Immovable>,
+            Without<ScatterTween>,
+        ),
+    >,
+) {
+    if trigger.event().button != PointerButton::Secondary {
+        return;
+    }
+    let Ok((piece, mut transform, mut rotation, move_together, move_start)) =
+        pieces.get_mut(trigger.entity())
+    else {
+        return;
+    };
+    // groups only form from upright pieces
+    if !move_together.is_empty() {
+        return;
+    }
+    let offset = rotate_piece(piece, &mut transform, &mut rotation, 1);
+    if let Some(mut move_start) = move_start {
+        move_start.image_position.translation += offset;
+    }
+}
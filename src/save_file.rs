@@ -0,0 +1,446 @@
+//! Exports the game being played into a single portable file, to resume it later or on another
+//! machine. "Export" on the play screen writes the file into the `saves` folder next to the
+//! settings, "Import" in the main menu resumes the newest file there, and a save dropped onto the
+//! window is resumed as well.
+//!
+//! A save holds a header line, the length of its RON part, the RON part and the image as PNG. The
+//! RON part keeps the [`PuzzleManifest`] of the generator, which cuts the same pieces again, a
+//! hash of the image and the layout of the table. Fields missing in saves of other versions keep
+//! their defaults.
+
+use crate::filters::ImageFilters;
+use crate::gameplay::{GameTimer, JigsawPuzzleGenerator, MoveTogether, ScatterTween};
+use crate::i18n::Localized;
+use crate::piece_shape::PieceShape;
+use crate::pinning::Pinned;
+use crate::rotation::PieceRotation;
+use crate::theme::ThemeColor;
+use crate::{
+    AppState, CustomGrid, GameState, OriginImage, Piece, PuzzleSeed, SelectGameMode, SelectPiece,
+};
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use core::time::Duration;
+use jigsaw_puzzle_generator::image::{DynamicImage, ImageFormat};
+use jigsaw_puzzle_generator::manifest::PuzzleManifest;
+use jigsaw_puzzle_generator::{DEFAULT_JITTER, DEFAULT_TAB_SIZE};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        Update,
+        import_dropped_save.run_if(in_state(AppState::MainMenu)),
+    )
+    .add_systems(OnEnter(AppState::MainMenu), forget_import)
+    .add_systems(
+        Update,
+        resume_import
+            .after(crate::gameplay::shuffle_pieces)
+            .run_if(resource_exists::<ImportedGame>)
+            .run_if(in_state(GameState::Play)),
+    );
+}
+
+/// The first line of every save
+const SAVE_HEADER: &str = "jigsaw-puzzle-save";
+const SAVE_EXTENSION: &str = "jigsaw";
+
+/// The RON part of a save
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+struct SavedGame {
+    /// The [`PuzzleManifest`] as text
+    manifest: String,
+    /// The FNV-1a hash of the RGBA pixels of the image, in hex
+    image_hash: String,
+    /// The time on the clock, in seconds
+    elapsed: f32,
+    pieces: Vec<SavedPiece>,
+    /// The groups by the indices of their pieces
+    groups: Vec<Vec<usize>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+struct SavedPiece {
+    index: usize,
+    x: f32,
+    y: f32,
+    /// Quarter turns clockwise
+    rotation: u8,
+    pinned: bool,
+}
+
+/// A save being resumed, its layout is restored once the pieces are shuffled
+#[derive(Resource)]
+struct ImportedGame {
+    saved: SavedGame,
+    /// The seed picked in the menu before the import, it is picked again once the game is resumed
+    menu_seed: PuzzleSeed,
+}
+
+/// Hashes the pixels, so a damaged image is noticed
+fn image_hash(image: &DynamicImage) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let size = [image.width().to_le_bytes(), image.height().to_le_bytes()].concat();
+    for byte in size.iter().chain(image.to_rgba8().as_raw().iter()) {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{:016x}", hash)
+}
+
+fn encode_save(saved: &SavedGame, image: &DynamicImage) -> Result<Vec<u8>, String> {
+    let ron = ron::ser::to_string_pretty(saved, ron::ser::PrettyConfig::default())
+        .map_err(|e| e.to_string())?;
+    let mut png = std::io::Cursor::new(vec![]);
+    DynamicImage::ImageRgba8(image.to_rgba8())
+        .write_to(&mut png, ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+    let mut bytes = format!("{}\n{}\n{}", SAVE_HEADER, ron.len(), ron).into_bytes();
+    bytes.extend(png.into_inner());
+    Ok(bytes)
+}
+
+fn decode_save(bytes: &[u8]) -> Result<(SavedGame, DynamicImage), String> {
+    let mut lines = bytes.splitn(3, |byte| *byte == b'\n');
+    if lines.next() != Some(SAVE_HEADER.as_bytes()) {
+        return Err("not a puzzle save".to_string());
+    }
+    let length: usize = lines
+        .next()
+        .and_then(|length| core::str::from_utf8(length).ok())
+        .and_then(|length| length.parse().ok())
+        .ok_or("the save is damaged")?;
+    let rest = lines.next().unwrap_or_default();
+    if rest.len() < length {
+        return Err("the save is damaged".to_string());
+    }
+    let (ron, png) = rest.split_at(length);
+    let saved: SavedGame = core::str::from_utf8(ron)
+        .map_err(|e| e.to_string())
+        .and_then(|ron| ron::from_str(ron).map_err(|e| e.to_string()))?;
+    let image = jigsaw_puzzle_generator::image::load_from_memory_with_format(png, ImageFormat::Png)
+        .map_err(|e| e.to_string())?;
+    if !saved.image_hash.is_empty() && saved.image_hash != image_hash(&image) {
+        return Err("the image of the save is damaged".to_string());
+    }
+    Ok((saved, image))
+}
+
+fn saves_folder() -> Option<PathBuf> {
+    crate::tutorial::config_path("saves")
+}
+
+/// Adds "Export", which saves the game into the saves folder
+pub(crate) fn spawn_export_button(p: &mut ChildBuilder) {
+    p.spawn((
+        Text::new("Export"),
+        Localized("Export"),
+        Node {
+            align_self: AlignSelf::Center,
+            margin: UiRect::axes(Val::Px(5.), Val::Px(5.)),
+            ..default()
+        },
+    ))
+    .observe(export_game);
+}
+
+fn export_game(
+    _trigger: Trigger<Pointer<Click>>,
+    generator: Res<JigsawPuzzleGenerator>,
+    game_mode: Res<SelectGameMode>,
+    timer: Res<GameTimer>,
+    pieces: Query<(
+        &Piece,
+        &Transform,
+        &MoveTogether,
+        Option<&PieceRotation>,
+        Has<Pinned>,
+    )>,
+) {
+    let mut groups: Vec<HashSet<Entity>> = vec![];
+    for (_, _, together, ..) in pieces.iter() {
+        if !together.is_empty() && !groups.contains(&together.0) {
+            groups.push(together.0.clone());
+        }
+    }
+    let image = generator.origin_image();
+    let saved = SavedGame {
+        manifest: generator.manifest(game_mode.0).to_text(),
+        image_hash: image_hash(image),
+        elapsed: timer.elapsed_secs(),
+        pieces: pieces
+            .iter()
+            .map(|(piece, transform, _, rotation, pinned)| SavedPiece {
+                index: piece.index,
+                x: transform.translation.x,
+                y: transform.translation.y,
+                rotation: rotation.map_or(0, |rotation| rotation.0),
+                pinned,
+            })
+            .collect(),
+        groups: groups
+            .iter()
+            .map(|group| {
+                pieces
+                    .iter_many(group)
+                    .map(|(piece, ..)| piece.index)
+                    .collect()
+            })
+            .collect(),
+    };
+
+    let Some(folder) = saves_folder() else {
+        warn!("There is no folder to export the puzzle to");
+        return;
+    };
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+    let path = folder.join(format!("puzzle-{}.{}", seconds, SAVE_EXTENSION));
+    let written = encode_save(&saved, image).and_then(|bytes| {
+        std::fs::create_dir_all(&folder)
+            .and_then(|_| std::fs::write(&path, bytes))
+            .map_err(|e| e.to_string())
+    });
+    match written {
+        Ok(()) => info!("Exported the puzzle to {}", path.display()),
+        Err(e) => error!("Could not export the puzzle: {}", e),
+    }
+}
+
+/// Adds "Import", which resumes the newest save of the saves folder
+pub(crate) fn spawn_import_button(p: &mut ChildBuilder, font: Handle<Font>) {
+    p.spawn((
+        Text::new("Import"),
+        Localized("Import"),
+        TextFont {
+            font,
+            font_size: 24.0,
+            ..default()
+        },
+        TextColor(Color::BLACK),
+        ThemeColor::Text,
+        Node {
+            margin: UiRect::new(Val::Px(20.0), Val::Px(0.0), Val::Px(5.0), Val::Px(0.0)),
+            ..default()
+        },
+    ))
+    .observe(
+        |_trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+            let newest = saves_folder()
+                .and_then(|folder| std::fs::read_dir(folder).ok())
+                .into_iter()
+                .flatten()
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == SAVE_EXTENSION))
+                .filter_map(|path| {
+                    let modified = path.metadata().and_then(|meta| meta.modified()).ok()?;
+                    Some((modified, path))
+                })
+                .max();
+            match newest {
+                Some((_, path)) => {
+                    commands.queue(move |world: &mut World| import_save(world, &path));
+                }
+                None => warn!("There is no exported puzzle to import"),
+            }
+        },
+    );
+}
+
+fn import_dropped_save(mut commands: Commands, mut events: EventReader<FileDragAndDrop>) {
+    for event in events.read() {
+        if let FileDragAndDrop::DroppedFile { path_buf, .. } = event {
+            if path_buf
+                .extension()
+                .is_some_and(|ext| ext == SAVE_EXTENSION)
+            {
+                let path = path_buf.clone();
+                commands.queue(move |world: &mut World| import_save(world, &path));
+            }
+        }
+    }
+}
+
+/// Picks the settings of the save and starts the game, its layout is restored once it is cut
+fn import_save(world: &mut World, path: &Path) {
+    let decoded = std::fs::read(path)
+        .map_err(|e| e.to_string())
+        .and_then(|bytes| decode_save(&bytes));
+    let (saved, image) = match decoded {
+        Ok(decoded) => decoded,
+        Err(e) => {
+            error!("Could not import {}: {}", path.display(), e);
+            return;
+        }
+    };
+    let manifest = match PuzzleManifest::parse(&saved.manifest) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            error!("Could not import {}: {}", path.display(), e);
+            return;
+        }
+    };
+
+    let image = world
+        .resource_mut::<Assets<Image>>()
+        .add(Image::from_dynamic(
+            image,
+            true,
+            RenderAssetUsages::default(),
+        ));
+    world.insert_resource(OriginImage(image));
+    world.resource_scope(|world, mut custom_grid: Mut<CustomGrid>| {
+        let mut select_piece = world.resource_mut::<SelectPiece>();
+        custom_grid.set(&mut select_piece, manifest.columns, manifest.rows);
+    });
+    world.resource_mut::<SelectGameMode>().0 = manifest.game_mode;
+    *world.resource_mut::<PieceShape>() = PieceShape {
+        tab_size: manifest.tab_size.unwrap_or(DEFAULT_TAB_SIZE),
+        jitter: manifest.jitter.unwrap_or(DEFAULT_JITTER),
+    };
+    world.resource_mut::<ImageFilters>().0 = manifest.filters.clone();
+    let menu_seed = *world.resource::<PuzzleSeed>();
+    world.resource_mut::<PuzzleSeed>().0 = manifest.seed;
+    world.insert_resource(ImportedGame { saved, menu_seed });
+    world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Gameplay);
+}
+
+/// A game left before it was resumed isn't resumed with the next one
+fn forget_import(mut commands: Commands, imported: Option<Res<ImportedGame>>) {
+    if let Some(imported) = imported {
+        commands.insert_resource(imported.menu_seed);
+        commands.remove_resource::<ImportedGame>();
+    }
+}
+
+fn resume_import(
+    mut commands: Commands,
+    imported: Res<ImportedGame>,
+    mut timer: ResMut<GameTimer>,
+    mut pieces: Query<(
+        Entity,
+        &Piece,
+        &mut Transform,
+        &mut MoveTogether,
+        Option<&mut PieceRotation>,
+    )>,
+) {
+    let saved = &imported.saved;
+    timer.set_elapsed(Duration::from_secs_f32(saved.elapsed.max(0.0)));
+    for (entity, piece, mut transform, _, rotation) in pieces.iter_mut() {
+        let Some(saved) = saved.pieces.iter().find(|saved| saved.index == piece.index) else {
+            continue;
+        };
+        // placed right away instead of flying to its shuffled place
+        commands.entity(entity).remove::<ScatterTween>();
+        transform.translation.x = saved.x;
+        transform.translation.y = saved.y;
+        let quarter_turns = saved.rotation % 4;
+        transform.rotation =
+            Quat::from_rotation_z(-core::f32::consts::FRAC_PI_2 * quarter_turns as f32);
+        match rotation {
+            Some(mut rotation) => rotation.0 = quarter_turns,
+            None if quarter_turns != 0 => {
+                commands.entity(entity).insert(PieceRotation(quarter_turns));
+            }
+            None => {}
+        }
+        if saved.pinned {
+            commands.entity(entity).insert(Pinned);
+        }
+    }
+    for indices in saved.groups.iter() {
+        let group: HashSet<Entity> = pieces
+            .iter()
+            .filter(|(_, piece, ..)| indices.contains(&piece.index))
+            .map(|(entity, ..)| entity)
+            .collect();
+        let mut iter = pieces.iter_many_mut(&group);
+        while let Some((_, _, _, mut together, _)) = iter.fetch_next() {
+            together.0 = group.clone();
+        }
+    }
+    commands.insert_resource(imported.menu_seed);
+    commands.remove_resource::<ImportedGame>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jigsaw_puzzle_generator::image::{Rgba, RgbaImage};
+
+    fn test_image() -> DynamicImage {
+        RgbaImage::from_fn(8, 6, |x, y| Rgba([x as u8 * 30, y as u8 * 40, 200, 255])).into()
+    }
+
+    fn test_save(image: &DynamicImage) -> SavedGame {
+        SavedGame {
+            manifest: "(pieces_in_column: 3, pieces_in_row: 2)".to_string(),
+            image_hash: image_hash(image),
+            elapsed: 12.5,
+            pieces: vec![SavedPiece {
+                index: 4,
+                x: 10.0,
+                y: -20.5,
+                rotation: 3,
+                pinned: true,
+            }],
+            groups: vec![vec![0, 1, 4]],
+        }
+    }
+
+    #[test]
+    fn test_save_roundtrip() {
+        let image = test_image();
+        let bytes = encode_save(&test_save(&image), &image).unwrap();
+        assert!(bytes.starts_with(SAVE_HEADER.as_bytes()));
+
+        let (saved, loaded) = decode_save(&bytes).unwrap();
+        assert_eq!(saved.manifest, "(pieces_in_column: 3, pieces_in_row: 2)");
+        assert_eq!(saved.image_hash, image_hash(&image));
+        assert_eq!(saved.elapsed, 12.5);
+        let piece = &saved.pieces[0];
+        assert_eq!((piece.index, piece.x, piece.y), (4, 10.0, -20.5));
+        assert_eq!((piece.rotation, piece.pinned), (3, true));
+        assert_eq!(saved.groups, vec![vec![0, 1, 4]]);
+        assert_eq!(loaded.to_rgba8(), image.to_rgba8());
+    }
+
+    #[test]
+    fn test_reject_corrupted_save() {
+        let image = test_image();
+        let mut saved = test_save(&image);
+        let bytes = encode_save(&saved, &image).unwrap();
+
+        assert_eq!(
+            decode_save(b"a text file\n3\nabc").unwrap_err(),
+            "not a puzzle save"
+        );
+        // cut off in the middle of the RON part
+        let cut = SAVE_HEADER.len() + 20;
+        assert_eq!(
+            decode_save(&bytes[..cut]).unwrap_err(),
+            "the save is damaged"
+        );
+        // a byte of the image flipped
+        let mut damaged = bytes.clone();
+        let at = damaged.len() - 20;
+        damaged[at] ^= 0xff;
+        assert!(decode_save(&damaged).is_err());
+        // another image than the hash was taken of
+        saved.image_hash = image_hash(&DynamicImage::new_rgba8(8, 6));
+        let bytes = encode_save(&saved, &image).unwrap();
+        assert_eq!(
+            decode_save(&bytes).unwrap_err(),
+            "the image of the save is damaged"
+        );
+    }
+}
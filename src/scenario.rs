@@ -0,0 +1,160 @@
+//! Scripted start layouts read from RON, e.g. a puzzle with its border already solved. They are
+//! laid out over the shuffled pieces whenever a puzzle starts, for tutorials, demos and
+//! reproducible bug reports.
+//!
+//! ```ron
+//! (
+//!     groups: [Border, Center(0.25)],
+//!     pinned: [Pieces([0])],
+//!     placements: [(piece: 7, x: 120.0, y: -40.0)],
+//! )
+//! ```
+
+use crate::gameplay::{
+    init_position, shuffle_pieces, JigsawPuzzleGenerator, MoveTogether, ScatterTween,
+};
+use crate::pinning::Pinned;
+use crate::rotation::PieceRotation;
+use crate::{GameState, Piece};
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use jigsaw_puzzle_generator::image::GenericImageView;
+use serde::Deserialize;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(
+        OnTransition {
+            exited: GameState::Generating,
+            entered: GameState::Play,
+        },
+        queue_scenario.run_if(resource_exists::<Scenario>),
+    )
+    .add_systems(
+        Update,
+        apply_scenario
+            .after(shuffle_pieces)
+            .run_if(resource_exists::<ScenarioPending>)
+            .run_if(in_state(GameState::Play)),
+    );
+}
+
+/// The layout every puzzle starts with while it is inserted
+#[derive(Resource, Deserialize, Debug, Clone, Default)]
+pub struct Scenario {
+    /// Each set is merged into one group at its place in the solved puzzle
+    #[serde(default)]
+    pub groups: Vec<PieceSet>,
+    /// Pieces pinned at their place in the solved puzzle
+    #[serde(default)]
+    pub pinned: Vec<PieceSet>,
+    /// Single pieces moved to a position on the table, the center of the puzzle is at the origin
+    #[serde(default)]
+    pub placements: Vec<Placement>,
+}
+
+impl Scenario {
+    pub fn from_ron(ron: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(ron)
+    }
+}
+
+/// Pieces chosen by their place in the puzzle
+#[derive(Deserialize, Debug, Clone)]
+pub enum PieceSet {
+    /// All pieces on the border
+    Border,
+    /// The pieces within the centered part of the puzzle covering this fraction of its area
+    Center(f32),
+    /// Pieces by their index, counted row by row from the top left
+    Pieces(Vec<usize>),
+}
+
+impl PieceSet {
+    fn contains(&self, piece: &Piece, image_size: Vec2) -> bool {
+        match self {
+            PieceSet::Border => piece.is_boarder,
+            PieceSet::Center(fraction) => {
+                let center = Vec2::new(
+                    piece.start_point.0 + piece.width / 2.0,
+                    piece.start_point.1 + piece.height / 2.0,
+                );
+                let half_size = image_size * fraction.clamp(0.0, 1.0).sqrt() / 2.0;
+                Rect::from_center_half_size(image_size / 2.0, half_size).contains(center)
+            }
+            PieceSet::Pieces(indices) => indices.contains(&piece.index),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub struct Placement {
+    pub piece: usize,
+    pub x: f32,
+    pub y: f32,
+}
+
+/// The scenario is laid out once the pieces are shuffled
+#[derive(Resource)]
+struct ScenarioPending;
+
+fn queue_scenario(mut commands: Commands) {
+    commands.insert_resource(ScenarioPending);
+}
+
+fn apply_scenario(
+    mut commands: Commands,
+    scenario: Res<Scenario>,
+    generator: Res<JigsawPuzzleGenerator>,
+    mut pieces: Query<(
+        Entity,
+        &Piece,
+        &mut Transform,
+        &mut MoveTogether,
+        Option<&mut PieceRotation>,
+    )>,
+) {
+    commands.remove_resource::<ScenarioPending>();
+    let image_size = generator.origin_image().dimensions();
+    let size = Vec2::new(image_size.0 as f32, image_size.1 as f32);
+
+    let mut solve = |set: &PieceSet, pin: bool| {
+        let group: HashSet<Entity> = pieces
+            .iter()
+            .filter(|(_, piece, ..)| set.contains(piece, size))
+            .map(|(entity, ..)| entity)
+            .collect();
+        let mut iter = pieces.iter_many_mut(&group);
+        while let Some((entity, piece, mut transform, mut together, rotation)) = iter.fetch_next() {
+            transform.translation =
+                init_position(piece, image_size).extend(transform.translation.z);
+            transform.rotation = Quat::IDENTITY;
+            if let Some(mut rotation) = rotation {
+                *rotation = PieceRotation::default();
+            }
+            // placed right away instead of flying to its shuffled place
+            commands.entity(entity).remove::<ScatterTween>();
+            if pin {
+                commands.entity(entity).insert(Pinned);
+            } else {
+                together.0 = group.clone();
+            }
+        }
+    };
+    for set in scenario.groups.iter() {
+        solve(set, false);
+    }
+    for set in scenario.pinned.iter() {
+        solve(set, true);
+    }
+
+    for placement in scenario.placements.iter() {
+        if let Some((entity, _, mut transform, ..)) = pieces
+            .iter_mut()
+            .find(|(_, piece, ..)| piece.index == placement.piece)
+        {
+            commands.entity(entity).remove::<ScatterTween>();
+            transform.translation.x = placement.x;
+            transform.translation.y = placement.y;
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use crate::board::Locked;
+use crate::gameplay::{Immovable, MoveStart, MoveTogether, ScatterTween, Selected};
+use crate::pinning::Pinned;
+use crate::tray::InTray;
+use crate::{GameState, Piece};
+use bevy::picking::focus::HoverMap;
+use bevy::picking::pointer::PointerId;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<MultiSelection>()
+        .init_resource::<SelectionBox>()
+        .add_systems(OnEnter(GameState::Generating), clear_selection)
+        .add_systems(
+            Update,
+            (start_selection_box, update_selection_box, end_selection_box)
+                .chain()
+                .run_if(in_state(GameState::Play)),
+        )
+        .add_observer(on_move_start)
+        .add_observer(on_move_stop);
+}
+
+/// Pieces selected with the selection box, they are moved together with the dragged piece
+#[derive(Resource, Debug, Default, Deref, DerefMut)]
+pub struct MultiSelection(pub HashSet<Entity>);
+
+/// The corners of the selection box being drawn, in world coordinates
+#[derive(Resource, Debug, Default)]
+struct SelectionBox(Option<(Vec2, Vec2)>);
+
+fn clear_selection(mut selection: ResMut<MultiSelection>, mut selection_box: ResMut<SelectionBox>) {
+    selection.clear();
+    selection_box.0 = None;
+}
+
+fn cursor_world_position(
+    window: &Window,
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+) -> Option<Vec2> {
+    let cursor = window.cursor_position()?;
+    camera.viewport_to_world_2d(camera_transform, cursor).ok()
+}
+
+/// A press on empty table space starts a new selection box
+fn start_selection_box(
+    mut commands: Commands,
+    mouse: Res<ButtonInput<MouseButton>>,
+    hover_map: Res<HoverMap>,
+    window: Single<&Window>,
+    camera: Single<(&Camera, &GlobalTransform), With<IsDefaultUiCamera>>,
+    mut selection: ResMut<MultiSelection>,
+    mut selection_box: ResMut<SelectionBox>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    if hover_map
+        .get(&PointerId::Mouse)
+        .is_some_and(|hits| !hits.is_empty())
+    {
+        return;
+    }
+    let (camera, camera_transform) = *camera;
+    let Some(point) = cursor_world_position(&window, camera, camera_transform) else {
+        return;
+    };
+
+    for entity in selection.drain() {
+        commands.entity(entity).remove::<Selected>();
+    }
+    selection_box.0 = Some((point, point));
+}
+
+fn update_selection_box(
+    mut gizmos: Gizmos,
+    window: Single<&Window>,
+    camera: Single<(&Camera, &GlobalTransform), With<IsDefaultUiCamera>>,
+    mut selection_box: ResMut<SelectionBox>,
+) {
+    let Some((start, end)) = selection_box.0.as_mut() else {
+        return;
+    };
+    let (camera, camera_transform) = *camera;
+    if let Some(point) = cursor_world_position(&window, camera, camera_transform) {
+        *end = point;
+    }
+    let rect = Rect::from_corners(*start, *end);
+    gizmos.rect_2d(
+        Isometry2d::from_translation(rect.center()),
+        rect.size(),
+        Color::srgb(1.0, 1.0, 0.0),
+    );
+}
+
+/// Selects all pieces whose image intersects the selection box, together with their groups
+fn end_selection_box(
+    mut commands: Commands,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut selection_box: ResMut<SelectionBox>,
+    mut selection: ResMut<MultiSelection>,
+    pieces: Query<
+        (Entity, &Piece, &Transform, &MoveTogether),
+        (
+            Without<Immovable>,
+            Without<Locked>,
+            Without<InTray>,
+            Without<Pinned>,
+            Without<ScatterTween>,
+        ),
+    >,
+) {
+    if !mouse.just_released(MouseButton::Left) {
+        return;
+    }
+    let Some((start, end)) = selection_box.0.take() else {
+        return;
+    };
+
+    let rect = Rect::from_corners(start, end);
+    for (entity, piece, transform, move_together) in pieces.iter() {
+        let image = piece.image_rect(transform.translation.xy());
+        if !image.intersect(rect).is_empty() {
+            selection.insert(entity);
+            selection.extend(move_together.iter().copied());
+        }
+    }
+
+    for entity in selection.iter() {
+        commands.entity(*entity).insert(Selected);
+    }
+}
+
+/// Dragging a piece outside of the selection drops the selection
+fn on_move_start(
+    trigger: Trigger<OnInsert, MoveStart>,
+    mut commands: Commands,
+    mut selection: ResMut<MultiSelection>,
+    pieces: Query<&MoveTogether>,
+) {
+    if selection.contains(&trigger.entity()) {
+        return;
+    }
+    let Ok(move_together) = pieces.get(trigger.entity()) else {
+        return;
+    };
+    for entity in selection.drain() {
+        if !move_together.contains(&entity) {
+            commands.entity(entity).remove::<Selected>();
+        }
+    }
+}
+
+/// The selection ends once the dragged piece has been dropped
+fn on_move_stop(
+    trigger: Trigger<OnRemove, MoveStart>,
+    mut commands: Commands,
+    mut selection: ResMut<MultiSelection>,
+    pieces: Query<&MoveTogether>,
+) {
+    let Ok(move_together) = pieces.get(trigger.entity()) else {
+        return;
+    };
+    for entity in selection.drain() {
+        if entity != trigger.entity() && !move_together.contains(&entity) {
+            commands.entity(entity).remove::<Selected>();
+        }
+    }
+}
@@ -0,0 +1,110 @@
+//! A soft shadow falls below the held pieces while they are lifted off the table, so it's clear
+//! which pieces are moving and which are resting. It is dropped again with the pieces.
+
+use crate::atlas::{PieceAtlas, PieceImage, PieceMaterial};
+use crate::gameplay::{ColorImage, MoveStart, MoveTogether};
+use crate::Piece;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_observer(lift_pieces).add_observer(drop_pieces);
+}
+
+/// How far the shadow falls from the piece, in pixels on the screen
+const SHADOW_OFFSET: Vec2 = Vec2::new(8.0, -8.0);
+/// How far the shadow is blurred, in pixels on the screen
+const SHADOW_BLUR: f32 = 6.0;
+const SHADOW_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.45);
+
+#[derive(Component)]
+struct PieceShadow;
+
+/// The held piece with its group
+fn held_group(entity: Entity, pieces: &Query<&MoveTogether>) -> HashSet<Entity> {
+    let mut group: HashSet<Entity> = pieces
+        .get(entity)
+        .map(|together| together.0.clone())
+        .unwrap_or_default();
+    group.insert(entity);
+    group
+}
+
+/// Casts the shadows of the held group below all of its pieces, the group is lifted as a whole
+#[allow(clippy::too_many_arguments)]
+fn lift_pieces(
+    trigger: Trigger<OnInsert, MoveStart>,
+    mut commands: Commands,
+    atlas: Option<Res<PieceAtlas>>,
+    camera: Single<&OrthographicProjection, (With<Camera2d>, With<IsDefaultUiCamera>)>,
+    groups: Query<&MoveTogether>,
+    pieces: Query<(&Piece, &Transform, &Children)>,
+    images: Query<&Mesh2d, With<ColorImage>>,
+    shadows: Query<(), With<PieceShadow>>,
+    mut materials: ResMut<Assets<PieceMaterial>>,
+) {
+    let Some(atlas) = atlas else {
+        return;
+    };
+    let group = held_group(trigger.entity(), &groups);
+    let bottom = group
+        .iter()
+        .filter_map(|entity| pieces.get(*entity).ok())
+        .map(|(_, transform, _)| transform.translation.z)
+        .fold(f32::INFINITY, f32::min);
+    let blur = SHADOW_BLUR * camera.scale;
+
+    for entity in group {
+        let Ok((piece, transform, children)) = pieces.get(entity) else {
+            continue;
+        };
+        // picked up again before it was dropped
+        if children.iter().any(|child| shadows.contains(*child)) {
+            continue;
+        }
+        let Some(mesh) = images.iter_many(children).next() else {
+            continue;
+        };
+        let mut material = atlas.material(&piece.0, PieceImage::Shadow);
+        material.set_color(SHADOW_COLOR);
+        material.set_outline(blur);
+
+        let (offset_x, offset_y) = piece.calc_offset();
+        let size = Vec2::new(piece.crop_width as f32, piece.crop_height as f32);
+        // the shadow falls the same way on the screen however the piece is rotated
+        let fall = transform.rotation.inverse() * (SHADOW_OFFSET * camera.scale).extend(0.0);
+        let shadow = commands
+            .spawn((
+                PieceShadow,
+                Mesh2d(mesh.0.clone()),
+                MeshMaterial2d(materials.add(material)),
+                Transform {
+                    // just below the silhouette, which outlines the group
+                    translation: Vec3::new(
+                        -offset_x - blur + fall.x,
+                        offset_y + blur + fall.y,
+                        bottom - transform.translation.z - 1.01,
+                    ),
+                    scale: ((size + 2.0 * blur) / size).extend(1.0),
+                    ..default()
+                },
+                PickingBehavior::IGNORE,
+            ))
+            .id();
+        commands.entity(entity).add_child(shadow);
+    }
+}
+
+fn drop_pieces(
+    trigger: Trigger<OnRemove, MoveStart>,
+    mut commands: Commands,
+    groups: Query<&MoveTogether>,
+    shadows: Query<(Entity, &Parent), With<PieceShadow>>,
+) {
+    let group = held_group(trigger.entity(), &groups);
+    for (shadow, parent) in shadows.iter() {
+        if group.contains(&parent.get()) {
+            commands.entity(shadow).despawn_recursive();
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! The staging grid keeps the table tidy without arranging all pieces: with "Grid" on, a loose
+//! piece dropped beside the board lines up on an invisible grid of cells as large as the largest
+//! piece, in the nearest cell no other loose piece takes. The setting is remembered for the next
+//! start.
+
+use crate::i18n::Language;
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(StagingGrid(load_staging_grid()))
+        .add_systems(
+            Update,
+            (
+                update_staging_text,
+                save_staging_grid.run_if(resource_changed::<StagingGrid>),
+            ),
+        );
+}
+
+/// The space between the pieces on the grid
+pub const STAGING_GAP: f32 = 10.0;
+/// How many rings of cells around the drop point are searched for a free cell
+const SEARCH_RINGS: i32 = 4;
+
+/// Whether loose pieces dropped beside the board line up on the staging grid
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Deref, DerefMut)]
+pub struct StagingGrid(pub bool);
+
+impl StagingGrid {
+    pub fn toggle(&mut self) {
+        self.0 = !self.0;
+    }
+}
+
+/// The center of the cell a loose piece covering `rect` is moved to, none on the board. Cells
+/// holding the center of one of the `occupied` pieces are taken
+pub fn staging_cell(rect: Rect, board: Rect, cell: f32, occupied: &[Vec2]) -> Option<Vec2> {
+    if cell <= 0.0 || board.contains(rect.center()) {
+        return None;
+    }
+    let origin = (rect.center() / cell).round().as_ivec2();
+    let mut cells: Vec<Vec2> = (-SEARCH_RINGS..=SEARCH_RINGS)
+        .flat_map(|x| (-SEARCH_RINGS..=SEARCH_RINGS).map(move |y| origin + IVec2::new(x, y)))
+        .map(|index| index.as_vec2() * cell)
+        .collect();
+    cells.sort_by(|a, b| {
+        a.distance_squared(rect.center())
+            .total_cmp(&b.distance_squared(rect.center()))
+    });
+    cells.into_iter().find(|center| {
+        let area = Rect::from_center_size(*center, Vec2::splat(cell));
+        area.intersect(board).is_empty() && !occupied.iter().any(|other| area.contains(*other))
+    })
+}
+
+/// The text switching the [`StagingGrid`]
+#[derive(Component)]
+struct StagingText;
+
+/// Adds "Grid", which switches the [`StagingGrid`]
+pub(crate) fn spawn_staging_button(p: &mut ChildBuilder) {
+    p.spawn((
+        Text::default(),
+        Node {
+            align_self: AlignSelf::Center,
+            margin: UiRect::axes(Val::Px(5.), Val::Px(5.)),
+            ..default()
+        },
+        StagingText,
+    ))
+    .observe(
+        |_trigger: Trigger<Pointer<Click>>, mut staging_grid: ResMut<StagingGrid>| {
+            staging_grid.toggle();
+        },
+    );
+}
+
+fn update_staging_text(
+    staging_grid: Res<StagingGrid>,
+    language: Res<Language>,
+    mut texts: Query<&mut Text, With<StagingText>>,
+    added: Query<(), Added<StagingText>>,
+) {
+    if added.is_empty() && !staging_grid.is_changed() && !language.is_changed() {
+        return;
+    }
+    let state = if staging_grid.0 { "On" } else { "Off" };
+    for mut text in texts.iter_mut() {
+        text.0 = format!("{}: {}", language.tr("Grid"), language.tr(state));
+    }
+}
+
+fn save_staging_grid(staging_grid: Res<StagingGrid>) {
+    if staging_grid.is_added() {
+        return;
+    }
+    store_staging_grid(staging_grid.0);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_staging_grid() -> bool {
+    crate::tutorial::config_path("staging_grid").is_some_and(|path| path.exists())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn store_staging_grid(staging_grid: bool) {
+    let Some(path) = crate::tutorial::config_path("staging_grid") else {
+        return;
+    };
+    let stored = if staging_grid {
+        path.parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(&path, ""))
+    } else if path.exists() {
+        std::fs::remove_file(&path)
+    } else {
+        Ok(())
+    };
+    if let Err(err) = stored {
+        warn!("Could not remember the staging grid: {}", err);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen::prelude::wasm_bindgen(inline_js = r#"
+export function load_staging_grid() {
+    return localStorage.getItem("jigsaw-staging-grid") !== null;
+}
+
+export function store_staging_grid(staging_grid) {
+    if (staging_grid) {
+        localStorage.setItem("jigsaw-staging-grid", "1");
+    } else {
+        localStorage.removeItem("jigsaw-staging-grid");
+    }
+}
+"#)]
+extern "C" {
+    fn load_staging_grid() -> bool;
+    fn store_staging_grid(staging_grid: bool);
+}
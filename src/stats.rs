@@ -0,0 +1,236 @@
+//! The play history of every image: how often it was completed and the best time at each piece
+//! count. It is remembered for the next start and shown as a badge on the images of the main
+//! menu, so the images which were never solved stand out.
+
+use crate::events::PuzzleCompleted;
+use crate::gameplay::JigsawPuzzleGenerator;
+use crate::i18n::Language;
+use crate::main_menu::ImageThumbnail;
+use crate::relaxed::Relaxed;
+use crate::{AppState, OriginImage};
+use alloc::collections::BTreeMap;
+use bevy::prelude::*;
+use core::time::Duration;
+use serde::{Deserialize, Serialize};
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(load_stats())
+        .add_systems(
+            Update,
+            (
+                record_completion,
+                save_stats.run_if(resource_changed::<PlayStats>),
+            )
+                .chain(),
+        )
+        .add_systems(
+            Update,
+            (key_thumbnails, badge_thumbnails)
+                .chain()
+                .run_if(in_state(AppState::MainMenu)),
+        );
+}
+
+/// Bytes of an image without an asset path which are hashed to recognize it, every this many
+const FINGERPRINT_STRIDE: usize = 61;
+
+/// The play history of one image
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImageStats {
+    /// How often the image was completed
+    pub completed: u32,
+    /// The best time by the number of pieces, puzzles solved in relaxed mode don't count
+    pub best_times: BTreeMap<usize, Duration>,
+}
+
+/// The play history of every image by its [`image_key`]
+#[derive(Resource, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PlayStats(pub BTreeMap<String, ImageStats>);
+
+/// The key of a thumbnail in the image strip, once its image can be recognized
+#[derive(Component)]
+struct ImageKey(String);
+
+#[derive(Component)]
+struct StatsBadge;
+
+/// Names an image the same way on every start: by its asset path, or for uploaded and made up
+/// images by a hash of its pixels, which needs the image to be loaded
+fn image_key(
+    image: &Handle<Image>,
+    asset_server: &AssetServer,
+    images: &Assets<Image>,
+) -> Option<String> {
+    if let Some(path) = asset_server.get_path(image.id()) {
+        return Some(path.to_string());
+    }
+    let image = images.get(image)?;
+    // FNV-1a, which unlike the hashers of the standard library stays the same between versions
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    let size = image.size();
+    let sample = image.data.iter().step_by(FINGERPRINT_STRIDE);
+    for byte in size
+        .x
+        .to_le_bytes()
+        .into_iter()
+        .chain(size.y.to_le_bytes())
+        .chain(sample.copied())
+    {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    Some(format!("image-{hash:016x}"))
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let minutes = seconds / 60;
+    let hours = minutes / 60;
+    format!("{:02}:{:02}:{:02}", hours, minutes % 60, seconds % 60)
+}
+
+fn record_completion(
+    mut completed: EventReader<PuzzleCompleted>,
+    mut stats: ResMut<PlayStats>,
+    origin_image: Option<Res<OriginImage>>,
+    generator: Option<Res<JigsawPuzzleGenerator>>,
+    relaxed: Res<Relaxed>,
+    asset_server: Res<AssetServer>,
+    images: Res<Assets<Image>>,
+) {
+    let Some(event) = completed.read().last() else {
+        return;
+    };
+    let (Some(origin_image), Some(generator)) = (origin_image, generator) else {
+        return;
+    };
+    let Some(key) = image_key(&origin_image.0, &asset_server, &images) else {
+        return;
+    };
+    let image_stats = stats.0.entry(key).or_default();
+    image_stats.completed += 1;
+    if !relaxed.0 && !event.duration.is_zero() {
+        let best = image_stats
+            .best_times
+            .entry(generator.pieces_count())
+            .or_insert(event.duration);
+        *best = (*best).min(event.duration);
+    }
+}
+
+/// Recognizes the images of the strip, the ones without an asset path once they are loaded
+fn key_thumbnails(
+    mut commands: Commands,
+    thumbnails: Query<(Entity, &ImageNode), (With<ImageThumbnail>, Without<ImageKey>)>,
+    asset_server: Res<AssetServer>,
+    images: Res<Assets<Image>>,
+) {
+    for (entity, image_node) in thumbnails.iter() {
+        if let Some(key) = image_key(&image_node.image, &asset_server, &images) {
+            commands.entity(entity).insert(ImageKey(key));
+        }
+    }
+}
+
+/// Badges the images which were completed before with the count and best times
+fn badge_thumbnails(
+    mut commands: Commands,
+    stats: Res<PlayStats>,
+    language: Res<Language>,
+    thumbnails: Query<(Entity, &ImageKey)>,
+    added: Query<(), Added<ImageKey>>,
+    badges: Query<Entity, With<StatsBadge>>,
+) {
+    if added.is_empty() && !stats.is_changed() && !language.is_changed() {
+        return;
+    }
+    for entity in badges.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for (entity, ImageKey(key)) in thumbnails.iter() {
+        let Some(image_stats) = stats.0.get(key).filter(|stats| stats.completed > 0) else {
+            continue;
+        };
+        let mut text = format!("{} {}x", language.tr("Solved"), image_stats.completed);
+        for (pieces, time) in &image_stats.best_times {
+            text.push_str(&format!(
+                "\n{} {}: {}",
+                pieces,
+                language.tr("pieces"),
+                format_duration(*time)
+            ));
+        }
+        let badge = commands
+            .spawn((
+                Text::new(text),
+                TextFont {
+                    font_size: 12.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                Node {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(4.0),
+                    bottom: Val::Px(4.0),
+                    padding: UiRect::axes(Val::Px(4.0), Val::Px(2.0)),
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+                BorderRadius::all(Val::Px(4.0)),
+                PickingBehavior::IGNORE,
+                StatsBadge,
+            ))
+            .id();
+        commands.entity(entity).add_child(badge);
+    }
+}
+
+fn save_stats(stats: Res<PlayStats>) {
+    if stats.is_added() {
+        return;
+    }
+    match ron::to_string(&*stats) {
+        Ok(text) => store_stats(&text),
+        Err(err) => warn!("Could not remember the play history: {}", err),
+    }
+}
+
+fn load_stats() -> PlayStats {
+    stored_stats()
+        .and_then(|text| ron::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn stored_stats() -> Option<String> {
+    std::fs::read_to_string(crate::tutorial::config_path("stats.ron")?).ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn store_stats(text: &str) {
+    let Some(path) = crate::tutorial::config_path("stats.ron") else {
+        return;
+    };
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, text));
+    if let Err(err) = written {
+        warn!("Could not remember the play history: {}", err);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen::prelude::wasm_bindgen(inline_js = r#"
+export function stored_stats() {
+    return localStorage.getItem("jigsaw-stats");
+}
+
+export function store_stats(text) {
+    localStorage.setItem("jigsaw-stats", text);
+}
+"#)]
+extern "C" {
+    fn stored_stats() -> Option<String>;
+    fn store_stats(text: &str);
+}
@@ -0,0 +1,175 @@
+//! The style of the cut line around every piece: white, black, a darker shade of the image or
+//! none. "Lines" on the play screen switches it right away, the shader of the pieces draws the
+//! line from the mask, so nothing is cut again. The style is remembered for the next start.
+
+use crate::atlas::{PieceAtlas, PieceMaterial};
+use crate::i18n::Language;
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.insert_resource(load_stroke()).add_systems(
+        Update,
+        (
+            apply_stroke,
+            update_stroke_text,
+            save_stroke.run_if(resource_changed::<PieceStroke>),
+        ),
+    );
+}
+
+/// How the cut line around the pieces is drawn
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PieceStroke {
+    #[default]
+    White,
+    Black,
+    /// A darker shade of the image under the line
+    Image,
+    None,
+}
+
+impl PieceStroke {
+    pub fn next(&mut self) {
+        *self = match self {
+            PieceStroke::White => PieceStroke::Black,
+            PieceStroke::Black => PieceStroke::Image,
+            PieceStroke::Image => PieceStroke::None,
+            PieceStroke::None => PieceStroke::White,
+        };
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            PieceStroke::White => "White",
+            PieceStroke::Black => "Black",
+            PieceStroke::Image => "Image",
+            PieceStroke::None => "None",
+        }
+    }
+
+    /// The color of the line with how much it covers the image, and how much of the image it
+    /// takes on
+    pub(crate) fn uniform(&self) -> (LinearRgba, f32) {
+        match self {
+            PieceStroke::White => (LinearRgba::WHITE, 0.0),
+            PieceStroke::Black => (LinearRgba::BLACK, 0.0),
+            PieceStroke::Image => (LinearRgba::WHITE, 1.0),
+            PieceStroke::None => (LinearRgba::NONE, 0.0),
+        }
+    }
+}
+
+/// The text switching the [`PieceStroke`]
+#[derive(Component)]
+struct StrokeText;
+
+/// Adds "Lines", which switches the [`PieceStroke`]
+pub(crate) fn spawn_stroke_button(p: &mut ChildBuilder) {
+    p.spawn((
+        Text::default(),
+        Node {
+            align_self: AlignSelf::Center,
+            margin: UiRect::axes(Val::Px(5.), Val::Px(5.)),
+            ..default()
+        },
+        StrokeText,
+    ))
+    .observe(
+        |_trigger: Trigger<Pointer<Click>>, mut stroke: ResMut<PieceStroke>| {
+            stroke.next();
+        },
+    );
+}
+
+/// Draws the pieces of the current puzzle and the ones still made in the [`PieceStroke`]
+fn apply_stroke(
+    stroke: Res<PieceStroke>,
+    atlas: Option<ResMut<PieceAtlas>>,
+    mut materials: ResMut<Assets<PieceMaterial>>,
+) {
+    let Some(mut atlas) = atlas else {
+        return;
+    };
+    if !stroke.is_changed() && !atlas.is_added() {
+        return;
+    }
+    atlas.set_stroke(*stroke);
+    for (_, material) in materials.iter_mut() {
+        material.set_stroke(*stroke);
+    }
+}
+
+fn update_stroke_text(
+    stroke: Res<PieceStroke>,
+    language: Res<Language>,
+    mut texts: Query<&mut Text, With<StrokeText>>,
+    added: Query<(), Added<StrokeText>>,
+) {
+    if added.is_empty() && !stroke.is_changed() && !language.is_changed() {
+        return;
+    }
+    for mut text in texts.iter_mut() {
+        text.0 = format!("{}: {}", language.tr("Lines"), language.tr(stroke.label()));
+    }
+}
+
+fn save_stroke(stroke: Res<PieceStroke>) {
+    if stroke.is_added() {
+        return;
+    }
+    store_stroke(stroke.label());
+}
+
+fn parse_stroke(label: &str) -> PieceStroke {
+    [
+        PieceStroke::White,
+        PieceStroke::Black,
+        PieceStroke::Image,
+        PieceStroke::None,
+    ]
+    .into_iter()
+    .find(|stroke| stroke.label() == label.trim())
+    .unwrap_or_default()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_stroke() -> PieceStroke {
+    crate::tutorial::config_path("stroke")
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .map(|label| parse_stroke(&label))
+        .unwrap_or_default()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn store_stroke(label: &str) {
+    let Some(path) = crate::tutorial::config_path("stroke") else {
+        return;
+    };
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, label));
+    if let Err(err) = written {
+        warn!("Could not remember the style of the lines: {}", err);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load_stroke() -> PieceStroke {
+    parse_stroke(&stored_stroke())
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen::prelude::wasm_bindgen(inline_js = r#"
+export function stored_stroke() {
+    return localStorage.getItem("jigsaw-stroke") || "";
+}
+
+export function store_stroke(label) {
+    localStorage.setItem("jigsaw-stroke", label);
+}
+"#)]
+extern "C" {
+    fn stored_stroke() -> String;
+    fn store_stroke(label: &str);
+}
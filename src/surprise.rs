@@ -0,0 +1,110 @@
+//! "Surprise me" in the main menu makes up a puzzle image from a random seed, a gradient noise,
+//! geometric pattern or mandala. The image is added to the image strip like any other,
+//! "Regenerate" replaces the last made up image with a new one.
+
+use crate::main_menu::LoadedImages;
+use crate::{AppState, OriginImage};
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task};
+use jigsaw_puzzle_generator::image::{DynamicImage, RgbaImage};
+use jigsaw_puzzle_generator::pattern::{generate_pattern, PatternStyle};
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<SurpriseImage>()
+        .add_event::<SurpriseMe>()
+        .add_systems(
+            Update,
+            (
+                start_surprise,
+                finish_surprise.run_if(resource_exists::<SurpriseTask>),
+            )
+                .chain()
+                .run_if(in_state(AppState::MainMenu)),
+        );
+}
+
+const IMAGE_SIZE: (u32, u32) = (1200, 800);
+
+/// Makes up a puzzle image and selects it
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SurpriseMe {
+    /// Adds another image to the strip
+    New,
+    /// Replaces the last made up image
+    Regenerate,
+}
+
+/// The last made up image
+#[derive(Resource, Debug, Default)]
+struct SurpriseImage(Option<Handle<Image>>);
+
+#[derive(Resource)]
+struct SurpriseTask {
+    task: Task<RgbaImage>,
+    seed: u64,
+    replace: bool,
+}
+
+/// The style follows from the seed, so a seed always makes the same image
+fn style_of(seed: u64) -> PatternStyle {
+    PatternStyle::ALL[(seed % PatternStyle::ALL.len() as u64) as usize]
+}
+
+fn start_surprise(
+    mut commands: Commands,
+    mut events: EventReader<SurpriseMe>,
+    task: Option<Res<SurpriseTask>>,
+) {
+    let Some(event) = events.read().last().copied() else {
+        return;
+    };
+    // one image at a time
+    if task.is_some() {
+        return;
+    }
+    let seed = rand::random::<u64>();
+    let (width, height) = IMAGE_SIZE;
+    let task = AsyncComputeTaskPool::get()
+        .spawn(async move { generate_pattern(style_of(seed), seed, width, height) });
+    commands.insert_resource(SurpriseTask {
+        task,
+        seed,
+        replace: event == SurpriseMe::Regenerate,
+    });
+}
+
+fn finish_surprise(
+    mut commands: Commands,
+    mut task: ResMut<SurpriseTask>,
+    mut surprise: ResMut<SurpriseImage>,
+    mut images: ResMut<Assets<Image>>,
+    mut loaded_images: ResMut<LoadedImages>,
+) {
+    let Some(image) = block_on(future::poll_once(&mut task.task)) else {
+        return;
+    };
+    commands.remove_resource::<SurpriseTask>();
+    info!(
+        "Made up a {:?} image of seed {}",
+        style_of(task.seed),
+        task.seed
+    );
+
+    let handle = images.add(Image::from_dynamic(
+        DynamicImage::ImageRgba8(image),
+        true,
+        RenderAssetUsages::default(),
+    ));
+    let last = surprise
+        .0
+        .as_ref()
+        .filter(|_| task.replace)
+        .and_then(|last| loaded_images.iter().position(|image| image == last));
+    match last {
+        Some(index) => loaded_images[index] = handle.clone(),
+        None => loaded_images.push(handle.clone()),
+    }
+    surprise.0 = Some(handle.clone());
+    commands.insert_resource(OriginImage(handle));
+}
@@ -0,0 +1,118 @@
+//! The finite table around the board. Pieces and the camera are kept on it, so no piece can be
+//! dragged, shuffled or synced to where the player can't reach it anymore.
+
+use crate::camera::CameraSettings;
+use crate::gameplay::{JigsawPuzzleGenerator, MoveTogether, OnPlayScreen};
+use crate::tray::InTray;
+use crate::{GameState, Piece};
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+use jigsaw_puzzle_generator::image::GenericImageView;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Play), setup_table)
+        .add_systems(
+            PostUpdate,
+            keep_pieces_on_table
+                .before(TransformSystem::TransformPropagate)
+                .run_if(resource_exists::<Table>)
+                .run_if(in_state(GameState::Play)),
+        );
+}
+
+/// The area of the table, centered on the board
+#[derive(Resource, Debug, Clone, Copy, Deref)]
+pub struct Table(pub Rect);
+
+impl Table {
+    /// The window size at `scale` cut down to the table, for spreading pieces over the view
+    pub fn spread_size(&self, window_size: Vec2, scale: f32) -> Vec2 {
+        window_size.min(self.size() / scale)
+    }
+}
+
+/// The tinted table surface, its border is the edge of the table
+#[derive(Component)]
+pub(crate) struct TableSurface;
+
+pub(crate) fn setup_table(
+    mut commands: Commands,
+    generator: Res<JigsawPuzzleGenerator>,
+    settings: Res<CameraSettings>,
+    surface: Query<(), With<TableSurface>>,
+) {
+    let (width, height) = generator.origin_image().dimensions();
+    let half_size = Vec2::new(width as f32, height as f32) * settings.play_area;
+    commands.insert_resource(Table(Rect::from_center_half_size(Vec2::ZERO, half_size)));
+    if !surface.is_empty() {
+        return;
+    }
+    commands.spawn((
+        Sprite::from_color(Color::srgba(0.0, 0.0, 0.0, 0.04), half_size * 2.0),
+        Transform::from_xyz(0.0, 0.0, -20.0),
+        TableSurface,
+        OnPlayScreen,
+    ));
+}
+
+/// Moves groups which left the table back onto it, as a whole so they stay together
+fn keep_pieces_on_table(
+    table: Res<Table>,
+    mut pieces: ParamSet<(
+        Query<(Entity, &Piece, Ref<Transform>, &MoveTogether), Without<InTray>>,
+        Query<&mut Transform, Without<InTray>>,
+    )>,
+) {
+    let mut handled = HashSet::default();
+    let mut moves = vec![];
+    let query = pieces.p0();
+    for (entity, _, transform, together) in query.iter() {
+        if !transform.is_changed() || handled.contains(&entity) {
+            continue;
+        }
+        let mut group: Vec<Entity> = together.iter().copied().collect();
+        if !together.contains(&entity) {
+            group.push(entity);
+        }
+        handled.extend(group.iter().copied());
+
+        let bounds = query
+            .iter_many(&group)
+            .map(|(_, piece, transform, _)| piece.image_rect(transform.translation.xy()))
+            .reduce(|bounds, rect| bounds.union(rect));
+        let Some(bounds) = bounds else {
+            continue;
+        };
+        let offset = offset_onto(bounds, table.0);
+        if offset != Vec2::ZERO {
+            moves.push((group, offset));
+        }
+    }
+
+    let mut transforms = pieces.p1();
+    for (group, offset) in moves {
+        let mut iter = transforms.iter_many_mut(&group);
+        while let Some(mut transform) = iter.fetch_next() {
+            transform.translation += offset.extend(0.0);
+        }
+    }
+}
+
+/// The offset which moves `rect` onto `table`, centered if it is larger than the table
+fn offset_onto(rect: Rect, table: Rect) -> Vec2 {
+    let axis = |min: f32, max: f32, table_min: f32, table_max: f32| {
+        if max - min > table_max - table_min {
+            (table_min + table_max - min - max) / 2.0
+        } else if min < table_min {
+            table_min - min
+        } else if max > table_max {
+            table_max - max
+        } else {
+            0.0
+        }
+    };
+    Vec2::new(
+        axis(rect.min.x, rect.max.x, table.min.x, table.max.x),
+        axis(rect.min.y, rect.max.y, table.min.y, table.max.y),
+    )
+}
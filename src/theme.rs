@@ -0,0 +1,156 @@
+use crate::{HOVERED_BUTTON, NORMAL_BUTTON, PRESSED_BUTTON};
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Theme>().add_systems(
+        Update,
+        (
+            apply_theme.run_if(resource_changed::<Theme>),
+            paint_added_elements,
+        ),
+    );
+}
+
+/// The color scheme of the user interface
+#[derive(Debug, Resource, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Theme {
+    #[default]
+    Light,
+    Dark,
+}
+
+impl core::fmt::Display for Theme {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Theme::Light => "Light",
+                Theme::Dark => "Dark",
+            }
+        )
+    }
+}
+
+/// The colors of a theme
+#[derive(Debug, Clone, Copy)]
+pub struct Palette {
+    /// Behind the table and the loading screen
+    pub background: Color,
+    /// Full screen panels like the finish screen
+    pub panel: Color,
+    /// The pause screen, the table shows through it
+    pub pause_panel: Color,
+    /// The options area of the main menu, drawn over the menu image
+    pub menu_panel: Color,
+    pub text: Color,
+    pub button: Color,
+    pub button_hovered: Color,
+    pub button_pressed: Color,
+    pub button_text: Color,
+}
+
+const LIGHT: Palette = Palette {
+    background: Color::srgb(0.9, 0.9, 0.9),
+    panel: Color::srgb(0.584, 0.647, 0.651),
+    pause_panel: Color::srgba(0.584, 0.647, 0.651, 0.6),
+    menu_panel: Color::srgba(0.2, 0.7, 0.5, 0.5),
+    text: Color::BLACK,
+    button: NORMAL_BUTTON,
+    button_hovered: HOVERED_BUTTON,
+    button_pressed: PRESSED_BUTTON,
+    button_text: Color::srgb(0.9, 0.9, 0.9),
+};
+
+const DARK: Palette = Palette {
+    background: Color::srgb(0.12, 0.12, 0.14),
+    panel: Color::srgb(0.17, 0.18, 0.2),
+    pause_panel: Color::srgba(0.17, 0.18, 0.2, 0.7),
+    menu_panel: Color::srgba(0.08, 0.1, 0.12, 0.85),
+    text: Color::srgb(0.9, 0.9, 0.9),
+    button: Color::srgb(0.3, 0.3, 0.34),
+    button_hovered: Color::srgb(0.42, 0.42, 0.46),
+    button_pressed: PRESSED_BUTTON,
+    button_text: Color::srgb(0.95, 0.95, 0.95),
+};
+
+impl Theme {
+    pub fn palette(&self) -> &'static Palette {
+        match self {
+            Theme::Light => &LIGHT,
+            Theme::Dark => &DARK,
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        *self = match self {
+            Theme::Light => Theme::Dark,
+            Theme::Dark => Theme::Light,
+        };
+    }
+}
+
+/// The palette color an element is painted with
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThemeColor {
+    Panel,
+    PausePanel,
+    MenuPanel,
+    Text,
+    Button,
+    ButtonText,
+}
+
+fn paint(
+    palette: &Palette,
+    theme_color: ThemeColor,
+    background: Option<Mut<BackgroundColor>>,
+    text: Option<Mut<TextColor>>,
+) {
+    let color = match theme_color {
+        ThemeColor::Panel => palette.panel,
+        ThemeColor::PausePanel => palette.pause_panel,
+        ThemeColor::MenuPanel => palette.menu_panel,
+        ThemeColor::Text => palette.text,
+        ThemeColor::Button => palette.button,
+        ThemeColor::ButtonText => palette.button_text,
+    };
+    if let Some(mut background) = background {
+        background.0 = color;
+    }
+    if let Some(mut text) = text {
+        text.0 = color;
+    }
+}
+
+fn apply_theme(
+    theme: Res<Theme>,
+    mut clear_color: ResMut<ClearColor>,
+    mut elements: Query<(
+        &ThemeColor,
+        Option<&mut BackgroundColor>,
+        Option<&mut TextColor>,
+    )>,
+) {
+    let palette = theme.palette();
+    clear_color.0 = palette.background;
+    for (theme_color, background, text) in elements.iter_mut() {
+        paint(palette, *theme_color, background, text);
+    }
+}
+
+fn paint_added_elements(
+    theme: Res<Theme>,
+    mut elements: Query<
+        (
+            &ThemeColor,
+            Option<&mut BackgroundColor>,
+            Option<&mut TextColor>,
+        ),
+        Added<ThemeColor>,
+    >,
+) {
+    for (theme_color, background, text) in elements.iter_mut() {
+        paint(theme.palette(), *theme_color, background, text);
+    }
+}
@@ -0,0 +1,125 @@
+//! Resting the cursor on a piece for a moment shows a small tooltip next to it with the size of
+//! its group and, in board mode, whether the group lies at its solved position. Nothing is shown
+//! while pieces are dragged.
+
+use crate::board::LOCK_DISTANCE;
+use crate::gameplay::{
+    init_position, JigsawPuzzleGenerator, MoveStart, MoveTogether, OnPlayScreen,
+};
+use crate::i18n::Language;
+use crate::layering::HoveredPiece;
+use crate::{GameState, Piece, SelectPlayMode};
+use bevy::prelude::*;
+use jigsaw_puzzle_generator::image::GenericImageView;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Play), setup_tooltip)
+        .add_systems(Update, update_tooltip.run_if(in_state(GameState::Play)));
+}
+
+/// How long the cursor rests on a piece before the tooltip shows
+const HOVER_SECS: f32 = 0.5;
+/// The distance of the tooltip from the cursor
+const CURSOR_OFFSET: Vec2 = Vec2::new(16.0, 16.0);
+
+#[derive(Component)]
+struct PieceTooltip;
+
+/// The piece the cursor rests on and since when, in real seconds
+#[derive(Debug, Default)]
+struct Resting {
+    piece: Option<Entity>,
+    since: f32,
+}
+
+fn setup_tooltip(mut commands: Commands, tooltips: Query<Entity, With<PieceTooltip>>) {
+    if !tooltips.is_empty() {
+        return;
+    }
+    commands.spawn((
+        Text::default(),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            padding: UiRect::axes(Val::Px(6.0), Val::Px(2.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+        BorderRadius::all(Val::Px(4.0)),
+        GlobalZIndex(i32::MAX - 1),
+        Visibility::Hidden,
+        PickingBehavior::IGNORE,
+        PieceTooltip,
+        OnPlayScreen,
+    ));
+}
+
+/// Whether every piece of the group lies at its solved position
+fn group_placed(
+    generator: &JigsawPuzzleGenerator,
+    piece: Entity,
+    together: &MoveTogether,
+    pieces: &Query<(&Piece, &Transform, &MoveTogether)>,
+) -> bool {
+    let image_size = generator.origin_image().dimensions();
+    let mut group = together.iter().copied().collect::<Vec<_>>();
+    group.push(piece);
+    pieces.iter_many(group).all(|(piece, transform, _)| {
+        transform
+            .translation
+            .xy()
+            .distance(init_position(piece, image_size))
+            < LOCK_DISTANCE
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_tooltip(
+    time: Res<Time<Real>>,
+    hovered: Res<HoveredPiece>,
+    generator: Res<JigsawPuzzleGenerator>,
+    play_mode: Res<SelectPlayMode>,
+    language: Res<Language>,
+    window: Single<&Window>,
+    pieces: Query<(&Piece, &Transform, &MoveTogether)>,
+    dragging: Query<(), With<MoveStart>>,
+    mut tooltip: Single<(&mut Text, &mut Node, &mut Visibility), With<PieceTooltip>>,
+    mut resting: Local<Resting>,
+) {
+    let now = time.elapsed_secs();
+    if resting.piece != hovered.0 || !dragging.is_empty() {
+        resting.piece = hovered.0;
+        resting.since = now;
+    }
+    let (text, node, visibility) = &mut *tooltip;
+    let shown = resting
+        .piece
+        .filter(|_| now - resting.since >= HOVER_SECS)
+        .and_then(|piece| Some((piece, pieces.get(piece).ok()?)))
+        .zip(window.cursor_position());
+    let Some(((piece, (_, _, together)), cursor)) = shown else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+
+    let size = together.len().max(1);
+    let mut label = format!("{} {}", size, language.tr("pieces"));
+    if *play_mode == SelectPlayMode::Board {
+        let placed = if group_placed(&generator, piece, together, &pieces) {
+            "Placed"
+        } else {
+            "Not placed"
+        };
+        label = format!("{}, {}", label, language.tr(placed));
+    }
+    if text.0 != label {
+        text.0 = label;
+    }
+    node.left = Val::Px(cursor.x + CURSOR_OFFSET.x);
+    node.top = Val::Px(cursor.y + CURSOR_OFFSET.y);
+    visibility.set_if_neq(Visibility::Visible);
+}
@@ -0,0 +1,495 @@
+use crate::board::Locked;
+use crate::gameplay::{
+    JigsawPuzzleTemplate, MoveStart, MoveTogether, OnPlayScreen, ScatterTween, Shuffle,
+};
+use crate::i18n::Localized;
+use crate::{GameState, Piece};
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use core::f32::consts::{FRAC_PI_4, TAU};
+use jigsaw_puzzle_generator::JigsawPiece;
+use rand::seq::SliceRandom;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<HoveredTray>()
+        .add_event::<CollectEdgePieces>()
+        .add_systems(OnEnter(GameState::Play), setup_sorting_tray)
+        .add_systems(OnExit(GameState::Play), clear_hovered_tray)
+        .add_systems(
+            Update,
+            (
+                follow_camera,
+                update_hovered_tray,
+                scroll_tray,
+                collect_edge_pieces,
+                stage_pieces,
+                layout_tray_pieces,
+            )
+                .chain()
+                .run_if(in_state(GameState::Play)),
+        )
+        .add_observer(put_in_tray)
+        .add_observer(take_from_tray);
+}
+
+/// Height of the tray header holding its name
+const HEADER_HEIGHT: f32 = 22.0;
+/// Number of piece rows of a tray docked at the bottom, or columns of a tray docked at a side
+const TRAY_LINES: usize = 2;
+/// Screen size of a tray across its docking side
+const TRAY_THICKNESS: f32 = 180.0;
+
+/// The window side a tray is docked to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrayDock {
+    Bottom,
+    Right,
+    Left,
+}
+
+/// A panel pinned to the screen which holds pieces for sorting. Pieces in a tray are laid out
+/// in a scrollable grid and don't snap to other pieces.
+#[derive(Component, Debug)]
+pub struct Tray {
+    pub dock: TrayDock,
+    /// Scroll offset along the tray in screen pixels
+    pub scroll: f32,
+}
+
+/// A piece sorted into a tray
+#[derive(Component, Debug, Clone, Copy)]
+pub struct InTray {
+    pub tray: Entity,
+    /// Pieces are laid out in increasing order
+    pub order: u32,
+}
+
+/// The tray below the cursor
+#[derive(Resource, Debug, Default, PartialEq, Deref, DerefMut)]
+pub struct HoveredTray(pub Option<Entity>);
+
+/// The tray holding the collected edge pieces
+#[derive(Component)]
+struct EdgeTray;
+
+/// The staging shelf the pieces are shuffled into, apart from the table where they are assembled
+#[derive(Component)]
+struct Shelf;
+
+/// Put a dropped piece into the hovered tray
+#[derive(Event)]
+pub struct PutInTray;
+
+/// Move all loose edge pieces into the edge tray
+#[derive(Event)]
+pub struct CollectEdgePieces;
+
+/// Spawns a tray docked to the given side of the window
+pub fn spawn_tray(commands: &mut Commands, name: &'static str, dock: TrayDock) -> Entity {
+    commands
+        .spawn((
+            Sprite::from_color(Color::srgba(0.2, 0.2, 0.2, 0.35), Vec2::ONE),
+            Transform::from_xyz(0.0, 0.0, 800.0),
+            Tray { dock, scroll: 0.0 },
+            Name::new(name.to_string()),
+            OnPlayScreen,
+        ))
+        .with_child((
+            Text2d::new(name),
+            Localized(name),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            Transform::from_xyz(0.0, 0.0, 1.0),
+        ))
+        .observe(toggle_dock)
+        .id()
+}
+
+fn setup_sorting_tray(mut commands: Commands, q_tray: Query<Entity, With<Tray>>) {
+    if !q_tray.is_empty() {
+        return;
+    }
+    spawn_tray(&mut commands, "Tray", TrayDock::Bottom);
+}
+
+fn clear_hovered_tray(mut hovered_tray: ResMut<HoveredTray>) {
+    hovered_tray.0 = None;
+}
+
+/// Right click docks a tray to the other side
+fn toggle_dock(trigger: Trigger<Pointer<Click>>, mut trays: Query<&mut Tray>) {
+    if trigger.event().button != PointerButton::Secondary {
+        return;
+    }
+    if let Ok(mut tray) = trays.get_mut(trigger.entity()) {
+        tray.dock = match tray.dock {
+            TrayDock::Bottom => TrayDock::Right,
+            TrayDock::Right => TrayDock::Left,
+            TrayDock::Left => TrayDock::Bottom,
+        };
+        tray.scroll = 0.0;
+    }
+}
+
+impl Tray {
+    /// The area of the tray in the viewport
+    fn viewport_rect(&self, window_size: Vec2) -> Rect {
+        match self.dock {
+            TrayDock::Bottom => Rect::new(
+                window_size.x * 0.2,
+                window_size.y - 60.0 - TRAY_THICKNESS,
+                window_size.x * 0.8,
+                window_size.y - 60.0,
+            ),
+            TrayDock::Right => Rect::new(
+                window_size.x - 10.0 - TRAY_THICKNESS,
+                window_size.y * 0.25,
+                window_size.x - 10.0,
+                window_size.y * 0.7,
+            ),
+            TrayDock::Left => Rect::new(
+                10.0,
+                window_size.y * 0.25,
+                10.0 + TRAY_THICKNESS,
+                window_size.y * 0.7,
+            ),
+        }
+    }
+
+    /// Screen size of a grid cell
+    fn cell_size(&self, size: Vec2) -> f32 {
+        match self.dock {
+            TrayDock::Bottom => (size.y - HEADER_HEIGHT) / TRAY_LINES as f32,
+            TrayDock::Right | TrayDock::Left => size.x / TRAY_LINES as f32,
+        }
+    }
+
+    /// Center of the cell at `slot` relative to the tray center, in screen pixels
+    fn cell_center(&self, slot: usize, size: Vec2) -> Vec2 {
+        let cell = self.cell_size(size);
+        let (along, across) = (slot / TRAY_LINES, slot % TRAY_LINES);
+        let along = cell * (along as f32 + 0.5) - self.scroll;
+        let across = cell * (across as f32 + 0.5);
+        match self.dock {
+            TrayDock::Bottom => {
+                Vec2::new(-size.x / 2.0 + along, size.y / 2.0 - HEADER_HEIGHT - across)
+            }
+            TrayDock::Right | TrayDock::Left => {
+                Vec2::new(-size.x / 2.0 + across, size.y / 2.0 - HEADER_HEIGHT - along)
+            }
+        }
+    }
+
+    /// The maximum scroll offset to show the last of `count` pieces
+    fn max_scroll(&self, count: usize, size: Vec2) -> f32 {
+        let cell = self.cell_size(size);
+        let content = count.div_ceil(TRAY_LINES) as f32 * cell;
+        let visible = match self.dock {
+            TrayDock::Bottom => size.x,
+            TrayDock::Right | TrayDock::Left => size.y - HEADER_HEIGHT,
+        };
+        (content - visible).max(0.0)
+    }
+}
+
+/// The center of the piece image relative to the piece origin, which is the top left corner of
+/// the piece without tabs
+fn piece_center(piece: &Piece) -> Vec2 {
+    piece.image_rect(Vec2::ZERO).center()
+}
+
+/// Keeps the trays at the same place of the screen while the camera moves or zooms
+fn follow_camera(
+    window: Single<&Window>,
+    camera: Single<(&Camera, &GlobalTransform, &OrthographicProjection), With<IsDefaultUiCamera>>,
+    mut trays: Query<(&Tray, &mut Transform, &mut Sprite, &Children)>,
+    mut labels: Query<&mut Transform, (With<Text2d>, Without<Tray>)>,
+) {
+    let (camera, camera_transform, projection) = *camera;
+    for (tray, mut transform, mut sprite, children) in trays.iter_mut() {
+        let rect = tray.viewport_rect(window.size());
+        let Ok(center) = camera.viewport_to_world_2d(camera_transform, rect.center()) else {
+            continue;
+        };
+        transform.translation = center.extend(transform.translation.z);
+        transform.scale = Vec3::splat(projection.scale);
+        sprite.custom_size = Some(rect.size());
+
+        for child in children.iter() {
+            if let Ok(mut label) = labels.get_mut(*child) {
+                label.translation.y = rect.height() / 2.0 - HEADER_HEIGHT / 2.0;
+            }
+        }
+    }
+}
+
+fn update_hovered_tray(
+    window: Single<&Window>,
+    trays: Query<(Entity, &Tray)>,
+    mut hovered_tray: ResMut<HoveredTray>,
+) {
+    let hovered = window.cursor_position().and_then(|cursor| {
+        trays
+            .iter()
+            .find(|(_, tray)| tray.viewport_rect(window.size()).contains(cursor))
+            .map(|(entity, _)| entity)
+    });
+    hovered_tray.set_if_neq(HoveredTray(hovered));
+}
+
+fn scroll_tray(
+    mut mouse_wheel_input: EventReader<MouseWheel>,
+    hovered_tray: Res<HoveredTray>,
+    window: Single<&Window>,
+    mut trays: Query<&mut Tray>,
+    pieces: Query<&InTray>,
+) {
+    let Some(mut tray) = hovered_tray.and_then(|entity| trays.get_mut(entity).ok()) else {
+        return;
+    };
+    let count = pieces
+        .iter()
+        .filter(|in_tray| Some(in_tray.tray) == **hovered_tray)
+        .count();
+    let max_scroll = tray.max_scroll(count, tray.viewport_rect(window.size()).size());
+    for event in mouse_wheel_input.read() {
+        tray.scroll = (tray.scroll - event.y * 30.0).clamp(0.0, max_scroll);
+    }
+}
+
+/// Lays out the pieces of each tray in a grid, scaled down to fit into a cell
+fn layout_tray_pieces(
+    window: Single<&Window>,
+    trays: Query<(Entity, &Tray, &Transform)>,
+    mut pieces: Query<(&Piece, &InTray, &mut Transform, &mut Visibility), Without<Tray>>,
+) {
+    for (tray_entity, tray, tray_transform) in trays.iter() {
+        let size = tray.viewport_rect(window.size()).size();
+        let cell = tray.cell_size(size);
+        let scale = tray_transform.scale.x;
+
+        let mut tray_pieces: Vec<_> = pieces
+            .iter_mut()
+            .filter(|(_, in_tray, ..)| in_tray.tray == tray_entity)
+            .collect();
+        tray_pieces.sort_by_key(|(_, in_tray, ..)| in_tray.order);
+
+        for (slot, (piece, _, mut transform, mut visibility)) in tray_pieces.into_iter().enumerate()
+        {
+            let center = tray.cell_center(slot, size);
+            let inside = center.x.abs() + cell / 2.0 <= size.x / 2.0 + 0.5
+                && center.y + cell / 2.0 <= size.y / 2.0 - HEADER_HEIGHT + 0.5
+                && center.y - cell / 2.0 >= -size.y / 2.0 - 0.5;
+            visibility.set_if_neq(if inside {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            });
+
+            let piece_scale = cell * 0.9 / piece.crop_width.max(piece.crop_height) as f32 * scale;
+            let translation = (tray_transform.translation.xy() + center * scale
+                - piece_center(piece) * piece_scale)
+                .extend(tray_transform.translation.z + 1.0);
+            if transform.translation != translation {
+                transform.translation = translation;
+            }
+            if transform.scale.x != piece_scale {
+                transform.scale = Vec3::splat(piece_scale);
+            }
+        }
+    }
+}
+
+/// The position of every edge piece going clockwise around the board, starting at the top left
+/// corner, so that the pieces of each side end up next to each other in the tray. The pieces
+/// are sorted by the angle of the middle of their cell around the middle of all edge pieces,
+/// which holds for every cut and for puzzles cut to an outline
+fn clockwise_order(pieces: &[JigsawPiece]) -> HashMap<usize, u32> {
+    let mut middles: Vec<(usize, Vec2)> = pieces
+        .iter()
+        .filter(|piece| piece.is_boarder)
+        .map(|piece| {
+            let (x, y) = piece.start_point;
+            (
+                piece.index,
+                Vec2::new(x + piece.width / 2.0, y + piece.height / 2.0),
+            )
+        })
+        .collect();
+    let (min, max) = middles
+        .iter()
+        .fold((Vec2::MAX, Vec2::MIN), |(min, max), (_, middle)| {
+            (min.min(*middle), max.max(*middle))
+        });
+    let (center, half_size) = ((min + max) / 2.0, ((max - min) / 2.0).max(Vec2::ONE));
+    // the y axis of the image points down, so the angle grows clockwise. The top left corner
+    // lies at -135° once the board is scaled to a square, starting just before it keeps the
+    // pieces of the left side below the corner at the end
+    let start = -3.0 * FRAC_PI_4 - 0.001;
+    let angle = |middle: Vec2| {
+        let offset = (middle - center) / half_size;
+        (offset.y.atan2(offset.x) - start).rem_euclid(TAU)
+    };
+    middles.sort_by(|(_, a), (_, b)| angle(*a).total_cmp(&angle(*b)));
+    middles
+        .into_iter()
+        .enumerate()
+        .map(|(order, (index, _))| (index, order as u32))
+        .collect()
+}
+
+fn collect_edge_pieces(
+    mut events: EventReader<CollectEdgePieces>,
+    mut commands: Commands,
+    edge_tray: Query<Entity, With<EdgeTray>>,
+    template: Option<Res<JigsawPuzzleTemplate>>,
+    pieces: Query<
+        (Entity, &Piece, &MoveTogether),
+        (Without<InTray>, Without<Locked>, Without<MoveStart>),
+    >,
+) {
+    let Some(template) = template else {
+        events.clear();
+        return;
+    };
+    let order = clockwise_order(&template.pieces);
+    for _ in events.read() {
+        let tray = edge_tray.get_single().unwrap_or_else(|_| {
+            let tray = spawn_tray(&mut commands, "Edges", TrayDock::Right);
+            commands.entity(tray).insert(EdgeTray);
+            tray
+        });
+        // pieces already combined with others stay on the table
+        for (entity, piece, _) in pieces
+            .iter()
+            .filter(|(_, piece, together)| piece.is_boarder() && together.is_empty())
+        {
+            commands.entity(entity).insert(InTray {
+                tray,
+                order: order.get(&piece.index).copied().unwrap_or_default(),
+            });
+        }
+    }
+}
+
+/// Moves the loose pieces into the shelf in a random order, dragging one out puts it on the table
+fn stage_pieces(
+    mut events: EventReader<Shuffle>,
+    mut commands: Commands,
+    shelf: Query<Entity, With<Shelf>>,
+    pieces: Query<(Entity, &MoveTogether), (Without<InTray>, Without<Locked>, Without<MoveStart>)>,
+    staged: Query<&InTray>,
+) {
+    for _ in events.read().filter(|event| **event == Shuffle::Shelf) {
+        let shelf = shelf.get_single().unwrap_or_else(|_| {
+            let shelf = spawn_tray(&mut commands, "Shelf", TrayDock::Left);
+            commands.entity(shelf).insert(Shelf);
+            shelf
+        });
+        let mut loose: Vec<Entity> = pieces
+            .iter()
+            .filter(|(_, together)| together.is_empty())
+            .map(|(entity, _)| entity)
+            .collect();
+        loose.shuffle(&mut rand::thread_rng());
+        // behind the pieces still on the shelf
+        let first = staged
+            .iter()
+            .filter(|in_tray| in_tray.tray == shelf)
+            .map(|in_tray| in_tray.order + 1)
+            .max()
+            .unwrap_or_default();
+        for (order, entity) in (first..).zip(loose) {
+            commands
+                .entity(entity)
+                .remove::<ScatterTween>()
+                .insert(InTray { tray: shelf, order });
+        }
+    }
+}
+
+fn put_in_tray(
+    trigger: Trigger<PutInTray>,
+    mut commands: Commands,
+    hovered_tray: Res<HoveredTray>,
+    pieces: Query<&InTray>,
+) {
+    let Some(tray) = **hovered_tray else {
+        return;
+    };
+    let last = pieces
+        .iter()
+        .filter(|in_tray| in_tray.tray == tray)
+        .map(|in_tray| in_tray.order)
+        .max()
+        .unwrap_or_default();
+    commands.entity(trigger.entity()).insert(InTray {
+        tray,
+        order: last + 1,
+    });
+}
+
+/// A piece picked up from a tray gets its original size back and is centered at the cursor
+fn take_from_tray(
+    trigger: Trigger<OnInsert, MoveStart>,
+    mut commands: Commands,
+    mut pieces: Query<(&Piece, &mut Transform, &mut Visibility, &mut MoveStart), With<InTray>>,
+) {
+    let Ok((piece, mut transform, mut visibility, mut move_start)) =
+        pieces.get_mut(trigger.entity())
+    else {
+        return;
+    };
+    transform.translation =
+        (move_start.click_position - piece_center(piece)).extend(transform.translation.z);
+    transform.scale = Vec3::ONE;
+    move_start.image_position = *transform;
+    *visibility = Visibility::Visible;
+    commands.entity(trigger.entity()).remove::<InTray>();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jigsaw_puzzle_generator::image::DynamicImage;
+    use jigsaw_puzzle_generator::{GameMode, JigsawGenerator};
+
+    #[test]
+    fn test_clockwise_order() {
+        let template = JigsawGenerator::new(DynamicImage::new_rgba8(400, 300), 4, 3)
+            .seed(1)
+            .generate(GameMode::Classic, false)
+            .unwrap();
+        let order = clockwise_order(&template.pieces);
+        let mut indices: Vec<usize> = order.keys().copied().collect();
+        indices.sort_by_key(|index| order[index]);
+        // along the top row, down the right side, back along the bottom row and up the left side
+        assert_eq!(indices, vec![0, 1, 2, 3, 7, 11, 10, 9, 8, 4]);
+
+        // the cells of other cuts aren't numbered row by row, their top row still comes first
+        for mode in [GameMode::Triangular, GameMode::Crazy] {
+            let template = JigsawGenerator::new(DynamicImage::new_rgba8(400, 300), 4, 3)
+                .seed(1)
+                .generate(mode, false)
+                .unwrap();
+            let order = clockwise_order(&template.pieces);
+            let edges = template.pieces.iter().filter(|piece| piece.is_boarder);
+            assert_eq!(order.len(), edges.count());
+
+            let mut top: Vec<&JigsawPiece> = template
+                .pieces
+                .iter()
+                .filter(|piece| piece.is_boarder && piece.start_point.1 == 0.0)
+                .collect();
+            top.sort_by(|a, b| {
+                (a.start_point.0 + a.width / 2.0).total_cmp(&(b.start_point.0 + b.width / 2.0))
+            });
+            let top: Vec<u32> = top.iter().map(|piece| order[&piece.index]).collect();
+            assert_eq!(top, (0..top.len() as u32).collect::<Vec<_>>(), "{mode:?}");
+        }
+    }
+}
@@ -0,0 +1,342 @@
+//! A short tutorial on a puzzle of four pieces, played the first time a game is started and again
+//! from the main menu. A callout explains one thing at a time and outlines the buttons it is
+//! about, each step ends as soon as the player has tried it.
+
+use crate::gameplay::{
+    AdjustScale, BackgroundHintButton, IdeaButton, MoveStart, MoveTogether, OnPlayScreen, Shuffle,
+    ShuffleButton, ToggleBackgroundHint, TogglePuzzleHint, ZoomInButton, ZoomOutButton,
+};
+use crate::i18n::{Language, Localized};
+use crate::{AppState, Difficulty, GameState, SelectPlayMode, SelectPlayers};
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<TutorialStep>()
+        .insert_resource(TutorialSeen(tutorial_seen()))
+        .add_systems(OnEnter(AppState::Gameplay), start_first_tutorial)
+        .add_systems(OnExit(AppState::Gameplay), end_tutorial)
+        .add_systems(
+            OnEnter(GameState::Play),
+            (setup_callout, tag_targets).run_if(in_tutorial),
+        )
+        .add_systems(OnEnter(GameState::Finish), end_tutorial)
+        .add_systems(
+            Update,
+            (advance_tutorial, update_callout_text, highlight_targets)
+                .chain()
+                .run_if(in_tutorial)
+                .run_if(in_state(GameState::Play)),
+        )
+        .add_observer(start_tutorial);
+}
+
+/// Columns and rows of the tutorial puzzle
+pub const TUTORIAL_GRID: (usize, usize) = (2, 2);
+const CALLOUT_BACKGROUND: Color = Color::srgba(0.0, 0.0, 0.0, 0.75);
+const HIGHLIGHT_COLOR: Color = Color::srgb(1.0, 0.85, 0.0);
+
+/// The step of the tutorial the player is at
+#[derive(Resource, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TutorialStep {
+    #[default]
+    Off,
+    Grab,
+    Snap,
+    Zoom,
+    Shuffle,
+    Hints,
+    Finish,
+}
+
+impl TutorialStep {
+    fn message(&self) -> &'static str {
+        match self {
+            TutorialStep::Off => "",
+            TutorialStep::Grab => "Drag a piece to move it",
+            TutorialStep::Snap => "Drop a piece next to its neighbor to snap them together",
+            TutorialStep::Zoom => "Scroll or click the zoom buttons to zoom",
+            TutorialStep::Shuffle => "Click the arrows to spread the pieces around the table",
+            TutorialStep::Hints => "The lamp and the ghost show hints when you are stuck",
+            TutorialStep::Finish => "Put all pieces together to finish the puzzle",
+        }
+    }
+
+    fn next(&self) -> TutorialStep {
+        match self {
+            TutorialStep::Off | TutorialStep::Finish => TutorialStep::Off,
+            TutorialStep::Grab => TutorialStep::Snap,
+            TutorialStep::Snap => TutorialStep::Zoom,
+            TutorialStep::Zoom => TutorialStep::Shuffle,
+            TutorialStep::Shuffle => TutorialStep::Hints,
+            TutorialStep::Hints => TutorialStep::Finish,
+        }
+    }
+}
+
+/// Whether the tutorial has been played or dismissed before
+#[derive(Resource, Debug)]
+pub struct TutorialSeen(pub bool);
+
+/// Plays the tutorial from the first step
+#[derive(Event, Debug)]
+pub struct StartTutorial;
+
+/// The button a step of the tutorial is about
+#[derive(Component, Debug)]
+struct TutorialTarget(TutorialStep);
+
+#[derive(Component)]
+struct TutorialCallout;
+
+#[derive(Component)]
+struct TutorialText;
+
+/// A file in the config folder of the game
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn config_path(name: &str) -> Option<std::path::PathBuf> {
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(std::path::PathBuf::from)
+        .or_else(|| std::env::var_os("APPDATA").map(std::path::PathBuf::from))
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| std::path::PathBuf::from(home).join(".config"))
+        })?;
+    Some(config.join("jigsaw_puzzle").join(name))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn tutorial_seen() -> bool {
+    config_path("tutorial_seen").is_some_and(|path| path.exists())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn mark_tutorial_seen() {
+    let Some(path) = config_path("tutorial_seen") else {
+        return;
+    };
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, ""));
+    if let Err(err) = written {
+        warn!("Could not remember the tutorial as seen: {}", err);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen::prelude::wasm_bindgen(inline_js = r#"
+export function tutorial_seen() {
+    return localStorage.getItem("jigsaw-tutorial-seen") !== null;
+}
+
+export function mark_tutorial_seen() {
+    localStorage.setItem("jigsaw-tutorial-seen", "1");
+}
+"#)]
+extern "C" {
+    fn tutorial_seen() -> bool;
+    fn mark_tutorial_seen();
+}
+
+fn in_tutorial(step: Res<TutorialStep>) -> bool {
+    *step != TutorialStep::Off
+}
+
+/// Snapping is only taught on the free table of a single player
+fn begin(step: &mut TutorialStep, play_mode: &mut SelectPlayMode, players: &mut SelectPlayers) {
+    *step = TutorialStep::Grab;
+    *play_mode = SelectPlayMode::Free;
+    *players = SelectPlayers::Solo;
+}
+
+fn start_tutorial(
+    _trigger: Trigger<StartTutorial>,
+    mut step: ResMut<TutorialStep>,
+    mut play_mode: ResMut<SelectPlayMode>,
+    mut players: ResMut<SelectPlayers>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    begin(&mut step, &mut play_mode, &mut players);
+    app_state.set(AppState::Gameplay);
+}
+
+fn start_first_tutorial(
+    seen: Res<TutorialSeen>,
+    mut step: ResMut<TutorialStep>,
+    mut play_mode: ResMut<SelectPlayMode>,
+    mut players: ResMut<SelectPlayers>,
+) {
+    if !seen.0 && *step == TutorialStep::Off {
+        begin(&mut step, &mut play_mode, &mut players);
+    }
+}
+
+/// Closes the callout and remembers that the tutorial was seen
+fn end_tutorial(
+    mut commands: Commands,
+    mut step: ResMut<TutorialStep>,
+    mut seen: ResMut<TutorialSeen>,
+    q_callout: Query<Entity, With<TutorialCallout>>,
+    targets: Query<Entity, With<TutorialTarget>>,
+) {
+    if *step == TutorialStep::Off {
+        return;
+    }
+    *step = TutorialStep::Off;
+    for entity in q_callout.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for entity in targets.iter() {
+        commands
+            .entity(entity)
+            .remove::<(TutorialTarget, Outline)>();
+    }
+    if !seen.0 {
+        seen.0 = true;
+        mark_tutorial_seen();
+    }
+}
+
+fn setup_callout(mut commands: Commands, q_callout: Query<Entity, With<TutorialCallout>>) {
+    if !q_callout.is_empty() {
+        return;
+    }
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(70.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            PickingBehavior::IGNORE,
+            TutorialCallout,
+            OnPlayScreen,
+        ))
+        .with_children(|p| {
+            p.spawn((
+                Node {
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(6.0),
+                    padding: UiRect::axes(Val::Px(16.0), Val::Px(10.0)),
+                    ..default()
+                },
+                BackgroundColor(CALLOUT_BACKGROUND),
+                BorderRadius::all(Val::Px(8.0)),
+            ))
+            .with_children(|p| {
+                p.spawn((
+                    Text::default(),
+                    TextFont {
+                        font_size: 22.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                    TutorialText,
+                ));
+                p.spawn((
+                    Text::new("Skip tutorial"),
+                    Localized("Skip tutorial"),
+                    TextFont {
+                        font_size: 16.0,
+                        ..default()
+                    },
+                    TextColor(Color::srgb(0.7, 0.7, 0.7)),
+                ))
+                .observe(skip_tutorial);
+            });
+        });
+}
+
+fn skip_tutorial(
+    _trigger: Trigger<Pointer<Click>>,
+    commands: Commands,
+    step: ResMut<TutorialStep>,
+    seen: ResMut<TutorialSeen>,
+    q_callout: Query<Entity, With<TutorialCallout>>,
+    targets: Query<Entity, With<TutorialTarget>>,
+) {
+    end_tutorial(commands, step, seen, q_callout, targets);
+}
+
+fn tag_targets(
+    mut commands: Commands,
+    zoom: Query<Entity, Or<(With<ZoomInButton>, With<ZoomOutButton>)>>,
+    shuffle: Query<Entity, With<ShuffleButton>>,
+    hints: Query<Entity, Or<(With<IdeaButton>, With<BackgroundHintButton>)>>,
+) {
+    for (entities, step) in [
+        (zoom.iter().collect::<Vec<_>>(), TutorialStep::Zoom),
+        (shuffle.iter().collect(), TutorialStep::Shuffle),
+        (hints.iter().collect(), TutorialStep::Hints),
+    ] {
+        for entity in entities {
+            commands.entity(entity).insert(TutorialTarget(step));
+        }
+    }
+}
+
+/// Moves on once the player did what the current step asks for
+#[allow(clippy::too_many_arguments)]
+fn advance_tutorial(
+    mut step: ResMut<TutorialStep>,
+    difficulty: Res<Difficulty>,
+    grabbed: Query<(), Added<MoveStart>>,
+    snapped: Query<&MoveTogether, Changed<MoveTogether>>,
+    mut zooms: EventReader<AdjustScale>,
+    mut shuffles: EventReader<Shuffle>,
+    mut puzzle_hints: EventReader<TogglePuzzleHint>,
+    mut background_hints: EventReader<ToggleBackgroundHint>,
+) {
+    // read every event, so nothing done in an earlier step counts for a later one
+    let zoomed = zooms.read().count() > 0;
+    let shuffled = shuffles.read().count() > 0;
+    let hinted = puzzle_hints.read().count() + background_hints.read().count() > 0;
+
+    let done = match *step {
+        TutorialStep::Off | TutorialStep::Finish => false,
+        TutorialStep::Grab => !grabbed.is_empty(),
+        TutorialStep::Snap => snapped.iter().any(|together| !together.is_empty()),
+        TutorialStep::Zoom => zoomed,
+        TutorialStep::Shuffle => shuffled,
+        TutorialStep::Hints => hinted || !difficulty.hints(),
+    };
+    if done {
+        *step = step.next();
+    }
+}
+
+fn update_callout_text(
+    step: Res<TutorialStep>,
+    language: Res<Language>,
+    mut texts: Query<&mut Text, With<TutorialText>>,
+) {
+    let message = language.tr(step.message());
+    for mut text in texts.iter_mut() {
+        if text.0 != message {
+            text.0 = message.to_string();
+        }
+    }
+}
+
+/// Pulses an outline around the buttons of the current step
+fn highlight_targets(
+    mut commands: Commands,
+    time: Res<Time>,
+    step: Res<TutorialStep>,
+    targets: Query<(Entity, &TutorialTarget, Has<Outline>)>,
+) {
+    let pulse = (time.elapsed_secs() * core::f32::consts::TAU).cos() * 0.5 + 0.5;
+    for (entity, target, outlined) in targets.iter() {
+        if target.0 == *step {
+            commands.entity(entity).insert(Outline {
+                width: Val::Px(3.0),
+                offset: Val::Px(2.0),
+                color: HIGHLIGHT_COLOR.with_alpha(0.3 + 0.7 * pulse),
+            });
+        } else if outlined {
+            commands.entity(entity).remove::<Outline>();
+        }
+    }
+}
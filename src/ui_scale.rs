@@ -0,0 +1,192 @@
+//! Scales the whole UI, whose sizes are given in pixels: tiny on 4K displays and cramped on small
+//! laptops otherwise. The scale is set with a slider in the main menu and remembered for the next
+//! start.
+
+use crate::i18n::Language;
+use crate::theme::ThemeColor;
+use bevy::prelude::*;
+use bevy::ui::RelativeCursorPosition;
+
+pub(super) fn plugin(app: &mut App) {
+    // the app the game is embedded in keeps its own scale until the player picks one
+    if let Some(scale) = load_ui_scale().filter(|scale| (MIN_SCALE..=MAX_SCALE).contains(scale)) {
+        app.insert_resource(UiScale(scale));
+    }
+    app.add_systems(
+        Update,
+        (
+            save_ui_scale.run_if(resource_changed::<UiScale>),
+            update_sliders,
+        ),
+    );
+}
+
+const MIN_SCALE: f32 = 0.75;
+const MAX_SCALE: f32 = 2.0;
+const STEP: f32 = 0.05;
+const SLIDER_WIDTH: f32 = 150.0;
+
+/// A slider for the [`UiScale`] with the value it is dragged to, which is applied once it is let
+/// go, so the slider doesn't move under the pointer while it is dragged
+#[derive(Component)]
+struct UiScaleSlider(f32);
+
+#[derive(Component)]
+struct SliderFill;
+
+#[derive(Component)]
+struct SliderLabel;
+
+/// The value at the pointer on the slider, in steps
+fn value_at(position: &RelativeCursorPosition) -> Option<f32> {
+    let x = position.normalized?.x.clamp(0.0, 1.0);
+    let value = MIN_SCALE + x * (MAX_SCALE - MIN_SCALE);
+    Some((value / STEP).round() * STEP)
+}
+
+/// Adds a labeled slider for the [`UiScale`]
+pub(crate) fn spawn_ui_scale_slider(p: &mut ChildBuilder, font: Handle<Font>, scale: f32) {
+    p.spawn(Node {
+        flex_direction: FlexDirection::Column,
+        row_gap: Val::Px(4.0),
+        margin: UiRect::new(Val::Px(20.0), Val::Px(0.0), Val::Px(5.0), Val::Px(0.0)),
+        ..default()
+    })
+    .with_children(|p| {
+        p.spawn((
+            Text::default(),
+            TextFont {
+                font,
+                font_size: 24.0,
+                ..default()
+            },
+            TextColor(Color::BLACK),
+            ThemeColor::Text,
+            SliderLabel,
+        ));
+        p.spawn((
+            Node {
+                width: Val::Px(SLIDER_WIDTH),
+                height: Val::Px(12.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.3)),
+            BorderRadius::MAX,
+            RelativeCursorPosition::default(),
+            UiScaleSlider(scale),
+        ))
+        .with_child((
+            Node {
+                height: Val::Percent(100.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.3, 0.75, 0.35)),
+            BorderRadius::MAX,
+            PickingBehavior::IGNORE,
+            SliderFill,
+        ))
+        .observe(drag_slider)
+        .observe(
+            |trigger: Trigger<Pointer<DragEnd>>,
+             sliders: Query<&UiScaleSlider>,
+             mut ui_scale: ResMut<UiScale>| {
+                if let Ok(slider) = sliders.get(trigger.entity()) {
+                    ui_scale.0 = slider.0;
+                }
+            },
+        )
+        .observe(
+            |trigger: Trigger<Pointer<Click>>,
+             mut sliders: Query<(&mut UiScaleSlider, &RelativeCursorPosition)>,
+             mut ui_scale: ResMut<UiScale>| {
+                let Ok((mut slider, position)) = sliders.get_mut(trigger.entity()) else {
+                    return;
+                };
+                if let Some(value) = value_at(position) {
+                    slider.0 = value;
+                    ui_scale.0 = value;
+                }
+            },
+        );
+    });
+}
+
+fn drag_slider(
+    trigger: Trigger<Pointer<Drag>>,
+    mut sliders: Query<(&mut UiScaleSlider, &RelativeCursorPosition)>,
+) {
+    let Ok((mut slider, position)) = sliders.get_mut(trigger.entity()) else {
+        return;
+    };
+    if let Some(value) = value_at(position) {
+        slider.0 = value;
+    }
+}
+
+fn update_sliders(
+    language: Res<Language>,
+    sliders: Query<Ref<UiScaleSlider>>,
+    mut fills: Query<&mut Node, With<SliderFill>>,
+    mut labels: Query<&mut Text, With<SliderLabel>>,
+) {
+    let Ok(slider) = sliders.get_single() else {
+        return;
+    };
+    if !slider.is_changed() && !language.is_changed() {
+        return;
+    }
+    for mut node in fills.iter_mut() {
+        node.width = Val::Percent((slider.0 - MIN_SCALE) / (MAX_SCALE - MIN_SCALE) * 100.0);
+    }
+    for mut text in labels.iter_mut() {
+        text.0 = format!("{}: {:.0}%", language.tr("UI scale"), slider.0 * 100.0);
+    }
+}
+
+fn save_ui_scale(ui_scale: Res<UiScale>) {
+    if ui_scale.is_added() {
+        return;
+    }
+    store_ui_scale(ui_scale.0);
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_ui_scale() -> Option<f32> {
+    let path = crate::tutorial::config_path("ui_scale")?;
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn store_ui_scale(scale: f32) {
+    let Some(path) = crate::tutorial::config_path("ui_scale") else {
+        return;
+    };
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, scale.to_string()));
+    if let Err(err) = written {
+        warn!("Could not remember the UI scale: {}", err);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load_ui_scale() -> Option<f32> {
+    let scale = stored_ui_scale();
+    (scale > 0.0).then_some(scale)
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen::prelude::wasm_bindgen(inline_js = r#"
+export function stored_ui_scale() {
+    return parseFloat(localStorage.getItem("jigsaw-ui-scale")) || 0;
+}
+
+export function store_ui_scale(scale) {
+    localStorage.setItem("jigsaw-ui-scale", String(scale));
+}
+"#)]
+extern "C" {
+    fn stored_ui_scale() -> f32;
+    fn store_ui_scale(scale: f32);
+}
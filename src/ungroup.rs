@@ -0,0 +1,172 @@
+//! Breaks wrongly merged groups apart. <kbd>U</kbd> splits the hovered piece off its group and
+//! moves it a little aside, <kbd>Shift</kbd> + <kbd>U</kbd> dissolves the whole group into single
+//! pieces. The pieces left over stay joined wherever they are neighbors in the solved puzzle and
+//! still fit together, so only the wrong joints come apart.
+
+use crate::board::Locked;
+use crate::gameplay::{MoveStart, MoveTogether};
+use crate::layering::HoveredPiece;
+use crate::pinning::Pinned;
+use crate::{GameState, Piece};
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_event::<Ungroup>().add_systems(
+        Update,
+        (handle_ungroup_keys, ungroup).run_if(in_state(GameState::Play)),
+    );
+}
+
+/// How far two neighbors may be off their place next to each other and still count as joined
+const FIT_TOLERANCE: f32 = 2.0;
+/// How far the split piece is moved aside, of its width
+const SPLIT_DISTANCE: f32 = 0.25;
+
+/// Breaks the group of a piece apart
+#[derive(Event, Debug, Clone, Copy)]
+pub enum Ungroup {
+    /// Only the piece leaves its group
+    Split(Entity),
+    /// Every piece of the group becomes a single piece
+    Dissolve(Entity),
+}
+
+fn handle_ungroup_keys(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    hovered: Res<HoveredPiece>,
+    mut events: EventWriter<Ungroup>,
+) {
+    let Some(entity) = hovered.0 else {
+        return;
+    };
+    if keyboard_input.just_pressed(KeyCode::KeyU) {
+        if keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+            events.send(Ungroup::Dissolve(entity));
+        } else {
+            events.send(Ungroup::Split(entity));
+        }
+    }
+}
+
+/// Whether the two pieces are neighbors in the solved puzzle and lie next to each other
+fn fit_together(a: (&Piece, &Transform), b: (&Piece, &Transform)) -> bool {
+    let ((piece_a, transform_a), (piece_b, transform_b)) = (a, b);
+    if !piece_a.beside(piece_b) {
+        return false;
+    }
+    let expected = Vec3::new(
+        piece_b.start_point.0 - piece_a.start_point.0,
+        piece_a.start_point.1 - piece_b.start_point.1,
+        0.0,
+    );
+    let offset = transform_b.translation - transform_a.translation;
+    (transform_a.rotation * expected).xy().distance(offset.xy()) < FIT_TOLERANCE
+}
+
+/// Splits the pieces into the groups which fit together
+fn connected_groups(
+    pieces: &HashSet<Entity>,
+    query: &Query<(&Piece, &mut Transform, &mut MoveTogether, Has<Pinned>)>,
+) -> Vec<HashSet<Entity>> {
+    let mut left = pieces.clone();
+    let mut groups = vec![];
+    while let Some(start) = left.iter().next().copied() {
+        left.remove(&start);
+        let mut group = HashSet::from_iter([start]);
+        let mut queue = vec![start];
+        while let Some(entity) = queue.pop() {
+            let Ok((piece, transform, _, _)) = query.get(entity) else {
+                continue;
+            };
+            let joined: Vec<Entity> = left
+                .iter()
+                .copied()
+                .filter(|other| {
+                    query
+                        .get(*other)
+                        .is_ok_and(|(other, other_transform, _, _)| {
+                            fit_together((piece, transform), (other, other_transform))
+                        })
+                })
+                .collect();
+            for other in joined {
+                left.remove(&other);
+                group.insert(other);
+                queue.push(other);
+            }
+        }
+        groups.push(group);
+    }
+    groups
+}
+
+fn ungroup(
+    mut commands: Commands,
+    mut events: EventReader<Ungroup>,
+    moving: Query<(), With<MoveStart>>,
+    locked: Query<(), With<Locked>>,
+    mut pieces: Query<(&Piece, &mut Transform, &mut MoveTogether, Has<Pinned>)>,
+) {
+    for event in events.read() {
+        let (Ungroup::Split(entity) | Ungroup::Dissolve(entity)) = *event;
+        let Ok((piece, _, together, _)) = pieces.get(entity) else {
+            continue;
+        };
+        let (group, distance) = (together.0.clone(), piece.width * SPLIT_DISTANCE);
+        // single pieces, held groups and pieces placed on the board stay as they are
+        if group.len() < 2
+            || group
+                .iter()
+                .any(|entity| moving.contains(*entity) || locked.contains(*entity))
+        {
+            continue;
+        }
+
+        let groups = match event {
+            Ungroup::Split(_) => {
+                let mut rest = group.clone();
+                rest.remove(&entity);
+                let center = rest
+                    .iter()
+                    .filter_map(|entity| pieces.get(*entity).ok())
+                    .map(|(_, transform, _, _)| transform.translation.xy())
+                    .sum::<Vec2>()
+                    / rest.len() as f32;
+                if let Ok((_, mut transform, _, _)) = pieces.get_mut(entity) {
+                    let away = (transform.translation.xy() - center).normalize_or(Vec2::X);
+                    transform.translation += (away * distance).extend(0.0);
+                }
+                let mut groups = connected_groups(&rest, &pieces);
+                groups.push(HashSet::from_iter([entity]));
+                groups
+            }
+            Ungroup::Dissolve(_) => group
+                .iter()
+                .map(|entity| HashSet::from_iter([*entity]))
+                .collect(),
+        };
+
+        for group in groups {
+            let pinned = group.len() > 1
+                && group
+                    .iter()
+                    .any(|entity| pieces.get(*entity).is_ok_and(|(.., pinned)| pinned));
+            for entity in group.iter() {
+                let Ok((_, _, mut together, was_pinned)) = pieces.get_mut(*entity) else {
+                    continue;
+                };
+                // the groups of single pieces are empty
+                together.0 = if group.len() > 1 {
+                    group.clone()
+                } else {
+                    HashSet::default()
+                };
+                // only groups are pinned
+                if was_pinned && !pinned {
+                    commands.entity(*entity).remove::<Pinned>();
+                }
+            }
+        }
+    }
+}
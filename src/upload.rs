@@ -0,0 +1,151 @@
+//! Lets the web build puzzle an image from the player's computer, as native paths and dialogs are
+//! not available in the browser. The image is chosen with a file input or dropped onto the canvas,
+//! decoded by the browser and handed over as RGBA bytes. GIFs are handed over as they are, so all
+//! their frames can be played.
+
+use crate::animated::{add_image_bytes, AnimatedImages};
+use crate::main_menu::LoadedImages;
+use crate::{AppState, OriginImage};
+use bevy::asset::RenderAssetUsages;
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use wasm_bindgen::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(Startup, install_upload)
+        .add_systems(Update, receive_upload.run_if(in_state(AppState::MainMenu)));
+}
+
+#[wasm_bindgen(inline_js = r#"
+let pending = null;
+let pending_gif = null;
+
+function decode(file) {
+    if (!file || !file.type.startsWith("image/")) {
+        return;
+    }
+    if (file.type === "image/gif") {
+        file.arrayBuffer().then((buffer) => {
+            pending_gif = new Uint8Array(buffer);
+        }, (error) => console.warn("Could not read the GIF", error));
+        return;
+    }
+    createImageBitmap(file).then((bitmap) => {
+        const canvas = document.createElement("canvas");
+        canvas.width = bitmap.width;
+        canvas.height = bitmap.height;
+        const context = canvas.getContext("2d");
+        context.drawImage(bitmap, 0, 0);
+        const pixels = context.getImageData(0, 0, bitmap.width, bitmap.height);
+        pending = {
+            width: bitmap.width,
+            height: bitmap.height,
+            data: new Uint8Array(pixels.data.buffer),
+        };
+        bitmap.close();
+    }, (error) => console.warn("Could not decode the image", error));
+}
+
+export function install_upload() {
+    const input = document.createElement("input");
+    input.type = "file";
+    input.accept = "image/*";
+    input.id = "jigsaw-upload";
+    input.style.display = "none";
+    input.addEventListener("change", () => {
+        decode(input.files[0]);
+        input.value = "";
+    });
+    document.body.appendChild(input);
+
+    const canvas = document.getElementById("bevy");
+    canvas.addEventListener("dragover", (event) => event.preventDefault());
+    canvas.addEventListener("drop", (event) => {
+        event.preventDefault();
+        decode(event.dataTransfer.files[0]);
+    });
+}
+
+export function open_upload() {
+    document.getElementById("jigsaw-upload").click();
+}
+
+export function upload_width() {
+    return pending ? pending.width : 0;
+}
+
+export function upload_height() {
+    return pending ? pending.height : 0;
+}
+
+export function take_upload() {
+    const data = pending ? pending.data : undefined;
+    pending = null;
+    return data;
+}
+
+export function take_gif_upload() {
+    const data = pending_gif ? pending_gif : undefined;
+    pending_gif = null;
+    return data;
+}
+"#)]
+extern "C" {
+    #[wasm_bindgen(js_name = install_upload)]
+    fn install_file_input();
+    fn open_upload();
+    fn upload_width() -> u32;
+    fn upload_height() -> u32;
+    fn take_upload() -> Option<js_sys::Uint8Array>;
+    fn take_gif_upload() -> Option<js_sys::Uint8Array>;
+}
+
+fn install_upload() {
+    install_file_input();
+}
+
+/// Opens the file dialog of the browser, has to be called right after a click of the player
+pub fn open_file_picker() {
+    open_upload();
+}
+
+/// Adds the decoded image to the image strip and selects it
+fn receive_upload(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut loaded_images: ResMut<LoadedImages>,
+    mut animated: ResMut<AnimatedImages>,
+) {
+    if let Some(bytes) = take_gif_upload() {
+        match add_image_bytes(&bytes.to_vec(), &mut images, &mut animated) {
+            Ok(handle) => {
+                loaded_images.push(handle.clone());
+                commands.insert_resource(OriginImage(handle));
+            }
+            Err(e) => warn!("Could not decode the GIF: {}", e),
+        }
+    }
+
+    let (width, height) = (upload_width(), upload_height());
+    let Some(data) = take_upload() else {
+        return;
+    };
+    if width == 0 || height == 0 {
+        return;
+    }
+
+    let image = Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data.to_vec(),
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    let handle = images.add(image);
+    loaded_images.push(handle.clone());
+    commands.insert_resource(OriginImage(handle));
+}
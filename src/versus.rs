@@ -0,0 +1,204 @@
+use crate::atlas::PieceMaterial;
+use crate::gameplay::{ColorImage, CombineTogether, Immovable, OnPlayScreen};
+use crate::i18n::Language;
+use crate::{GameState, Piece, SelectPlayers};
+use bevy::prelude::*;
+use rand::seq::SliceRandom;
+
+pub(super) fn plugin(app: &mut App) {
+    app.add_systems(OnEnter(GameState::Generating), setup_versus)
+        .add_systems(
+            OnEnter(GameState::Play),
+            (assign_owners, setup_versus_ui)
+                .chain()
+                .run_if(resource_exists::<Versus>),
+        )
+        .add_systems(
+            Update,
+            (update_immovable, update_versus_text)
+                .run_if(resource_exists_and_changed::<Versus>)
+                .run_if(in_state(GameState::Play)),
+        )
+        .add_observer(count_placement);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Player {
+    #[default]
+    One,
+    Two,
+}
+
+impl Player {
+    fn other(&self) -> Player {
+        match self {
+            Player::One => Player::Two,
+            Player::Two => Player::One,
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Player::One => Color::srgb(0.85, 0.25, 0.25),
+            Player::Two => Color::srgb(0.25, 0.45, 0.9),
+        }
+    }
+
+    /// The tint of the pieces owned by this player
+    fn tint(&self) -> Color {
+        match self {
+            Player::One => Color::srgb(1.0, 0.8, 0.8),
+            Player::Two => Color::srgb(0.8, 0.85, 1.0),
+        }
+    }
+}
+
+impl core::fmt::Display for Player {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Player::One => write!(f, "Player 1"),
+            Player::Two => write!(f, "Player 2"),
+        }
+    }
+}
+
+/// The player who may move a piece
+#[derive(Component, Debug, Clone, Copy, Deref)]
+pub struct Owner(pub Player);
+
+/// State of a two players hot-seat game. Players take turns and each successful snap counts as
+/// a placement for the player who dropped the piece.
+#[derive(Resource, Debug, Default)]
+pub struct Versus {
+    pub current: Player,
+    pub placements: [u32; 2],
+}
+
+impl Versus {
+    fn placements_of(&self, player: Player) -> u32 {
+        match player {
+            Player::One => self.placements[0],
+            Player::Two => self.placements[1],
+        }
+    }
+
+    /// Describes the winner of the game
+    pub fn result(&self, language: &Language) -> String {
+        let (one, two) = (self.placements[0], self.placements[1]);
+        let wins = language.tr("wins");
+        let name = |player: Player| language.tr(&player.to_string()).to_string();
+        match one.cmp(&two) {
+            core::cmp::Ordering::Greater => {
+                format!("{} {wins} {} : {}", name(Player::One), one, two)
+            }
+            core::cmp::Ordering::Less => format!("{} {wins} {} : {}", name(Player::Two), two, one),
+            core::cmp::Ordering::Equal => format!("{} {} : {}", language.tr("Draw"), one, two),
+        }
+    }
+}
+
+#[derive(Component)]
+struct VersusText(Player);
+
+fn setup_versus(mut commands: Commands, select_players: Res<SelectPlayers>) {
+    match *select_players {
+        SelectPlayers::Solo => commands.remove_resource::<Versus>(),
+        SelectPlayers::Versus => commands.insert_resource(Versus::default()),
+    }
+}
+
+/// Splits the pieces randomly into two equal pools
+fn assign_owners(
+    mut commands: Commands,
+    pieces: Query<(Entity, &Children), (With<Piece>, Without<Owner>)>,
+    images: Query<&MeshMaterial2d<PieceMaterial>, With<ColorImage>>,
+    mut materials: ResMut<Assets<PieceMaterial>>,
+) {
+    let mut entities: Vec<_> = pieces.iter().collect();
+    entities.shuffle(&mut rand::thread_rng());
+    let half = entities.len() / 2;
+    for (i, (entity, children)) in entities.into_iter().enumerate() {
+        let player = if i < half { Player::One } else { Player::Two };
+        commands.entity(entity).insert(Owner(player));
+        for material in images.iter_many(children) {
+            if let Some(material) = materials.get_mut(material) {
+                material.set_color(player.tint());
+            }
+        }
+    }
+}
+
+fn setup_versus_ui(
+    mut commands: Commands,
+    language: Res<Language>,
+    q_text: Query<Entity, With<VersusText>>,
+) {
+    if !q_text.is_empty() {
+        return;
+    }
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(10.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                column_gap: Val::Px(30.0),
+                ..default()
+            },
+            PickingBehavior::IGNORE,
+            OnPlayScreen,
+        ))
+        .with_children(|p| {
+            for player in [Player::One, Player::Two] {
+                p.spawn((
+                    Text::new(format!("{}: 0", language.tr(&player.to_string()))),
+                    TextColor(player.color()),
+                    VersusText(player),
+                ));
+            }
+        });
+}
+
+/// Only the pieces of the current player can be picked up
+fn update_immovable(mut commands: Commands, versus: Res<Versus>, pieces: Query<(Entity, &Owner)>) {
+    for (entity, owner) in pieces.iter() {
+        if **owner == versus.current {
+            commands.entity(entity).remove::<Immovable>();
+        } else {
+            commands.entity(entity).insert(Immovable);
+        }
+    }
+}
+
+fn update_versus_text(
+    versus: Res<Versus>,
+    language: Res<Language>,
+    mut texts: Query<(&mut Text, &VersusText)>,
+) {
+    for (mut text, VersusText(player)) in texts.iter_mut() {
+        let marker = if *player == versus.current { "> " } else { "" };
+        text.0 = format!(
+            "{}{}: {}",
+            marker,
+            language.tr(&player.to_string()),
+            versus.placements_of(*player)
+        );
+    }
+}
+
+/// Counts a placement if the dropped piece snapped and passes the turn to the other player
+fn count_placement(trigger: Trigger<CombineTogether>, versus: Option<ResMut<Versus>>) {
+    let Some(mut versus) = versus else {
+        return;
+    };
+    if !trigger.event().0.is_empty() {
+        let index = match versus.current {
+            Player::One => 0,
+            Player::Two => 1,
+        };
+        versus.placements[index] += 1;
+    }
+    versus.current = versus.current.other();
+}
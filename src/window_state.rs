@@ -0,0 +1,264 @@
+//! Remembers the size, position and fullscreen state of the window for the next start, and the
+//! monitor fullscreen is shown on, which is picked in the main menu. In the browser the page
+//! decides the size of the canvas, so nothing is remembered there.
+
+use crate::i18n::Language;
+use crate::theme::ThemeColor;
+use bevy::prelude::*;
+use bevy::window::{Monitor, MonitorSelection, PrimaryWindow, WindowMode, WindowPosition};
+
+pub(super) fn plugin(app: &mut App) {
+    let stored =
+        load_window_state().map_or_else(StoredWindow::default, |text| StoredWindow::parse(&text));
+    app.insert_resource(FullscreenMonitor(stored.monitor.clone()))
+        .insert_resource(stored)
+        .add_systems(Startup, restore_size)
+        .add_systems(
+            Update,
+            (restore_position, save_window_state, update_monitor_text),
+        );
+}
+
+/// How long the window has to stay unchanged before it is remembered, in seconds, so dragging
+/// its border doesn't write the file on every frame
+const SAVE_DELAY: f32 = 0.5;
+
+/// The monitor fullscreen is shown on by its name, the one the window is on if none is picked
+#[derive(Resource, Debug, Default, Clone, PartialEq, Eq)]
+pub struct FullscreenMonitor(pub Option<String>);
+
+impl FullscreenMonitor {
+    /// The fullscreen mode on the picked monitor, the current one if it isn't connected anymore
+    pub fn mode(&self, monitors: &Query<(Entity, &Monitor)>) -> WindowMode {
+        let selection = monitors
+            .iter()
+            .find(|(_, monitor)| monitor.name.is_some() && monitor.name == self.0)
+            .map_or(MonitorSelection::Current, |(entity, _)| {
+                MonitorSelection::Entity(entity)
+            });
+        WindowMode::Fullscreen(selection)
+    }
+
+    /// Picks the next monitor, after the last one the current monitor again
+    fn next(&mut self, monitors: &Query<(Entity, &Monitor)>) {
+        let names: Vec<String> = monitors
+            .iter()
+            .filter_map(|(_, monitor)| monitor.name.clone())
+            .collect();
+        let index = self
+            .0
+            .as_ref()
+            .and_then(|name| names.iter().position(|other| other == name));
+        self.0 = match index {
+            Some(index) => names.get(index + 1).cloned(),
+            None => names.first().cloned(),
+        };
+    }
+}
+
+/// The window as it was left at the last run
+#[derive(Resource, Debug, Default, Clone, PartialEq)]
+struct StoredWindow {
+    /// The size of the window when it isn't fullscreen, in logical pixels
+    size: Option<Vec2>,
+    /// The top left corner of the window, in physical pixels
+    position: Option<IVec2>,
+    fullscreen: bool,
+    monitor: Option<String>,
+}
+
+impl StoredWindow {
+    fn parse(text: &str) -> Self {
+        let mut stored = StoredWindow::default();
+        for line in text.lines() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            let numbers: Vec<f32> = value
+                .split_whitespace()
+                .filter_map(|number| number.parse().ok())
+                .collect();
+            match (key, numbers.as_slice()) {
+                ("size", [width, height]) if *width > 0.0 && *height > 0.0 => {
+                    stored.size = Some(Vec2::new(*width, *height));
+                }
+                ("position", [x, y]) => stored.position = Some(IVec2::new(*x as i32, *y as i32)),
+                ("fullscreen", _) => stored.fullscreen = true,
+                ("monitor", _) if !value.is_empty() => stored.monitor = Some(value.to_string()),
+                _ => {}
+            }
+        }
+        stored
+    }
+
+    fn to_text(&self) -> String {
+        let mut lines = vec![];
+        if let Some(size) = self.size {
+            lines.push(format!("size {} {}", size.x, size.y));
+        }
+        if let Some(position) = self.position {
+            lines.push(format!("position {} {}", position.x, position.y));
+        }
+        if self.fullscreen {
+            lines.push("fullscreen".to_string());
+        }
+        if let Some(monitor) = &self.monitor {
+            lines.push(format!("monitor {}", monitor));
+        }
+        lines.join("\n")
+    }
+}
+
+/// The window opens in its last size
+fn restore_size(stored: Res<StoredWindow>, mut window: Single<&mut Window, With<PrimaryWindow>>) {
+    if let Some(size) = stored.size {
+        window.resolution.set(size.x, size.y);
+    }
+}
+
+/// Moves the window back and makes it fullscreen again once the monitors are known. A position
+/// on a monitor which isn't connected anymore is left to the system
+fn restore_position(
+    stored: Res<StoredWindow>,
+    fullscreen_monitor: Res<FullscreenMonitor>,
+    monitors: Query<(Entity, &Monitor)>,
+    mut window: Single<&mut Window, With<PrimaryWindow>>,
+    mut restored: Local<bool>,
+) {
+    if *restored || monitors.is_empty() {
+        return;
+    }
+    *restored = true;
+    if let Some(position) = stored.position {
+        let on_screen = monitors.iter().any(|(_, monitor)| {
+            IRect::from_corners(
+                monitor.physical_position,
+                monitor.physical_position + monitor.physical_size().as_ivec2(),
+            )
+            .contains(position)
+        });
+        if on_screen {
+            window.position = WindowPosition::At(position);
+        }
+    }
+    if stored.fullscreen {
+        window.mode = fullscreen_monitor.mode(&monitors);
+    }
+}
+
+/// Remembers the window once it stopped changing for a moment
+fn save_window_state(
+    time: Res<Time>,
+    mut stored: ResMut<StoredWindow>,
+    fullscreen_monitor: Res<FullscreenMonitor>,
+    window: Single<&Window, With<PrimaryWindow>>,
+    mut pending: Local<Option<(StoredWindow, f32)>>,
+) {
+    let fullscreen = window.mode != WindowMode::Windowed;
+    let current = StoredWindow {
+        // the windowed size and position are kept while fullscreen
+        size: if fullscreen {
+            stored.size
+        } else {
+            Some(window.size())
+        },
+        position: match window.position {
+            WindowPosition::At(position) if !fullscreen => Some(position),
+            _ => stored.position,
+        },
+        fullscreen,
+        monitor: fullscreen_monitor.0.clone(),
+    };
+    if current == *stored {
+        *pending = None;
+        return;
+    }
+    let waited = match pending.as_mut() {
+        Some((state, waited)) if *state == current => {
+            *waited += time.delta_secs();
+            *waited
+        }
+        _ => {
+            *pending = Some((current, 0.0));
+            return;
+        }
+    };
+    if waited >= SAVE_DELAY {
+        *stored = current;
+        store_window_state(&stored.to_text());
+        *pending = None;
+    }
+}
+
+/// The text switching the [`FullscreenMonitor`]
+#[derive(Component)]
+struct MonitorText;
+
+/// Adds "Fullscreen on", which switches the [`FullscreenMonitor`]
+pub(crate) fn spawn_monitor_button(p: &mut ChildBuilder, font: Handle<Font>) {
+    p.spawn((
+        MonitorText,
+        Text::default(),
+        TextFont {
+            font,
+            font_size: 24.0,
+            ..default()
+        },
+        TextColor(Color::BLACK),
+        ThemeColor::Text,
+        Node {
+            margin: UiRect::new(Val::Px(20.0), Val::Px(0.0), Val::Px(5.0), Val::Px(0.0)),
+            ..default()
+        },
+    ))
+    .observe(
+        |_trigger: Trigger<Pointer<Click>>,
+         mut fullscreen_monitor: ResMut<FullscreenMonitor>,
+         monitors: Query<(Entity, &Monitor)>| {
+            fullscreen_monitor.next(&monitors);
+        },
+    );
+}
+
+fn update_monitor_text(
+    fullscreen_monitor: Res<FullscreenMonitor>,
+    language: Res<Language>,
+    mut texts: Query<&mut Text, With<MonitorText>>,
+    added: Query<(), Added<MonitorText>>,
+) {
+    if added.is_empty() && !fullscreen_monitor.is_changed() && !language.is_changed() {
+        return;
+    }
+    let monitor = fullscreen_monitor
+        .0
+        .clone()
+        .unwrap_or_else(|| language.tr("Current monitor").to_string());
+    for mut text in texts.iter_mut() {
+        text.0 = format!("{}: {}", language.tr("Fullscreen on"), monitor);
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn load_window_state() -> Option<String> {
+    crate::tutorial::config_path("window").and_then(|path| std::fs::read_to_string(path).ok())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn store_window_state(text: &str) {
+    let Some(path) = crate::tutorial::config_path("window") else {
+        return;
+    };
+    let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, text));
+    if let Err(err) = written {
+        warn!("Could not remember the window: {}", err);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn load_window_state() -> Option<String> {
+    None
+}
+
+#[cfg(target_arch = "wasm32")]
+fn store_window_state(_text: &str) {}
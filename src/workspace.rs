@@ -0,0 +1,318 @@
+//! Named workspaces next to the table of the board, to sort pieces into, e.g. "Sky" and
+//! "Building". Every workspace keeps its own camera framing, the number keys switch between
+//! them and a piece held while switching is carried along.
+
+use crate::camera::{fit_rect, CameraAnimation};
+use crate::gameplay::OnPlayScreen;
+use crate::i18n::Language;
+use crate::locate::entering_number;
+use crate::table::{setup_table, Table};
+use crate::tray::InTray;
+use crate::{GameState, Piece, NORMAL_BUTTON, PRESSED_BUTTON};
+use bevy::prelude::*;
+
+pub(super) fn plugin(app: &mut App) {
+    app.init_resource::<Workspaces>()
+        .add_event::<CreateWorkspace>()
+        .add_event::<SwitchWorkspace>()
+        .add_systems(
+            OnTransition {
+                exited: GameState::Generating,
+                entered: GameState::Play,
+            },
+            reset_workspaces,
+        )
+        .add_systems(
+            OnEnter(GameState::Play),
+            (extend_table.after(setup_table), setup_workspace_tabs),
+        )
+        .add_systems(
+            Update,
+            (
+                handle_workspace_keys.run_if(not(entering_number)),
+                create_workspace,
+                switch_workspace,
+                track_workspace_pieces,
+                update_workspace_tabs,
+            )
+                .chain()
+                .run_if(in_state(GameState::Play)),
+        );
+}
+
+/// The number keys only reach this many workspaces
+const MAX_WORKSPACES: usize = 9;
+
+const DIGITS: [KeyCode; MAX_WORKSPACES] = [
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub name: String,
+    /// The camera translation and scale when the player last left the workspace
+    framing: Option<(Vec2, f32)>,
+}
+
+/// The workspaces of the puzzle, the first one holds the board. The others are lined up to the
+/// right of it, each half as wide as the table of the board.
+#[derive(Resource, Debug)]
+pub struct Workspaces {
+    pub list: Vec<Workspace>,
+    pub active: usize,
+    /// The table of the board, before it is extended by the other workspaces
+    base: Rect,
+}
+
+impl Default for Workspaces {
+    fn default() -> Self {
+        Self {
+            list: vec![Workspace {
+                name: "Board".to_string(),
+                framing: None,
+            }],
+            active: 0,
+            base: Rect::default(),
+        }
+    }
+}
+
+impl Workspaces {
+    /// The area of the workspace at `index` on the table
+    pub fn area(&self, index: usize) -> Rect {
+        if index == 0 {
+            return self.base;
+        }
+        let width = self.base.width() / 2.0;
+        let min_x = self.base.max.x + width * (index - 1) as f32;
+        Rect::new(min_x, self.base.min.y, min_x + width, self.base.max.y)
+    }
+
+    /// The workspace a piece centered at `point` belongs to, the board outside of all of them
+    pub fn index_at(&self, point: Vec2) -> usize {
+        (1..self.list.len())
+            .find(|index| self.area(*index).contains(point))
+            .unwrap_or_default()
+    }
+
+    fn table(&self) -> Rect {
+        self.base.union(self.area(self.list.len() - 1))
+    }
+}
+
+/// The workspace a piece lies in
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InWorkspace(pub usize);
+
+/// Adds a workspace with this name and moves the camera to it
+#[derive(Event, Debug, Clone)]
+pub struct CreateWorkspace(pub String);
+
+/// Moves the camera to the workspace at this index
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SwitchWorkspace(pub usize);
+
+/// The tinted surface of a workspace besides the board
+#[derive(Component)]
+struct WorkspaceSurface;
+
+/// The row of tabs at the top of the window, one per workspace and one to add a workspace
+#[derive(Component)]
+struct WorkspaceTabs;
+
+fn reset_workspaces(mut commands: Commands, surfaces: Query<Entity, With<WorkspaceSurface>>) {
+    commands.insert_resource(Workspaces::default());
+    for entity in surfaces.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// The table of the board was set up again, the other workspaces are added to its right
+fn extend_table(mut workspaces: ResMut<Workspaces>, mut table: ResMut<Table>) {
+    workspaces.base = table.0;
+    table.0 = workspaces.table();
+}
+
+fn setup_workspace_tabs(mut commands: Commands, tabs: Query<(), With<WorkspaceTabs>>) {
+    if !tabs.is_empty() {
+        return;
+    }
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            column_gap: Val::Px(4.0),
+            ..default()
+        },
+        PickingBehavior::IGNORE,
+        WorkspaceTabs,
+        OnPlayScreen,
+    ));
+}
+
+fn handle_workspace_keys(keyboard_input: Res<ButtonInput<KeyCode>>, mut commands: Commands) {
+    if let Some(index) = DIGITS
+        .iter()
+        .position(|key| keyboard_input.just_pressed(*key))
+    {
+        commands.send_event(SwitchWorkspace(index));
+    }
+}
+
+fn create_workspace(
+    mut commands: Commands,
+    mut events: EventReader<CreateWorkspace>,
+    mut workspaces: ResMut<Workspaces>,
+    mut table: ResMut<Table>,
+) {
+    for CreateWorkspace(name) in events.read() {
+        if workspaces.list.len() >= MAX_WORKSPACES {
+            continue;
+        }
+        workspaces.list.push(Workspace {
+            name: name.clone(),
+            framing: None,
+        });
+        table.0 = workspaces.table();
+
+        let index = workspaces.list.len() - 1;
+        let area = workspaces.area(index);
+        // every other workspace a bit darker, so they can be told apart
+        let alpha = 0.04 + 0.04 * (index % 2) as f32;
+        commands
+            .spawn((
+                Sprite::from_color(Color::srgba(0.0, 0.0, 0.0, alpha), area.size()),
+                Transform::from_translation(area.center().extend(-20.0)),
+                WorkspaceSurface,
+                OnPlayScreen,
+            ))
+            .with_child((
+                Text2d::new(name.clone()),
+                TextFont {
+                    font_size: 40.0,
+                    ..default()
+                },
+                TextColor(Color::srgba(0.0, 0.0, 0.0, 0.3)),
+                Transform::from_xyz(0.0, area.height() / 2.0 - 40.0, 1.0),
+            ));
+        commands.send_event(SwitchWorkspace(index));
+    }
+}
+
+/// Keeps the framing of the workspace which is left and frames the new one, where it was left
+/// or as a whole on the first visit
+fn switch_workspace(
+    mut events: EventReader<SwitchWorkspace>,
+    mut workspaces: ResMut<Workspaces>,
+    mut animation: ResMut<CameraAnimation>,
+    window: Single<&Window>,
+    camera: Single<(&Transform, &OrthographicProjection), With<IsDefaultUiCamera>>,
+) {
+    let (transform, projection) = *camera;
+    for SwitchWorkspace(index) in events.read() {
+        if *index >= workspaces.list.len() || *index == workspaces.active {
+            continue;
+        }
+        let active = workspaces.active;
+        workspaces.list[active].framing = Some((transform.translation.xy(), projection.scale));
+        workspaces.active = *index;
+        let framing = workspaces.list[*index]
+            .framing
+            .unwrap_or_else(|| fit_rect(workspaces.area(*index), window.size()));
+        animation.0 = Some(framing);
+    }
+}
+
+fn track_workspace_pieces(
+    mut commands: Commands,
+    workspaces: Res<Workspaces>,
+    pieces: Query<
+        (Entity, &Piece, &Transform, Option<&InWorkspace>),
+        (Changed<Transform>, Without<InTray>),
+    >,
+) {
+    for (entity, piece, transform, in_workspace) in pieces.iter() {
+        let index = workspaces.index_at(piece.image_rect(transform.translation.xy()).center());
+        if in_workspace != Some(&InWorkspace(index)) {
+            commands.entity(entity).insert(InWorkspace(index));
+        }
+    }
+}
+
+/// Lists the workspaces with the number of their pieces, whenever one is added, switched to or
+/// pieces move between them
+fn update_workspace_tabs(
+    mut commands: Commands,
+    workspaces: Res<Workspaces>,
+    language: Res<Language>,
+    tabs: Single<Entity, With<WorkspaceTabs>>,
+    pieces: Query<&InWorkspace>,
+    changed: Query<(), Changed<InWorkspace>>,
+) {
+    if !workspaces.is_changed() && !language.is_changed() && changed.is_empty() {
+        return;
+    }
+    let mut counts = vec![0; workspaces.list.len()];
+    for InWorkspace(index) in pieces.iter() {
+        if let Some(count) = counts.get_mut(*index) {
+            *count += 1;
+        }
+    }
+
+    let tab = |text: String, active: bool| {
+        (
+            Text::new(text),
+            TextFont {
+                font_size: 16.0,
+                ..default()
+            },
+            TextColor(Color::WHITE),
+            Node {
+                padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+                ..default()
+            },
+            BackgroundColor(if active {
+                PRESSED_BUTTON
+            } else {
+                NORMAL_BUTTON
+            }),
+            BorderRadius::all(Val::Px(4.0)),
+        )
+    };
+    commands
+        .entity(*tabs)
+        .despawn_descendants()
+        .with_children(|p| {
+            for (index, workspace) in workspaces.list.iter().enumerate() {
+                let text = format!(
+                    "{} {} ({})",
+                    index + 1,
+                    language.tr(&workspace.name),
+                    counts[index]
+                );
+                p.spawn(tab(text, index == workspaces.active)).observe(
+                    move |_trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+                        commands.send_event(SwitchWorkspace(index));
+                    },
+                );
+            }
+            if workspaces.list.len() < MAX_WORKSPACES {
+                let name = format!("Workspace {}", workspaces.list.len() + 1);
+                p.spawn(tab("+".to_string(), false)).observe(
+                    move |_trigger: Trigger<Pointer<Click>>, mut commands: Commands| {
+                        commands.send_event(CreateWorkspace(name.clone()));
+                    },
+                );
+            }
+        });
+}
@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+use jigsaw_puzzle::{
+    drop_piece, spawn_puzzle, GameState, HeadlessPuzzlePlugin, PieceSet, PuzzleCompleted, Scenario,
+};
+use jigsaw_puzzle_generator::bezier_rs::Subpath;
+use jigsaw_puzzle_generator::glam::DVec2;
+use jigsaw_puzzle_generator::image::DynamicImage;
+use jigsaw_puzzle_generator::{GameMode, JigsawGenerator};
+
+#[test]
+fn test_merge_and_finish() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, HeadlessPuzzlePlugin));
+    let generator = JigsawGenerator::new(DynamicImage::new_rgba8(400, 400), 2, 2);
+    let pieces = spawn_puzzle(app.world_mut(), generator, GameMode::Classic).unwrap();
+    app.update();
+    assert_eq!(*app.world().resource::<State<GameState>>(), GameState::Play);
+
+    let translation = |app: &App, piece| app.world().get::<Transform>(piece).unwrap().translation;
+    let solved = translation(&app, pieces[0]);
+    drop_piece(app.world_mut(), pieces[0], Vec2::new(1000.0, 1000.0));
+    drop_piece(
+        app.world_mut(),
+        pieces[0],
+        solved.xy() + Vec2::new(3.0, -2.0),
+    );
+    app.update();
+    assert_eq!(translation(&app, pieces[0]).xy(), solved.xy());
+    assert_eq!(*app.world().resource::<State<GameState>>(), GameState::Play);
+
+    let solved = translation(&app, pieces[3]);
+    drop_piece(
+        app.world_mut(),
+        pieces[3],
+        solved.xy() + Vec2::new(-4.0, 1.0),
+    );
+    app.update();
+    assert_eq!(
+        *app.world().resource::<State<GameState>>(),
+        GameState::Finish
+    );
+    assert!(!app.world().resource::<Events<PuzzleCompleted>>().is_empty());
+}
+
+#[test]
+fn test_finish_an_outlined_puzzle() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, HeadlessPuzzlePlugin));
+    // the outline leaves out the right one of three pieces
+    let generator = JigsawGenerator::new(DynamicImage::new_rgba8(600, 200), 3, 1)
+        .seed(1)
+        .outline(Subpath::new_ellipse(DVec2::ZERO, DVec2::new(240.0, 200.0)));
+    let pieces = spawn_puzzle(app.world_mut(), generator, GameMode::Classic).unwrap();
+    assert_eq!(pieces.len(), 2);
+    app.update();
+
+    let solved = app.world().get::<Transform>(pieces[1]).unwrap().translation;
+    drop_piece(
+        app.world_mut(),
+        pieces[1],
+        solved.xy() + Vec2::new(2.0, 2.0),
+    );
+    app.update();
+    assert_eq!(
+        *app.world().resource::<State<GameState>>(),
+        GameState::Finish
+    );
+}
+
+#[test]
+fn test_scenario_from_ron() {
+    let scenario = Scenario::from_ron(
+        "(groups: [Border, Center(0.25)], placements: [(piece: 7, x: 120.0, y: -40.0)])",
+    )
+    .unwrap();
+    assert!(
+        matches!(scenario.groups[..], [PieceSet::Border, PieceSet::Center(fraction)] if fraction == 0.25)
+    );
+    assert!(scenario.pinned.is_empty());
+    assert_eq!(scenario.placements[0].piece, 7);
+}